@@ -0,0 +1,118 @@
+//! Parsing throughput on generated files of three sizes.
+//!
+//! Run with `cargo bench --bench parse`; `cargo bench --bench parse -- huge`
+//! runs one size only. `parse_attributes` times one task with thousands of
+//! attributes on a single line. With `--features parallel`, `parse_parallel/huge`
+//! parses the huge file with `ToDoParser::parse_parallel`. With
+//! `--features fast-parser`, every group uses the hand-written parser.
+
+use std::fmt::Write;
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use to_do_parcer::ToDoParser;
+
+/// Tasks per project in the generated files.
+const TASKS_PER_PROJECT: usize = 50;
+
+/// A file of `tasks` tasks using every attribute, with comments.
+fn source(tasks: usize) -> String {
+    let mut out = String::new();
+    for p in 0..tasks.div_ceil(TASKS_PER_PROJECT) {
+        let _ = writeln!(out, "// Project {}\nproject \"Project {}\" {{", p, p);
+        for t in 0..TASKS_PER_PROJECT.min(tasks - p * TASKS_PER_PROJECT) {
+            let status = if t % 3 == 0 { "done" } else { "todo" };
+            let _ = write!(
+                out,
+                "  {}: \"Task {} of project {}\", id: t{}-{}, @high, due: 2025-{:02}-{:02}, assign: @user{}, @tag: \"area-{}\",",
+                status,
+                t,
+                p,
+                p,
+                t,
+                t % 12 + 1,
+                t % 28 + 1,
+                t % 7,
+                t % 5
+            );
+            if t > 0 {
+                let _ = write!(out, " depends_on: \"Task {} of project {}\",", t - 1, p);
+            }
+            if t % 10 == 0 {
+                out.push_str(" // checkpoint");
+            }
+            out.push('\n');
+        }
+        out.push_str("}\n\n");
+    }
+    out
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_projects");
+    for (name, tasks) in [("small", 10), ("medium", 1_000), ("huge", 20_000)] {
+        let input = source(tasks);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        if tasks >= 20_000 {
+            group.sample_size(10);
+        }
+        group.bench_with_input(BenchmarkId::from_parameter(name), &input, |b, input| {
+            b.iter(|| ToDoParser::parse_projects(black_box(input)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+/// One task with `attributes` attributes of every kind on a single line.
+fn long_task(attributes: usize) -> String {
+    const KINDS: [&str; 8] = [
+        "@tag: \"x\"",
+        "@high",
+        "due: 2025-01-02",
+        "id: t1",
+        "assign: @bob",
+        "depends_on: \"A\"",
+        "start: 2025-01-01",
+        "completed: 2025-01-03",
+    ];
+    let mut out = String::from("project \"P\" {\n  done: \"Long\"");
+    for kind in KINDS.iter().cycle().take(attributes) {
+        let _ = write!(out, ", {}", kind);
+    }
+    out.push_str(",\n}\n");
+    out
+}
+
+/// Tasks with thousands of attributes, which used to take quadratic time.
+fn parse_attributes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_attributes");
+    for attributes in [1_000, 10_000] {
+        let input = long_task(attributes);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(attributes),
+            &input,
+            |b, input| b.iter(|| ToDoParser::parse_projects(black_box(input)).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+/// The huge file parsed on every core, next to `parse_projects/huge`.
+#[cfg(feature = "parallel")]
+fn parse_parallel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_parallel");
+    let input = source(20_000);
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.sample_size(10);
+    group.bench_with_input(BenchmarkId::from_parameter("huge"), &input, |b, input| {
+        b.iter(|| ToDoParser::parse_parallel(black_box(input)).unwrap())
+    });
+    group.finish();
+}
+
+#[cfg(not(feature = "parallel"))]
+criterion_group!(benches, parse, parse_attributes);
+#[cfg(feature = "parallel")]
+criterion_group!(benches, parse, parse_attributes, parse_parallel);
+criterion_main!(benches);
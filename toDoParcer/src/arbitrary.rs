@@ -0,0 +1,140 @@
+//! [`Arbitrary`] implementations for the AST and a generator of valid DSL
+//! text, for property-based and fuzz tests.
+//!
+//! Every generated value is something the parser could have produced:
+//! quoted strings hold no `"` or control characters, ids and handles are
+//! identifiers and dates exist. Positions, comments and extensions are
+//! left empty, so [`format_document`] of a generated [`Document`] parses
+//! back to an equal document.
+//!
+//! ```
+//! use arbitrary::Unstructured;
+//! use to_do_parcer::ToDoParser;
+//! use to_do_parcer::pretty::format_document;
+//!
+//! let bytes: Vec<u8> = (0..512u32).map(|i| (i * 37 % 251) as u8).collect();
+//! let source = to_do_parcer::arbitrary::source(&mut Unstructured::new(&bytes))?;
+//! let doc = ToDoParser::parse_document(&source).unwrap();
+//! assert_eq!(format_document(&doc), source);
+//! # Ok::<(), arbitrary::Error>(())
+//! ```
+
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+use chrono::{Days, NaiveDate};
+
+use crate::parser::{Document, Priority, Project, SUPPORTED_VERSIONS, Task, TaskStatus};
+use crate::pretty::format_document;
+
+/// Most projects in a generated document.
+const MAX_PROJECTS: usize = 4;
+/// Most tasks in a generated project.
+const MAX_TASKS: usize = 8;
+/// Most tags on a generated task.
+const MAX_TAGS: usize = 3;
+/// Longest generated quoted string, in characters.
+const MAX_TEXT: usize = 24;
+
+/// Text of a valid DSL file: [`format_document`] of an arbitrary
+/// [`Document`].
+///
+/// # Arguments
+/// * `u` - Source of the choices
+pub fn source(u: &mut Unstructured<'_>) -> Result<String> {
+    Ok(format_document(&Document::arbitrary(u)?))
+}
+
+impl<'a> Arbitrary<'a> for TaskStatus {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(*u.choose(&[TaskStatus::Todo, TaskStatus::Done])?)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Priority {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(*u.choose(&[Priority::High, Priority::Medium, Priority::Low])?)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Task {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut task = Task::new(u.arbitrary()?, quoted(u)?);
+        task.id = optional(u, identifier)?;
+        task.priority = u.arbitrary()?;
+        task.start_date = optional(u, date)?;
+        task.due_date = optional(u, date)?;
+        task.completed = optional(u, date)?;
+        task.assignee = optional(u, identifier)?;
+        task.depends_on = optional(u, quoted)?;
+        for _ in 0..u.int_in_range(0..=MAX_TAGS)? {
+            task.tags.push(quoted(u)?);
+        }
+        Ok(task)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Project {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut project = Project::new(quoted(u)?);
+        for _ in 0..u.int_in_range(0..=MAX_TASKS)? {
+            project.tasks.push(u.arbitrary()?);
+        }
+        Ok(project)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Document {
+    /// A document of one or more projects, in a supported format version,
+    /// without includes.
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut projects = Vec::new();
+        for _ in 0..u.int_in_range(1..=MAX_PROJECTS)? {
+            projects.push(u.arbitrary()?);
+        }
+        Ok(Document {
+            version: u.int_in_range(SUPPORTED_VERSIONS)?,
+            projects,
+            includes: Vec::new(),
+            trailing_comments: Vec::new(),
+            warnings: Vec::new(),
+        })
+    }
+}
+
+/// `Some` of a generated value, or `None`.
+fn optional<T>(
+    u: &mut Unstructured<'_>,
+    generate: impl FnOnce(&mut Unstructured<'_>) -> Result<T>,
+) -> Result<Option<T>> {
+    if u.arbitrary()? {
+        Ok(Some(generate(u)?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Contents of a quoted string: any characters but `"` and controls.
+fn quoted(u: &mut Unstructured<'_>) -> Result<String> {
+    let len = u.int_in_range(0..=MAX_TEXT)?;
+    let mut text = String::with_capacity(len);
+    for _ in 0..len {
+        let c: char = u.arbitrary()?;
+        text.push(if c == '"' || c.is_control() { ' ' } else { c });
+    }
+    Ok(text)
+}
+
+/// An identifier: letters, digits, `_` and `-`.
+fn identifier(u: &mut Unstructured<'_>) -> Result<String> {
+    const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_-";
+    let len = u.int_in_range(1..=12)?;
+    (0..len)
+        .map(|_| u.choose(CHARS).map(|&b| char::from(b)))
+        .collect()
+}
+
+/// A date between 2000 and 2099, as `YYYY-MM-DD`.
+fn date(u: &mut Unstructured<'_>) -> Result<String> {
+    let first = NaiveDate::from_ymd_opt(2000, 1, 1).expect("valid date");
+    let days = u.int_in_range(0..=36_524)?;
+    Ok((first + Days::new(days)).format("%Y-%m-%d").to_string())
+}
@@ -0,0 +1,110 @@
+//! Moving finished work out of active files.
+//!
+//! Done tasks are removed from the source text and appended to an archive
+//! text under a project of the same name, using [`Editor`] on both sides
+//! so the rest of each file is left as written. A project whose tasks are
+//! all done is removed as a whole. Done tasks that open tasks still depend
+//! on stay, so archiving never leaves a dangling `depends_on`.
+
+use std::collections::HashSet;
+
+use crate::edit::{EditError, Editor};
+use crate::parser::{Task, TaskStatus};
+
+/// Outcome of [`archive`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Archived {
+    /// New text of the active file.
+    pub source: String,
+    /// New text of the archive file.
+    pub archive: String,
+    /// Number of tasks moved.
+    pub tasks: usize,
+    /// Number of projects removed from the active file as a whole.
+    pub projects: usize,
+    /// `Project/Title` of done tasks kept because open tasks depend on them.
+    pub kept: Vec<String>,
+}
+
+/// Moves the done tasks of `source` to the end of `archive`.
+///
+/// # Arguments
+/// * `source` - Text of the active file
+/// * `archive` - Text of the archive file; blank for a new one
+///
+/// # Returns
+/// * `Err(EditError)` if either text doesn't parse
+///
+/// # Example
+/// ```
+/// use to_do_parcer::archive::archive;
+///
+/// let source = "project \"P\" {\n  todo: \"A\",\n  done: \"B\",\n}\n";
+/// let result = archive(source, "")?;
+/// assert_eq!(result.source, "project \"P\" {\n  todo: \"A\",\n}\n");
+/// assert_eq!(result.archive, "project \"P\" {\n  done: \"B\",\n}\n");
+/// # Ok::<(), to_do_parcer::edit::EditError>(())
+/// ```
+pub fn archive(source: &str, archive: &str) -> Result<Archived, EditError> {
+    let mut active = Editor::new(source)?;
+    let mut target = Editor::new(archive)?;
+
+    let doc = active.document();
+    let deps = doc.dependencies();
+    let needed: HashSet<_> = doc
+        .task_refs()
+        .filter(|r| r.get(doc).is_some_and(|t| t.status == TaskStatus::Todo))
+        .filter_map(|r| deps.of(r))
+        .collect();
+
+    let mut moves: Vec<(String, Vec<Task>)> = Vec::new();
+    let mut whole = Vec::new();
+    let mut single = Vec::new();
+    let mut kept = Vec::new();
+    for (p, project) in doc.projects.iter().enumerate() {
+        let mut moved = Vec::new();
+        for r in doc.task_refs().filter(|r| r.project == p) {
+            let task = r.get(doc).expect("task ref is valid");
+            if task.status != TaskStatus::Done {
+                continue;
+            }
+            if needed.contains(&r) {
+                kept.push(format!("{}/{}", project.name, task.title));
+                continue;
+            }
+            moved.push((r, task.clone()));
+        }
+        if moved.is_empty() {
+            continue;
+        }
+        if moved.len() == project.tasks.len() {
+            whole.push(p);
+        } else {
+            single.extend(moved.iter().map(|(r, _)| *r));
+        }
+        moves.push((
+            project.name.clone(),
+            moved.into_iter().map(|(_, t)| t).collect(),
+        ));
+    }
+
+    for &p in &whole {
+        active.remove_project_at(p);
+    }
+    for &r in &single {
+        active.remove_at(r);
+    }
+    let mut tasks = 0;
+    for (project, moved) in moves {
+        tasks += moved.len();
+        target.add_tasks(&project, moved)?;
+    }
+
+    Ok(Archived {
+        source: active.finish(),
+        archive: target.finish(),
+        tasks,
+        projects: whole.len(),
+        kept,
+    })
+}
@@ -0,0 +1,155 @@
+//! Day-by-day counts of open tasks, for burndown charts.
+//!
+//! The format has no creation date, so a task enters the series on its
+//! `start:` date, or is there from the first day if it has none. It leaves
+//! on its `completed:` date. Done tasks without a completion date can't be
+//! placed and are left out entirely.
+//!
+//! CSV output has the header `date,open,done,total`; JSON output is an
+//! array of objects with the same fields. `done` counts completions up to
+//! and including the day, `total` is `open + done`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::parser::{Document, TaskStatus};
+
+/// Output format of `burndown`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BurndownFormat {
+    Csv,
+    Json,
+}
+
+impl BurndownFormat {
+    /// Every supported format, in the order shown in help texts.
+    pub const ALL: &'static [BurndownFormat] = &[BurndownFormat::Csv, BurndownFormat::Json];
+
+    /// Name of the format as accepted by `FromStr`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BurndownFormat::Csv => "csv",
+            BurndownFormat::Json => "json",
+        }
+    }
+}
+
+impl fmt::Display for BurndownFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for BurndownFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BurndownFormat::ALL
+            .iter()
+            .find(|f| f.as_str().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<_> = BurndownFormat::ALL.iter().map(|f| f.as_str()).collect();
+                format!(
+                    "unknown format '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Task counts at the end of one day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BurndownDay {
+    pub date: NaiveDate,
+    pub open: usize,
+    pub done: usize,
+    pub total: usize,
+}
+
+/// Computes the series for every day from `from` to `to`, both inclusive.
+///
+/// # Returns
+/// * An empty series if `from` is after `to`.
+///
+/// # Example
+/// ```
+/// use chrono::NaiveDate;
+/// use to_do_parcer::ToDoParser;
+/// use to_do_parcer::burndown::burndown;
+///
+/// let doc = ToDoParser::parse_document(
+///     r#"project "P" { todo: "A", done: "B", completed: 2025-01-02, }"#,
+/// )?;
+/// let day = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+/// let open: Vec<_> = burndown(&doc, day(1), day(3)).iter().map(|d| d.open).collect();
+/// assert_eq!(open, [2, 1, 1]);
+/// # Ok::<(), to_do_parcer::ParseError>(())
+/// ```
+pub fn burndown(doc: &Document, from: NaiveDate, to: NaiveDate) -> Vec<BurndownDay> {
+    // (entered, left) per task that can be placed.
+    let spans: Vec<(Option<NaiveDate>, Option<NaiveDate>)> = doc
+        .tasks()
+        .filter_map(|(_, task)| {
+            let completed = task.completed_on();
+            if task.status == TaskStatus::Done && completed.is_none() {
+                return None;
+            }
+            Some((task.start(), completed))
+        })
+        .collect();
+
+    from.iter_days()
+        .take_while(|date| *date <= to)
+        .map(|date| {
+            let entered = |start: &Option<NaiveDate>| start.is_none_or(|s| s <= date);
+            let open = spans
+                .iter()
+                .filter(|(start, end)| entered(start) && end.is_none_or(|e| e > date))
+                .count();
+            let done = spans
+                .iter()
+                .filter(|(start, end)| entered(start) && end.is_some_and(|e| e <= date))
+                .count();
+            BurndownDay {
+                date,
+                open,
+                done,
+                total: open + done,
+            }
+        })
+        .collect()
+}
+
+/// Renders a series in the given format.
+pub fn render_burndown(days: &[BurndownDay], format: BurndownFormat) -> String {
+    match format {
+        BurndownFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            for day in days {
+                writer
+                    .serialize(day)
+                    .expect("writing CSV to memory cannot fail");
+            }
+            if days.is_empty() {
+                writer
+                    .write_record(["date", "open", "done", "total"])
+                    .expect("writing CSV to memory cannot fail");
+            }
+            let bytes = writer
+                .into_inner()
+                .expect("flushing CSV to memory cannot fail");
+            String::from_utf8(bytes).expect("CSV output is built from UTF-8 strings")
+        }
+        BurndownFormat::Json => {
+            let mut out =
+                serde_json::to_string_pretty(days).expect("burndown series serializes to JSON");
+            out.push('\n');
+            out
+        }
+    }
+}
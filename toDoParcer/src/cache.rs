@@ -0,0 +1,185 @@
+//! Parsed documents kept across parses of files that rarely change.
+//!
+//! A [`DocumentCache`] remembers the document parsed from each path along
+//! with a hash of the file's bytes and of the files it included. Asking
+//! again reads the files, but only parses them when a hash changed, which
+//! is what long-running commands like `serve` need: most requests find
+//! every file as it was.
+//!
+//! Included files are tracked through the `include` directives of the
+//! main file and the files projects came from. A nested include that
+//! contributes no projects itself isn't tracked; [`DocumentCache::invalidate`]
+//! drops an entry by hand.
+//!
+//! ```no_run
+//! use to_do_parcer::cache::DocumentCache;
+//! use to_do_parcer::options::ParseOptions;
+//!
+//! let mut cache = DocumentCache::new();
+//! let options = ParseOptions::default();
+//! let first = cache.parse_file("tasks.todo", &options)?.projects.len();
+//! // Unchanged on disk: read and hashed, but not parsed again.
+//! let again = cache.parse_file("tasks.todo", &options)?.projects.len();
+//! assert_eq!(first, again);
+//! assert_eq!(cache.stats().hits, 1);
+//! # Ok::<(), to_do_parcer::ParseError>(())
+//! ```
+
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hasher};
+use std::path::{Path, PathBuf};
+
+use crate::options::ParseOptions;
+use crate::parser::{Document, ParseError, ToDoParser, check_file_size, decode_source};
+
+/// Parsed documents by path, reparsed only when their content changes.
+///
+/// The cache doesn't look at the options it is given: use one cache per
+/// set of options, or [`clear`](DocumentCache::clear) it when they change.
+#[derive(Debug, Default)]
+pub struct DocumentCache {
+    entries: HashMap<PathBuf, Entry>,
+    stats: CacheStats,
+}
+
+/// How often a [`DocumentCache`] could skip parsing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Lookups answered with a cached document.
+    pub hits: usize,
+    /// Lookups that parsed the file.
+    pub misses: usize,
+}
+
+/// A cached document and the hashes it was parsed from.
+#[derive(Debug)]
+struct Entry {
+    /// Hash of the main file.
+    hash: u64,
+    /// Included files and their hashes when the document was parsed.
+    includes: Vec<(PathBuf, u64)>,
+    doc: Document,
+}
+
+impl DocumentCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse the file at `path` like [`ToDoParser::parse_file_with`],
+    /// unless neither it nor the files it included changed since the
+    /// last call for the same path.
+    ///
+    /// Failed parses aren't cached; the next call parses the file again.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the input file
+    /// * `options` - Strictness, allowed priorities, include handling, etc.
+    ///
+    /// # Returns
+    /// * `Ok(&Document)` from the cache or freshly parsed
+    /// * `Err(ParseError)` if reading or parsing fails
+    pub fn parse_file(
+        &mut self,
+        path: impl AsRef<Path>,
+        options: &ParseOptions,
+    ) -> Result<&Document, ParseError> {
+        let path = path.as_ref();
+        check_file_size(path, 0, options)?;
+        let bytes = std::fs::read(path)?;
+        let hash = hash_bytes(&bytes);
+
+        let fresh = self
+            .entries
+            .get(path)
+            .is_some_and(|entry| entry.hash == hash && includes_unchanged(&entry.includes));
+        if fresh {
+            log::debug!("{} unchanged, using cached document", path.display());
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+            self.entries.remove(path);
+            let (content, replaced) = decode_source(bytes, options.lossy)?;
+            let name = path.to_string_lossy();
+            let doc = ToDoParser::parse_file_contents(&name, &content, replaced, options)?;
+            let base_dir = match &options.base_dir {
+                Some(dir) => dir.clone(),
+                None => path.parent().map(Path::to_path_buf).unwrap_or_default(),
+            };
+            let entry = Entry {
+                hash,
+                includes: hash_includes(&doc, &base_dir),
+                doc,
+            };
+            self.entries.insert(path.to_path_buf(), entry);
+        }
+        Ok(&self.entries[path].doc)
+    }
+
+    /// The document cached for `path`, without looking at the file.
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<&Document> {
+        self.entries.get(path.as_ref()).map(|entry| &entry.doc)
+    }
+
+    /// Drops the document cached for `path`, so the next call parses it.
+    ///
+    /// # Returns
+    /// * `true` if a document was cached for `path`
+    pub fn invalidate(&mut self, path: impl AsRef<Path>) -> bool {
+        self.entries.remove(path.as_ref()).is_some()
+    }
+
+    /// Drops every cached document. Statistics are kept.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Number of cached documents.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no document is cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Hits and misses since the cache was created.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+}
+
+/// Hash of a file's bytes; only compared within one process.
+fn hash_bytes(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+/// Files `doc` was read from besides the main file, with their hashes.
+fn hash_includes(doc: &Document, base_dir: &Path) -> Vec<(PathBuf, u64)> {
+    let mut paths: Vec<PathBuf> = doc
+        .includes
+        .iter()
+        .map(|include| base_dir.join(&include.path))
+        .chain(doc.projects.iter().filter_map(|p| p.file.clone()))
+        .collect();
+    paths.sort();
+    paths.dedup();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let hash = hash_bytes(&std::fs::read(&path).ok()?);
+            Some((path, hash))
+        })
+        .collect()
+}
+
+/// Whether every included file still has the hash it was parsed with.
+fn includes_unchanged(includes: &[(PathBuf, u64)]) -> bool {
+    includes
+        .iter()
+        .all(|(path, hash)| std::fs::read(path).is_ok_and(|bytes| hash_bytes(&bytes) == *hash))
+}
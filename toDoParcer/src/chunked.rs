@@ -0,0 +1,222 @@
+//! Parsing a reader block by block, in memory bounded by the largest
+//! project rather than the whole input.
+//!
+//! Lines are buffered until a top-level `project` or `include` starts
+//! after at least [`MIN_CHUNK_BYTES`] of complete blocks; the buffer is
+//! then parsed on its own and its events are streamed to the sink. Events,
+//! positions and warnings are the same as for the whole input parsed with
+//! [`ToDoParser::parse_events_with`], and so are errors, except that they
+//! are found chunk by chunk: an unknown attribute early in the input is
+//! reported even if a syntax error follows, which a whole parse would
+//! report first.
+//!
+//! ```
+//! use to_do_parcer::ToDoParser;
+//! use to_do_parcer::events::Event;
+//! use to_do_parcer::options::ParseOptions;
+//!
+//! let input = "project \"A\" { todo: \"1\", }\n".repeat(10_000);
+//! let mut tasks = 0;
+//! ToDoParser::parse_reader(
+//!     input.as_bytes(),
+//!     &ParseOptions::default(),
+//!     &mut |event: Event<'_>| {
+//!         if let Event::TaskParsed(_) = event {
+//!             tasks += 1;
+//!         }
+//!     },
+//! )?;
+//! assert_eq!(tasks, 10_000);
+//! # Ok::<(), to_do_parcer::ParseError>(())
+//! ```
+
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::io::BufRead;
+
+use crate::events::{Event, TodoSink};
+use crate::options::ParseOptions;
+use crate::parser::{Origin, ParseError, ParseWarning, ToDoParser, normalize_source};
+
+/// Text buffered before a new block starts a new chunk, in bytes. Small
+/// chunks would spend more time setting up parses than parsing.
+pub const MIN_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Tracks quotes, `//` comments and braces line by line, to find where
+/// top-level blocks start.
+///
+/// This is enough for valid input; chunks cut from invalid input fail to
+/// parse like the whole input would.
+#[derive(Debug, Default)]
+pub(crate) struct BlockScanner {
+    depth: usize,
+    in_quote: bool,
+}
+
+/// What a line is, as seen by a [`BlockScanner`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LineKind {
+    /// Blank or only a comment, between blocks.
+    Blank,
+    /// Starts a top-level `project` block.
+    Project,
+    /// Starts a top-level `include` directive.
+    Include,
+    /// Anything else: inside a block, or the `version` pragma.
+    Other,
+}
+
+impl BlockScanner {
+    /// Classifies `line`, without its line ending, and moves past it.
+    pub(crate) fn line(&mut self, line: &str) -> LineKind {
+        let text = line.trim_start();
+        let kind = if self.depth > 0 || self.in_quote {
+            LineKind::Other
+        } else if text.is_empty() || text.starts_with("//") {
+            return LineKind::Blank;
+        } else if starts_with_keyword(text, "project") {
+            LineKind::Project
+        } else if starts_with_keyword(text, "include") {
+            LineKind::Include
+        } else {
+            LineKind::Other
+        };
+
+        let bytes = line.as_bytes();
+        for (i, &b) in bytes.iter().enumerate() {
+            match b {
+                b'"' => self.in_quote = !self.in_quote,
+                b'/' if !self.in_quote && bytes.get(i + 1) == Some(&b'/') => break,
+                b'{' if !self.in_quote => self.depth += 1,
+                b'}' if !self.in_quote => self.depth = self.depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        kind
+    }
+}
+
+/// Returns whether `text` starts with `keyword`, in any case, as a whole word.
+fn starts_with_keyword(text: &str, keyword: &str) -> bool {
+    let bytes = text.as_bytes();
+    bytes.len() > keyword.len()
+        && bytes[..keyword.len()].eq_ignore_ascii_case(keyword.as_bytes())
+        && !(bytes[keyword.len()].is_ascii_alphanumeric() || bytes[keyword.len()] == b'_')
+}
+
+impl ToDoParser {
+    /// Parse text from `reader`, streaming projects and tasks to `sink`
+    /// one chunk of top-level blocks at a time.
+    ///
+    /// Only the current chunk is held in memory: about
+    /// [`MIN_CHUNK_BYTES`], or the largest project if that is bigger.
+    /// Events already streamed stay with the sink when a later chunk
+    /// fails. With `options.lossy`, the warning about replaced invalid
+    /// UTF-8 follows the events of the chunk it was found in.
+    ///
+    /// # Arguments
+    /// * `reader` - Source of the text, such as a `BufReader<File>`
+    /// * `options` - Strictness, allowed priorities, include handling, etc.
+    /// * `sink` - Receiver of `Version`/`ProjectStart`/`TaskParsed`/`ProjectEnd` events
+    ///
+    /// # Returns
+    /// * `Ok(())` once the whole input was streamed
+    /// * `Err(ParseError::Io)` if reading fails
+    /// * `Err(ParseError::Encoding)` for invalid UTF-8 unless `options.lossy`
+    /// * `Err(ParseError)` if parsing fails
+    pub fn parse_reader(
+        mut reader: impl BufRead,
+        options: &ParseOptions,
+        sink: &mut impl TodoSink,
+    ) -> Result<(), ParseError> {
+        let bytes_read = Cell::new(0);
+        let mut scanner = BlockScanner::default();
+        let (mut raw, mut chunk) = (Vec::new(), String::new());
+        // Bytes and lines of raw input before the current line.
+        let (mut raw_offset, mut line) = (0, 0);
+        let mut origin = Origin::default();
+        let mut version = None;
+        let (mut has_block, mut replaced) = (false, false);
+        let mut replacement = None;
+
+        loop {
+            raw.clear();
+            if reader.read_until(b'\n', &mut raw)? == 0 {
+                break;
+            }
+            let text = match std::str::from_utf8(&raw) {
+                Ok(text) => Cow::Borrowed(text),
+                Err(e) if !options.lossy => {
+                    return Err(ParseError::Encoding {
+                        line: line + 1,
+                        offset: raw_offset + e.valid_up_to(),
+                    });
+                }
+                Err(_) if replaced => String::from_utf8_lossy(&raw),
+                Err(e) => {
+                    replaced = true;
+                    let warning = ParseWarning {
+                        file: None,
+                        line: line + 1,
+                        message: format!(
+                            "invalid UTF-8 from byte {} replaced with U+FFFD",
+                            raw_offset + e.valid_up_to()
+                        ),
+                    };
+                    if options.strict {
+                        return Err(ParseError::Strict(warning));
+                    }
+                    // Sent after the chunk, so `Version` stays the first event.
+                    replacement = Some(warning);
+                    String::from_utf8_lossy(&raw)
+                }
+            };
+            raw_offset += raw.len();
+            line += 1;
+
+            let kind = scanner.line(text.trim_end_matches(['\n', '\r']));
+            let starts_block = matches!(kind, LineKind::Project | LineKind::Include);
+            if starts_block && has_block && chunk.len() >= MIN_CHUNK_BYTES {
+                version = Some(Self::parse_buffered(
+                    &chunk,
+                    &mut origin,
+                    version,
+                    options,
+                    &bytes_read,
+                    sink,
+                )?);
+                if let Some(warning) = replacement.take() {
+                    sink.event(Event::Warning(warning));
+                }
+                chunk.clear();
+            }
+            has_block |= starts_block;
+            chunk.push_str(&text);
+        }
+
+        if !chunk.is_empty() || version.is_none() {
+            Self::parse_buffered(&chunk, &mut origin, version, options, &bytes_read, sink)?;
+        }
+        if let Some(warning) = replacement {
+            sink.event(Event::Warning(warning));
+        }
+        log::info!("parsed {} bytes in chunks", raw_offset);
+        Ok(())
+    }
+
+    /// Parses one buffered chunk and moves `origin` past it.
+    fn parse_buffered(
+        chunk: &str,
+        origin: &mut Origin,
+        version: Option<u32>,
+        options: &ParseOptions,
+        bytes_read: &Cell<usize>,
+        sink: &mut impl TodoSink,
+    ) -> Result<u32, ParseError> {
+        let text = normalize_source(chunk);
+        let version = Self::parse_chunk_events(&text, *origin, version, options, bytes_read, sink)?;
+        origin.offset += text.len();
+        origin.lines_before += text.bytes().filter(|&b| b == b'\n').count();
+        Ok(version)
+    }
+}
@@ -0,0 +1,284 @@
+//! ANSI colors for terminal output.
+//!
+//! Coloring is off unless asked for: callers pick a [`Palette`] from the
+//! user's [`ColorChoice`] and whether the output stream is a terminal.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+
+use crate::diagnostic::Severity;
+use crate::parser::{Priority, Task, TaskStatus};
+use crate::schedule::Clock;
+
+/// When to color output, as chosen with `--color`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// Color terminals, unless the `NO_COLOR` environment variable is set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Every choice, in the order shown in help texts.
+    pub const ALL: &'static [ColorChoice] =
+        &[ColorChoice::Auto, ColorChoice::Always, ColorChoice::Never];
+
+    /// Name of the choice as accepted by `FromStr`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ColorChoice::Auto => "auto",
+            ColorChoice::Always => "always",
+            ColorChoice::Never => "never",
+        }
+    }
+
+    /// Decides whether to color a stream.
+    ///
+    /// # Arguments
+    /// * `is_terminal` - Whether the stream is connected to a terminal
+    pub fn enabled(&self, is_terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                is_terminal && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+            }
+        }
+    }
+}
+
+impl fmt::Display for ColorChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ColorChoice::ALL
+            .iter()
+            .find(|c| c.as_str().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| {
+                format!(
+                    "unknown color choice '{}', expected auto, always or never",
+                    s
+                )
+            })
+    }
+}
+
+/// What a piece of text means, which decides its color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Todo,
+    Done,
+    High,
+    Medium,
+    Low,
+    Overdue,
+    Error,
+    Warning,
+    Hint,
+}
+
+impl Style {
+    /// Style of a task status.
+    pub fn status(status: TaskStatus) -> Style {
+        match status {
+            TaskStatus::Todo => Style::Todo,
+            TaskStatus::Done => Style::Done,
+        }
+    }
+
+    /// Style of a priority.
+    pub fn priority(priority: Priority) -> Style {
+        match priority {
+            Priority::High => Style::High,
+            Priority::Medium => Style::Medium,
+            Priority::Low => Style::Low,
+        }
+    }
+
+    /// Style of an issue severity.
+    pub fn severity(severity: Severity) -> Style {
+        match severity {
+            Severity::Error => Style::Error,
+            Severity::Warning => Style::Warning,
+            Severity::Hint => Style::Hint,
+        }
+    }
+
+    /// SGR parameters of the style.
+    fn sgr(&self) -> &'static str {
+        match self {
+            Style::Todo => "33",
+            Style::Done => "32",
+            Style::High => "1;31",
+            Style::Medium => "33",
+            Style::Low => "34",
+            Style::Overdue => "1;31",
+            Style::Error => "1;31",
+            Style::Warning => "1;33",
+            Style::Hint => "36",
+        }
+    }
+}
+
+/// Colors text for one output stream, and shows due dates in the user's
+/// preferred format.
+///
+/// The default palette leaves text unchanged.
+#[derive(Debug, Clone, Default)]
+pub struct Palette {
+    enabled: bool,
+    /// Due dates before this day are painted as overdue.
+    today: Option<NaiveDate>,
+    /// `strftime` format due dates are shown in, instead of `YYYY-MM-DD`.
+    date_format: Option<String>,
+}
+
+impl Palette {
+    /// Creates a palette that colors text if `enabled` is true.
+    pub fn new(enabled: bool) -> Palette {
+        Palette {
+            enabled,
+            today: None,
+            date_format: None,
+        }
+    }
+
+    /// Shows due dates in the `strftime` format `format`, e.g. `%d.%m.%Y`.
+    ///
+    /// The format must be valid; see [`crate::config`] for how it is
+    /// checked when read from a file.
+    pub fn with_date_format(self, format: impl Into<String>) -> Palette {
+        Palette {
+            date_format: Some(format.into()),
+            ..self
+        }
+    }
+
+    /// Uses `now` to decide which due dates are overdue.
+    pub fn with_clock(self, now: &impl Clock) -> Palette {
+        Palette {
+            today: Some(now.today()),
+            ..self
+        }
+    }
+
+    /// Returns whether the palette colors anything.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Wraps `text` in the escape codes of `style`.
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::color::{Palette, Style};
+    ///
+    /// assert_eq!(Palette::new(true).paint(Style::Done, "done"), "\x1b[32mdone\x1b[0m");
+    /// assert_eq!(Palette::default().paint(Style::Done, "done"), "done");
+    /// ```
+    pub fn paint(&self, style: Style, text: &str) -> String {
+        let mut out = String::new();
+        let _ = self.write_painted(&mut out, style, text);
+        out
+    }
+
+    /// Writes `text` to `w` in the escape codes of `style`, like
+    /// [`Palette::paint`] without building a string.
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::color::{Palette, Style};
+    ///
+    /// let mut out = String::new();
+    /// Palette::new(true).write_painted(&mut out, Style::Done, 42)?;
+    /// assert_eq!(out, "\x1b[32m42\x1b[0m");
+    /// # Ok::<(), std::fmt::Error>(())
+    /// ```
+    pub fn write_painted(
+        &self,
+        w: &mut impl fmt::Write,
+        style: Style,
+        text: impl fmt::Display,
+    ) -> fmt::Result {
+        if self.enabled {
+            write!(w, "\x1b[{}m{}\x1b[0m", style.sgr(), text)
+        } else {
+            write!(w, "{}", text)
+        }
+    }
+
+    /// Paints `text`, the due date of `task`, if the task is overdue, after
+    /// rewriting it in the date format if one is set.
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::ToDoParser;
+    /// use to_do_parcer::color::Palette;
+    ///
+    /// let doc = ToDoParser::parse_document(r#"project "P" { todo: "A", due: 2025-12-01, }"#)?;
+    /// let task = &doc.projects[0].tasks[0];
+    /// let palette = Palette::default().with_date_format("%d.%m.%Y");
+    /// assert_eq!(palette.due(task, "2025-12-01"), "01.12.2025");
+    /// # Ok::<(), to_do_parcer::ParseError>(())
+    /// ```
+    pub fn due(&self, task: &Task, text: &str) -> String {
+        let mut out = String::new();
+        let _ = self.write_due(&mut out, task, text);
+        out
+    }
+
+    /// Writes what [`Palette::due`] returns to `w`.
+    pub fn write_due(&self, w: &mut impl fmt::Write, task: &Task, text: &str) -> fmt::Result {
+        let date = match &self.date_format {
+            Some(format) => NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .ok()
+                .map(|date| date.format(format)),
+            None => None,
+        };
+        match (date, self.is_overdue(task)) {
+            (Some(date), true) => self.write_painted(w, Style::Overdue, date),
+            (Some(date), false) => write!(w, "{}", date),
+            (None, true) => self.write_painted(w, Style::Overdue, text),
+            (None, false) => w.write_str(text),
+        }
+    }
+
+    /// Returns whether `task` is open and due before the palette's clock
+    /// date; always false without [`Palette::with_clock`].
+    pub fn is_overdue(&self, task: &Task) -> bool {
+        task.status == TaskStatus::Todo
+            && matches!((task.due(), self.today), (Some(due), Some(today)) if due < today)
+    }
+
+    /// Paints `text` if `task` is overdue, leaving it as is otherwise.
+    pub fn overdue(&self, task: &Task, text: &str) -> String {
+        if self.is_overdue(task) {
+            self.paint(Style::Overdue, text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Rewrites a `YYYY-MM-DD` date in the date format, if one is set.
+    /// Other text is returned unchanged.
+    pub fn date(&self, text: &str) -> String {
+        match (
+            &self.date_format,
+            NaiveDate::parse_from_str(text, "%Y-%m-%d"),
+        ) {
+            (Some(format), Ok(date)) => date.format(format).to_string(),
+            _ => text.to_string(),
+        }
+    }
+}
@@ -0,0 +1,248 @@
+//! User defaults read from TOML files.
+//!
+//! Settings are read from `$XDG_CONFIG_HOME/todo-parser/config.toml`
+//! (`~/.config/todo-parser/config.toml` when the variable is unset), then
+//! from `.todoparser.toml` in the current directory. A later file overrides
+//! the keys it sets; command-line flags override both. Missing files are
+//! skipped, and unknown keys are an error so that typos don't go unnoticed.
+//!
+//! ```toml
+//! file = "tasks.todo"          # used when --file is left out
+//! format = "yaml"              # default export format
+//! color = "never"              # auto, always or never
+//! date-format = "%d.%m.%Y"     # how due dates are shown (strftime)
+//!
+//! [lint]
+//! disable = ["missing-due"]
+//! enable = ["overdue"]
+//! severity = { duplicate-title = "error" }
+//! words = ["kubernetes", "oleksii"]   # accepted by `spelling` (spellcheck feature)
+//!
+//! [validate]
+//! tag-style = "kebab-case"                   # kebab-case, snake_case or lowercase
+//! allowed-tags = ["backend", "frontend"]     # the only tags `validate` accepts
+//! assignees = ["tanya", "oleksii"]           # the only handles `assign:` may name
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use chrono::format::{Item, StrftimeItems};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::color::ColorChoice;
+use crate::diagnostic::Severity;
+use crate::export::ExportFormat;
+use crate::lint::{LintConfig, LintRule};
+use crate::validate::TagStyle;
+
+/// Errors from reading configuration files.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    /// The file exists but can't be read.
+    #[error("{}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// The file isn't valid TOML or has unknown keys.
+    #[error("{}: {message}", path.display())]
+    Syntax { path: PathBuf, message: String },
+
+    /// A key has a value that isn't allowed, e.g. an unknown color choice.
+    #[error("{}: {key}: {message}", path.display())]
+    Value {
+        path: PathBuf,
+        key: String,
+        message: String,
+    },
+}
+
+/// Defaults for CLI options, merged from every configuration file.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    /// File read by commands whose `--file` is left out.
+    pub file: Option<String>,
+    /// Default `export` format.
+    pub format: Option<ExportFormat>,
+    /// Default `--color` choice.
+    pub color: Option<ColorChoice>,
+    /// `strftime` format for due dates in human-readable output.
+    pub date_format: Option<String>,
+    /// Read `@Alice` and `@alice` as the same assignee.
+    pub ignore_assignee_case: Option<bool>,
+    /// Lint rules to run and their severities.
+    pub lint: LintConfig,
+    /// Spelling `validate` requires of tags.
+    pub tag_style: Option<TagStyle>,
+    /// The only tags `validate` accepts.
+    pub allowed_tags: Option<Vec<String>>,
+    /// The only handles `validate` accepts in `assign:`, without `@`.
+    pub assignees: Option<Vec<String>>,
+}
+
+/// File layout, before values are checked.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct RawConfig {
+    file: Option<String>,
+    format: Option<String>,
+    color: Option<String>,
+    date_format: Option<String>,
+    ignore_assignee_case: Option<bool>,
+    lint: RawLint,
+    validate: RawValidate,
+}
+
+/// The `[lint]` table.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawLint {
+    disable: Vec<String>,
+    enable: Vec<String>,
+    severity: BTreeMap<String, String>,
+    words: Vec<String>,
+}
+
+/// The `[validate]` table.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct RawValidate {
+    tag_style: Option<String>,
+    allowed_tags: Option<Vec<String>>,
+    assignees: Option<Vec<String>>,
+}
+
+impl Config {
+    /// Configuration files in the order they are applied: the user's, then
+    /// the current directory's.
+    pub fn default_paths() -> Vec<PathBuf> {
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")));
+        config_home
+            .map(|dir| dir.join("todo-parser").join("config.toml"))
+            .into_iter()
+            .chain(std::iter::once(PathBuf::from(".todoparser.toml")))
+            .collect()
+    }
+
+    /// Reads the files of [`Config::default_paths`].
+    pub fn load() -> Result<Config, ConfigError> {
+        Config::load_from(&Config::default_paths())
+    }
+
+    /// Reads `paths` in order, skipping files that don't exist.
+    pub fn load_from(paths: &[PathBuf]) -> Result<Config, ConfigError> {
+        let mut config = Config::default();
+        for path in paths {
+            match std::fs::read_to_string(path) {
+                Ok(text) => config.apply(&text, path)?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(source) => {
+                    return Err(ConfigError::Io {
+                        path: path.clone(),
+                        source,
+                    });
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    /// Overrides settings with those of one file.
+    ///
+    /// # Arguments
+    /// * `text` - TOML text of the file
+    /// * `path` - Where the text came from, for error messages
+    ///
+    /// # Example
+    /// ```
+    /// use std::path::Path;
+    /// use to_do_parcer::color::ColorChoice;
+    /// use to_do_parcer::config::Config;
+    ///
+    /// let mut config = Config::default();
+    /// config.apply("color = \"never\"\nfile = \"a.todo\"", Path::new("global.toml"))?;
+    /// config.apply("file = \"b.todo\"", Path::new("local.toml"))?;
+    /// assert_eq!(config.color, Some(ColorChoice::Never));
+    /// assert_eq!(config.file.as_deref(), Some("b.todo"));
+    /// # Ok::<(), to_do_parcer::config::ConfigError>(())
+    /// ```
+    pub fn apply(&mut self, text: &str, path: &Path) -> Result<(), ConfigError> {
+        let raw: RawConfig = toml::from_str(text).map_err(|e| ConfigError::Syntax {
+            path: path.to_path_buf(),
+            message: e.message().to_string(),
+        })?;
+        let value = |key: &str, message: String| ConfigError::Value {
+            path: path.to_path_buf(),
+            key: key.to_string(),
+            message,
+        };
+
+        if let Some(file) = raw.file {
+            self.file = Some(file);
+        }
+        if let Some(format) = raw.format {
+            self.format = Some(parse(&format).map_err(|e| value("format", e))?);
+        }
+        if let Some(color) = raw.color {
+            self.color = Some(parse(&color).map_err(|e| value("color", e))?);
+        }
+        if let Some(format) = raw.date_format {
+            if StrftimeItems::new(&format).any(|item| matches!(item, Item::Error)) {
+                return Err(value(
+                    "date-format",
+                    format!("'{}' is not a valid strftime format", format),
+                ));
+            }
+            self.date_format = Some(format);
+        }
+        if let Some(ignore) = raw.ignore_assignee_case {
+            self.ignore_assignee_case = Some(ignore);
+        }
+
+        if let Some(style) = raw.validate.tag_style {
+            self.tag_style = Some(parse(&style).map_err(|e| value("validate.tag-style", e))?);
+        }
+        if let Some(tags) = raw.validate.allowed_tags {
+            self.allowed_tags = Some(tags);
+        }
+        if let Some(handles) = raw.validate.assignees {
+            let handles = handles
+                .iter()
+                .map(|h| h.trim_start_matches('@').to_string());
+            self.assignees = Some(handles.collect());
+        }
+
+        for (rule, level) in &raw.lint.severity {
+            let key = format!("lint.severity.{}", rule);
+            let rule: LintRule = parse(rule).map_err(|e| value(&key, e))?;
+            let level: Severity = parse(level).map_err(|e| value(&key, e))?;
+            self.lint.set_severity(rule, level);
+        }
+        for rule in &raw.lint.disable {
+            let rule = parse(rule).map_err(|e| value("lint.disable", e))?;
+            self.lint.disable(rule);
+        }
+        for rule in &raw.lint.enable {
+            let rule = parse(rule).map_err(|e| value("lint.enable", e))?;
+            if self.lint.severity(rule).is_none() {
+                self.lint.enable(rule);
+            }
+        }
+        #[cfg(feature = "spellcheck")]
+        self.lint.add_words(&raw.lint.words);
+        Ok(())
+    }
+}
+
+/// Parses a setting with the same rules as the matching CLI flag.
+fn parse<T: FromStr<Err = String>>(text: &str) -> Result<T, String> {
+    text.parse()
+}
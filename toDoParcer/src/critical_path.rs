@@ -0,0 +1,212 @@
+//! Critical path analysis over the open tasks of a document.
+//!
+//! There is no estimate attribute, so a task's duration comes from its
+//! dates: the days from `start:` to `due:`, both inclusive, when it has
+//! both, and one day otherwise. Done tasks take no time and are left out;
+//! so are references that don't resolve.
+//!
+//! Every open task is scheduled as early as its dependencies allow
+//! (the forward pass), then as late as possible without delaying the end
+//! of the whole plan (the backward pass). The difference is the task's
+//! slack; tasks without slack form the critical path. Offsets are in days
+//! from the start of the plan.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use thiserror::Error;
+
+use crate::deps::TaskRef;
+use crate::parser::{Document, Task, TaskStatus};
+
+/// Why a document can't be scheduled.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CriticalPathError {
+    /// Open tasks depend on each other in a loop.
+    #[error("dependency cycle through \"{0}\"")]
+    Cycle(String),
+}
+
+/// Placement of one open task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduledTask {
+    pub task: TaskRef,
+    /// Length in days.
+    pub duration: u32,
+    /// First day the task can start, once its dependencies are finished.
+    pub earliest_start: u32,
+    /// Last day the task can start without delaying the plan.
+    pub latest_start: u32,
+}
+
+impl ScheduledTask {
+    /// Days the task can slip without delaying the plan.
+    pub fn slack(&self) -> u32 {
+        self.latest_start - self.earliest_start
+    }
+
+    /// Day after the task finishes when started as early as possible.
+    pub fn earliest_finish(&self) -> u32 {
+        self.earliest_start + self.duration
+    }
+
+    /// Whether the task is on the critical path.
+    pub fn is_critical(&self) -> bool {
+        self.slack() == 0
+    }
+}
+
+/// Result of the analysis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    /// Every open task, in document order.
+    pub tasks: Vec<ScheduledTask>,
+    /// Days needed for the whole plan.
+    pub length: u32,
+    /// Longest chain of dependent tasks without slack, first task first.
+    pub path: Vec<TaskRef>,
+}
+
+impl Schedule {
+    /// Placement of `task`, if it is open.
+    pub fn get(&self, task: TaskRef) -> Option<&ScheduledTask> {
+        self.tasks.iter().find(|s| s.task == task)
+    }
+}
+
+/// Duration of a task in days, from its dates.
+pub fn duration(task: &Task) -> u32 {
+    match (task.start(), task.due()) {
+        (Some(start), Some(due)) if due >= start => {
+            u32::try_from((due - start).num_days() + 1).unwrap_or(u32::MAX)
+        }
+        _ => 1,
+    }
+}
+
+/// Schedules the open tasks of `doc` and finds the critical path.
+///
+/// # Returns
+/// * `Err(CriticalPathError::Cycle)` if open tasks depend on each other
+///   in a loop.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::ToDoParser;
+/// use to_do_parcer::critical_path::schedule;
+///
+/// let doc = ToDoParser::parse_document(
+///     r#"project "P" {
+///   todo: "Design", start: 2025-11-01, due: 2025-11-03,
+///   todo: "Build", depends_on: "Design",
+///   todo: "Docs",
+/// }"#,
+/// )?;
+/// let plan = schedule(&doc).unwrap();
+/// assert_eq!(plan.length, 4);
+/// let path: Vec<_> = plan.path.iter().map(|r| &r.get(&doc).unwrap().title).collect();
+/// assert_eq!(path, ["Design", "Build"]);
+/// assert_eq!(plan.tasks[2].slack(), 3);
+/// # Ok::<(), to_do_parcer::ParseError>(())
+/// ```
+pub fn schedule(doc: &Document) -> Result<Schedule, CriticalPathError> {
+    let open: Vec<TaskRef> = doc
+        .task_refs()
+        .filter(|r| r.get(doc).is_some_and(|t| t.status == TaskStatus::Todo))
+        .collect();
+    let index: HashMap<TaskRef, usize> = open.iter().enumerate().map(|(i, r)| (*r, i)).collect();
+    let task = |r: TaskRef| r.get(doc).expect("task ref is valid");
+
+    // Dependencies between open tasks, as indexes into `open`.
+    let resolved = doc.dependencies();
+    let deps: Vec<Vec<usize>> = open
+        .iter()
+        .map(|r| {
+            let mut deps: Vec<usize> = resolved
+                .of(*r)
+                .iter()
+                .filter_map(|d| index.get(d).copied())
+                .collect();
+            deps.dedup();
+            deps
+        })
+        .collect();
+    let mut dependents = vec![Vec::new(); open.len()];
+    for (i, ds) in deps.iter().enumerate() {
+        for &d in ds {
+            dependents[d].push(i);
+        }
+    }
+
+    // Kahn's algorithm; whatever is left unordered sits on a cycle.
+    let mut waiting: Vec<usize> = deps.iter().map(Vec::len).collect();
+    let mut order: Vec<usize> = (0..open.len()).filter(|&i| waiting[i] == 0).collect();
+    let mut next = 0;
+    while next < order.len() {
+        for &d in &dependents[order[next]] {
+            waiting[d] -= 1;
+            if waiting[d] == 0 {
+                order.push(d);
+            }
+        }
+        next += 1;
+    }
+    if let Some(stuck) = (0..open.len()).find(|&i| waiting[i] > 0) {
+        return Err(CriticalPathError::Cycle(task(open[stuck]).title.clone()));
+    }
+
+    let durations: Vec<u32> = open.iter().map(|r| duration(task(*r))).collect();
+    let mut earliest = vec![0u32; open.len()];
+    for &i in &order {
+        earliest[i] = deps[i]
+            .iter()
+            .map(|&d| earliest[d] + durations[d])
+            .max()
+            .unwrap_or(0);
+    }
+    let length = (0..open.len())
+        .map(|i| earliest[i] + durations[i])
+        .max()
+        .unwrap_or(0);
+    let mut latest = vec![0u32; open.len()];
+    for &i in order.iter().rev() {
+        let finish = dependents[i]
+            .iter()
+            .map(|&d| latest[d])
+            .min()
+            .unwrap_or(length);
+        latest[i] = finish - durations[i];
+    }
+
+    let tasks: Vec<ScheduledTask> = (0..open.len())
+        .map(|i| ScheduledTask {
+            task: open[i],
+            duration: durations[i],
+            earliest_start: earliest[i],
+            latest_start: latest[i],
+        })
+        .collect();
+
+    // Walk back from the first critical task that ends the plan.
+    let mut path = Vec::new();
+    let mut current =
+        (0..open.len()).find(|&i| tasks[i].is_critical() && tasks[i].earliest_finish() == length);
+    while let Some(i) = current {
+        path.push(open[i]);
+        current = deps[i].iter().copied().find(|&d| {
+            tasks[d].is_critical() && tasks[d].earliest_finish() == tasks[i].earliest_start
+        });
+    }
+    path.reverse();
+
+    Ok(Schedule {
+        tasks,
+        length,
+        path,
+    })
+}
+
+/// Calendar date of a day offset in a plan that starts on `start`.
+pub fn plan_date(start: NaiveDate, offset: u32) -> NaiveDate {
+    start + chrono::Days::new(u64::from(offset))
+}
@@ -0,0 +1,225 @@
+//! Detection and merging of duplicate tasks within a project.
+//!
+//! Two titles are duplicates when they are equal after normalizing (case,
+//! punctuation and runs of spaces are ignored), or when their normalized
+//! forms differ in at most one character in ten and contain the same
+//! digits — enough to catch "Fix login bug" / "Fix login bugs", but not
+//! "Buy milk" / "Buy silk" or "Release 1.0" / "Release 2.0".
+//!
+//! Each group of duplicates is kept as its first task, which takes over the
+//! attributes of the others: done if any copy is done, the highest priority,
+//! the earliest start and due dates, the union of the tags, and the id,
+//! assignee, dependency and completion date of the first copy that has one.
+
+use crate::deps::{Dependencies, TaskRef};
+use crate::edit::Editor;
+use crate::parser::{Document, Task, TaskStatus};
+use crate::pretty::format_task;
+use crate::sort::SortKey;
+use crate::suggest::edit_distance;
+
+/// Tasks of one project that are copies of each other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    /// The task that stays: the first of the group in the file.
+    pub keep: TaskRef,
+    /// The copies after it, in file order.
+    pub duplicates: Vec<TaskRef>,
+}
+
+/// Title reduced to lowercase words of letters and digits.
+fn normalize(title: &str) -> String {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether two titles name the same task, as described above.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::dedupe::similar_titles;
+///
+/// assert!(similar_titles("Fix login bug", "fix  login-bug!"));
+/// assert!(similar_titles("Fix login bug", "Fix login bugs"));
+/// assert!(!similar_titles("Buy milk", "Buy silk"));
+/// ```
+pub fn similar_titles(a: &str, b: &str) -> bool {
+    let (a, b) = (normalize(a), normalize(b));
+    if a == b {
+        return true;
+    }
+    let digits = |s: &str| s.chars().filter(char::is_ascii_digit).collect::<String>();
+    if digits(&a) != digits(&b) {
+        return false;
+    }
+    let longest = a.chars().count().max(b.chars().count());
+    edit_distance(&a, &b) * 10 <= longest
+}
+
+/// Finds the groups of duplicate tasks in each project of `doc`.
+///
+/// A task joins the group of the first earlier task it is similar to.
+pub fn find_duplicates(doc: &Document) -> Vec<DuplicateGroup> {
+    let mut groups = Vec::new();
+    for (p, project) in doc.projects.iter().enumerate() {
+        let mut project_groups: Vec<DuplicateGroup> = Vec::new();
+        for (t, task) in project.tasks.iter().enumerate() {
+            let r = TaskRef {
+                project: p,
+                task: t,
+            };
+            let home = project_groups
+                .iter_mut()
+                .find(|g| similar_titles(&project.tasks[g.keep.task].title, &task.title));
+            match home {
+                Some(group) => group.duplicates.push(r),
+                None => project_groups.push(DuplicateGroup {
+                    keep: r,
+                    duplicates: Vec::new(),
+                }),
+            }
+        }
+        groups.extend(
+            project_groups
+                .into_iter()
+                .filter(|g| !g.duplicates.is_empty()),
+        );
+    }
+    groups
+}
+
+/// The kept task of `group` with the attributes of its copies merged in.
+pub fn merge_group(doc: &Document, group: &DuplicateGroup) -> Task {
+    merge_with(doc, &doc.dependencies(), group)
+}
+
+/// [`merge_group`] with the dependencies of `doc` already resolved.
+fn merge_with(doc: &Document, deps: &Dependencies, group: &DuplicateGroup) -> Task {
+    let keep = group.keep.get(doc).expect("task ref is valid");
+    let copies: Vec<&Task> = group
+        .duplicates
+        .iter()
+        .map(|r| r.get(doc).expect("task ref is valid"))
+        .collect();
+    let all = || std::iter::once(keep).chain(copies.iter().copied());
+
+    let mut merged = keep.clone();
+    if all().any(|t| t.status == TaskStatus::Done) {
+        merged.status = TaskStatus::Done;
+    }
+    merged.priority = all()
+        .min_by(|a, b| SortKey::Priority.compare(a, b))
+        .and_then(|t| t.priority);
+    merged.start_date = all()
+        .filter_map(|t| t.start().map(|d| (d, &t.start_date)))
+        .min_by_key(|(d, _)| *d)
+        .map_or(keep.start_date.clone(), |(_, s)| s.clone());
+    merged.due_date = all()
+        .filter_map(|t| t.due().map(|d| (d, &t.due_date)))
+        .min_by_key(|(d, _)| *d)
+        .map_or(keep.due_date.clone(), |(_, s)| s.clone());
+    merged.id = all().find_map(|t| t.id.clone());
+    if let Some(t) = all().find(|t| t.assignee.is_some()) {
+        merged.assignee = t.assignee.clone();
+        merged.assignee_as_written = t.assignee_as_written.clone();
+    }
+    merged.completed = all().find_map(|t| t.completed.clone());
+    // A dependency on another copy would point at the merged task itself.
+    merged.depends_on = std::iter::once(group.keep)
+        .chain(group.duplicates.iter().copied())
+        .filter(|r| {
+            deps.of(*r)
+                .is_none_or(|d| d != group.keep && !group.duplicates.contains(&d))
+        })
+        .find_map(|r| r.get(doc)?.depends_on.clone());
+    for tag in all().flat_map(|t| &t.tags) {
+        if !merged.tags.contains(tag) {
+            merged.tags.push(tag.clone());
+        }
+    }
+    merged
+}
+
+impl Editor<'_> {
+    /// Merges every group of duplicates: the kept task is rewritten with
+    /// the merged attributes and the copies are removed. References to a
+    /// copy are pointed at the kept task.
+    ///
+    /// # Returns
+    /// * The groups that were merged.
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::edit::Editor;
+    ///
+    /// let source = "project \"P\" {\n  todo: \"Fix bug\",\n  done: \"fix bug\", @high,\n}\n";
+    /// let mut editor = Editor::new(source)?;
+    /// assert_eq!(editor.dedupe().len(), 1);
+    /// assert_eq!(editor.finish(), "project \"P\" {\n  done: \"Fix bug\", @high,\n}\n");
+    /// # Ok::<(), to_do_parcer::edit::EditError>(())
+    /// ```
+    pub fn dedupe(&mut self) -> Vec<DuplicateGroup> {
+        let doc = self.document().clone();
+        let groups = find_duplicates(&doc);
+        let deps = doc.dependencies();
+        let removed: Vec<TaskRef> = groups
+            .iter()
+            .flat_map(|g| g.duplicates.iter().copied())
+            .collect();
+        let keeper_of = |r: TaskRef| {
+            groups
+                .iter()
+                .find(|g| g.duplicates.contains(&r))
+                .map(|g| g.keep)
+        };
+
+        let mut rewritten = Vec::new();
+        for group in &groups {
+            let merged = merge_with(&doc, &deps, group);
+            let keep = group.keep.get(&doc).expect("task ref is valid");
+            if !same_attributes(keep, &merged) {
+                self.replace_at(group.keep, format_task(&merged, doc.version));
+                rewritten.push(group.keep);
+            }
+            for r in &group.duplicates {
+                self.remove_at(*r);
+            }
+        }
+
+        for r in doc.task_refs() {
+            if removed.contains(&r) || rewritten.contains(&r) {
+                continue;
+            }
+            let task = r.get(&doc).expect("task ref is valid");
+            let Some(keep) = deps.of(r).and_then(keeper_of) else {
+                continue;
+            };
+            let title = &keep.get(&doc).expect("task ref is valid").title;
+            let reference = if doc.resolve(title, Some(r.project)) == Some(keep) {
+                title.clone()
+            } else {
+                let project = &keep.project(&doc).expect("task ref is valid").name;
+                format!("{}/{}", project, title)
+            };
+            self.replace_reference(task, reference);
+        }
+        groups
+    }
+}
+
+/// Whether `a` and `b` would be written the same way.
+fn same_attributes(a: &Task, b: &Task) -> bool {
+    a.status == b.status
+        && a.id == b.id
+        && a.priority == b.priority
+        && a.start_date == b.start_date
+        && a.due_date == b.due_date
+        && a.completed == b.completed
+        && a.assignee == b.assignee
+        && a.depends_on == b.depends_on
+        && a.tags == b.tags
+}
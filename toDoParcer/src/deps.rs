@@ -0,0 +1,367 @@
+//! Resolution of `depends_on` references to the tasks they name.
+//!
+//! A reference of the form `"#id"` names the task with that `id:`. Any
+//! other reference is matched, in order, against:
+//! 1. task titles in the same project,
+//! 2. the qualified form `"Project/Task"`,
+//! 3. task titles in any other project, in document order.
+
+use std::collections::HashMap;
+
+use crate::parser::{Document, Project, Task, TaskStatus};
+use crate::sort::SortKey;
+
+/// Handle to a task inside a `Document`: project index plus task index.
+///
+/// Handles stay valid as long as the document's projects and tasks are not
+/// reordered, added or removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TaskRef {
+    pub project: usize,
+    pub task: usize,
+}
+
+impl TaskRef {
+    /// Returns the referenced task, if the handle is valid for `doc`.
+    pub fn get<'d>(&self, doc: &'d Document) -> Option<&'d Task> {
+        doc.projects.get(self.project)?.tasks.get(self.task)
+    }
+
+    /// Returns the project of the referenced task.
+    pub fn project<'d>(&self, doc: &'d Document) -> Option<&'d Project> {
+        doc.projects.get(self.project)
+    }
+}
+
+/// The resolved `depends_on` of every task of a document, built once by
+/// [`Document::dependencies`].
+///
+/// Looking a task up is an index into a table, so walking the dependencies
+/// of every task is linear in the size of the document. Like [`TaskRef`],
+/// the table describes the document as it was when it was built.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Dependencies {
+    /// Target of each task's reference, by project and task index.
+    targets: Vec<Vec<Option<TaskRef>>>,
+}
+
+impl Dependencies {
+    /// Returns the task that `task` depends on.
+    ///
+    /// # Returns
+    /// * `Some(TaskRef)` for the task its `depends_on` resolves to
+    /// * `None` if it has no `depends_on`, the reference matches nothing or
+    ///   the handle isn't valid for the document
+    pub fn of(&self, task: TaskRef) -> Option<TaskRef> {
+        *self.targets.get(task.project)?.get(task.task)?
+    }
+}
+
+/// Lookup tables answering [`Document::resolve`] for every task at once.
+struct Index<'d> {
+    /// First task with each `id`.
+    ids: HashMap<&'d str, TaskRef>,
+    /// First task with each title, per project.
+    titles: Vec<HashMap<&'d str, usize>>,
+    /// First project with each name.
+    projects: HashMap<&'d str, usize>,
+    /// First task with each title in the document.
+    anywhere: HashMap<&'d str, TaskRef>,
+}
+
+impl<'d> Index<'d> {
+    fn new(doc: &'d Document) -> Self {
+        let mut index = Index {
+            ids: HashMap::new(),
+            titles: Vec::with_capacity(doc.projects.len()),
+            projects: HashMap::new(),
+            anywhere: HashMap::new(),
+        };
+        for (p, project) in doc.projects.iter().enumerate() {
+            index.projects.entry(&project.name).or_insert(p);
+            let mut titles = HashMap::new();
+            for (t, task) in project.tasks.iter().enumerate() {
+                let r = TaskRef {
+                    project: p,
+                    task: t,
+                };
+                if let Some(id) = &task.id {
+                    index.ids.entry(id).or_insert(r);
+                }
+                titles.entry(task.title.as_str()).or_insert(t);
+                index.anywhere.entry(&task.title).or_insert(r);
+            }
+            index.titles.push(titles);
+        }
+        index
+    }
+
+    /// Same as [`Document::resolve`].
+    fn resolve(&self, reference: &str, from_project: Option<usize>) -> Option<TaskRef> {
+        if let Some(id) = reference.strip_prefix('#') {
+            return self.ids.get(id).copied();
+        }
+        let by_title = |p: usize, title: &str| {
+            self.titles[p].get(title).map(|&t| TaskRef {
+                project: p,
+                task: t,
+            })
+        };
+        if let Some(found) = from_project.and_then(|p| by_title(p, reference)) {
+            return Some(found);
+        }
+        let qualified = reference
+            .split_once('/')
+            .and_then(|(project, title)| by_title(*self.projects.get(project)?, title));
+        // The first task with the title anywhere isn't in `from_project`:
+        // that one would have been found above.
+        qualified.or_else(|| self.anywhere.get(reference).copied())
+    }
+}
+
+impl Document {
+    /// Finds the handle of a task borrowed from this document.
+    ///
+    /// Tasks are compared by address, so clones of a task are not found.
+    pub fn locate(&self, task: &Task) -> Option<TaskRef> {
+        self.task_refs()
+            .find(|r| r.get(self).is_some_and(|t| std::ptr::eq(t, task)))
+    }
+
+    /// Iterates over handles to every task, in source order.
+    pub fn task_refs(&self) -> impl Iterator<Item = TaskRef> + '_ {
+        self.projects.iter().enumerate().flat_map(|(p, project)| {
+            (0..project.tasks.len()).map(move |t| TaskRef {
+                project: p,
+                task: t,
+            })
+        })
+    }
+
+    /// Resolves a `depends_on` reference as seen from the project with
+    /// index `from_project` (if any).
+    ///
+    /// # Arguments
+    /// * `reference` - Title, `Project/Task` path or `#id` of the target
+    /// * `from_project` - Project the reference is written in
+    ///
+    /// # Returns
+    /// * `Some(TaskRef)` for the first matching task
+    /// * `None` if nothing matches
+    pub fn resolve(&self, reference: &str, from_project: Option<usize>) -> Option<TaskRef> {
+        if let Some(id) = reference.strip_prefix('#') {
+            return self
+                .task_refs()
+                .find(|r| r.get(self).is_some_and(|t| t.id.as_deref() == Some(id)));
+        }
+
+        let by_title = |p: usize| {
+            self.projects[p]
+                .tasks
+                .iter()
+                .position(|t| t.title == reference)
+                .map(|t| TaskRef {
+                    project: p,
+                    task: t,
+                })
+        };
+
+        if let Some(found) = from_project.and_then(by_title) {
+            return Some(found);
+        }
+
+        let qualified = reference.split_once('/').and_then(|(project, title)| {
+            let p = self.projects.iter().position(|p| p.name == project)?;
+            let t = self.projects[p]
+                .tasks
+                .iter()
+                .position(|t| t.title == title)?;
+            Some(TaskRef {
+                project: p,
+                task: t,
+            })
+        });
+        if qualified.is_some() {
+            return qualified;
+        }
+
+        (0..self.projects.len())
+            .filter(|p| Some(*p) != from_project)
+            .find_map(by_title)
+    }
+
+    /// Resolves the `depends_on` of every task, each as seen from its own
+    /// project.
+    ///
+    /// Use this rather than [`Task::resolved_dependencies`] when looking at
+    /// the dependencies of many tasks: the table is built in one pass.
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::ToDoParser;
+    ///
+    /// let doc = ToDoParser::parse_document(
+    ///     r#"project "P" { todo: "A", todo: "B", depends_on: "A", }"#,
+    /// )?;
+    /// let deps = doc.dependencies();
+    /// for r in doc.task_refs() {
+    ///     if let Some(dep) = deps.of(r) {
+    ///         assert_eq!(dep.get(&doc).unwrap().title, "A");
+    ///     }
+    /// }
+    /// # Ok::<(), to_do_parcer::ParseError>(())
+    /// ```
+    pub fn dependencies(&self) -> Dependencies {
+        let index = Index::new(self);
+        let targets = (self.projects.iter().enumerate())
+            .map(|(p, project)| {
+                (project.tasks.iter())
+                    .map(|task| {
+                        let reference = task.depends_on.as_deref()?;
+                        index.resolve(reference, Some(p))
+                    })
+                    .collect()
+            })
+            .collect();
+        Dependencies { targets }
+    }
+
+    /// Returns the open tasks whose dependencies are all done, most urgent
+    /// first: by priority, then by due date, then in source order.
+    ///
+    /// References that don't resolve to a task don't block anything.
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::ToDoParser;
+    ///
+    /// let doc = ToDoParser::parse_document(
+    ///     r#"project "P" { todo: "A", todo: "B", depends_on: "A", todo: "C", @high, }"#,
+    /// )?;
+    /// let titles: Vec<_> = doc.actionable().iter().map(|(_, t)| t.title.as_str()).collect();
+    /// assert_eq!(titles, ["C", "A"]);
+    /// # Ok::<(), to_do_parcer::ParseError>(())
+    /// ```
+    pub fn actionable(&self) -> Vec<(&Project, &Task)> {
+        let deps = self.dependencies();
+        let mut ready: Vec<_> = self
+            .task_refs()
+            .filter(|r| {
+                deps.of(*r)
+                    .and_then(|d| d.get(self))
+                    .is_none_or(|dep| dep.status == TaskStatus::Done)
+            })
+            .filter_map(|r| Some((r.project(self)?, r.get(self)?)))
+            .filter(|(_, t)| t.status == TaskStatus::Todo)
+            .collect();
+        ready.sort_by(|(_, a), (_, b)| {
+            SortKey::Priority
+                .compare(a, b)
+                .then_with(|| SortKey::Due.compare(a, b))
+        });
+        ready
+    }
+
+    /// Finds the loops in the dependency graph.
+    ///
+    /// Each cycle lists its tasks in dependency order, starting with the one
+    /// that comes first in the document: `[A, B]` means A depends on B and
+    /// B depends on A. A task that depends on itself is a cycle of one.
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::ToDoParser;
+    ///
+    /// let doc = ToDoParser::parse_document(
+    ///     r#"project "P" { todo: "A", depends_on: "B", todo: "B", depends_on: "A", todo: "C", }"#,
+    /// )?;
+    /// let cycles = doc.dependency_cycles();
+    /// let titles: Vec<_> = cycles[0].iter().map(|r| &r.get(&doc).unwrap().title).collect();
+    /// assert_eq!(titles, ["A", "B"]);
+    /// # Ok::<(), to_do_parcer::ParseError>(())
+    /// ```
+    pub fn dependency_cycles(&self) -> Vec<Vec<TaskRef>> {
+        let refs: Vec<TaskRef> = self.task_refs().collect();
+        let index: HashMap<TaskRef, usize> =
+            refs.iter().enumerate().map(|(i, r)| (*r, i)).collect();
+        let deps = self.dependencies();
+        let edges: Vec<Vec<usize>> = refs
+            .iter()
+            .map(|r| deps.of(*r).iter().map(|d| index[d]).collect())
+            .collect();
+
+        // Depth-first search; an edge back onto the current path closes a cycle.
+        let mut visited = vec![false; refs.len()];
+        let mut on_path = vec![false; refs.len()];
+        let mut cycles = Vec::new();
+        for root in 0..refs.len() {
+            if visited[root] {
+                continue;
+            }
+            let mut path = vec![root];
+            let mut next_edge = vec![0];
+            visited[root] = true;
+            on_path[root] = true;
+            while let Some(&node) = path.last() {
+                let edge = next_edge
+                    .last_mut()
+                    .expect("path and next_edge have the same length");
+                let Some(&target) = edges[node].get(*edge) else {
+                    on_path[node] = false;
+                    path.pop();
+                    next_edge.pop();
+                    continue;
+                };
+                *edge += 1;
+                if on_path[target] {
+                    let start = path
+                        .iter()
+                        .position(|&n| n == target)
+                        .expect("target is on the path");
+                    let mut cycle: Vec<TaskRef> = path[start..].iter().map(|&n| refs[n]).collect();
+                    let first = (0..cycle.len()).min_by_key(|&i| cycle[i]).unwrap_or(0);
+                    cycle.rotate_left(first);
+                    cycles.push(cycle);
+                } else if !visited[target] {
+                    visited[target] = true;
+                    on_path[target] = true;
+                    path.push(target);
+                    next_edge.push(0);
+                }
+            }
+        }
+        cycles.sort();
+        cycles
+    }
+}
+
+impl Task {
+    /// Resolves this task's `depends_on` into handles into `doc`.
+    ///
+    /// The task should be borrowed from `doc`, so that references are
+    /// looked up in its own project first; otherwise only qualified and
+    /// document-wide matches are found. Unresolved references are skipped.
+    ///
+    /// Finding the task takes a scan of `doc`; for the dependencies of
+    /// every task, use [`Document::dependencies`].
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::ToDoParser;
+    ///
+    /// let doc = ToDoParser::parse_document(
+    ///     r#"project "P" { todo: "A", todo: "B", depends_on: "A", }"#,
+    /// )?;
+    /// let b = &doc.projects[0].tasks[1];
+    /// let deps = b.resolved_dependencies(&doc);
+    /// assert_eq!(deps[0].get(&doc).unwrap().title, "A");
+    /// # Ok::<(), to_do_parcer::ParseError>(())
+    /// ```
+    pub fn resolved_dependencies(&self, doc: &Document) -> Vec<TaskRef> {
+        let home = doc.locate(self).map(|r| r.project);
+        self.depends_on
+            .iter()
+            .filter_map(|reference| doc.resolve(reference, home))
+            .collect()
+    }
+}
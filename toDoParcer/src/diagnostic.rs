@@ -0,0 +1,271 @@
+//! Problems reported by the parser, `validate` and `lint`, in one shape.
+//!
+//! Every subsystem turns what it finds into a [`Diagnostic`], so the CLI
+//! prints, counts and filters them the same way whatever their origin.
+//! A [`DiagnosticReport`] collects them for `--format json`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::parser::Span;
+
+/// How serious a diagnostic is, from least to most.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Worth knowing, but nothing is wrong.
+    Hint,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// Every severity, from least to most serious.
+    pub const ALL: &'static [Severity] = &[Severity::Hint, Severity::Warning, Severity::Error];
+
+    /// Name of the severity as shown in reports and accepted by `FromStr`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Hint => "hint",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Severity::ALL
+            .iter()
+            .find(|severity| severity.as_str().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<_> = Severity::ALL.iter().map(|s| s.as_str()).collect();
+                format!(
+                    "unknown severity '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// A single problem found in a document.
+///
+/// Serializes with the fields below; `span`, `suggestion` and `fix` are
+/// `null` when unknown.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Stable, kebab-case identifier of the check, e.g. `empty-project`.
+    pub code: &'static str,
+    /// 1-based line the diagnostic points at; 0 when it has no position,
+    /// such as an unreadable file.
+    pub line: usize,
+    /// Exact location of the offending text, when it is known.
+    pub span: Option<Span>,
+    pub message: String,
+    /// What the author probably meant, e.g. the closest existing task
+    /// title for a misspelled dependency. The message mentions it too.
+    pub suggestion: Option<String>,
+    /// Edit that resolves the problem, when it can be applied without
+    /// asking the author; see [`crate::fix`].
+    pub fix: Option<Fix>,
+}
+
+/// A machine-applicable edit: replace the text of `span` with
+/// `replacement`. An empty span is an insertion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Fix {
+    pub span: Span,
+    pub replacement: String,
+}
+
+impl Fix {
+    /// `source` with the edit applied.
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::diagnostic::Fix;
+    /// use to_do_parcer::parser::Span;
+    ///
+    /// let fix = Fix {
+    ///     span: Span { start: 9, end: 9, line: 1, column: 10 },
+    ///     replacement: ",".to_string(),
+    /// };
+    /// assert_eq!(fix.apply("todo: \"A\" @high"), "todo: \"A\", @high");
+    /// ```
+    pub fn apply(&self, source: &str) -> String {
+        let mut text = String::with_capacity(source.len() + self.replacement.len());
+        text.push_str(&source[..self.span.start]);
+        text.push_str(&self.replacement);
+        text.push_str(&source[self.span.end..]);
+        text
+    }
+}
+
+impl fmt::Display for Fix {
+    /// Formats as ``insert `,` `` or ``replace with `2025-12-31` ``.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.span.start == self.span.end {
+            write!(f, "insert `{}`", self.replacement)
+        } else {
+            write!(f, "replace with `{}`", self.replacement)
+        }
+    }
+}
+
+impl Diagnostic {
+    /// A diagnostic without span or suggestion.
+    pub fn new(severity: Severity, code: &'static str, line: usize, message: String) -> Self {
+        Diagnostic {
+            severity,
+            code,
+            line,
+            span: None,
+            message,
+            suggestion: None,
+            fix: None,
+        }
+    }
+
+    /// `line` or `line:column` of the diagnostic, as shown in front of it;
+    /// empty without a position.
+    pub fn location(&self) -> String {
+        match self.span {
+            Some(span) => format!("{}:{}", span.line, span.column),
+            None if self.line == 0 => String::new(),
+            None => self.line.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    /// Formats as `line: severity[code]: message`, or
+    /// `line:column: severity[code]: message` when the span is known.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let location = self.location();
+        if !location.is_empty() {
+            write!(f, "{}: ", location)?;
+        }
+        write!(f, "{}[{}]: {}", self.severity, self.code, self.message)
+    }
+}
+
+/// How `validate` and `lint` print their diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticFormat {
+    /// One `file:line: severity[code]: message` line each, then a summary.
+    Text,
+    /// A [`DiagnosticReport`] as JSON.
+    Json,
+}
+
+impl DiagnosticFormat {
+    /// Every format, in the order shown in help texts.
+    pub const ALL: &'static [DiagnosticFormat] = &[DiagnosticFormat::Text, DiagnosticFormat::Json];
+
+    /// Name of the format as accepted by `FromStr`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagnosticFormat::Text => "text",
+            DiagnosticFormat::Json => "json",
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DiagnosticFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DiagnosticFormat::ALL
+            .iter()
+            .find(|f| f.as_str().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<_> = DiagnosticFormat::ALL.iter().map(|f| f.as_str()).collect();
+                format!(
+                    "unknown format '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// A diagnostic together with the file it was found in.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileDiagnostic {
+    /// Path of the file as given on the command line.
+    pub file: String,
+    #[serde(flatten)]
+    pub diagnostic: Diagnostic,
+}
+
+/// Diagnostics of one or more files with their totals, the JSON output
+/// of `validate` and `lint`.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::diagnostic::{Diagnostic, DiagnosticReport, Severity};
+///
+/// let mut report = DiagnosticReport::default();
+/// report.push("a.todo", [Diagnostic::new(Severity::Warning, "empty-project", 3, "project \"P\" has no tasks".into())]);
+/// let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+/// assert_eq!(json["diagnostics"][0]["file"], "a.todo");
+/// assert_eq!(json["diagnostics"][0]["severity"], "warning");
+/// assert_eq!(json["diagnostics"][0]["line"], 3);
+/// assert_eq!(json["summary"]["warnings"], 1);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DiagnosticReport {
+    /// Every diagnostic, file by file in the order they were pushed.
+    pub diagnostics: Vec<FileDiagnostic>,
+    pub summary: Summary,
+}
+
+/// Number of diagnostics of each severity in a [`DiagnosticReport`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Summary {
+    pub errors: usize,
+    pub warnings: usize,
+    pub hints: usize,
+}
+
+impl DiagnosticReport {
+    /// Adds the diagnostics found in `file` and counts them.
+    pub fn push(&mut self, file: &str, diagnostics: impl IntoIterator<Item = Diagnostic>) {
+        for diagnostic in diagnostics {
+            match diagnostic.severity {
+                Severity::Error => self.summary.errors += 1,
+                Severity::Warning => self.summary.warnings += 1,
+                Severity::Hint => self.summary.hints += 1,
+            }
+            self.diagnostics.push(FileDiagnostic {
+                file: file.to_string(),
+                diagnostic,
+            });
+        }
+    }
+
+    /// The report as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("diagnostics serialize to JSON")
+    }
+}
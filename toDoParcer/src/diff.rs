@@ -0,0 +1,285 @@
+//! Semantic comparison of two versions of a document.
+//!
+//! Instead of lines, the diff reports what happened to tasks: added,
+//! removed, completed, reopened, or attributes changed. Tasks are paired
+//! up by `id:` first, then by project and title, and finally by title
+//! alone, which reports a task that moved to another project as a change
+//! of its `project` field.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::deps::TaskRef;
+use crate::parser::{Document, Task, TaskStatus};
+
+/// A task attribute that can differ between two versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    Project,
+    Title,
+    Priority,
+    Start,
+    Due,
+    Completed,
+    Assignee,
+    DependsOn,
+    Tags,
+}
+
+impl Field {
+    /// Name of the field as written in the text and JSON output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Field::Project => "project",
+            Field::Title => "title",
+            Field::Priority => "priority",
+            Field::Start => "start",
+            Field::Due => "due",
+            Field::Completed => "completed",
+            Field::Assignee => "assignee",
+            Field::DependsOn => "depends_on",
+            Field::Tags => "tags",
+        }
+    }
+}
+
+/// One difference between two documents.
+///
+/// `project` and `title` name the task as it appears in the new version,
+/// or in the old one for removed tasks.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Change {
+    Added {
+        project: String,
+        title: String,
+    },
+    Removed {
+        project: String,
+        title: String,
+    },
+    Completed {
+        project: String,
+        title: String,
+    },
+    Reopened {
+        project: String,
+        title: String,
+    },
+    Changed {
+        project: String,
+        title: String,
+        field: Field,
+        /// Old value; `None` if the attribute was missing.
+        old: Option<String>,
+        /// New value; `None` if the attribute was removed.
+        new: Option<String>,
+    },
+}
+
+impl fmt::Display for Change {
+    /// Formats as one line: `+`, `-`, `x`, `o` or `~`, then `Project/Title`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Added { project, title } => write!(f, "+ {}/{}", project, title),
+            Change::Removed { project, title } => write!(f, "- {}/{}", project, title),
+            Change::Completed { project, title } => write!(f, "x {}/{}", project, title),
+            Change::Reopened { project, title } => write!(f, "o {}/{}", project, title),
+            Change::Changed {
+                project,
+                title,
+                field,
+                old,
+                new,
+            } => write!(
+                f,
+                "~ {}/{}: {} {} -> {}",
+                project,
+                title,
+                field.as_str(),
+                old.as_deref().unwrap_or("none"),
+                new.as_deref().unwrap_or("none")
+            ),
+        }
+    }
+}
+
+/// All differences between two documents.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Diff {
+    /// Changes in the order of the new document; removals come last.
+    pub changes: Vec<Change>,
+}
+
+impl Diff {
+    /// Compares `old` with `new`.
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::ToDoParser;
+    /// use to_do_parcer::diff::Diff;
+    ///
+    /// let old = ToDoParser::parse_document(r#"project "P" { todo: "A", }"#)?;
+    /// let new = ToDoParser::parse_document(r#"project "P" { done: "A", todo: "B", }"#)?;
+    /// let diff = Diff::compute(&old, &new);
+    /// assert_eq!(diff.to_string(), "x P/A\n+ P/B\n");
+    /// # Ok::<(), to_do_parcer::ParseError>(())
+    /// ```
+    pub fn compute(old: &Document, new: &Document) -> Diff {
+        let matching = TaskMatching::new(old, new);
+        let mut changes = Vec::new();
+
+        for (new_ref, old_ref) in new.task_refs().zip(&matching.old_for_new) {
+            let project = &new.projects[new_ref.project].name;
+            let task = task_at(new, new_ref);
+            let Some(old_ref) = old_ref else {
+                changes.push(Change::Added {
+                    project: project.clone(),
+                    title: task.title.clone(),
+                });
+                continue;
+            };
+
+            let before = task_at(old, *old_ref);
+            let old_project = &old.projects[old_ref.project].name;
+            match (before.status, task.status) {
+                (TaskStatus::Todo, TaskStatus::Done) => changes.push(Change::Completed {
+                    project: project.clone(),
+                    title: task.title.clone(),
+                }),
+                (TaskStatus::Done, TaskStatus::Todo) => changes.push(Change::Reopened {
+                    project: project.clone(),
+                    title: task.title.clone(),
+                }),
+                _ => {}
+            }
+
+            let mut fields = vec![(
+                Field::Project,
+                Some(old_project.clone()),
+                Some(project.clone()),
+            )];
+            fields.extend(
+                field_values(before)
+                    .into_iter()
+                    .zip(field_values(task))
+                    .map(|((field, old), (_, new))| (field, old, new)),
+            );
+            for (field, old_value, new_value) in fields {
+                // A completion date appearing with the status flip is part
+                // of the `Completed` change.
+                let implied = field == Field::Completed
+                    && before.status != task.status
+                    && old_value.is_none();
+                if old_value != new_value && !implied {
+                    changes.push(Change::Changed {
+                        project: project.clone(),
+                        title: task.title.clone(),
+                        field,
+                        old: old_value,
+                        new: new_value,
+                    });
+                }
+            }
+        }
+
+        for old_ref in old.task_refs() {
+            if !matching.old_for_new.contains(&Some(old_ref)) {
+                let task = task_at(old, old_ref);
+                changes.push(Change::Removed {
+                    project: old.projects[old_ref.project].name.clone(),
+                    title: task.title.clone(),
+                });
+            }
+        }
+
+        Diff { changes }
+    }
+
+    /// Returns `true` if the documents have the same tasks.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for Diff {
+    /// Formats one change per line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+/// Pairing of the tasks of two documents.
+pub(crate) struct TaskMatching {
+    /// For each task of the new document, in `task_refs` order, the old
+    /// task it corresponds to.
+    pub(crate) old_for_new: Vec<Option<TaskRef>>,
+}
+
+impl TaskMatching {
+    /// Pairs tasks by id, then by project and title, then by title.
+    pub(crate) fn new(old: &Document, new: &Document) -> TaskMatching {
+        let old_refs: Vec<TaskRef> = old.task_refs().collect();
+        let new_refs: Vec<TaskRef> = new.task_refs().collect();
+        let mut taken = vec![false; old_refs.len()];
+        let mut old_for_new = vec![None; new_refs.len()];
+
+        let same = |pass: usize, o: TaskRef, n: TaskRef| {
+            let (before, after) = (task_at(old, o), task_at(new, n));
+            match pass {
+                0 => before.id.is_some() && before.id == after.id,
+                1 => {
+                    old.projects[o.project].name == new.projects[n.project].name
+                        && before.title == after.title
+                }
+                _ => before.title == after.title,
+            }
+        };
+
+        for pass in 0..3 {
+            for (n, slot) in old_for_new.iter_mut().enumerate() {
+                if slot.is_some() {
+                    continue;
+                }
+                if let Some(o) =
+                    (0..old_refs.len()).find(|&o| !taken[o] && same(pass, old_refs[o], new_refs[n]))
+                {
+                    taken[o] = true;
+                    *slot = Some(old_refs[o]);
+                }
+            }
+        }
+
+        TaskMatching { old_for_new }
+    }
+}
+
+/// Returns the task behind a handle taken from `doc` itself.
+fn task_at(doc: &Document, r: TaskRef) -> &Task {
+    r.get(doc).expect("handle comes from the same document")
+}
+
+/// Values of every attribute compared by the diff, except the project.
+fn field_values(task: &Task) -> Vec<(Field, Option<String>)> {
+    vec![
+        (Field::Title, Some(task.title.clone())),
+        (
+            Field::Priority,
+            task.priority.map(|p| p.as_str().to_string()),
+        ),
+        (Field::Start, task.start_date.clone()),
+        (Field::Due, task.due_date.clone()),
+        (Field::Completed, task.completed.clone()),
+        (Field::Assignee, task.assignee.clone()),
+        (Field::DependsOn, task.depends_on.clone()),
+        (
+            Field::Tags,
+            (!task.tags.is_empty()).then(|| task.tags.join(", ")),
+        ),
+    ]
+}
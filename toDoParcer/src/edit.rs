@@ -0,0 +1,1089 @@
+//! Format-preserving edits of `.todo` source text.
+//!
+//! Instead of re-printing the whole document (as `pretty` does), an
+//! [`Editor`] turns each change into a small [`SourceEdit`] on the original
+//! text, using the spans recorded by the parser. Everything the change does
+//! not touch — spacing, attribute order, comments — is left exactly as the
+//! author wrote it.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use chrono::NaiveDate;
+use pest::Parser;
+use thiserror::Error;
+
+use crate::deps::TaskRef;
+use crate::options::{IncludePolicy, ParseOptions};
+use crate::parser::{DEFAULT_VERSION, SUPPORTED_VERSIONS, Span, normalize_source, tag_keyword};
+use crate::parser::{Document, ParseError, Priority, Project, Rule, Task, TaskStatus, ToDoParser};
+use crate::pretty::{format_project, format_task};
+use crate::sort::SortKey;
+
+/// Indentation used for tasks when there is no existing task to copy it from.
+const DEFAULT_INDENT: &str = "  ";
+
+/// Errors from editing a document.
+#[derive(Debug, Error)]
+pub enum EditError {
+    /// The source text doesn't parse.
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+
+    /// No task matches the selector.
+    #[error("No task matches \"{0}\"")]
+    TaskNotFound(String),
+
+    /// A value can't be written in the DSL, e.g. a title containing `"`.
+    #[error("Cannot write {what} {value:?}: {reason}")]
+    Unrepresentable {
+        what: &'static str,
+        value: String,
+        reason: &'static str,
+    },
+
+    /// The task to remove is a dependency of other tasks.
+    #[error("\"{task}\" is needed by {}; use --force to remove it anyway", dependents.join(", "))]
+    HasDependents {
+        task: String,
+        dependents: Vec<String>,
+    },
+
+    /// No project has the given name.
+    #[error("No project named \"{0}\"")]
+    ProjectNotFound(String),
+
+    /// A project with the new name already exists.
+    #[error("A project named \"{0}\" already exists")]
+    ProjectExists(String),
+
+    /// Several tasks match the selector.
+    #[error("\"{selector}\" is ambiguous: it matches tasks in {}", projects.join(", "))]
+    Ambiguous {
+        selector: String,
+        projects: Vec<String>,
+    },
+
+    /// The task to move and the destination project are in different
+    /// files, one of them included by the other.
+    #[error("\"{task}\" and project \"{project}\" are in different files")]
+    DifferentFiles { task: String, project: String },
+}
+
+/// Attribute changes for [`Editor::update_task`].
+///
+/// Each field is `None` to leave the attribute alone, `Some(None)` to
+/// remove it and `Some(Some(value))` to set it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TaskChanges {
+    pub priority: Option<Option<Priority>>,
+    pub due: Option<Option<NaiveDate>>,
+    /// Assignee name, without the `@`.
+    pub assignee: Option<Option<String>>,
+    pub depends_on: Option<Option<String>>,
+}
+
+/// Replacement of a byte range of the source text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceEdit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+/// Applies non-overlapping edits to `source`.
+///
+/// Edits may be given in any order; insertions at the same offset are
+/// applied in the order given.
+///
+/// # Panics
+/// If two edits overlap or a range is out of bounds.
+pub fn apply_edits(source: &str, edits: &[SourceEdit]) -> String {
+    let mut sorted: Vec<&SourceEdit> = edits.iter().collect();
+    sorted.sort_by_key(|e| (e.range.start, e.range.end));
+
+    let mut out = String::with_capacity(source.len());
+    let mut cursor = 0;
+    for edit in sorted {
+        assert!(edit.range.start >= cursor, "overlapping source edits");
+        out.push_str(&source[cursor..edit.range.start]);
+        out.push_str(&edit.replacement);
+        cursor = edit.range.end;
+    }
+    out.push_str(&source[cursor..]);
+    out
+}
+
+/// Collects edits against one source text and applies them at the end.
+///
+/// Lookups always see the original text: a task added by [`Editor::add_task`]
+/// can't be selected by a later call on the same editor.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::edit::Editor;
+/// use to_do_parcer::{Task, TaskStatus};
+///
+/// let source = "project \"P\" {\n    todo: \"A\",   // keep me\n}\n";
+/// let mut editor = Editor::new(source)?;
+/// editor.add_task("P", Task::new(TaskStatus::Todo, "B"))?;
+/// assert_eq!(
+///     editor.finish(),
+///     "project \"P\" {\n    todo: \"A\",   // keep me\n    todo: \"B\",\n}\n"
+/// );
+/// # Ok::<(), to_do_parcer::edit::EditError>(())
+/// ```
+#[derive(Debug)]
+pub struct Editor<'s> {
+    /// The original text after [`normalize_source`].
+    source: Cow<'s, str>,
+    /// Whether the original text started with a byte order mark.
+    bom: bool,
+    /// Whether the original text used `\r\n` line endings.
+    crlf: bool,
+    document: Document,
+    edits: Vec<SourceEdit>,
+}
+
+impl<'s> Editor<'s> {
+    /// Parses `source` for editing.
+    ///
+    /// `include` directives are kept as they are; tasks of included files
+    /// are not visible to the editor. Blank or comment-only text is
+    /// accepted as an empty document, so that tasks can be added to a new
+    /// file. A byte order
+    /// mark and Windows line endings are kept in the finished text.
+    pub fn new(source: &'s str) -> Result<Self, EditError> {
+        let options = ParseOptions {
+            includes: IncludePolicy::Ignore,
+            allow_empty: true,
+            ..ParseOptions::default()
+        };
+        let document = ToDoParser::parse_with(source, &options)?;
+        Ok(Editor {
+            source: normalize_source(source),
+            bom: source.starts_with('\u{feff}'),
+            crlf: source.contains("\r\n"),
+            document,
+            edits: Vec::new(),
+        })
+    }
+
+    /// The parsed original text.
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    /// Applies the collected edits and returns the new text.
+    pub fn finish(self) -> String {
+        let mut text = apply_edits(&self.source, &self.edits);
+        if self.crlf {
+            text = text.replace('\n', "\r\n");
+        }
+        if self.bom {
+            text.insert(0, '\u{feff}');
+        }
+        text
+    }
+
+    /// Appends `task` to the project named `project`, after its last task.
+    ///
+    /// The new task copies the indentation of the previous one. If no such
+    /// project exists, a new one is added at the end of the file.
+    ///
+    /// # Returns
+    /// * `Err(EditError::Unrepresentable)` if a name or attribute can't be
+    ///   written as `.todo` text
+    pub fn add_task(&mut self, project: &str, task: Task) -> Result<(), EditError> {
+        self.add_tasks(project, vec![task])
+    }
+
+    /// Appends several tasks to the project named `project`, in order, like
+    /// [`Editor::add_task`]. A missing project is created once for all of
+    /// them.
+    pub fn add_tasks(&mut self, project: &str, tasks: Vec<Task>) -> Result<(), EditError> {
+        check_quoted("project name", project)?;
+        for task in &tasks {
+            check_task(task)?;
+        }
+        if tasks.is_empty() {
+            return Ok(());
+        }
+        let version = self.document.version;
+        let texts: Vec<String> = tasks.iter().map(|t| format_task(t, version)).collect();
+        self.append_tasks(project, tasks, texts);
+        Ok(())
+    }
+
+    /// Inserts `texts` after the last task of `project`, or adds the project
+    /// with `tasks` at the end of the file if it doesn't exist.
+    fn append_tasks(&mut self, project: &str, tasks: Vec<Task>, texts: Vec<String>) {
+        let Some(existing) = self.document.projects.iter().find(|p| p.name == project) else {
+            let mut new_project = Project::new(project);
+            new_project.tasks = tasks;
+            let block = format_project(&new_project, self.document.version);
+            let separator = match self.source.as_ref() {
+                s if s.trim().is_empty() => "",
+                s if s.ends_with("\n\n") => "",
+                s if s.ends_with('\n') => "\n",
+                _ => "\n\n",
+            };
+            let end = self.source.len();
+            self.push(end..end, format!("{}{}", separator, block));
+            return;
+        };
+
+        let close = existing.span.end - 1;
+        if let Some(last) = existing.tasks.last() {
+            let line_end = self.line_end(last.span.end);
+            if line_end < close {
+                let indent = self.indent_of(last.span.start);
+                let text = texts.iter().map(|t| format!("\n{}{}", indent, t)).collect();
+                self.push(line_end..line_end, text);
+            } else {
+                // Single-line project: `project "P" { todo: "A", }`.
+                let text = texts.iter().map(|t| format!("{} ", t)).collect();
+                self.push(close..close, text);
+            }
+            return;
+        }
+
+        let brace_line = self.line_start(close);
+        if self.source[brace_line..close].trim().is_empty() {
+            // Closing brace on its own line, possibly after comments.
+            let indent = format!("{}{}", &self.source[brace_line..close], DEFAULT_INDENT);
+            let text = texts.iter().map(|t| format!("{}{}\n", indent, t)).collect();
+            self.push(brace_line..brace_line, text);
+        } else {
+            let open = self.source[existing.span.start..close]
+                .rfind('{')
+                .map(|i| existing.span.start + i)
+                .expect("project block has an opening brace");
+            let outer = self.indent_of(existing.span.start);
+            let text: String = texts
+                .iter()
+                .map(|t| format!("\n{}{}{}", outer, DEFAULT_INDENT, t))
+                .collect();
+            self.push(open..close + 1, format!("{{{}\n{}}}", text, outer));
+        }
+    }
+
+    /// Marks the selected task as done.
+    ///
+    /// # Arguments
+    /// * `selector` - Task title, `Project/Title` path or `#id`
+    /// * `completed` - Completion date to record, unless the task already
+    ///   has one
+    ///
+    /// # Returns
+    /// * `Ok(false)` if the task was already done (nothing is changed)
+    /// * `Ok(true)` otherwise
+    pub fn complete(
+        &mut self,
+        selector: &str,
+        completed: Option<NaiveDate>,
+    ) -> Result<bool, EditError> {
+        let found = self.find_task(selector)?;
+        Ok(self.complete_at(found, completed))
+    }
+
+    /// Marks the task at `task` as done, like [`Editor::complete`].
+    pub(crate) fn complete_at(&mut self, task: TaskRef, completed: Option<NaiveDate>) -> bool {
+        let task = task.get(&self.document).expect("task ref is valid");
+        if task.status == TaskStatus::Done {
+            return false;
+        }
+
+        let span = task.span;
+        let has_completed = task.completed.is_some();
+        self.push(span.start..span.start + "todo".len(), "done".to_string());
+        if let Some(date) = completed.filter(|_| !has_completed) {
+            // `span.end` is just past the task's trailing comma.
+            let comma = span.end - 1;
+            self.push(
+                comma..comma,
+                format!(", completed: {}", date.format("%Y-%m-%d")),
+            );
+        }
+        true
+    }
+
+    /// Removes the selected task.
+    ///
+    /// A task on a line of its own is removed together with the line and its
+    /// trailing comment; comments on the lines above it are kept.
+    ///
+    /// # Arguments
+    /// * `selector` - Task title, `Project/Title` path or `#id`
+    /// * `force` - Remove the task even if other tasks depend on it
+    ///
+    /// # Returns
+    /// * `Err(EditError::HasDependents)` if other tasks depend on it and
+    ///   `force` is not set
+    pub fn remove_task(&mut self, selector: &str, force: bool) -> Result<(), EditError> {
+        let found = self.find_task(selector)?;
+        let task = found.get(&self.document).expect("found task exists");
+
+        if !force {
+            let doc = &self.document;
+            let deps = doc.dependencies();
+            let dependents: Vec<String> = doc
+                .task_refs()
+                .filter(|r| deps.of(*r) == Some(found))
+                .filter_map(|r| r.get(doc))
+                .map(|t| format!("\"{}\"", t.title))
+                .collect();
+            if !dependents.is_empty() {
+                return Err(EditError::HasDependents {
+                    task: task.title.clone(),
+                    dependents,
+                });
+            }
+        }
+
+        self.remove_span(task.span);
+        Ok(())
+    }
+
+    /// Removes the task at `task` without checking for dependents.
+    pub(crate) fn remove_at(&mut self, task: TaskRef) {
+        let span = task.get(&self.document).expect("task ref is valid").span;
+        self.remove_span(span);
+    }
+
+    /// Replaces the text of the task at `task` (keyword to trailing comma)
+    /// with `text`.
+    pub(crate) fn replace_at(&mut self, task: TaskRef, text: String) {
+        let span = task.get(&self.document).expect("task ref is valid").span;
+        self.push(span.start..span.end, text);
+    }
+
+    /// Points the `depends_on` of `task` at `reference` instead.
+    pub(crate) fn replace_reference(&mut self, task: &Task, reference: String) {
+        if let Some(range) = self.reference_range(task) {
+            self.push(range, reference);
+        }
+    }
+
+    /// Replaces the whole block of the project with index `project` with
+    /// `text`.
+    pub(crate) fn replace_project_at(&mut self, project: usize, text: String) {
+        let span = self.document.projects[project].span;
+        self.push(span.start..span.end, text);
+    }
+
+    /// Original text at `range`.
+    pub(crate) fn text(&self, range: Range<usize>) -> &str {
+        &self.source[range]
+    }
+
+    /// Removes the project with index `project`, with its lines and one
+    /// blank line after them when it has them to itself. Comments above it
+    /// are kept.
+    pub(crate) fn remove_project_at(&mut self, project: usize) {
+        let span = self.document.projects[project].span;
+        let start = self.line_start(span.start);
+        let end = self.line_end(span.end);
+        if !self.source[start..span.start].trim().is_empty()
+            || !self.source[span.end..end].trim().is_empty()
+        {
+            self.remove_span(span);
+            return;
+        }
+        let mut end = (end + 1).min(self.source.len());
+        if self.source[end..].starts_with('\n') {
+            end += 1;
+        }
+        self.push(start..end, String::new());
+    }
+
+    /// Removes the text at `span`: its whole line (with any trailing comment)
+    /// when nothing else is on it, otherwise just the text and the spaces
+    /// after it.
+    fn remove_span(&mut self, span: Span) {
+        let start = self.line_start(span.start);
+        let end = self.line_end(span.end);
+        let before = &self.source[start..span.start];
+        let after = self.source[span.end..end].trim();
+        if before.trim().is_empty() && (after.is_empty() || after.starts_with("//")) {
+            let end = (end + 1).min(self.source.len());
+            self.push(start..end, String::new());
+        } else {
+            let rest = &self.source[span.end..];
+            let spaces = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+            self.push(span.start..span.end + spaces, String::new());
+        }
+    }
+
+    /// Changes attributes of the selected task.
+    ///
+    /// Attributes that are set are replaced where they are written; new
+    /// ones are appended after the last attribute. Nothing else in the task
+    /// is touched.
+    ///
+    /// # Arguments
+    /// * `selector` - Task title, `Project/Title` path or `#id`
+    /// * `changes` - Attributes to set or remove
+    pub fn update_task(&mut self, selector: &str, changes: &TaskChanges) -> Result<(), EditError> {
+        let found = self.find_task(selector)?;
+        let span = found.get(&self.document).expect("found task exists").span;
+
+        if let Some(Some(assignee)) = &changes.assignee {
+            check_identifier("assignee", assignee)?;
+        }
+        if let Some(Some(dep)) = &changes.depends_on {
+            check_quoted("dependency", dep)?;
+        }
+
+        let layout = TaskLayout::read(&self.source, span.start..span.end);
+        let mut append = Vec::new();
+        let mut apply = |rule: Rule, value: Option<String>| {
+            let existing = layout.attributes.iter().filter(|(r, _, _)| *r == rule);
+            let mut replaced = false;
+            for (_, range, separator_start) in existing {
+                match &value {
+                    Some(text) if !replaced => {
+                        self.edits.push(SourceEdit {
+                            range: range.clone(),
+                            replacement: text.clone(),
+                        });
+                        replaced = true;
+                    }
+                    _ => self.edits.push(SourceEdit {
+                        range: *separator_start..range.end,
+                        replacement: String::new(),
+                    }),
+                }
+            }
+            if let (Some(text), false) = (value, replaced) {
+                append.push(text);
+            }
+        };
+
+        if let Some(priority) = changes.priority {
+            apply(Rule::priority, priority.map(|p| format!("@{}", p.as_str())));
+        }
+        if let Some(due) = changes.due {
+            apply(
+                Rule::due_date,
+                due.map(|d| format!("due: {}", d.format("%Y-%m-%d"))),
+            );
+        }
+        if let Some(assignee) = &changes.assignee {
+            apply(
+                Rule::assignee,
+                assignee.as_ref().map(|a| format!("assign: @{}", a)),
+            );
+        }
+        if let Some(dep) = &changes.depends_on {
+            apply(
+                Rule::depends_on,
+                dep.as_ref().map(|d| format!("depends_on: \"{}\"", d)),
+            );
+        }
+
+        if !append.is_empty() {
+            let text: String = append.iter().map(|a| format!(", {}", a)).collect();
+            self.push(layout.body_end..layout.body_end, text);
+        }
+        Ok(())
+    }
+
+    /// Hands the tasks assigned to `from` over to `to`.
+    ///
+    /// Only open tasks change unless `include_done` is set, so finished
+    /// work stays credited to whoever did it. Each `assign:` attribute is
+    /// rewritten in place.
+    ///
+    /// # Arguments
+    /// * `from` - Current assignee, with or without `@`
+    /// * `to` - New assignee, with or without `@`
+    /// * `project` - Only reassign tasks of this project
+    /// * `include_done` - Also reassign done tasks
+    ///
+    /// # Returns
+    /// * `Ok(n)` with the number of reassigned tasks
+    /// * `Err(EditError::ProjectNotFound)` if `project` doesn't exist
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::edit::Editor;
+    ///
+    /// let source = r#"project "P" { todo: "A", assign: @ann, done: "B", assign: @ann, }"#;
+    /// let mut editor = Editor::new(source)?;
+    /// assert_eq!(editor.reassign("ann", "@bob", None, false)?, 1);
+    /// assert_eq!(
+    ///     editor.finish(),
+    ///     r#"project "P" { todo: "A", assign: @bob, done: "B", assign: @ann, }"#
+    /// );
+    /// # Ok::<(), to_do_parcer::edit::EditError>(())
+    /// ```
+    pub fn reassign(
+        &mut self,
+        from: &str,
+        to: &str,
+        project: Option<&str>,
+        include_done: bool,
+    ) -> Result<usize, EditError> {
+        let from = from.trim_start_matches('@');
+        let to = to.trim_start_matches('@');
+        check_identifier("assignee", to)?;
+        let doc = &self.document;
+        let scope = match project {
+            Some(name) => Some(
+                doc.projects
+                    .iter()
+                    .position(|p| p.name == name)
+                    .ok_or_else(|| EditError::ProjectNotFound(name.to_string()))?,
+            ),
+            None => None,
+        };
+
+        let mut edits = Vec::new();
+        let mut reassigned = 0;
+        for r in doc.task_refs() {
+            let task = r.get(doc).expect("task ref is valid");
+            if scope.is_some_and(|p| p != r.project)
+                || task.assignee.as_deref() != Some(from)
+                || (task.status == TaskStatus::Done && !include_done)
+            {
+                continue;
+            }
+            let layout = TaskLayout::read(&self.source, task.span.start..task.span.end);
+            edits.extend(
+                layout
+                    .attributes
+                    .into_iter()
+                    .filter(|(rule, _, _)| *rule == Rule::assignee)
+                    .map(|(_, range, _)| SourceEdit {
+                        range,
+                        replacement: format!("assign: @{}", to),
+                    }),
+            );
+            reassigned += 1;
+        }
+
+        self.edits.extend(edits);
+        Ok(reassigned)
+    }
+
+    /// Renames the project `old` to `new`.
+    ///
+    /// Qualified `depends_on: "Old/Task"` references to its tasks, in any
+    /// project, are rewritten to the new name; references by title alone
+    /// still resolve and are left as they are.
+    ///
+    /// # Returns
+    /// * `Ok(n)` with the number of rewritten references
+    /// * `Err(EditError::ProjectNotFound)` if there is no project `old`
+    /// * `Err(EditError::ProjectExists)` if a project `new` already exists
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::edit::Editor;
+    ///
+    /// let source = r#"project "A" { todo: "X", } project "B" { todo: "Y", depends_on: "A/X", }"#;
+    /// let mut editor = Editor::new(source)?;
+    /// assert_eq!(editor.rename_project("A", "Core")?, 1);
+    /// assert_eq!(
+    ///     editor.finish(),
+    ///     r#"project "Core" { todo: "X", } project "B" { todo: "Y", depends_on: "Core/X", }"#
+    /// );
+    /// # Ok::<(), to_do_parcer::edit::EditError>(())
+    /// ```
+    pub fn rename_project(&mut self, old: &str, new: &str) -> Result<usize, EditError> {
+        check_quoted("project name", new)?;
+        let doc = &self.document;
+        let p = doc
+            .projects
+            .iter()
+            .position(|p| p.name == old)
+            .ok_or_else(|| EditError::ProjectNotFound(old.to_string()))?;
+        if old != new && doc.projects.iter().any(|p| p.name == new) {
+            return Err(EditError::ProjectExists(new.to_string()));
+        }
+
+        let span = doc.projects[p].span;
+        let name = ToDoParser::parse(Rule::project, &self.source[span.start..span.end])
+            .expect("text of a parsed project parses again")
+            .next()
+            .and_then(|project| project.into_inner().next())
+            .expect("project has a name")
+            .as_span();
+        let mut edits = vec![SourceEdit {
+            range: span.start + name.start()..span.start + name.end(),
+            replacement: format!("\"{}\"", new),
+        }];
+
+        let deps = doc.dependencies();
+        for r in doc.task_refs() {
+            let task = r.get(doc).expect("task ref is valid");
+            let Some(reference) = &task.depends_on else {
+                continue;
+            };
+            let Some(title) = reference
+                .strip_prefix(old)
+                .and_then(|rest| rest.strip_prefix('/'))
+            else {
+                continue;
+            };
+            let qualified = deps
+                .of(r)
+                .filter(|target| target.project == p)
+                .and_then(|target| target.get(doc))
+                .is_some_and(|target| target.title == title);
+            if let (true, Some(range)) = (qualified, self.reference_range(task)) {
+                edits.push(SourceEdit {
+                    range,
+                    replacement: format!("{}/{}", new, title),
+                });
+            }
+        }
+
+        let rewritten = edits.len() - 1;
+        self.edits.extend(edits);
+        Ok(rewritten)
+    }
+
+    /// Rewrites the file for format version `to`.
+    ///
+    /// The `version` pragma is updated, or added at the top of the file
+    /// when there is none, and every tag marker is respelled for the new
+    /// version (`@tag:` before version 2, `tag:` from it on). Comments,
+    /// spacing and everything else are kept. Included files have versions
+    /// of their own and are not touched.
+    ///
+    /// # Arguments
+    /// * `to` - Format version to write
+    ///
+    /// # Returns
+    /// * `Ok(n)` with the number of tag markers rewritten
+    /// * `Err(EditError::Parse)` with `ParseError::UnsupportedVersion` if
+    ///   `to` isn't a supported version
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::edit::Editor;
+    ///
+    /// let source = "// Sprint 12\nproject \"P\" {\n  todo: \"A\", @tag: \"ui\", // later\n}\n";
+    /// let mut editor = Editor::new(source)?;
+    /// assert_eq!(editor.migrate(2)?, 1);
+    /// assert_eq!(
+    ///     editor.finish(),
+    ///     "version 2\n\n// Sprint 12\nproject \"P\" {\n  todo: \"A\", tag: \"ui\", // later\n}\n"
+    /// );
+    /// # Ok::<(), to_do_parcer::edit::EditError>(())
+    /// ```
+    pub fn migrate(&mut self, to: u32) -> Result<usize, EditError> {
+        if !SUPPORTED_VERSIONS.contains(&to) {
+            return Err(ParseError::UnsupportedVersion { found: to }.into());
+        }
+        // Blank and comment-only files have nothing to migrate.
+        let Ok(mut pairs) = ToDoParser::parse(Rule::file, &self.source) else {
+            return Ok(0);
+        };
+        let file = pairs.next().expect("file rule matched");
+
+        let mut edits = Vec::new();
+        let mut pragma = false;
+        let mut rewritten = 0;
+        let mut stack: Vec<_> = file.into_inner().collect();
+        while let Some(pair) = stack.pop() {
+            match pair.as_rule() {
+                Rule::version_number => {
+                    pragma = true;
+                    if pair.as_str() != to.to_string() {
+                        edits.push(SourceEdit {
+                            range: pair.as_span().start()..pair.as_span().end(),
+                            replacement: to.to_string(),
+                        });
+                    }
+                }
+                Rule::tag_marker if pair.as_str() != tag_keyword(to) => {
+                    rewritten += 1;
+                    edits.push(SourceEdit {
+                        range: pair.as_span().start()..pair.as_span().end(),
+                        replacement: tag_keyword(to).to_string(),
+                    });
+                }
+                _ => stack.extend(pair.into_inner()),
+            }
+        }
+
+        if !pragma && to != DEFAULT_VERSION {
+            edits.push(SourceEdit {
+                range: 0..0,
+                replacement: format!("version {}\n\n", to),
+            });
+        }
+        self.edits.extend(edits);
+        Ok(rewritten)
+    }
+
+    /// Moves the selected task to the end of the project `project`, which is
+    /// created if it doesn't exist.
+    ///
+    /// The task keeps its text and trailing comment. Any `depends_on` that
+    /// would resolve to a different task after the move (the moved task's
+    /// own, or a reference to it) is rewritten as a `Project/Task` path.
+    ///
+    /// # Arguments
+    /// * `selector` - Task title, `Project/Title` path or `#id`
+    /// * `project` - Name of the destination project
+    ///
+    /// # Returns
+    /// * `Ok(false)` if the task is already in `project` (nothing is changed)
+    /// * `Ok(true)` otherwise
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::edit::Editor;
+    ///
+    /// // In B, "X" would mean B's own X.
+    /// let source = r#"project "A" { todo: "X", todo: "Y", depends_on: "X", } project "B" { todo: "X", }"#;
+    /// let mut editor = Editor::new(source)?;
+    /// assert!(editor.move_task("Y", "B")?);
+    /// assert_eq!(
+    ///     editor.finish(),
+    ///     r#"project "A" { todo: "X", } project "B" { todo: "X", todo: "Y", depends_on: "A/X", }"#
+    /// );
+    /// # Ok::<(), to_do_parcer::edit::EditError>(())
+    /// ```
+    pub fn move_task(&mut self, selector: &str, project: &str) -> Result<bool, EditError> {
+        check_quoted("project name", project)?;
+        let found = self.find_task(selector)?;
+        let doc = &self.document;
+        if doc.projects[found.project].name == project {
+            return Ok(false);
+        }
+
+        // The document as it will be after the move, to see how references
+        // resolve there.
+        let mut moved = doc.clone();
+        let task = moved.projects[found.project].tasks.remove(found.task);
+        let to = match moved.projects.iter().position(|p| p.name == project) {
+            Some(q) => q,
+            None => {
+                moved.projects.push(Project::new(project));
+                moved.projects.len() - 1
+            }
+        };
+        moved.projects[to].tasks.push(task);
+        let destination = TaskRef {
+            project: to,
+            task: moved.projects[to].tasks.len() - 1,
+        };
+        let after = |r: TaskRef| match r {
+            r if r == found => destination,
+            r if r.project == found.project && r.task > found.task => TaskRef {
+                task: r.task - 1,
+                ..r
+            },
+            r => r,
+        };
+
+        let (before_deps, moved_deps) = (doc.dependencies(), moved.dependencies());
+        let mut rewrites = Vec::new();
+        for r in doc.task_refs() {
+            let Some(target) = before_deps.of(r) else {
+                continue;
+            };
+            let now = moved_deps.of(after(r));
+            if now != Some(after(target)) {
+                let path = format!(
+                    "{}/{}",
+                    moved.projects[after(target).project].name,
+                    target.get(doc).expect("resolved task exists").title
+                );
+                rewrites.push((r, path));
+            }
+        }
+
+        let task = found.get(doc).expect("found task exists");
+        let span = task.span;
+        let mut text = self.source[span.start..span.end].to_string();
+        let line_start = self.line_start(span.start);
+        let line_rest = self.source[span.end..self.line_end(span.end)].trim();
+        if self.source[line_start..span.start].trim().is_empty() && line_rest.starts_with("//") {
+            text = format!("{} {}", text, line_rest);
+        }
+        let mut moved_task = task.clone();
+        let mut edits = Vec::new();
+        for (r, path) in rewrites {
+            let dependent = r.get(doc).expect("task ref is valid");
+            let Some(range) = self.reference_range(dependent) else {
+                continue;
+            };
+            if r == found {
+                let local = range.start - span.start..range.end - span.start;
+                text.replace_range(local, &path);
+                moved_task.depends_on = Some(path);
+            } else {
+                edits.push(SourceEdit {
+                    range,
+                    replacement: path,
+                });
+            }
+        }
+
+        self.edits.extend(edits);
+        self.remove_at(found);
+        self.append_tasks(project, vec![moved_task], vec![text]);
+        Ok(true)
+    }
+
+    /// Range of the `depends_on` reference of `task` inside its quotes.
+    fn reference_range(&self, task: &Task) -> Option<Range<usize>> {
+        let reference = task.depends_on.as_deref()?;
+        let layout = TaskLayout::read(&self.source, task.span.start..task.span.end);
+        layout
+            .attributes
+            .iter()
+            .rev()
+            .filter(|(rule, _, _)| *rule == Rule::depends_on)
+            // The reference is the quoted string ending the attribute.
+            .map(|(_, range, _)| range.end - 1)
+            .find(|&end| self.source[..end].ends_with(reference))
+            .map(|end| end - reference.len()..end)
+    }
+
+    /// Reorders the tasks of every project by `key`.
+    ///
+    /// When every task of a project sits on lines of its own, whole lines
+    /// move, so comments above a task and blank lines before it travel
+    /// with it. Otherwise the task texts trade places and the text between
+    /// them stays where it is.
+    pub fn sort_tasks(&mut self, key: SortKey) {
+        for p in 0..self.document.projects.len() {
+            let project = &self.document.projects[p];
+            let mut order: Vec<usize> = (0..project.tasks.len()).collect();
+            order.sort_by(|&a, &b| key.compare(&project.tasks[a], &project.tasks[b]));
+            if order.iter().enumerate().all(|(i, &t)| i == t) {
+                continue;
+            }
+
+            let spans: Vec<Span> = project.tasks.iter().map(|t| t.span).collect();
+            match self.task_lines(project) {
+                Some(lines) => {
+                    let text: String = order
+                        .iter()
+                        .map(|&t| &self.source[lines[t].clone()])
+                        .collect();
+                    let range = lines[0].start..lines[lines.len() - 1].end;
+                    self.push(range, text);
+                }
+                None => {
+                    for (slot, &t) in order.iter().enumerate() {
+                        if slot != t {
+                            let text = self.source[spans[t].start..spans[t].end].to_string();
+                            self.push(spans[slot].start..spans[slot].end, text);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Splits the tasks of `project` into consecutive runs of whole lines,
+    /// each ending with the line a task ends on. `None` if some task shares
+    /// a line with the braces or another task.
+    fn task_lines(&self, project: &Project) -> Option<Vec<Range<usize>>> {
+        let first = project.tasks.first()?;
+        let mut start = self.line_start(first.span.start);
+        if start <= project.span.start {
+            return None;
+        }
+        // Lines above the first task belong to it, up to the opening brace.
+        let open_line_end = self.line_end(
+            self.source[project.span.start..first.span.start]
+                .rfind('{')
+                .map(|i| project.span.start + i)?,
+        );
+        if open_line_end >= first.span.start {
+            return None;
+        }
+        start = start.min(open_line_end + 1);
+
+        let mut lines = Vec::with_capacity(project.tasks.len());
+        for task in &project.tasks {
+            let before = &self.source[self.line_start(task.span.start)..task.span.start];
+            let line_end = self.line_end(task.span.end);
+            let after = self.source[task.span.end..line_end].trim();
+            if !before.trim().is_empty() || !(after.is_empty() || after.starts_with("//")) {
+                return None;
+            }
+            let end = line_end + 1;
+            lines.push(start..end);
+            start = end;
+        }
+        // The closing brace must be on a later line.
+        (start <= self.line_start(project.span.end - 1)).then_some(lines)
+    }
+
+    /// Finds the task named by `selector`.
+    ///
+    /// A selector is a task title, a `Project/Title` path or `#id`. A title
+    /// that exists in several projects is ambiguous.
+    pub fn find_task(&self, selector: &str) -> Result<TaskRef, EditError> {
+        find_task(&self.document, selector)
+    }
+
+    /// Records a replacement of `range`.
+    fn push(&mut self, range: Range<usize>, replacement: String) {
+        self.edits.push(SourceEdit { range, replacement });
+    }
+
+    /// Offset of the start of the line containing `offset`.
+    fn line_start(&self, offset: usize) -> usize {
+        self.source[..offset].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    /// Offset of the newline ending the line containing `offset`, or the
+    /// end of the text.
+    fn line_end(&self, offset: usize) -> usize {
+        self.source[offset..]
+            .find('\n')
+            .map_or(self.source.len(), |i| offset + i)
+    }
+
+    /// Leading whitespace of the line containing `offset`.
+    fn indent_of(&self, offset: usize) -> &str {
+        let start = self.line_start(offset);
+        let line = &self.source[start..];
+        let width = line.len() - line.trim_start_matches([' ', '\t']).len();
+        &line[..width]
+    }
+}
+
+/// Positions of the parts of one task in the source text.
+struct TaskLayout {
+    /// Kind, range, and start of the preceding `,` separator of each
+    /// attribute, in source order.
+    attributes: Vec<(Rule, Range<usize>, usize)>,
+    /// End of the title or last attribute, where new attributes go.
+    body_end: usize,
+}
+
+impl TaskLayout {
+    /// Re-parses the task at `range` of `source`.
+    fn read(source: &str, range: Range<usize>) -> TaskLayout {
+        let offset = range.start;
+        let task = ToDoParser::parse(Rule::task, &source[range])
+            .expect("text of a parsed task parses again")
+            .next()
+            .expect("task rule produces a pair");
+        let body = task.into_inner().next().expect("task has a body");
+
+        let mut layout = TaskLayout {
+            attributes: Vec::new(),
+            body_end: offset,
+        };
+        for part in body.into_inner() {
+            match part.as_rule() {
+                Rule::quoted => layout.body_end = offset + part.as_span().end(),
+                Rule::attribute_list => {
+                    for attribute in part.into_inner() {
+                        let inner = attribute.into_inner().next().expect("attribute has a kind");
+                        let span = inner.as_span();
+                        let range = offset + span.start()..offset + span.end();
+                        layout
+                            .attributes
+                            .push((inner.as_rule(), range.clone(), layout.body_end));
+                        layout.body_end = range.end;
+                    }
+                }
+                _ => {}
+            }
+        }
+        layout
+    }
+}
+
+/// Checks that every field of `task` can be written as `.todo` text.
+fn check_task(task: &Task) -> Result<(), EditError> {
+    check_quoted("title", &task.title)?;
+    if let Some(id) = &task.id {
+        check_identifier("id", id)?;
+    }
+    if let Some(dep) = &task.depends_on {
+        check_quoted("dependency", dep)?;
+    }
+    for tag in &task.tags {
+        check_quoted("tag", tag)?;
+    }
+    if let Some(assignee) = &task.assignee {
+        check_identifier("assignee", assignee)?;
+    }
+    Ok(())
+}
+
+/// Checks that `value` can be written as a bare identifier.
+fn check_identifier(what: &'static str, value: &str) -> Result<(), EditError> {
+    let valid = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(EditError::Unrepresentable {
+            what,
+            value: value.to_string(),
+            reason: "only letters, digits, '_' and '-' are allowed",
+        });
+    }
+    Ok(())
+}
+
+/// Checks that `value` fits in a quoted DSL string.
+fn check_quoted(what: &'static str, value: &str) -> Result<(), EditError> {
+    if value.contains(['"', '\n']) {
+        return Err(EditError::Unrepresentable {
+            what,
+            value: value.to_string(),
+            reason: "double quotes and line breaks are not allowed",
+        });
+    }
+    Ok(())
+}
+
+/// Finds the task named by `selector` in `doc`.
+///
+/// `#id` selects by `id:`. Otherwise an exact title match wins, then
+/// `Project/Title` is tried. A title that exists in several projects is
+/// reported as ambiguous.
+pub fn find_task(doc: &Document, selector: &str) -> Result<TaskRef, EditError> {
+    if let Some(id) = selector.strip_prefix('#') {
+        return doc
+            .task_refs()
+            .find(|r| r.get(doc).is_some_and(|t| t.id.as_deref() == Some(id)))
+            .ok_or_else(|| EditError::TaskNotFound(selector.to_string()));
+    }
+
+    let by_title: Vec<TaskRef> = doc
+        .task_refs()
+        .filter(|r| r.get(doc).is_some_and(|t| t.title == selector))
+        .collect();
+
+    match by_title.as_slice() {
+        [found] => return Ok(*found),
+        [] => {}
+        several => {
+            return Err(EditError::Ambiguous {
+                selector: selector.to_string(),
+                projects: several
+                    .iter()
+                    .filter_map(|r| r.project(doc))
+                    .map(|p| format!("\"{}\"", p.name))
+                    .collect(),
+            });
+        }
+    }
+
+    selector
+        .split_once('/')
+        .and_then(|(project, title)| {
+            doc.task_refs().find(|r| {
+                r.project(doc).is_some_and(|p| p.name == project)
+                    && r.get(doc).is_some_and(|t| t.title == title)
+            })
+        })
+        .ok_or_else(|| EditError::TaskNotFound(selector.to_string()))
+}
@@ -0,0 +1,137 @@
+//! Streaming (SAX-style) view of a parse.
+//!
+//! Instead of collecting a `Document`, the parser can hand each project and
+//! task to a [`TodoSink`] as soon as it is recognized.
+
+use std::path::Path;
+
+use crate::parser::{Document, IncludeDirective, ParseWarning, Project, Span, Task};
+
+/// A single step of a streaming parse.
+// Tasks are moved straight into the sink; boxing them would cost an
+// allocation per task for no benefit.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum Event<'a> {
+    /// Format version of the input; always the first event.
+    Version(u32),
+    /// A project block was opened. The name borrows from the input and
+    /// the span covers the whole block. `file` is the included file the
+    /// project comes from, or `None` for the main input.
+    ProjectStart {
+        name: &'a str,
+        span: Span,
+        file: Option<&'a Path>,
+    },
+    /// A task of the currently open project was parsed.
+    TaskParsed(Task),
+    /// The currently open project block was closed.
+    ProjectEnd,
+    /// A recoverable problem was found; emitted before the affected task.
+    Warning(ParseWarning),
+    /// A `// comment`, including the slashes. `trailing` is set when it
+    /// follows a task on the same line.
+    Comment {
+        text: &'a str,
+        line: usize,
+        trailing: bool,
+    },
+    /// An `include "file"` directive of the main input, reported before the
+    /// included file's projects (if includes are resolved).
+    Include { path: &'a str, span: Span },
+}
+
+/// Receiver of parse events.
+///
+/// Any `FnMut(Event)` closure is a sink, which is handy for counting:
+///
+/// ```
+/// use to_do_parcer::ToDoParser;
+/// use to_do_parcer::events::Event;
+///
+/// let mut tasks = 0;
+/// ToDoParser::parse_events(r#"project "P" { todo: "A", done: "B", }"#, &mut |e: Event| {
+///     if let Event::TaskParsed(_) = e {
+///         tasks += 1;
+///     }
+/// })?;
+/// assert_eq!(tasks, 2);
+/// # Ok::<(), to_do_parcer::ParseError>(())
+/// ```
+pub trait TodoSink {
+    /// Called once for every event, in source order.
+    fn event(&mut self, event: Event<'_>);
+}
+
+impl<F: FnMut(Event<'_>)> TodoSink for F {
+    fn event(&mut self, event: Event<'_>) {
+        self(event)
+    }
+}
+
+/// Sink that collects events back into a `Document`.
+///
+/// Comments are attached to the node that follows them, except trailing
+/// comments, which belong to the task before them.
+#[derive(Debug, Default)]
+pub(crate) struct DocumentBuilder {
+    document: Document,
+    /// Comments waiting for the next node.
+    pending: Vec<String>,
+    /// Tasks built so far, for `Task::order`.
+    tasks: usize,
+}
+
+impl DocumentBuilder {
+    /// Returns the built document; leftover comments become trailing ones.
+    pub(crate) fn finish(mut self) -> Document {
+        self.document.trailing_comments = self.pending;
+        self.document
+    }
+}
+
+impl TodoSink for DocumentBuilder {
+    fn event(&mut self, event: Event<'_>) {
+        match event {
+            Event::Version(version) => self.document.version = version,
+            Event::ProjectStart { name, span, file } => {
+                let mut project = Project::new(name);
+                project.span = span;
+                project.file = file.map(Path::to_path_buf);
+                project.comments = std::mem::take(&mut self.pending);
+                self.document.projects.push(project);
+            }
+            Event::TaskParsed(mut task) => {
+                if let Some(project) = self.document.projects.last_mut() {
+                    task.comments = std::mem::take(&mut self.pending);
+                    task.order = self.tasks;
+                    self.tasks += 1;
+                    project.tasks.push(task);
+                }
+            }
+            Event::ProjectEnd => {
+                if let Some(project) = self.document.projects.last_mut() {
+                    project.footer_comments = std::mem::take(&mut self.pending);
+                }
+            }
+            Event::Warning(warning) => self.document.warnings.push(warning),
+            Event::Comment { text, trailing, .. } => {
+                let last_task = self
+                    .document
+                    .projects
+                    .last_mut()
+                    .and_then(|p| p.tasks.last_mut());
+                match last_task {
+                    Some(task) if trailing => task.trailing_comment = Some(text.to_string()),
+                    _ => self.pending.push(text.to_string()),
+                }
+            }
+            Event::Include { path, span } => self.document.includes.push(IncludeDirective {
+                path: path.to_string(),
+                position: self.document.projects.len(),
+                comments: std::mem::take(&mut self.pending),
+                span,
+            }),
+        }
+    }
+}
@@ -0,0 +1,616 @@
+//! Conversion of a `Document` into formats other tools can consume.
+//!
+//! # JSON layout
+//!
+//! The JSON output is a stable interface; fields are only ever added.
+//!
+//! ```text
+//! {
+//!   "version": 1,                    // format version of the source file
+//!   "projects": [
+//!     {
+//!       "name": "Backend",
+//!       "file": null,                // path of the included file, if any
+//!       "line": 1,
+//!       "tasks": [
+//!         {
+//!           "status": "todo",        // "todo" or "done"
+//!           "title": "Set up database",
+//!           "id": "db",              // from `id:`, or null
+//!           "priority": "high",      // "high", "medium", "low" or null
+//!           "start": null,           // as written, or null
+//!           "due": "2025-11-15",     // as written, or null
+//!           "completed": null,       // completion date, or null
+//!           "assignee": "ivan",      // without the "@", or null
+//!           "depends_on": null,      // referenced title, or null
+//!           "tags": ["db"],
+//!           "line": 2
+//!         }
+//!       ]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! YAML output maps the same structure, field for field.
+//!
+//! # CSV layout
+//!
+//! One row per task, after a header row:
+//! `project,title,status,priority,due,assignee,tags`. Missing values are
+//! empty cells and tags are joined with `;`.
+//!
+//! # Markdown layout
+//!
+//! GitHub-flavored Markdown with one `##` heading per project and one
+//! checkbox per task. Attributes follow the title after an em dash:
+//!
+//! ```text
+//! ## Backend
+//!
+//! - [ ] Set up database — priority: high; due: 2025-11-15; assignee: ivan; tags: db
+//! - [x] Write schema
+//! ```
+//!
+//! # Org layout
+//!
+//! One level-1 heading per project and a level-2 `TODO`/`DONE` heading per
+//! task. Priorities become `[#A]`–`[#C]` cookies, due dates a `DEADLINE:`
+//! line, and assignee and dependency a property drawer:
+//!
+//! ```text
+//! * Backend
+//! ** TODO [#A] Set up database :db:
+//! DEADLINE: <2025-11-15 Sat>
+//! :PROPERTIES:
+//! :ASSIGNEE: ivan
+//! :END:
+//! ```
+//!
+//! Characters Org doesn't allow in tags are replaced with `_`.
+//!
+//! # Jira layout
+//!
+//! CSV for Jira's issue importer, one row per task. Column names are the
+//! ones Jira uses in its own CSV exports, so the importer maps them
+//! without help:
+//!
+//! ```text
+//! Issue Id,Summary,Issue Type,Status,Priority,Due Date,Assignee,Component,Labels,Labels,Inward issue link (Blocks)
+//! 1,Set up database,Task,Done,High,2025-11-15,ivan,Backend,db,,
+//! 2,Write API,Task,To Do,,,,Backend,,,1
+//! ```
+//!
+//! Issue ids number the tasks in document order. A task's `depends_on`
+//! becomes an inward "Blocks" link from the task it names. Projects become
+//! components, and each label gets its own `Labels` column, with spaces
+//! replaced by `_` since Jira labels can't contain them. Due dates stay in
+//! `YYYY-MM-DD`, which is the date format to choose in the importer.
+//!
+//! # Taskwarrior layout
+//!
+//! A JSON array for `task import`, one object per task:
+//!
+//! ```text
+//! [
+//!   {
+//!     "uuid": "5f0c…",                 // derived from project and title
+//!     "description": "Write API",
+//!     "status": "pending",             // or "completed"
+//!     "project": "Backend",
+//!     "priority": "H",                 // H, M or L; left out if unset
+//!     "due": "20251130T000000Z",
+//!     "end": "20251201T000000Z",       // from `completed:`
+//!     "tags": ["api"],
+//!     "depends": ["9a41…"]
+//!   }
+//! ]
+//! ```
+//!
+//! UUIDs stay the same as long as a task keeps its project and title, so
+//! importing again updates tasks instead of duplicating them. Spaces in
+//! tags become `_`. Taskwarrior has no assignees; they are left out.
+
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::deps::TaskRef;
+use crate::parser::{Document, Priority, Project, Task, TaskStatus};
+
+/// Output format of `export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Yaml,
+    Csv,
+    Markdown,
+    Org,
+    Jira,
+    Taskwarrior,
+}
+
+impl ExportFormat {
+    /// Every supported format, in the order shown in help texts.
+    pub const ALL: &'static [ExportFormat] = &[
+        ExportFormat::Json,
+        ExportFormat::Yaml,
+        ExportFormat::Csv,
+        ExportFormat::Markdown,
+        ExportFormat::Org,
+        ExportFormat::Jira,
+        ExportFormat::Taskwarrior,
+    ];
+
+    /// Name of the format as accepted by `FromStr`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Yaml => "yaml",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Markdown => "markdown",
+            ExportFormat::Org => "org",
+            ExportFormat::Jira => "jira",
+            ExportFormat::Taskwarrior => "taskwarrior",
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ExportFormat::ALL
+            .iter()
+            .find(|f| f.as_str().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<_> = ExportFormat::ALL.iter().map(|f| f.as_str()).collect();
+                format!(
+                    "unknown format '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Renders `doc` in the given format.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::ToDoParser;
+/// use to_do_parcer::export::{ExportFormat, export};
+///
+/// let doc = ToDoParser::parse_document(r#"project "P" { todo: "A", @high, }"#)?;
+/// let json = export(&doc, ExportFormat::Json);
+/// assert!(json.contains(r#""priority": "high""#));
+/// # Ok::<(), to_do_parcer::ParseError>(())
+/// ```
+pub fn export(doc: &Document, format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Json => to_json(doc),
+        ExportFormat::Yaml => to_yaml(doc),
+        ExportFormat::Csv => to_csv(doc),
+        ExportFormat::Markdown => to_markdown(doc),
+        ExportFormat::Org => to_org(doc),
+        ExportFormat::Jira => to_jira(doc),
+        ExportFormat::Taskwarrior => to_taskwarrior(doc),
+    }
+}
+
+/// Renders `doc` as pretty-printed JSON in the layout described above.
+pub fn to_json(doc: &Document) -> String {
+    let mut out = serde_json::to_string_pretty(&DocumentView::from(doc))
+        .expect("document view serializes to JSON");
+    out.push('\n');
+    out
+}
+
+/// Renders `doc` as YAML with the same structure as the JSON output.
+pub fn to_yaml(doc: &Document) -> String {
+    serde_yaml_ng::to_string(&DocumentView::from(doc)).expect("document view serializes to YAML")
+}
+
+/// Renders the tasks of `doc` as CSV, one row per task.
+pub fn to_csv(doc: &Document) -> String {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    writer
+        .write_record(CSV_HEADER)
+        .expect("writing CSV to memory cannot fail");
+    for (project, task) in doc.tasks() {
+        let row = CsvRow {
+            project: &project.name,
+            title: &task.title,
+            status: task.status.as_str(),
+            priority: task.priority.as_ref().map_or("", |p| p.as_str()),
+            due: task.due_date.as_deref().unwrap_or(""),
+            assignee: task.assignee.as_deref().unwrap_or(""),
+            tags: task.tags.join(";"),
+        };
+        writer
+            .serialize(row)
+            .expect("writing CSV to memory cannot fail");
+    }
+    let bytes = writer
+        .into_inner()
+        .expect("flushing CSV to memory cannot fail");
+    String::from_utf8(bytes).expect("CSV output is built from UTF-8 strings")
+}
+
+/// Renders `doc` as a Markdown checklist grouped under project headings.
+pub fn to_markdown(doc: &Document) -> String {
+    let mut out = String::new();
+    for project in &doc.projects {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        let _ = writeln!(out, "## {}", escape_markdown(&project.name));
+        if !project.tasks.is_empty() {
+            out.push('\n');
+        }
+        for task in &project.tasks {
+            let checkbox = match task.status {
+                TaskStatus::Todo => "[ ]",
+                TaskStatus::Done => "[x]",
+            };
+            let _ = write!(out, "- {} {}", checkbox, escape_markdown(&task.title));
+
+            let mut details = Vec::new();
+            if let Some(priority) = &task.priority {
+                details.push(format!("priority: {}", priority.as_str()));
+            }
+            if let Some(due) = &task.due_date {
+                details.push(format!("due: {}", due));
+            }
+            if let Some(assignee) = &task.assignee {
+                details.push(format!("assignee: {}", escape_markdown(assignee)));
+            }
+            if let Some(dep) = &task.depends_on {
+                details.push(format!("depends on: {}", escape_markdown(dep)));
+            }
+            if !task.tags.is_empty() {
+                let tags: Vec<_> = task.tags.iter().map(|t| escape_markdown(t)).collect();
+                details.push(format!("tags: {}", tags.join(", ")));
+            }
+            if !details.is_empty() {
+                let _ = write!(out, " — {}", details.join("; "));
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Backslash-escapes characters Markdown would treat as inline syntax.
+fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Renders `doc` as an Org outline with projects as top-level headings.
+pub fn to_org(doc: &Document) -> String {
+    let mut out = String::new();
+    for project in &doc.projects {
+        let _ = writeln!(out, "* {}", project.name);
+        for task in &project.tasks {
+            let keyword = match task.status {
+                TaskStatus::Todo => "TODO",
+                TaskStatus::Done => "DONE",
+            };
+            let _ = write!(out, "** {}", keyword);
+            if let Some(priority) = &task.priority {
+                let cookie = match priority {
+                    Priority::High => 'A',
+                    Priority::Medium => 'B',
+                    Priority::Low => 'C',
+                };
+                let _ = write!(out, " [#{}]", cookie);
+            }
+            let _ = write!(out, " {}", task.title);
+            if !task.tags.is_empty() {
+                let tags: Vec<_> = task.tags.iter().map(|t| org_tag(t)).collect();
+                let _ = write!(out, " :{}:", tags.join(":"));
+            }
+            out.push('\n');
+
+            if let Some(due) = &task.due_date {
+                match task.due() {
+                    Some(date) => {
+                        let _ = writeln!(out, "DEADLINE: <{}>", date.format("%Y-%m-%d %a"));
+                    }
+                    None => {
+                        let _ = writeln!(out, "DEADLINE: <{}>", due);
+                    }
+                }
+            }
+            if task.assignee.is_some() || task.depends_on.is_some() {
+                out.push_str(":PROPERTIES:\n");
+                if let Some(assignee) = &task.assignee {
+                    let _ = writeln!(out, ":ASSIGNEE: {}", assignee);
+                }
+                if let Some(dep) = &task.depends_on {
+                    let _ = writeln!(out, ":DEPENDS_ON: {}", dep);
+                }
+                out.push_str(":END:\n");
+            }
+        }
+    }
+    out
+}
+
+/// Renders the tasks of `doc` as Jira importer CSV in the layout described
+/// above.
+pub fn to_jira(doc: &Document) -> String {
+    let refs: Vec<TaskRef> = doc.task_refs().collect();
+    let issue_id = |r: TaskRef| {
+        refs.iter()
+            .position(|&other| other == r)
+            .map(|i| (i + 1).to_string())
+    };
+    let label_columns = doc
+        .tasks()
+        .map(|(_, t)| t.tags.len())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    let mut header = vec![
+        "Issue Id",
+        "Summary",
+        "Issue Type",
+        "Status",
+        "Priority",
+        "Due Date",
+        "Assignee",
+        "Component",
+    ];
+    header.extend(std::iter::repeat_n("Labels", label_columns));
+    header.push("Inward issue link (Blocks)");
+    writer
+        .write_record(&header)
+        .expect("writing CSV to memory cannot fail");
+
+    let deps = doc.dependencies();
+    for &r in &refs {
+        let task = r.get(doc).expect("task ref is valid");
+        let project = r.project(doc).expect("task ref is valid");
+        let mut row = vec![
+            issue_id(r).unwrap_or_default(),
+            task.title.clone(),
+            "Task".to_string(),
+            match task.status {
+                TaskStatus::Todo => "To Do",
+                TaskStatus::Done => "Done",
+            }
+            .to_string(),
+            task.priority
+                .map_or("", |p| match p {
+                    Priority::High => "High",
+                    Priority::Medium => "Medium",
+                    Priority::Low => "Low",
+                })
+                .to_string(),
+            task.due_date.clone().unwrap_or_default(),
+            task.assignee.clone().unwrap_or_default(),
+            project.name.clone(),
+        ];
+        let mut labels: Vec<String> = task.tags.iter().map(|t| t.replace(' ', "_")).collect();
+        labels.resize(label_columns, String::new());
+        row.extend(labels);
+        row.push(deps.of(r).and_then(issue_id).unwrap_or_default());
+        writer
+            .write_record(&row)
+            .expect("writing CSV to memory cannot fail");
+    }
+    let bytes = writer
+        .into_inner()
+        .expect("flushing CSV to memory cannot fail");
+    String::from_utf8(bytes).expect("CSV output is built from UTF-8 strings")
+}
+
+/// Renders `doc` as Taskwarrior import JSON in the layout described above.
+pub fn to_taskwarrior(doc: &Document) -> String {
+    let uuid = |r: TaskRef| {
+        let project = r.project(doc).expect("task ref is valid");
+        let task = r.get(doc).expect("task ref is valid");
+        task_uuid(&project.name, &task.title)
+    };
+    let deps = doc.dependencies();
+    let tasks: Vec<TaskwarriorTask> = doc
+        .task_refs()
+        .map(|r| {
+            let task = r.get(doc).expect("task ref is valid");
+            TaskwarriorTask {
+                uuid: uuid(r),
+                description: &task.title,
+                status: match task.status {
+                    TaskStatus::Todo => "pending",
+                    TaskStatus::Done => "completed",
+                },
+                project: &r.project(doc).expect("task ref is valid").name,
+                priority: task.priority.map(|p| match p {
+                    Priority::High => "H",
+                    Priority::Medium => "M",
+                    Priority::Low => "L",
+                }),
+                due: task.due().map(taskwarrior_date),
+                end: task.completed_on().map(taskwarrior_date),
+                tags: task.tags.iter().map(|t| t.replace(' ', "_")).collect(),
+                depends: deps.of(r).into_iter().map(uuid).collect(),
+            }
+        })
+        .collect();
+    let mut out =
+        serde_json::to_string_pretty(&tasks).expect("Taskwarrior tasks serialize to JSON");
+    out.push('\n');
+    out
+}
+
+/// A task in Taskwarrior's import format.
+#[derive(Serialize)]
+struct TaskwarriorTask<'a> {
+    uuid: String,
+    description: &'a str,
+    status: &'static str,
+    project: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    priority: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    due: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    depends: Vec<String>,
+}
+
+/// Midnight UTC of `date` in Taskwarrior's date format.
+fn taskwarrior_date(date: NaiveDate) -> String {
+    date.format("%Y%m%dT000000Z").to_string()
+}
+
+/// A UUID (version 8, RFC 9562) that depends only on `project` and `title`.
+fn task_uuid(project: &str, title: &str) -> String {
+    // Two FNV-1a hashes with different offsets give the 128 bits.
+    let fnv = |offset: u64| {
+        project
+            .bytes()
+            .chain([0])
+            .chain(title.bytes())
+            .fold(offset, |hash, b| {
+                (hash ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3)
+            })
+    };
+    let bits =
+        (u128::from(fnv(0xcbf2_9ce4_8422_2325)) << 64) | u128::from(fnv(0x6c62_272e_07bb_0142));
+    let bits = (bits & !(0xf << 76) & !(0b11 << 62)) | (0x8 << 76) | (0b10 << 62);
+    let hex = format!("{:032x}", bits);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// Replaces characters that are not valid in an Org tag with `_`.
+fn org_tag(tag: &str) -> String {
+    tag.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '_' | '@' | '#' | '%') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Column names of the CSV export, in order.
+const CSV_HEADER: [&str; 7] = [
+    "project", "title", "status", "priority", "due", "assignee", "tags",
+];
+
+/// One CSV row; field order must match `CSV_HEADER`.
+#[derive(Serialize)]
+struct CsvRow<'a> {
+    project: &'a str,
+    title: &'a str,
+    status: &'static str,
+    priority: &'static str,
+    due: &'a str,
+    assignee: &'a str,
+    tags: String,
+}
+
+/// Serializable view of a `Document`, decoupled from the AST so that the
+/// exported layout does not change when internal fields do.
+#[derive(Serialize)]
+struct DocumentView<'a> {
+    version: u32,
+    projects: Vec<ProjectView<'a>>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct ProjectView<'a> {
+    name: &'a str,
+    file: Option<String>,
+    line: usize,
+    tasks: Vec<TaskView<'a>>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct TaskView<'a> {
+    status: &'static str,
+    title: &'a str,
+    id: Option<&'a str>,
+    priority: Option<&'static str>,
+    start: Option<&'a str>,
+    due: Option<&'a str>,
+    completed: Option<&'a str>,
+    assignee: Option<&'a str>,
+    depends_on: Option<&'a str>,
+    tags: &'a [String],
+    line: usize,
+}
+
+impl<'a> From<&'a Document> for DocumentView<'a> {
+    fn from(doc: &'a Document) -> Self {
+        DocumentView {
+            version: doc.version,
+            projects: doc.projects.iter().map(ProjectView::from).collect(),
+        }
+    }
+}
+
+impl<'a> From<&'a Project> for ProjectView<'a> {
+    fn from(project: &'a Project) -> Self {
+        ProjectView {
+            name: &project.name,
+            file: project.file.as_ref().map(|f| f.display().to_string()),
+            line: project.span.line,
+            tasks: project.tasks.iter().map(TaskView::from).collect(),
+        }
+    }
+}
+
+impl<'a> From<&'a Task> for TaskView<'a> {
+    fn from(task: &'a Task) -> Self {
+        TaskView {
+            status: task.status.as_str(),
+            title: &task.title,
+            id: task.id.as_deref(),
+            priority: task.priority.as_ref().map(|p| p.as_str()),
+            start: task.start_date.as_deref(),
+            due: task.due_date.as_deref(),
+            completed: task.completed.as_deref(),
+            assignee: task.assignee.as_deref(),
+            depends_on: task.depends_on.as_deref(),
+            tags: &task.tags,
+            line: task.span.line,
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Typed side-table attached to AST nodes.
+//!
+//! Analyzers can store their results (lint findings, computed schedules,
+//! resolved references, ...) on a `Task` or `Project` without the core
+//! structs having to know about them. Values are keyed by their type, so each
+//! analyzer typically defines its own newtype.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A map holding at most one value per type.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::extensions::Extensions;
+///
+/// #[derive(Clone, Debug, PartialEq)]
+/// struct Slack(u32);
+///
+/// let mut ext = Extensions::new();
+/// ext.insert(Slack(3));
+/// assert_eq!(ext.get::<Slack>(), Some(&Slack(3)));
+/// ```
+#[derive(Default)]
+pub struct Extensions {
+    map: HashMap<TypeId, Box<dyn AnyClone>>,
+}
+
+/// Object-safe `Any + Clone`, so `Extensions` itself can be `Clone`.
+trait AnyClone: Any + Send + Sync {
+    fn clone_box(&self) -> Box<dyn AnyClone>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<T: Clone + Send + Sync + 'static> AnyClone for T {
+    fn clone_box(&self) -> Box<dyn AnyClone> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+impl Extensions {
+    /// Creates an empty map. Does not allocate.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores `value`, returning the previous value of the same type.
+    pub fn insert<T: Clone + Send + Sync + 'static>(&mut self, value: T) -> Option<T> {
+        self.map
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|old| old.into_any().downcast().ok().map(|b| *b))
+    }
+
+    /// Returns a reference to the value of type `T`, if present.
+    pub fn get<T: 'static>(&self) -> Option<&T> {
+        self.map
+            .get(&TypeId::of::<T>())
+            .and_then(|v| (**v).as_any().downcast_ref())
+    }
+
+    /// Returns a mutable reference to the value of type `T`, if present.
+    pub fn get_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        self.map
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|v| (**v).as_any_mut().downcast_mut())
+    }
+
+    /// Removes and returns the value of type `T`, if present.
+    pub fn remove<T: 'static>(&mut self) -> Option<T> {
+        self.map
+            .remove(&TypeId::of::<T>())
+            .and_then(|old| old.into_any().downcast().ok().map(|b| *b))
+    }
+
+    /// Returns `true` if a value of type `T` is stored.
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.get::<T>().is_some()
+    }
+
+    /// Number of stored values.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if nothing is stored.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all stored values.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+}
+
+impl Clone for Extensions {
+    fn clone(&self) -> Self {
+        Self {
+            map: self
+                .map
+                .iter()
+                .map(|(k, v)| (*k, (**v).clone_box()))
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Extensions")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
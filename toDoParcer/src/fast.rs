@@ -0,0 +1,452 @@
+//! A hand-written recursive-descent parser for `grammar.pest`, used in
+//! place of the pest-generated one with the `fast-parser` feature.
+//!
+//! It accepts the same input and builds the same tree: the same rules,
+//! with the same spans, down to the whitespace pest leaves inside a rule
+//! before an empty repetition. The walk that turns the tree into events is
+//! shared, so documents, warnings and semantic errors don't depend on the
+//! parser. Only finding syntax errors is left to pest: input this parser
+//! rejects is parsed again by pest, which reports what it expected where.
+//!
+//! Every rule is a function that takes the position to match at and
+//! returns the position after the match, or `None`. Like the generated
+//! parser, sequences skip whitespace and comments between their elements,
+//! and ordered choices try each alternative from the same position.
+
+use crate::parser::{Rule, SyntaxNode};
+
+/// The rules matched by a successful parse of a whole file.
+pub(crate) struct Tree<'i> {
+    input: &'i str,
+    /// Rules in pre-order: every node is followed by its descendants.
+    nodes: Vec<RawNode>,
+}
+
+/// One matched rule.
+#[derive(Debug, Clone, Copy)]
+struct RawNode {
+    rule: Rule,
+    start: usize,
+    end: usize,
+    /// Index of the first node after this one's descendants.
+    next: usize,
+}
+
+/// A rule in a [`Tree`].
+#[derive(Clone, Copy)]
+pub(crate) struct Node<'t> {
+    tree: &'t Tree<'t>,
+    index: usize,
+}
+
+/// The children of a [`Node`], in source order.
+#[derive(Clone)]
+pub(crate) struct Children<'t> {
+    tree: &'t Tree<'t>,
+    next: usize,
+    end: usize,
+}
+
+impl<'i> Tree<'i> {
+    /// The `file` rule.
+    pub(crate) fn root(&self) -> Node<'_> {
+        Node {
+            tree: self,
+            index: 0,
+        }
+    }
+}
+
+impl<'t> SyntaxNode<'t> for Node<'t> {
+    type Inner = Children<'t>;
+
+    fn as_rule(&self) -> Rule {
+        self.raw().rule
+    }
+
+    fn as_str(&self) -> &'t str {
+        &self.tree.input[self.raw().start..self.raw().end]
+    }
+
+    fn start(&self) -> usize {
+        self.raw().start
+    }
+
+    fn end(&self) -> usize {
+        self.raw().end
+    }
+
+    fn input(&self) -> &'t str {
+        self.tree.input
+    }
+
+    fn into_inner(self) -> Children<'t> {
+        Children {
+            tree: self.tree,
+            next: self.index + 1,
+            end: self.raw().next,
+        }
+    }
+}
+
+impl Node<'_> {
+    fn raw(&self) -> &RawNode {
+        &self.tree.nodes[self.index]
+    }
+}
+
+impl<'t> Iterator for Children<'t> {
+    type Item = Node<'t>;
+
+    fn next(&mut self) -> Option<Node<'t>> {
+        if self.next >= self.end {
+            return None;
+        }
+        let node = Node {
+            tree: self.tree,
+            index: self.next,
+        };
+        self.next = self.tree.nodes[self.next].next;
+        Some(node)
+    }
+}
+
+/// Parses `input` with the `file` rule.
+///
+/// # Returns
+/// * `Some(Tree)` if `input` matches
+/// * `None` otherwise, including for input without any project or include
+pub(crate) fn parse(input: &str) -> Option<Tree<'_>> {
+    let mut parser = Parser {
+        input: input.as_bytes(),
+        nodes: Vec::new(),
+    };
+    parser.file()?;
+    Some(Tree {
+        input,
+        nodes: parser.nodes,
+    })
+}
+
+struct Parser<'i> {
+    input: &'i [u8],
+    nodes: Vec<RawNode>,
+}
+
+/// Bytes of `identifier`.
+fn is_identifier(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'-'
+}
+
+impl Parser<'_> {
+    /// Matches `rule` with `body`, recording a node for it; on failure,
+    /// drops the nodes `body` recorded.
+    fn rule(
+        &mut self,
+        rule: Rule,
+        pos: usize,
+        body: impl FnOnce(&mut Self, usize) -> Option<usize>,
+    ) -> Option<usize> {
+        let index = self.nodes.len();
+        self.nodes.push(RawNode {
+            rule,
+            start: pos,
+            end: pos,
+            next: 0,
+        });
+        match body(self, pos) {
+            Some(end) => {
+                let next = self.nodes.len();
+                let node = &mut self.nodes[index];
+                node.end = end;
+                node.next = next;
+                Some(end)
+            }
+            None => {
+                self.nodes.truncate(index);
+                None
+            }
+        }
+    }
+
+    /// `e*` of a non-atomic rule: `e` as often as it matches, with
+    /// whitespace skipped between repetitions but not after the last.
+    fn repeat(
+        &mut self,
+        pos: usize,
+        mut e: impl FnMut(&mut Self, usize) -> Option<usize>,
+    ) -> usize {
+        let Some(mut pos) = e(self, pos) else {
+            return pos;
+        };
+        loop {
+            let skipped = self.skip(pos);
+            match e(self, skipped) {
+                Some(next) => pos = next,
+                None => return pos,
+            }
+        }
+    }
+
+    /// The implicit `WHITESPACE* ~ (COMMENT ~ WHITESPACE*)*` between the
+    /// elements of a sequence.
+    fn skip(&self, mut pos: usize) -> usize {
+        let input = self.input;
+        loop {
+            match input.get(pos) {
+                Some(b' ' | b'\t' | b'\n') => pos += 1,
+                Some(b'\r') if input.get(pos + 1) == Some(&b'\n') => pos += 2,
+                Some(b'/') if input.get(pos + 1) == Some(&b'/') => {
+                    // The comment and the line break that ends it.
+                    pos = match input[pos + 2..].iter().position(|&b| b == b'\n') {
+                        Some(n) => pos + 2 + n + 1,
+                        None => input.len(),
+                    };
+                }
+                _ => return pos,
+            }
+        }
+    }
+
+    /// A literal, matched exactly.
+    fn literal(&self, pos: usize, text: &str) -> Option<usize> {
+        let end = pos + text.len();
+        (self.input.get(pos..end)? == text.as_bytes()).then_some(end)
+    }
+
+    /// A `^"..."` literal, matched in any ASCII case.
+    fn keyword(&self, pos: usize, text: &str) -> Option<usize> {
+        let end = pos + text.len();
+        self.input
+            .get(pos..end)?
+            .eq_ignore_ascii_case(text.as_bytes())
+            .then_some(end)
+    }
+
+    /// `file = { SOI ~ version? ~ (project | include)+ ~ EOI }`
+    fn file(&mut self) -> Option<usize> {
+        self.rule(Rule::file, 0, |p, pos| {
+            let mut pos = p.skip(pos);
+            pos = p.version(pos).unwrap_or(pos);
+            pos = p.skip(pos);
+            pos = p.item(pos)?;
+            pos = p.skip(pos);
+            pos = p.repeat(pos, Self::item);
+            pos = p.skip(pos);
+            if pos != p.input.len() {
+                return None;
+            }
+            p.rule(Rule::EOI, pos, |_, pos| Some(pos))
+        })
+    }
+
+    /// `project | include`
+    fn item(&mut self, pos: usize) -> Option<usize> {
+        self.project(pos).or_else(|| self.include(pos))
+    }
+
+    /// `version = { ^"version" ~ version_number }`
+    fn version(&mut self, pos: usize) -> Option<usize> {
+        self.rule(Rule::version, pos, |p, pos| {
+            let pos = p.keyword(pos, "version")?;
+            let pos = p.skip(pos);
+            p.rule(Rule::version_number, pos, |p, pos| {
+                let digits = p.input[pos..].iter().take_while(|b| b.is_ascii_digit());
+                Some(pos + digits.count()).filter(|&end| end > pos)
+            })
+        })
+    }
+
+    /// `include = { ^"include" ~ quoted }`
+    fn include(&mut self, pos: usize) -> Option<usize> {
+        self.rule(Rule::include, pos, |p, pos| {
+            let pos = p.keyword(pos, "include")?;
+            let pos = p.skip(pos);
+            p.quoted(pos)
+        })
+    }
+
+    /// `project = { ^"project" ~ quoted ~ "{" ~ task* ~ "}" }`
+    fn project(&mut self, pos: usize) -> Option<usize> {
+        self.rule(Rule::project, pos, |p, pos| {
+            let pos = p.keyword(pos, "project")?;
+            let pos = p.skip(pos);
+            let pos = p.quoted(pos)?;
+            let pos = p.skip(pos);
+            let pos = p.literal(pos, "{")?;
+            let pos = p.skip(pos);
+            let pos = p.repeat(pos, Self::task);
+            let pos = p.skip(pos);
+            p.literal(pos, "}")
+        })
+    }
+
+    /// `task = { (todo_task | done_task) ~ "," }`
+    fn task(&mut self, pos: usize) -> Option<usize> {
+        self.rule(Rule::task, pos, |p, pos| {
+            let pos = p
+                .task_body(Rule::todo_task, "todo:", pos)
+                .or_else(|| p.task_body(Rule::done_task, "done:", pos))?;
+            let pos = p.skip(pos);
+            p.literal(pos, ",")
+        })
+    }
+
+    /// `todo_task = { ^"todo:" ~ quoted ~ attribute_list }`, or the same
+    /// for `done_task`.
+    fn task_body(&mut self, rule: Rule, keyword: &str, pos: usize) -> Option<usize> {
+        self.rule(rule, pos, |p, pos| {
+            let pos = p.keyword(pos, keyword)?;
+            let pos = p.skip(pos);
+            let pos = p.quoted(pos)?;
+            let pos = p.skip(pos);
+            p.rule(Rule::attribute_list, pos, |p, pos| {
+                Some(p.repeat(pos, |p, pos| {
+                    let pos = p.literal(pos, ",")?;
+                    let pos = p.skip(pos);
+                    p.attribute(pos)
+                }))
+            })
+        })
+    }
+
+    /// `attribute = { tag | priority | due_date | id | assignee |
+    /// depends_on | start_date | completed | unknown_attribute }`
+    fn attribute(&mut self, pos: usize) -> Option<usize> {
+        self.rule(Rule::attribute, pos, |p, pos| {
+            p.tag(pos)
+                .or_else(|| p.priority(pos))
+                .or_else(|| p.prefixed(Rule::due_date, "due:", pos, Self::date))
+                .or_else(|| p.prefixed(Rule::id, "id:", pos, Self::identifier))
+                .or_else(|| p.assignee(pos))
+                .or_else(|| p.prefixed(Rule::depends_on, "depends_on:", pos, Self::quoted))
+                .or_else(|| p.prefixed(Rule::start_date, "start:", pos, Self::date))
+                .or_else(|| p.prefixed(Rule::completed, "completed:", pos, Self::date))
+                .or_else(|| p.unknown_attribute(pos))
+        })
+    }
+
+    /// A rule of the form `^"name:" ~ value`.
+    fn prefixed(
+        &mut self,
+        rule: Rule,
+        keyword: &str,
+        pos: usize,
+        value: impl FnOnce(&mut Self, usize) -> Option<usize>,
+    ) -> Option<usize> {
+        self.rule(rule, pos, |p, pos| {
+            let pos = p.keyword(pos, keyword)?;
+            let pos = p.skip(pos);
+            value(p, pos)
+        })
+    }
+
+    /// `priority = @{ (^"@high" | ^"@medium" | ^"@low") ~ !identifier }`
+    fn priority(&mut self, pos: usize) -> Option<usize> {
+        self.rule(Rule::priority, pos, |p, pos| {
+            let end = p
+                .keyword(pos, "@high")
+                .or_else(|| p.keyword(pos, "@medium"))
+                .or_else(|| p.keyword(pos, "@low"))?;
+            match p.input.get(end) {
+                Some(&b) if is_identifier(b) => None,
+                _ => Some(end),
+            }
+        })
+    }
+
+    /// `assignee = { ^"assign:" ~ "@" ~ identifier }`
+    fn assignee(&mut self, pos: usize) -> Option<usize> {
+        self.rule(Rule::assignee, pos, |p, pos| {
+            let pos = p.keyword(pos, "assign:")?;
+            let pos = p.skip(pos);
+            let pos = p.literal(pos, "@")?;
+            let pos = p.skip(pos);
+            p.identifier(pos)
+        })
+    }
+
+    /// `tag = { tag_marker ~ quoted }` with
+    /// `tag_marker = @{ ^"@tag:" | ^"tag:" }`
+    fn tag(&mut self, pos: usize) -> Option<usize> {
+        self.rule(Rule::tag, pos, |p, pos| {
+            let pos = p.rule(Rule::tag_marker, pos, |p, pos| {
+                p.keyword(pos, "@tag:").or_else(|| p.keyword(pos, "tag:"))
+            })?;
+            let pos = p.skip(pos);
+            p.quoted(pos)
+        })
+    }
+
+    /// `unknown_attribute = { !task_keyword ~ attribute_name ~ ":" ~ attribute_value }`
+    fn unknown_attribute(&mut self, pos: usize) -> Option<usize> {
+        self.rule(Rule::unknown_attribute, pos, |p, pos| {
+            if p.task_keyword(pos) {
+                return None;
+            }
+            let pos = p.skip(pos);
+            let pos = p.rule(Rule::attribute_name, pos, |p, pos| {
+                if !p.input.get(pos)?.is_ascii_alphabetic() {
+                    return None;
+                }
+                let rest = p.input[pos + 1..].iter();
+                let rest = rest.take_while(|b| b.is_ascii_alphanumeric() || **b == b'_');
+                Some(pos + 1 + rest.count())
+            })?;
+            let pos = p.skip(pos);
+            let pos = p.literal(pos, ":")?;
+            let pos = p.skip(pos);
+            p.rule(Rule::attribute_value, pos, |p, pos| {
+                p.quoted(pos).or_else(|| {
+                    let pos = p.literal(pos, "@").unwrap_or(pos);
+                    let pos = p.skip(pos);
+                    p.identifier(pos)
+                })
+            })
+        })
+    }
+
+    /// Whether `task_keyword = _{ (^"todo" | ^"done") ~ ":" }` matches.
+    fn task_keyword(&self, pos: usize) -> bool {
+        self.keyword(pos, "todo")
+            .or_else(|| self.keyword(pos, "done"))
+            .and_then(|end| self.literal(self.skip(end), ":"))
+            .is_some()
+    }
+
+    /// `quoted = @{ "\"" ~ (!"\"" ~ ANY)* ~ "\"" }`
+    fn quoted(&mut self, pos: usize) -> Option<usize> {
+        if self.input.get(pos) != Some(&b'"') {
+            return None;
+        }
+        let len = self.input[pos + 1..].iter().position(|&b| b == b'"')?;
+        let end = pos + 1 + len + 1;
+        self.rule(Rule::quoted, pos, |_, _| Some(end))
+    }
+
+    /// `identifier = @{ (ASCII_ALPHANUMERIC | "_" | "-")+ }`
+    fn identifier(&mut self, pos: usize) -> Option<usize> {
+        let len = self.input[pos..]
+            .iter()
+            .take_while(|&&b| is_identifier(b))
+            .count();
+        if len == 0 {
+            return None;
+        }
+        self.rule(Rule::identifier, pos, |_, pos| Some(pos + len))
+    }
+
+    /// `date`: `YYYY-MM-DD`, `DD.MM.YYYY` or `MM/DD/YYYY`.
+    fn date(&mut self, pos: usize) -> Option<usize> {
+        let text = self.input.get(pos..pos + 10)?;
+        let digits = |range: std::ops::Range<usize>| text[range].iter().all(u8::is_ascii_digit);
+        let local = |sep: u8| text[2] == sep && text[5] == sep;
+        let iso =
+            text[4] == b'-' && text[7] == b'-' && digits(0..4) && digits(5..7) && digits(8..10);
+        let local = (local(b'.') || local(b'/')) && digits(0..2) && digits(3..5) && digits(6..10);
+        if !(iso || local) {
+            return None;
+        }
+        self.rule(Rule::date, pos, |_, pos| Some(pos + 10))
+    }
+}
@@ -0,0 +1,100 @@
+//! A hash of what a document says, not how it is written.
+//!
+//! Two documents have the same fingerprint when they have the same
+//! projects with the same tasks, whatever their layout, comments, format
+//! version, order of projects, tasks and tags, or the file each project
+//! was included from. Projects that share a name count as one.
+
+use std::collections::BTreeMap;
+
+use crate::parser::{Document, Task};
+
+/// FNV-1a parameters: the hash is the same on every platform and release.
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+impl Document {
+    /// Hash of the projects and tasks of the document that ignores
+    /// whitespace, comments and ordering.
+    ///
+    /// Everything the parser reads is covered: names, titles, status,
+    /// ids, priorities, dates, assignees, dependencies and tags. A tag
+    /// written twice counts once; a task written twice counts twice.
+    ///
+    /// # Returns
+    /// * A 64-bit FNV-1a hash; different documents can collide, but
+    ///   rarely.
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::ToDoParser;
+    ///
+    /// let a = ToDoParser::parse_document(
+    ///     "project \"P\" {\n  todo: \"A\", @tag: \"x\", @tag: \"y\",\n  done: \"B\",\n}\n",
+    /// )?;
+    /// let b = ToDoParser::parse_document(
+    ///     "// reordered\nproject \"P\" { done: \"B\", todo: \"A\", @tag: \"y\", @tag: \"x\", }",
+    /// )?;
+    /// let c = ToDoParser::parse_document("project \"P\" { todo: \"A\", done: \"B\", }")?;
+    /// assert_eq!(a.fingerprint(), b.fingerprint());
+    /// assert_ne!(a.fingerprint(), c.fingerprint());
+    /// # Ok::<(), to_do_parcer::ParseError>(())
+    /// ```
+    pub fn fingerprint(&self) -> u64 {
+        let mut projects: BTreeMap<&str, Vec<Vec<u8>>> = BTreeMap::new();
+        for project in &self.projects {
+            projects
+                .entry(&project.name)
+                .or_default()
+                .extend(project.tasks.iter().map(encode_task));
+        }
+
+        let mut bytes = Vec::new();
+        for (name, mut tasks) in projects {
+            tasks.sort_unstable();
+            field(&mut bytes, Some(name));
+            bytes.extend_from_slice(&(tasks.len() as u64).to_le_bytes());
+            for task in tasks {
+                bytes.extend_from_slice(&task);
+            }
+        }
+        bytes.iter().fold(FNV_OFFSET, |hash, &b| {
+            (hash ^ u64::from(b)).wrapping_mul(FNV_PRIME)
+        })
+    }
+}
+
+/// Unambiguous bytes for the parts of `task` the fingerprint covers.
+fn encode_task(task: &Task) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    field(&mut bytes, Some(task.status.as_str()));
+    field(&mut bytes, Some(&task.title));
+    field(&mut bytes, task.id.as_deref());
+    field(&mut bytes, task.priority.map(|p| p.as_str()));
+    field(&mut bytes, task.start_date.as_deref());
+    field(&mut bytes, task.due_date.as_deref());
+    field(&mut bytes, task.completed.as_deref());
+    field(&mut bytes, task.assignee.as_deref());
+    field(&mut bytes, task.depends_on.as_deref());
+    let mut tags: Vec<&str> = task.tags.iter().map(String::as_str).collect();
+    tags.sort_unstable();
+    tags.dedup();
+    bytes.extend_from_slice(&(tags.len() as u64).to_le_bytes());
+    for tag in tags {
+        field(&mut bytes, Some(tag));
+    }
+    bytes
+}
+
+/// Appends `value` with its length, or a marker for a missing value, so
+/// that no two sequences of fields encode the same.
+fn field(bytes: &mut Vec<u8>, value: Option<&str>) {
+    match value {
+        Some(value) => {
+            bytes.push(1);
+            bytes.extend_from_slice(&(value.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(value.as_bytes());
+        }
+        None => bytes.push(0),
+    }
+}
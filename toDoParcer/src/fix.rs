@@ -0,0 +1,228 @@
+//! Machine-applicable fixes for common syntax errors.
+//!
+//! Three mistakes are recognized:
+//!
+//! * a task line without its trailing comma;
+//! * a quote that is opened but not closed on its line, closed before the
+//!   first comma after it, or at the end of the line;
+//! * a `start:`, `due:` or `completed:` date written with `/` or `.`
+//!   between year, month and day, such as `2025/12/31`.
+//!
+//! The grammar can't tell where such a mistake is, so the text is looked
+//! at line by line, and the fix of a line is only suggested if the parser
+//! gets past it: the file is syntactically valid, or fails at a later
+//! position on a later line.
+
+use crate::diagnostic::Fix;
+use crate::options::ParseOptions;
+use crate::parser::{ParseError, Span, ToDoParser};
+
+/// Attributes whose value is a date.
+const DATE_KEYWORDS: &[&str] = &["start:", "due:", "completed:"];
+
+/// Result of [`fix_all`].
+#[derive(Debug)]
+pub struct Fixed {
+    /// The text with every fix applied.
+    pub text: String,
+    /// The fixes, in the order they were applied. Each span points into
+    /// the text as it was after the fixes before it.
+    pub applied: Vec<Fix>,
+    /// Error the text still fails with, if fixing didn't get it to parse.
+    pub remaining: Option<ParseError>,
+}
+
+/// The fix for the first syntax error of `source`, if one is recognized.
+///
+/// # Arguments
+/// * `source` - Text of the file
+/// * `options` - Options the file is parsed with
+///
+/// # Returns
+/// * `None` if the text parses, fails with anything but a syntax error,
+///   or no candidate gets the parser further.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::fix::suggest_fix;
+/// use to_do_parcer::ParseOptions;
+///
+/// let source = "project \"P\" {\n  todo: \"A\", due: 2025/12/31,\n}\n";
+/// let fix = suggest_fix(source, &ParseOptions::default()).unwrap();
+/// assert_eq!((fix.span.line, fix.span.column), (2, 19));
+/// assert_eq!(fix.to_string(), "replace with `2025-12-31`");
+/// ```
+pub fn suggest_fix(source: &str, options: &ParseOptions) -> Option<Fix> {
+    let error = ToDoParser::parse_with(source, options).err()?;
+    if !matches!(error, ParseError::Pest(_)) {
+        return None;
+    }
+    let failed_at = error.position().map(|(line, column, _)| (line, column));
+    candidates(source).into_iter().find(|fix| {
+        match ToDoParser::parse_with(&fix.apply(source), options) {
+            Ok(_) => true,
+            // The syntax is right now; what is left is for the author.
+            Err(e) if !matches!(e, ParseError::Pest(_)) => true,
+            Err(e) => {
+                let now = e.position().map(|(line, column, _)| (line, column));
+                now > failed_at && now.is_some_and(|(line, _)| line > fix.span.line)
+            }
+        }
+    })
+}
+
+/// Applies [`suggest_fix`] until the text parses or nothing more can be
+/// fixed.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::fix::fix_all;
+/// use to_do_parcer::ParseOptions;
+///
+/// let source = "project \"P\" {\n  todo: \"A\n  todo: \"B\", @high\n}\n";
+/// let fixed = fix_all(source, &ParseOptions::default());
+/// assert_eq!(fixed.text, "project \"P\" {\n  todo: \"A\",\n  todo: \"B\", @high,\n}\n");
+/// assert_eq!(fixed.applied.len(), 2);
+/// assert!(fixed.remaining.is_none());
+/// ```
+pub fn fix_all(source: &str, options: &ParseOptions) -> Fixed {
+    let mut text = source.to_string();
+    let mut applied = Vec::new();
+    // Every fix moves the first error further, so this is only a backstop.
+    for _ in 0..=3 * source.lines().count() {
+        let Some(fix) = suggest_fix(&text, options) else {
+            break;
+        };
+        text = fix.apply(&text);
+        applied.push(fix);
+    }
+    let remaining = ToDoParser::parse_with(&text, options).err();
+    Fixed {
+        text,
+        applied,
+        remaining,
+    }
+}
+
+/// The fix of every line that looks like it needs one, in file order.
+fn candidates(source: &str) -> Vec<Fix> {
+    let mut fixes = Vec::new();
+    let mut start = 0;
+    for (index, line) in source.split_inclusive('\n').enumerate() {
+        let content = code_of(line).trim_end();
+        let fixed = fix_line(content);
+        if fixed != content {
+            // Replace only what changed: an insertion, or the whole words
+            // that a replacement touches.
+            let mut prefix = common_prefix(content, &fixed);
+            let mut suffix = common_suffix(&content[prefix..], &fixed[prefix..]);
+            if prefix + suffix < content.len() {
+                let word = |c: char| !(c.is_whitespace() || c == ',');
+                prefix = content[..prefix].trim_end_matches(word).len();
+                suffix = content[content.len() - suffix..]
+                    .trim_start_matches(word)
+                    .len();
+            }
+            fixes.push(Fix {
+                span: Span {
+                    start: start + prefix,
+                    end: start + content.len() - suffix,
+                    line: index + 1,
+                    column: content[..prefix].chars().count() + 1,
+                },
+                replacement: fixed[prefix..fixed.len() - suffix].to_string(),
+            });
+        }
+        start += line.len();
+    }
+    fixes
+}
+
+/// `content`, a line without its comment, with the mistakes described
+/// in the module documentation corrected.
+fn fix_line(content: &str) -> String {
+    let mut fixed = content.to_string();
+    for keyword in DATE_KEYWORDS {
+        let Some(found) = fixed.to_ascii_lowercase().find(keyword) else {
+            continue;
+        };
+        let after = &fixed[found + keyword.len()..];
+        let value = after.trim_start();
+        let offset = fixed.len() - value.len();
+        let len = value
+            .find(|c: char| !(c.is_ascii_digit() || matches!(c, '-' | '/' | '.')))
+            .unwrap_or(value.len());
+        if let Some(iso) = iso_date(&value[..len]) {
+            fixed.replace_range(offset..offset + len, &iso);
+        }
+    }
+
+    if fixed.matches('"').count() % 2 == 1 {
+        let open = fixed.rfind('"').expect("an odd count means one quote");
+        let close = fixed[open..]
+            .find(',')
+            .map_or(fixed.len(), |comma| open + comma);
+        fixed.insert(close, '"');
+    }
+
+    let task = fixed.trim_start().to_ascii_lowercase();
+    if (task.starts_with("todo:") || task.starts_with("done:")) && !fixed.ends_with(',') {
+        fixed.push(',');
+    }
+    fixed
+}
+
+/// Length in bytes of the longest common prefix of `a` and `b`, at a
+/// character boundary.
+fn common_prefix(a: &str, b: &str) -> usize {
+    a.char_indices()
+        .zip(b.chars())
+        .find(|((_, x), y)| x != y)
+        .map_or(a.len().min(b.len()), |((i, _), _)| i)
+}
+
+/// Length in bytes of the longest common suffix of `a` and `b`, at a
+/// character boundary.
+fn common_suffix(a: &str, b: &str) -> usize {
+    a.chars()
+        .rev()
+        .zip(b.chars().rev())
+        .take_while(|(x, y)| x == y)
+        .map(|(x, _)| x.len_utf8())
+        .sum()
+}
+
+/// `line` without its `//` comment, if it has one outside quotes.
+fn code_of(line: &str) -> &str {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '/' if !quoted && line[i + 1..].starts_with('/') => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// `YYYY-MM-DD` for a date written `YYYY/MM/DD` or `YYYY.MM.DD`.
+fn iso_date(token: &str) -> Option<String> {
+    let bytes = token.as_bytes();
+    let digits = |range: std::ops::Range<usize>| bytes[range].iter().all(u8::is_ascii_digit);
+    let separator = |b: u8| matches!(b, b'-' | b'/' | b'.');
+    let shaped = bytes.len() == 10
+        && digits(0..4)
+        && digits(5..7)
+        && digits(8..10)
+        && separator(bytes[4])
+        && separator(bytes[7]);
+    if !shaped || (bytes[4] == b'-' && bytes[7] == b'-') {
+        return None;
+    }
+    Some(format!(
+        "{}-{}-{}",
+        &token[0..4],
+        &token[5..7],
+        &token[8..10]
+    ))
+}
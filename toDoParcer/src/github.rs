@@ -0,0 +1,379 @@
+//! Two-way sync of tasks with GitHub issues (`github` feature).
+//!
+//! Tasks carrying the sync tag (`@tag: "github"` by default) are matched
+//! to issues of one repository by exact title. Syncing then:
+//!
+//! * creates an issue for each open task that has none; its labels are the
+//!   task's other tags plus `priority: <level>`, and its assignee is the
+//!   task's, translated through the assignee map;
+//! * adds missing labels and assignees to matched issues (nothing is ever
+//!   removed, so labels added on GitHub survive) and closes issues of done
+//!   tasks;
+//! * marks open tasks done when their issue was closed, recording the day
+//!   it was closed.
+//!
+//! [`plan`] works out these steps without any network access; [`Client`]
+//! talks to the REST API and [`apply`] writes the pulled state back.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::{DateTime, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::deps::TaskRef;
+use crate::edit::Editor;
+use crate::parser::{Document, Task, TaskStatus};
+
+/// Base URL of the public GitHub REST API.
+pub const API_URL: &str = "https://api.github.com";
+
+/// Errors from talking to GitHub.
+#[derive(Debug, Error)]
+pub enum GithubError {
+    /// The API answered with an error status.
+    #[error("GitHub answered {status}: {message}")]
+    Http { status: u16, message: String },
+
+    /// The request didn't reach GitHub or the answer was cut off.
+    #[error("cannot reach GitHub: {0}")]
+    Transport(String),
+
+    /// The answer isn't the JSON that was expected.
+    #[error("unexpected answer from GitHub: {0}")]
+    Decode(String),
+}
+
+/// An issue as returned by the REST API; only the fields sync uses.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Issue {
+    pub number: u64,
+    pub title: String,
+    /// `open` or `closed`.
+    pub state: String,
+    /// RFC 3339 time the issue was closed.
+    #[serde(default)]
+    pub closed_at: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    #[serde(default)]
+    pub assignees: Vec<User>,
+    /// Present when the "issue" is a pull request.
+    #[serde(default)]
+    pub pull_request: Option<Value>,
+}
+
+/// Label of an issue.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Label {
+    pub name: String,
+}
+
+/// GitHub account.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct User {
+    pub login: String,
+}
+
+impl Issue {
+    /// Whether the issue is closed.
+    pub fn is_closed(&self) -> bool {
+        self.state == "closed"
+    }
+
+    /// Day the issue was closed, if known.
+    fn closed_on(&self) -> Option<NaiveDate> {
+        let closed_at = self.closed_at.as_deref()?;
+        DateTime::parse_from_rfc3339(closed_at)
+            .ok()
+            .map(|time| time.date_naive())
+    }
+}
+
+/// Which tasks take part in a sync and how names map to GitHub.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncOptions {
+    /// Tag marking the tasks to sync.
+    pub tag: String,
+    /// GitHub login for each assignee whose login differs from their name.
+    pub assignees: BTreeMap<String, String>,
+}
+
+impl Default for SyncOptions {
+    fn default() -> Self {
+        SyncOptions {
+            tag: "github".to_string(),
+            assignees: BTreeMap::new(),
+        }
+    }
+}
+
+/// Issue fields sent when creating or updating an issue.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IssueFields {
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    pub labels: Vec<String>,
+    pub assignees: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<&'static str>,
+}
+
+/// One step of a sync.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    /// Open a new issue for the task.
+    Create { task: TaskRef, fields: IssueFields },
+    /// Add labels or assignees to an issue, or close it.
+    Update {
+        task: TaskRef,
+        number: u64,
+        fields: IssueFields,
+    },
+    /// Mark the task done because its issue was closed.
+    Complete {
+        task: TaskRef,
+        number: u64,
+        closed_on: Option<NaiveDate>,
+    },
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Create { fields, .. } => write!(f, "create issue \"{}\"", fields.title),
+            Action::Update { number, fields, .. } if fields.state == Some("closed") => {
+                write!(f, "close issue #{} \"{}\"", number, fields.title)
+            }
+            Action::Update { number, fields, .. } => {
+                write!(f, "update issue #{} \"{}\"", number, fields.title)
+            }
+            Action::Complete { number, .. } => {
+                write!(f, "mark task of closed issue #{} done", number)
+            }
+        }
+    }
+}
+
+/// Works out the steps that bring `doc` and `issues` in line.
+///
+/// # Arguments
+/// * `doc` - Document holding the tasks; handles in the actions point into it
+/// * `issues` - Every issue of the repository, open and closed
+/// * `options` - Sync tag and assignee map
+///
+/// # Example
+/// ```
+/// use to_do_parcer::ToDoParser;
+/// use to_do_parcer::github::{Action, SyncOptions, plan};
+///
+/// let doc = ToDoParser::parse_document(
+///     r#"project "P" { todo: "Fix login", @high, @tag: "github", @tag: "bug", }"#,
+/// )?;
+/// let actions = plan(&doc, &[], &SyncOptions::default());
+/// let Action::Create { fields, .. } = &actions[0] else { panic!() };
+/// assert_eq!(fields.labels, ["bug", "priority: high"]);
+/// # Ok::<(), to_do_parcer::ParseError>(())
+/// ```
+pub fn plan(doc: &Document, issues: &[Issue], options: &SyncOptions) -> Vec<Action> {
+    let mut actions = Vec::new();
+    for r in doc.task_refs() {
+        let task = r.get(doc).expect("task ref is valid");
+        if !task.tags.contains(&options.tag) {
+            continue;
+        }
+        let project = r.project(doc).expect("task ref is valid");
+        let labels = labels(task, options);
+        let assignees: Vec<String> = task
+            .assignee
+            .iter()
+            .map(|a| options.assignees.get(a).unwrap_or(a).clone())
+            .collect();
+        let issue = issues
+            .iter()
+            .find(|i| i.pull_request.is_none() && i.title == task.title);
+
+        let Some(issue) = issue else {
+            if task.status == TaskStatus::Todo {
+                actions.push(Action::Create {
+                    task: r,
+                    fields: IssueFields {
+                        title: task.title.clone(),
+                        body: Some(issue_body(&project.name, task)),
+                        labels,
+                        assignees,
+                        state: None,
+                    },
+                });
+            }
+            continue;
+        };
+
+        if issue.is_closed() && task.status == TaskStatus::Todo {
+            actions.push(Action::Complete {
+                task: r,
+                number: issue.number,
+                closed_on: issue.closed_on(),
+            });
+            continue;
+        }
+
+        let have_labels: Vec<String> = issue.labels.iter().map(|l| l.name.clone()).collect();
+        let have_assignees: Vec<String> = issue.assignees.iter().map(|u| u.login.clone()).collect();
+        let close = task.status == TaskStatus::Done && !issue.is_closed();
+        let missing_labels = labels.iter().any(|l| !have_labels.contains(l));
+        let missing_assignees = assignees.iter().any(|a| !have_assignees.contains(a));
+        if close || missing_labels || missing_assignees {
+            actions.push(Action::Update {
+                task: r,
+                number: issue.number,
+                fields: IssueFields {
+                    title: issue.title.clone(),
+                    body: None,
+                    labels: union(have_labels, labels),
+                    assignees: union(have_assignees, assignees),
+                    state: close.then_some("closed"),
+                },
+            });
+        }
+    }
+    actions
+}
+
+/// Labels of the issue for `task`: its tags other than the sync tag, then
+/// its priority.
+fn labels(task: &Task, options: &SyncOptions) -> Vec<String> {
+    task.tags
+        .iter()
+        .filter(|t| **t != options.tag)
+        .cloned()
+        .chain(task.priority.map(|p| format!("priority: {}", p.as_str())))
+        .collect()
+}
+
+/// Description of a new issue.
+fn issue_body(project: &str, task: &Task) -> String {
+    let mut body = format!("Synced from project \"{}\".", project);
+    if let Some(due) = &task.due_date {
+        body.push_str(&format!("\n\nDue: {}", due));
+    }
+    body
+}
+
+/// `have` followed by the items of `want` it lacks.
+fn union(mut have: Vec<String>, want: Vec<String>) -> Vec<String> {
+    for item in want {
+        if !have.contains(&item) {
+            have.push(item);
+        }
+    }
+    have
+}
+
+/// Marks the tasks of `Complete` actions done in `editor`, whose document
+/// the actions were planned on.
+///
+/// # Returns
+/// * The number of tasks changed.
+pub fn apply(editor: &mut Editor, actions: &[Action]) -> usize {
+    actions
+        .iter()
+        .filter(|action| match action {
+            Action::Complete {
+                task, closed_on, ..
+            } => editor.complete_at(*task, *closed_on),
+            _ => false,
+        })
+        .count()
+}
+
+/// Minimal client for the issue endpoints of one repository.
+#[derive(Debug)]
+pub struct Client {
+    agent: ureq::Agent,
+    api: String,
+    repo: String,
+    token: Option<String>,
+}
+
+impl Client {
+    /// Creates a client for `repo` (`owner/name`) at `api`.
+    ///
+    /// Without a token only public repositories can be read.
+    pub fn new(api: &str, repo: &str, token: Option<String>) -> Client {
+        Client {
+            agent: ureq::AgentBuilder::new().build(),
+            api: api.trim_end_matches('/').to_string(),
+            repo: repo.to_string(),
+            token,
+        }
+    }
+
+    /// Every issue of the repository, open and closed, pull requests
+    /// included.
+    pub fn issues(&self) -> Result<Vec<Issue>, GithubError> {
+        let mut all = Vec::new();
+        for page in 1.. {
+            let request = self
+                .request("GET", "issues")
+                .query("state", "all")
+                .query("per_page", "100")
+                .query("page", &page.to_string());
+            let batch: Vec<Issue> = read(request.call())?;
+            let last = batch.len() < 100;
+            all.extend(batch);
+            if last {
+                break;
+            }
+        }
+        Ok(all)
+    }
+
+    /// Opens an issue.
+    pub fn create(&self, fields: &IssueFields) -> Result<Issue, GithubError> {
+        read(self.request("POST", "issues").send_json(fields))
+    }
+
+    /// Changes the issue `number`.
+    pub fn update(&self, number: u64, fields: &IssueFields) -> Result<Issue, GithubError> {
+        let path = format!("issues/{}", number);
+        read(self.request("PATCH", &path).send_json(fields))
+    }
+
+    /// Request to `path` under the repository, with the API headers.
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        let url = format!("{}/repos/{}/{}", self.api, self.repo, path);
+        let request = self
+            .agent
+            .request(method, &url)
+            .set("Accept", "application/vnd.github+json")
+            .set("User-Agent", "to_do_parcer");
+        match &self.token {
+            Some(token) => request.set("Authorization", &format!("Bearer {}", token)),
+            None => request,
+        }
+    }
+}
+
+/// Decodes a JSON answer or turns the failure into a `GithubError`.
+fn read<T: for<'de> Deserialize<'de>>(
+    result: Result<ureq::Response, ureq::Error>,
+) -> Result<T, GithubError> {
+    match result {
+        Ok(response) => response
+            .into_json()
+            .map_err(|e| GithubError::Decode(e.to_string())),
+        Err(ureq::Error::Status(status, response)) => {
+            let message = response
+                .into_json::<Value>()
+                .ok()
+                .and_then(|body| body["message"].as_str().map(str::to_string))
+                .unwrap_or_default();
+            Err(GithubError::Http { status, message })
+        }
+        Err(e) => Err(GithubError::Transport(e.to_string())),
+    }
+}
@@ -0,0 +1,270 @@
+//! Rendering of task dependencies as graphs for external tools.
+//!
+//! # DOT layout
+//!
+//! One Graphviz cluster per project and one box per task, filled by status
+//! (done tasks green, open ones grey). Every resolved `depends_on` becomes
+//! an edge from the dependency to the task waiting for it:
+//!
+//! ```text
+//! digraph tasks {
+//!   rankdir=LR;
+//!   node [shape=box, style=filled];
+//!   subgraph cluster_0 {
+//!     label="Backend";
+//!     t0_0 [label="Set up database", fillcolor="palegreen"];
+//!     t0_1 [label="Write API", fillcolor="lightgrey"];
+//!   }
+//!   t0_0 -> t0_1;
+//! }
+//! ```
+//!
+//! Unresolved references are left out; `validate` reports them.
+//!
+//! # Mermaid Gantt layout
+//!
+//! One section per project and one bar per task that can be placed on the
+//! timeline. A bar starts at the task's `start:` date, or else right after
+//! its dependencies, and ends at its `due:` date (one day long without
+//! one). Tasks with only a due date become milestones on that date, and
+//! tasks with no dates and no placed dependencies are left out:
+//!
+//! ```text
+//! gantt
+//!   dateFormat YYYY-MM-DD
+//!   section Backend
+//!     Set up database :done, t0_0, 2025-11-01, 2025-11-15
+//!     Write API :t0_1, after t0_0, 2025-11-30
+//!     Release :milestone, t0_2, 2025-12-01, 0d
+//! ```
+//!
+//! Task ids are the same as the DOT node ids. `:`, `;` and `#` in names are
+//! written as Mermaid entity codes.
+
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+use crate::deps::TaskRef;
+use crate::parser::{Document, Task, TaskStatus};
+
+/// Output format of `graph`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    Dot,
+    MermaidGantt,
+}
+
+impl GraphFormat {
+    /// Every supported format, in the order shown in help texts.
+    pub const ALL: &'static [GraphFormat] = &[GraphFormat::Dot, GraphFormat::MermaidGantt];
+
+    /// Name of the format as accepted by `FromStr`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GraphFormat::Dot => "dot",
+            GraphFormat::MermaidGantt => "mermaid-gantt",
+        }
+    }
+}
+
+impl fmt::Display for GraphFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        GraphFormat::ALL
+            .iter()
+            .find(|f| f.as_str().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<_> = GraphFormat::ALL.iter().map(|f| f.as_str()).collect();
+                format!(
+                    "unknown format '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Renders the dependency graph of `doc` in the given format.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::ToDoParser;
+/// use to_do_parcer::graph::{GraphFormat, graph};
+///
+/// let doc = ToDoParser::parse_document(r#"project "P" { todo: "A", todo: "B", depends_on: "A", }"#)?;
+/// let dot = graph(&doc, GraphFormat::Dot);
+/// assert!(dot.contains("t0_0 -> t0_1;"));
+/// # Ok::<(), to_do_parcer::ParseError>(())
+/// ```
+pub fn graph(doc: &Document, format: GraphFormat) -> String {
+    match format {
+        GraphFormat::Dot => to_dot(doc),
+        GraphFormat::MermaidGantt => to_mermaid_gantt(doc),
+    }
+}
+
+/// Renders `doc` as a Graphviz digraph in the layout described above.
+pub fn to_dot(doc: &Document) -> String {
+    let mut out =
+        String::from("digraph tasks {\n  rankdir=LR;\n  node [shape=box, style=filled];\n");
+    for (p, project) in doc.projects.iter().enumerate() {
+        let _ = writeln!(out, "  subgraph cluster_{} {{", p);
+        let _ = writeln!(out, "    label={};", quote(&project.name));
+        for (t, task) in project.tasks.iter().enumerate() {
+            let fill = match task.status {
+                TaskStatus::Done => "palegreen",
+                TaskStatus::Todo => "lightgrey",
+            };
+            let _ = writeln!(
+                out,
+                "    {} [label={}, fillcolor=\"{}\"];",
+                node_id(TaskRef {
+                    project: p,
+                    task: t
+                }),
+                quote(&task.title),
+                fill
+            );
+        }
+        out.push_str("  }\n");
+    }
+    let deps = doc.dependencies();
+    for target in doc.task_refs() {
+        if let Some(dep) = deps.of(target) {
+            let _ = writeln!(out, "  {} -> {};", node_id(dep), node_id(target));
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Renders `doc` as a Mermaid Gantt chart in the layout described above.
+pub fn to_mermaid_gantt(doc: &Document) -> String {
+    let resolved = doc.dependencies();
+    let deps: Vec<Vec<Vec<TaskRef>>> = (doc.projects.iter().enumerate())
+        .map(|(p, project)| {
+            (0..project.tasks.len())
+                .map(|t| {
+                    let r = TaskRef {
+                        project: p,
+                        task: t,
+                    };
+                    resolved.of(r).into_iter().collect()
+                })
+                .collect()
+        })
+        .collect();
+
+    // A task is placed if it has a date or depends on a placed task.
+    let mut placed: Vec<Vec<bool>> = doc
+        .projects
+        .iter()
+        .map(|p| {
+            p.tasks
+                .iter()
+                .map(|t| t.start_date.is_some() || t.due_date.is_some())
+                .collect()
+        })
+        .collect();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for r in doc.task_refs() {
+            if !placed[r.project][r.task]
+                && deps[r.project][r.task]
+                    .iter()
+                    .any(|d| placed[d.project][d.task])
+            {
+                placed[r.project][r.task] = true;
+                changed = true;
+            }
+        }
+    }
+
+    let mut out = String::from("gantt\n  dateFormat YYYY-MM-DD\n");
+    for (p, project) in doc.projects.iter().enumerate() {
+        if !placed[p].contains(&true) {
+            continue;
+        }
+        let _ = writeln!(out, "  section {}", mermaid_text(&project.name));
+        for (t, task) in project.tasks.iter().enumerate() {
+            if !placed[p][t] {
+                continue;
+            }
+            let id = TaskRef {
+                project: p,
+                task: t,
+            };
+            let after: Vec<_> = deps[p][t]
+                .iter()
+                .filter(|d| placed[d.project][d.task])
+                .map(|&d| node_id(d))
+                .collect();
+            let _ = writeln!(
+                out,
+                "    {} :{}{}",
+                mermaid_text(&task.title),
+                if task.status == TaskStatus::Done {
+                    "done, "
+                } else {
+                    ""
+                },
+                gantt_timing(task, &node_id(id), &after)
+            );
+        }
+    }
+    out
+}
+
+/// The `id, start, end` part of a Gantt task line, with a `milestone`
+/// marker for tasks that only have a due date.
+fn gantt_timing(task: &Task, id: &str, after: &[String]) -> String {
+    let end = task.due_date.as_deref().unwrap_or("1d");
+    match (&task.start_date, &task.due_date) {
+        (Some(start), _) => format!("{}, {}, {}", id, start, end),
+        (None, _) if !after.is_empty() => format!("{}, after {}, {}", id, after.join(" "), end),
+        (None, Some(due)) => format!("milestone, {}, {}, 0d", id, due),
+        (None, None) => unreachable!("unplaced tasks are skipped"),
+    }
+}
+
+/// Replaces characters Mermaid treats as Gantt syntax with entity codes.
+fn mermaid_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            ':' => out.push_str("#58;"),
+            ';' => out.push_str("#59;"),
+            '#' => out.push_str("#35;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// DOT node and Mermaid task identifier of a task.
+fn node_id(task: TaskRef) -> String {
+    format!("t{}_{}", task.project, task.task)
+}
+
+/// Quotes `text` as a DOT string, escaping quotes and backslashes.
+fn quote(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        if matches!(c, '"' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,225 @@
+//! Grouping of tasks by assignee, tag, status, or due week.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::parser::{Document, Priority, Project, Task, TaskStatus};
+
+/// What to group tasks by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupKey {
+    Assignee,
+    Tag,
+    Status,
+    /// Calendar week (Monday to Sunday) of the due date.
+    DueWeek,
+}
+
+/// A single group produced by [`Document::group_by`].
+///
+/// Groups order naturally (names alphabetically, weeks chronologically),
+/// with the "missing value" group of each key sorting last.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Group {
+    Assignee(String),
+    Unassigned,
+    Tag(String),
+    Untagged,
+    Status(TaskStatus),
+    /// Week identified by its Monday.
+    DueWeek(NaiveDate),
+    NoDueDate,
+}
+
+impl fmt::Display for Group {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Group::Assignee(name) => write!(f, "@{}", name),
+            Group::Unassigned => write!(f, "(unassigned)"),
+            Group::Tag(tag) => write!(f, "{}", tag),
+            Group::Untagged => write!(f, "(untagged)"),
+            Group::Status(TaskStatus::Todo) => write!(f, "todo"),
+            Group::Status(TaskStatus::Done) => write!(f, "done"),
+            Group::DueWeek(monday) => write!(f, "week of {}", monday),
+            Group::NoDueDate => write!(f, "(no due date)"),
+        }
+    }
+}
+
+/// Tasks of every tag, built once with [`Document::tag_index`].
+#[derive(Debug, Clone, Default)]
+pub struct TagIndex<'d> {
+    tags: BTreeMap<&'d str, Vec<(&'d Project, &'d Task)>>,
+}
+
+impl<'d> TagIndex<'d> {
+    /// Returns the tasks carrying `tag`, in source order.
+    pub fn tasks(&self, tag: &str) -> &[(&'d Project, &'d Task)] {
+        self.tags.get(tag).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns every tag with the number of tasks carrying it, most used
+    /// first and alphabetically among equals.
+    pub fn counts(&self) -> Vec<(&'d str, usize)> {
+        let mut counts: Vec<_> = self
+            .tags
+            .iter()
+            .map(|(tag, tasks)| (*tag, tasks.len()))
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        counts
+    }
+
+    /// Number of distinct tags.
+    pub fn len(&self) -> usize {
+        self.tags.len()
+    }
+
+    /// Whether no task has a tag.
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+}
+
+/// Open work of one assignee, as produced by [`Document::workload`].
+#[derive(Debug, Clone)]
+pub struct Workload<'d> {
+    /// Open tasks, in source order.
+    pub open: Vec<(&'d Project, &'d Task)>,
+    /// How many of the open tasks are `@high`.
+    pub high: usize,
+    /// Earliest due date among the open tasks.
+    pub next_due: Option<NaiveDate>,
+}
+
+impl Document {
+    /// Indexes the tasks of the document by tag.
+    ///
+    /// A task with a tag written twice is listed once under it.
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::ToDoParser;
+    ///
+    /// let doc = ToDoParser::parse_document(
+    ///     r#"project "P" { todo: "A", @tag: "bug", todo: "B", @tag: "bug", @tag: "ui", }"#,
+    /// )?;
+    /// let index = doc.tag_index();
+    /// assert_eq!(index.counts(), [("bug", 2), ("ui", 1)]);
+    /// assert_eq!(index.tasks("ui")[0].1.title, "B");
+    /// # Ok::<(), to_do_parcer::ParseError>(())
+    /// ```
+    pub fn tag_index(&self) -> TagIndex<'_> {
+        let mut index = TagIndex::default();
+        for (project, task) in self.tasks() {
+            for tag in &task.tags {
+                let tasks = index.tags.entry(tag.as_str()).or_default();
+                if !tasks.last().is_some_and(|(_, t)| std::ptr::eq(*t, task)) {
+                    tasks.push((project, task));
+                }
+            }
+        }
+        index
+    }
+
+    /// Summarizes the open tasks of each assignee.
+    ///
+    /// Keys are `Group::Assignee` or `Group::Unassigned`; people whose tasks
+    /// are all done are left out.
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::ToDoParser;
+    /// use to_do_parcer::group::Group;
+    ///
+    /// let doc = ToDoParser::parse_document(
+    ///     r#"project "P" { todo: "A", assign: @bob, @high, due: 2025-11-20, done: "B", assign: @ann, }"#,
+    /// )?;
+    /// let load = doc.workload();
+    /// assert_eq!(load[&Group::Assignee("bob".into())].high, 1);
+    /// assert!(!load.contains_key(&Group::Assignee("ann".into())));
+    /// # Ok::<(), to_do_parcer::ParseError>(())
+    /// ```
+    pub fn workload(&self) -> BTreeMap<Group, Workload<'_>> {
+        self.group_by(GroupKey::Assignee)
+            .into_iter()
+            .filter_map(|(group, tasks)| {
+                let open: Vec<_> = tasks
+                    .into_iter()
+                    .filter(|(_, t)| t.status == TaskStatus::Todo)
+                    .collect();
+                if open.is_empty() {
+                    return None;
+                }
+                let high = open
+                    .iter()
+                    .filter(|(_, t)| t.priority == Some(Priority::High))
+                    .count();
+                let next_due = open.iter().filter_map(|(_, t)| t.due()).min();
+                Some((
+                    group,
+                    Workload {
+                        open,
+                        high,
+                        next_due,
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    /// Groups every task by `key`.
+    ///
+    /// A task appears once per group it belongs to, so with
+    /// `GroupKey::Tag` a task with two tags is listed twice. Within a group
+    /// tasks keep their source order.
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::ToDoParser;
+    /// use to_do_parcer::group::{Group, GroupKey};
+    ///
+    /// let doc = ToDoParser::parse_document(
+    ///     r#"project "P" { todo: "A", assign: @bob, todo: "B", }"#,
+    /// )?;
+    /// let groups = doc.group_by(GroupKey::Assignee);
+    /// assert_eq!(groups[&Group::Assignee("bob".into())].len(), 1);
+    /// assert_eq!(groups[&Group::Unassigned].len(), 1);
+    /// # Ok::<(), to_do_parcer::ParseError>(())
+    /// ```
+    pub fn group_by(&self, key: GroupKey) -> BTreeMap<Group, Vec<(&Project, &Task)>> {
+        let mut groups: BTreeMap<Group, Vec<(&Project, &Task)>> = BTreeMap::new();
+
+        for (project, task) in self.tasks() {
+            for group in groups_of(task, key) {
+                groups.entry(group).or_default().push((project, task));
+            }
+        }
+
+        groups
+    }
+}
+
+/// Lists the groups a single task belongs to.
+fn groups_of(task: &Task, key: GroupKey) -> Vec<Group> {
+    match key {
+        GroupKey::Assignee => vec![match &task.assignee {
+            Some(name) => Group::Assignee(name.clone()),
+            None => Group::Unassigned,
+        }],
+        GroupKey::Tag if task.tags.is_empty() => vec![Group::Untagged],
+        GroupKey::Tag => task.tags.iter().cloned().map(Group::Tag).collect(),
+        GroupKey::Status => vec![Group::Status(task.status)],
+        GroupKey::DueWeek => vec![match task.due() {
+            Some(due) => Group::DueWeek(week_start(due)),
+            None => Group::NoDueDate,
+        }],
+    }
+}
+
+/// Returns the Monday of the week containing `date`.
+pub(crate) fn week_start(date: NaiveDate) -> NaiveDate {
+    date - chrono::Days::new(u64::from(date.weekday().num_days_from_monday()))
+}
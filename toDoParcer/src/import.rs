@@ -0,0 +1,850 @@
+//! Conversion of other task formats into a `Document`.
+//!
+//! Importers are lenient: input they can't represent is dropped or adjusted
+//! and reported in `Document::warnings` rather than failing the import.
+//! Use `pretty::format_document` to turn the result into `.todo` text.
+//!
+//! # Markdown
+//!
+//! Every heading starts a project; headings without tasks are skipped.
+//! Checkbox items (`- [ ]`, `* [x]`, `+ [X]`, at any indentation) become
+//! tasks, and all other lines are ignored. Tasks before the first heading
+//! go to a project named `Inbox`. Attributes written by the Markdown
+//! export (`Title — priority: high; due: 2025-11-15; ...`) are read back.
+//!
+//! # Org
+//!
+//! Headings with a `TODO` or `DONE` keyword become tasks; other headings
+//! start a project. Nested task headings are flattened into the enclosing
+//! project. `[#A]`–`[#C]` cookies map to priorities, trailing `:tags:` to
+//! tags, a `DEADLINE:` timestamp to the due date, and the `ASSIGNEE` and
+//! `DEPENDS_ON` properties to the assignee and dependency.
+//!
+//! # Trello
+//!
+//! A board exported as JSON (*Menu → Print, export and share → Export as
+//! JSON*). Open lists become projects and open cards tasks, both in board
+//! order; archived ones are skipped. Card labels become tags (a label
+//! without a name is tagged with its color), the date of the card's due
+//! time becomes the due date, and the first member's username the
+//! assignee. Cards whose due date is marked complete are done. Warnings
+//! point at the line where the card's id first appears in the file.
+//!
+//! # CSV
+//!
+//! One task per row, after a header row. A `CsvMapping` binds task fields
+//! to header names (matched case-insensitively); by default each field is
+//! read from the column of the same name, so the output of
+//! `export --format csv` imports unchanged. Only the title is required:
+//! rows without a project go to `Inbox`, and projects appear in the order
+//! they are first seen. Statuses `done`, `x`, `yes`, `true`, `completed`
+//! and `closed` mark a task done; tags may be separated by `;` or `,`.
+//! Values that can't be represented are dropped with a warning.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate};
+use serde::Deserialize;
+
+use crate::parser::{Document, ParseWarning, Priority, Project, Task, TaskStatus};
+
+/// Project that receives tasks appearing before any heading.
+pub const DEFAULT_PROJECT: &str = "Inbox";
+
+/// Input format of `import`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Markdown,
+    Org,
+    Trello,
+    Csv,
+}
+
+impl ImportFormat {
+    /// Every supported format, in the order shown in help texts.
+    pub const ALL: &'static [ImportFormat] = &[
+        ImportFormat::Markdown,
+        ImportFormat::Org,
+        ImportFormat::Trello,
+        ImportFormat::Csv,
+    ];
+
+    /// Name of the format as accepted by `FromStr`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportFormat::Markdown => "markdown",
+            ImportFormat::Org => "org",
+            ImportFormat::Trello => "trello",
+            ImportFormat::Csv => "csv",
+        }
+    }
+}
+
+impl fmt::Display for ImportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ImportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ImportFormat::ALL
+            .iter()
+            .find(|f| f.as_str().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<_> = ImportFormat::ALL.iter().map(|f| f.as_str()).collect();
+                format!(
+                    "unknown format '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Converts `input` in the given format into a `Document`.
+///
+/// CSV input is read with the default `CsvMapping`; use `from_csv` to bind
+/// other column names.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::import::{ImportFormat, import};
+/// use to_do_parcer::pretty::format_document;
+///
+/// let doc = import("# Chores\n- [ ] Buy milk\n- [x] Call mom\n", ImportFormat::Markdown);
+/// assert_eq!(
+///     format_document(&doc),
+///     "project \"Chores\" {\n  todo: \"Buy milk\",\n  done: \"Call mom\",\n}\n"
+/// );
+/// ```
+pub fn import(input: &str, format: ImportFormat) -> Document {
+    match format {
+        ImportFormat::Markdown => from_markdown(input),
+        ImportFormat::Org => from_org(input),
+        ImportFormat::Trello => from_trello(input),
+        ImportFormat::Csv => from_csv(input, &CsvMapping::default()),
+    }
+}
+
+/// Converts a Markdown checklist into a `Document`.
+pub fn from_markdown(input: &str) -> Document {
+    let mut doc = Document::default();
+    let mut current: Option<Project> = None;
+
+    for (index, raw) in input.lines().enumerate() {
+        let line = index + 1;
+        let trimmed = raw.trim();
+
+        if let Some(heading) = markdown_heading(trimmed) {
+            push_non_empty(&mut doc, current.take());
+            let name = sanitize(&unescape_markdown(heading), line, &mut doc.warnings);
+            current = Some(Project::new(name));
+            continue;
+        }
+
+        let Some((status, text)) = markdown_checkbox(trimmed) else {
+            continue;
+        };
+        let task = markdown_task(status, text, line, &mut doc.warnings);
+        current
+            .get_or_insert_with(|| Project::new(DEFAULT_PROJECT))
+            .tasks
+            .push(task);
+    }
+
+    push_non_empty(&mut doc, current);
+    doc
+}
+
+/// Returns the text of an ATX heading (`# Title`), without closing `#`s.
+fn markdown_heading(line: &str) -> Option<&str> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    let text = rest.trim().trim_end_matches('#').trim_end();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Splits a checkbox list item into its status and text.
+fn markdown_checkbox(line: &str) -> Option<(TaskStatus, &str)> {
+    let rest = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .or_else(|| line.strip_prefix("+ "))?
+        .trim_start();
+    let status = match rest.get(..3)? {
+        "[ ]" => TaskStatus::Todo,
+        "[x]" | "[X]" => TaskStatus::Done,
+        _ => return None,
+    };
+    let text = rest[3..].trim();
+    (!text.is_empty()).then_some((status, text))
+}
+
+/// Builds a task from checkbox text, reading an attribute suffix if every
+/// part of it is understood.
+fn markdown_task(
+    status: TaskStatus,
+    text: &str,
+    line: usize,
+    warnings: &mut Vec<ParseWarning>,
+) -> Task {
+    if let Some((title, details)) = text.rsplit_once(" — ")
+        && let Some(task) = markdown_details(status, title, details, line, warnings)
+    {
+        return task;
+    }
+    let title = sanitize(&unescape_markdown(text), line, warnings);
+    Task::new(status, title)
+}
+
+/// Parses `key: value; ...` pairs written by the Markdown export.
+///
+/// Warnings are only recorded if the whole suffix is understood.
+fn markdown_details(
+    status: TaskStatus,
+    title: &str,
+    details: &str,
+    line: usize,
+    warnings: &mut Vec<ParseWarning>,
+) -> Option<Task> {
+    let mut task = Task::new(status, String::new());
+    let mut pending = Vec::new();
+    for part in details.split(';') {
+        let (key, value) = part.split_once(':')?;
+        let value = unescape_markdown(value.trim());
+        match key.trim() {
+            "priority" => task.priority = Some(Priority::from_name(&value)?),
+            "due" => {
+                NaiveDate::parse_from_str(&value, "%Y-%m-%d").ok()?;
+                task.due_date = Some(value);
+            }
+            "assignee" => {
+                let name = value.strip_prefix('@').unwrap_or(&value);
+                if !is_identifier(name) {
+                    return None;
+                }
+                task.assignee = Some(name.to_string());
+            }
+            "depends on" => task.depends_on = Some(sanitize(&value, line, &mut pending)),
+            "tags" => {
+                for tag in value.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+                    task.tags.push(sanitize(tag, line, &mut pending));
+                }
+            }
+            _ => return None,
+        }
+    }
+    task.title = sanitize(&unescape_markdown(title), line, warnings);
+    warnings.append(&mut pending);
+    Some(task)
+}
+
+/// Converts an Org outline into a `Document`.
+pub fn from_org(input: &str) -> Document {
+    let mut doc = Document::default();
+    let mut current: Option<Project> = None;
+    // Whether the lines below belong to the last task of `current`.
+    let mut in_task = false;
+
+    for (index, raw) in input.lines().enumerate() {
+        let line = index + 1;
+
+        if let Some(heading) = org_heading(raw) {
+            let (status, rest) = match heading.split_once(' ') {
+                Some(("TODO", rest)) => (Some(TaskStatus::Todo), rest.trim_start()),
+                Some(("DONE", rest)) => (Some(TaskStatus::Done), rest.trim_start()),
+                _ if heading == "TODO" => (Some(TaskStatus::Todo), ""),
+                _ if heading == "DONE" => (Some(TaskStatus::Done), ""),
+                _ => (None, heading),
+            };
+            let (priority, rest) = org_priority(rest);
+            let (title, tags) = org_tags(rest);
+
+            let Some(status) = status else {
+                push_non_empty(&mut doc, current.take());
+                let name = sanitize(title, line, &mut doc.warnings);
+                current = Some(Project::new(name));
+                in_task = false;
+                continue;
+            };
+
+            let mut task = Task::new(status, sanitize(title, line, &mut doc.warnings));
+            task.priority = priority;
+            task.tags = tags
+                .into_iter()
+                .map(|t| sanitize(t, line, &mut doc.warnings))
+                .collect();
+            current
+                .get_or_insert_with(|| Project::new(DEFAULT_PROJECT))
+                .tasks
+                .push(task);
+            in_task = true;
+            continue;
+        }
+
+        if !in_task {
+            continue;
+        }
+        let Some(task) = current.as_mut().and_then(|p| p.tasks.last_mut()) else {
+            continue;
+        };
+        let body = raw.trim();
+        if let Some(date) = org_deadline(body) {
+            task.due_date = Some(date.format("%Y-%m-%d").to_string());
+        } else if let Some(value) = body.strip_prefix(":ASSIGNEE:") {
+            let name = value.trim().trim_start_matches('@');
+            if is_identifier(name) {
+                task.assignee = Some(name.to_string());
+            } else {
+                doc.warnings.push(ParseWarning {
+                    file: None,
+                    line,
+                    message: format!("dropped assignee \"{}\": not an identifier", name),
+                });
+            }
+        } else if let Some(value) = body.strip_prefix(":DEPENDS_ON:") {
+            task.depends_on = Some(sanitize(value.trim(), line, &mut doc.warnings));
+        }
+    }
+
+    push_non_empty(&mut doc, current);
+    doc
+}
+
+/// The parts of a Trello board export that are imported.
+#[derive(Debug, Deserialize)]
+struct TrelloBoard {
+    #[serde(default)]
+    lists: Vec<TrelloList>,
+    #[serde(default)]
+    cards: Vec<TrelloCard>,
+    #[serde(default)]
+    members: Vec<TrelloMember>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrelloList {
+    id: String,
+    name: String,
+    #[serde(default)]
+    closed: bool,
+    #[serde(default)]
+    pos: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloCard {
+    id: String,
+    name: String,
+    id_list: String,
+    #[serde(default)]
+    closed: bool,
+    #[serde(default)]
+    pos: f64,
+    /// RFC 3339 due time.
+    #[serde(default)]
+    due: Option<String>,
+    #[serde(default)]
+    due_complete: bool,
+    #[serde(default)]
+    labels: Vec<TrelloLabel>,
+    #[serde(default)]
+    id_members: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrelloLabel {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    color: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrelloMember {
+    id: String,
+    username: String,
+}
+
+/// Converts a Trello board export into a `Document`.
+pub fn from_trello(input: &str) -> Document {
+    let mut doc = Document::default();
+    let mut board: TrelloBoard = match serde_json::from_str(input) {
+        Ok(board) => board,
+        Err(e) => {
+            doc.warnings.push(ParseWarning {
+                file: None,
+                line: e.line(),
+                message: format!("not a Trello board export: {}", e),
+            });
+            return doc;
+        }
+    };
+    board.lists.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+    board.cards.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+
+    for list in board.lists.iter().filter(|l| !l.closed) {
+        let line = line_of(input, &list.id);
+        let mut project = Project::new(sanitize(&list.name, line, &mut doc.warnings));
+        for card in board
+            .cards
+            .iter()
+            .filter(|c| c.id_list == list.id && !c.closed)
+        {
+            project
+                .tasks
+                .push(trello_task(card, &board, input, &mut doc.warnings));
+        }
+        push_non_empty(&mut doc, Some(project));
+    }
+    doc
+}
+
+/// Converts one Trello card into a task.
+fn trello_task(
+    card: &TrelloCard,
+    board: &TrelloBoard,
+    input: &str,
+    warnings: &mut Vec<ParseWarning>,
+) -> Task {
+    let line = line_of(input, &card.id);
+    let status = if card.due_complete {
+        TaskStatus::Done
+    } else {
+        TaskStatus::Todo
+    };
+    let mut task = Task::new(status, sanitize(&card.name, line, warnings));
+    task.tags = card
+        .labels
+        .iter()
+        .filter_map(|label| match (label.name.as_str(), &label.color) {
+            ("", color) => color.clone(),
+            (name, _) => Some(name.to_string()),
+        })
+        .map(|tag| sanitize(&tag, line, warnings))
+        .collect();
+
+    if let Some(due) = &card.due {
+        match DateTime::parse_from_rfc3339(due) {
+            Ok(time) => task.due_date = Some(time.date_naive().format("%Y-%m-%d").to_string()),
+            Err(_) => warnings.push(ParseWarning {
+                file: None,
+                line,
+                message: format!("dropped due date \"{}\" of \"{}\"", due, task.title),
+            }),
+        }
+    }
+
+    let usernames: Vec<&str> = card
+        .id_members
+        .iter()
+        .filter_map(|id| board.members.iter().find(|m| &m.id == id))
+        .map(|m| m.username.as_str())
+        .collect();
+    if let Some(&first) = usernames.first() {
+        if is_identifier(first) {
+            task.assignee = Some(first.to_string());
+        } else {
+            warnings.push(ParseWarning {
+                file: None,
+                line,
+                message: format!("dropped assignee \"{}\": not an identifier", first),
+            });
+        }
+        if usernames.len() > 1 {
+            warnings.push(ParseWarning {
+                file: None,
+                line,
+                message: format!(
+                    "\"{}\" has {} members; kept only @{}",
+                    task.title,
+                    usernames.len(),
+                    first
+                ),
+            });
+        }
+    }
+    task
+}
+
+/// 1-based line of the first `"id"` string equal to `id` in `input`.
+fn line_of(input: &str, id: &str) -> usize {
+    let quoted = format!("\"{}\"", id);
+    input
+        .find(&quoted)
+        .map_or(1, |at| input[..at].matches('\n').count() + 1)
+}
+
+/// Task field that can be read from a CSV column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CsvField {
+    Project,
+    Title,
+    Status,
+    Id,
+    Priority,
+    Start,
+    Due,
+    Completed,
+    Assignee,
+    DependsOn,
+    Tags,
+}
+
+impl CsvField {
+    /// Every field, in the order shown in help texts.
+    pub const ALL: &'static [CsvField] = &[
+        CsvField::Project,
+        CsvField::Title,
+        CsvField::Status,
+        CsvField::Id,
+        CsvField::Priority,
+        CsvField::Start,
+        CsvField::Due,
+        CsvField::Completed,
+        CsvField::Assignee,
+        CsvField::DependsOn,
+        CsvField::Tags,
+    ];
+
+    /// Name of the field as accepted by `FromStr`, also its default column.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CsvField::Project => "project",
+            CsvField::Title => "title",
+            CsvField::Status => "status",
+            CsvField::Id => "id",
+            CsvField::Priority => "priority",
+            CsvField::Start => "start",
+            CsvField::Due => "due",
+            CsvField::Completed => "completed",
+            CsvField::Assignee => "assignee",
+            CsvField::DependsOn => "depends_on",
+            CsvField::Tags => "tags",
+        }
+    }
+}
+
+impl fmt::Display for CsvField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CsvField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CsvField::ALL
+            .iter()
+            .find(|f| f.as_str().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<_> = CsvField::ALL.iter().map(|f| f.as_str()).collect();
+                format!(
+                    "unknown field '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Which CSV column each task field is read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvMapping {
+    columns: HashMap<CsvField, String>,
+}
+
+impl Default for CsvMapping {
+    /// Reads every field from the column named after it.
+    fn default() -> Self {
+        CsvMapping {
+            columns: CsvField::ALL
+                .iter()
+                .map(|f| (*f, f.as_str().to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl CsvMapping {
+    /// Reads `field` from the column with header `column` instead.
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::import::{CsvField, CsvMapping, from_csv};
+    ///
+    /// let mut mapping = CsvMapping::default();
+    /// mapping.bind(CsvField::Title, "Summary");
+    /// let doc = from_csv("Summary,Status\nShip it,Done\n", &mapping);
+    /// assert_eq!(doc.projects[0].name, "Inbox");
+    /// assert_eq!(doc.projects[0].tasks[0].title, "Ship it");
+    /// ```
+    pub fn bind(&mut self, field: CsvField, column: impl Into<String>) -> &mut Self {
+        self.columns.insert(field, column.into());
+        self
+    }
+
+    /// Header name `field` is read from.
+    pub fn column(&self, field: CsvField) -> &str {
+        &self.columns[&field]
+    }
+}
+
+/// Converts CSV rows into a `Document`, reading columns per `mapping`.
+pub fn from_csv(input: &str, mapping: &CsvMapping) -> Document {
+    let mut doc = Document::default();
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(input.as_bytes());
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => {
+            doc.warnings.push(csv_warning(&e));
+            return doc;
+        }
+    };
+
+    let mut index = HashMap::new();
+    for field in CsvField::ALL {
+        let column = mapping.column(*field);
+        match headers.iter().position(|h| h.eq_ignore_ascii_case(column)) {
+            Some(i) => {
+                index.insert(*field, i);
+            }
+            None if column != field.as_str() || *field == CsvField::Title => {
+                doc.warnings.push(ParseWarning {
+                    file: None,
+                    line: 1,
+                    message: format!("no column \"{}\" for {}", column, field),
+                });
+            }
+            None => {}
+        }
+    }
+    if !index.contains_key(&CsvField::Title) {
+        return doc;
+    }
+
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                doc.warnings.push(csv_warning(&e));
+                continue;
+            }
+        };
+        let line = record.position().map_or(1, |p| p.line() as usize);
+        let get = |field| {
+            index
+                .get(&field)
+                .and_then(|i| record.get(*i))
+                .filter(|v| !v.is_empty())
+        };
+        let Some(title) = get(CsvField::Title) else {
+            doc.warnings.push(ParseWarning {
+                file: None,
+                line,
+                message: "skipped row without a title".to_string(),
+            });
+            continue;
+        };
+        let task = csv_task(title, &get, line, &mut doc.warnings);
+        let project = sanitize(
+            get(CsvField::Project).unwrap_or(DEFAULT_PROJECT),
+            line,
+            &mut doc.warnings,
+        );
+        match doc.projects.iter_mut().find(|p| p.name == project) {
+            Some(existing) => existing.tasks.push(task),
+            None => {
+                let mut new = Project::new(project);
+                new.tasks.push(task);
+                doc.projects.push(new);
+            }
+        }
+    }
+    doc
+}
+
+/// Builds a task from the non-empty values of one CSV row.
+fn csv_task<'r>(
+    title: &str,
+    get: &impl Fn(CsvField) -> Option<&'r str>,
+    line: usize,
+    warnings: &mut Vec<ParseWarning>,
+) -> Task {
+    let dropped = |field: CsvField, value: &str, warnings: &mut Vec<ParseWarning>| {
+        warnings.push(ParseWarning {
+            file: None,
+            line,
+            message: format!("dropped {} \"{}\" of \"{}\"", field, value, title),
+        })
+    };
+
+    let status = match get(CsvField::Status)
+        .map(str::to_ascii_lowercase)
+        .as_deref()
+    {
+        None | Some("todo" | "open" | "no" | "false" | "") => TaskStatus::Todo,
+        Some("done" | "x" | "yes" | "true" | "completed" | "closed") => TaskStatus::Done,
+        Some(other) => {
+            dropped(CsvField::Status, other, warnings);
+            TaskStatus::Todo
+        }
+    };
+    let mut task = Task::new(status, sanitize(title, line, warnings));
+
+    if let Some(id) = get(CsvField::Id) {
+        if is_identifier(id) {
+            task.id = Some(id.to_string());
+        } else {
+            dropped(CsvField::Id, id, warnings);
+        }
+    }
+    if let Some(priority) = get(CsvField::Priority) {
+        task.priority = Priority::from_name(&priority.to_ascii_lowercase());
+        if task.priority.is_none() {
+            dropped(CsvField::Priority, priority, warnings);
+        }
+    }
+    for (field, slot) in [
+        (CsvField::Start, &mut task.start_date),
+        (CsvField::Due, &mut task.due_date),
+        (CsvField::Completed, &mut task.completed),
+    ] {
+        if let Some(date) = get(field) {
+            match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+                Ok(_) => *slot = Some(date.to_string()),
+                Err(_) => dropped(field, date, warnings),
+            }
+        }
+    }
+    if let Some(assignee) = get(CsvField::Assignee) {
+        let name = assignee.trim_start_matches('@');
+        if is_identifier(name) {
+            task.assignee = Some(name.to_string());
+        } else {
+            dropped(CsvField::Assignee, assignee, warnings);
+        }
+    }
+    if let Some(reference) = get(CsvField::DependsOn) {
+        task.depends_on = Some(sanitize(reference, line, warnings));
+    }
+    if let Some(tags) = get(CsvField::Tags) {
+        task.tags = tags
+            .split([';', ','])
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(|t| sanitize(t, line, warnings))
+            .collect();
+    }
+    task
+}
+
+/// Warning for a CSV row that can't be read.
+fn csv_warning(error: &csv::Error) -> ParseWarning {
+    ParseWarning {
+        file: None,
+        line: error.position().map_or(1, |p| p.line() as usize),
+        message: format!("unreadable CSV: {}", error),
+    }
+}
+
+/// Returns the text of an Org heading (`** Title`), without the stars.
+fn org_heading(line: &str) -> Option<&str> {
+    let stars = line.chars().take_while(|&c| c == '*').count();
+    if stars == 0 {
+        return None;
+    }
+    let rest = line[stars..].strip_prefix(' ')?.trim();
+    (!rest.is_empty()).then_some(rest)
+}
+
+/// Splits a leading `[#A]`-style priority cookie off a heading.
+fn org_priority(text: &str) -> (Option<Priority>, &str) {
+    let priority = match text.get(..4) {
+        Some("[#A]") => Priority::High,
+        Some("[#B]") => Priority::Medium,
+        Some("[#C]") => Priority::Low,
+        _ => return (None, text),
+    };
+    (Some(priority), text[4..].trim_start())
+}
+
+/// Splits trailing `:tag1:tag2:` off a heading.
+fn org_tags(text: &str) -> (&str, Vec<&str>) {
+    if let Some((title, last)) = text.rsplit_once(' ')
+        && last.len() > 2
+        && last.starts_with(':')
+        && last.ends_with(':')
+        && !last.contains(char::is_whitespace)
+    {
+        let tags = last.trim_matches(':').split(':').filter(|t| !t.is_empty());
+        return (title.trim_end(), tags.collect());
+    }
+    (text, Vec::new())
+}
+
+/// Finds the date of a `DEADLINE: <2025-11-15 Sat>` planning entry.
+fn org_deadline(line: &str) -> Option<NaiveDate> {
+    let (_, rest) = line.split_once("DEADLINE:")?;
+    let stamp = rest.trim_start().strip_prefix('<')?;
+    NaiveDate::parse_from_str(stamp.get(..10)?, "%Y-%m-%d").ok()
+}
+
+/// Adds `project` to `doc` unless it is missing or has no tasks.
+fn push_non_empty(doc: &mut Document, project: Option<Project>) {
+    if let Some(project) = project.filter(|p| !p.tasks.is_empty()) {
+        doc.projects.push(project);
+    }
+}
+
+/// Removes backslash escapes in front of ASCII punctuation.
+fn unescape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' && chars.peek().is_some_and(|n| n.is_ascii_punctuation()) {
+            continue;
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Makes `text` fit in a quoted DSL string, which can't contain `"`.
+fn sanitize(text: &str, line: usize, warnings: &mut Vec<ParseWarning>) -> String {
+    if !text.contains('"') {
+        return text.to_string();
+    }
+    warnings.push(ParseWarning {
+        file: None,
+        line,
+        message: format!("replaced '\"' with '\\'' in \"{}\"", text),
+    });
+    text.replace('"', "'")
+}
+
+/// Whether `name` can be written as an `assign: @name` identifier.
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
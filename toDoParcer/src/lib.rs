@@ -1,9 +1,157 @@
 #![doc = include_str!("../docs.md")]
 /// Library module for To-Do list parsing.
-/// 
+///
 /// Contains the main parser and related data structures.
 /// Crate entry for **to_do_parcer** — a parser and CLI for a lightweight
 pub mod parser;
 
+/// Streaming (SAX-style) parse events for consumers that don't need an AST.
+pub mod events;
+
+/// Parsing a reader chunk by chunk in bounded memory.
+pub mod chunked;
+
+/// Parsed documents cached by path and content hash.
+pub mod cache;
+
+/// Format-preserving edits of source text (add, complete, remove tasks).
+pub mod edit;
+
+/// Moving done tasks and finished projects into an archive file.
+pub mod archive;
+
+/// Typed side-table for annotating AST nodes.
+pub mod extensions;
+
+/// Resolution of `depends_on` references into task handles.
+pub mod deps;
+
+/// Problems found in documents, shared by parser, validator and linter.
+pub mod diagnostic;
+
+/// Critical path and slack of open tasks.
+pub mod critical_path;
+
+/// Machine-applicable fixes for common syntax errors.
+pub mod fix;
+
+/// Detection and merging of duplicate tasks.
+pub mod dedupe;
+
+/// Export of documents to JSON and other interchange formats.
+pub mod export;
+
+/// Dependency graphs for Graphviz and other tools.
+pub mod graph;
+
+/// ANSI colors for terminal output.
+pub mod color;
+
+/// Semantic comparison of two documents.
+pub mod diff;
+
+/// Three-way semantic merge of documents.
+pub mod merge;
+
+/// Day-by-day open task counts for burndown charts.
+pub mod burndown;
+
+/// User defaults from configuration files.
+pub mod config;
+
+/// Conversion of Markdown and other formats into documents.
+pub mod import;
+
+/// Desktop notifications for due and overdue tasks.
+pub mod notify;
+
+/// Parser configuration: strictness, allowed priorities, includes, etc.
+pub mod options;
+
+/// Order- and layout-independent hashes of documents.
+pub mod fingerprint;
+
+/// Grouping of tasks for reports and views.
+pub mod group;
+
+/// Pretty-printer producing canonical DSL text.
+pub mod pretty;
+
+/// Attribute filters and task selection.
+pub mod query;
+
+/// Self-contained HTML reports with progress per project.
+pub mod report;
+
+/// Deadline queries (overdue, due soon) driven by an injectable clock.
+pub mod schedule;
+
+/// Configurable lint rules layered on top of validation.
+pub mod lint;
+
+/// Case-insensitive and fuzzy text search over tasks.
+pub mod search;
+
+/// Two-way sync of tagged tasks with GitHub issues.
+#[cfg(feature = "github")]
+pub mod github;
+
+/// `Arbitrary` for the AST and a generator of valid DSL text.
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+
+/// Parsing files through a memory map.
+#[cfg(feature = "mmap")]
+pub mod mmap;
+
+/// Hand-written parser used instead of the pest-generated one.
+#[cfg(feature = "fast-parser")]
+mod fast;
+
+/// Parsing the projects of one large input in parallel.
+#[cfg(feature = "parallel")]
+pub mod parallel;
+
+/// Dictionary behind the `spelling` lint.
+#[cfg(feature = "spellcheck")]
+pub mod spell;
+
+/// Read-only JSON answers for the HTTP API of `serve`.
+pub mod serve;
+
+/// Compact storage for the tags of a task.
+pub mod tags;
+
+/// Task ordering by due date, priority or status.
+pub mod sort;
+
+/// Breaking a file up into one file per project.
+pub mod split;
+
+/// "Did you mean" hints for misspelled keywords and names.
+pub mod suggest;
+
+/// Summary statistics (totals, completion rate, per-field counts).
+pub mod stats;
+
+/// Aligned plain-text task tables.
+pub mod table;
+
+/// Starter files for `init`.
+pub mod template;
+
+/// Syntax tree dumps as indented text or JSON.
+pub mod tree;
+
+/// Semantic checks on parsed documents.
+pub mod validate;
+
+/// A directory of files reparsed and revalidated as they change.
+pub mod workspace;
+
 /// Re-exports core types and parser for easy access.
-pub use parser::{ParseError, Priority, Project, Task, TaskStatus, ToDoParser};
+pub use options::ParseOptions;
+pub use parser::{
+    Document, ParseError, ParseWarning, Priority, Project, Task, TaskStatus, ToDoParser,
+};
+pub use schedule::{Clock, SystemClock};
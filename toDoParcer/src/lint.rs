@@ -0,0 +1,312 @@
+//! Opinionated checks on top of `validate`, each of which can be turned
+//! off or given a different severity.
+//!
+//! | Rule                  | Default | Reports                                   |
+//! |-----------------------|---------|-------------------------------------------|
+//! | `missing-due`         | warning | `@high` open tasks without a due date     |
+//! | `dangling-dependency` | error   | `depends_on` naming no task               |
+//! | `duplicate-title`     | warning | a title used by more than one task        |
+//! | `overdue`             | warning | open tasks whose due date has passed      |
+//! | `spelling`            | hint    | likely misspellings in titles and tags    |
+//!
+//! `spelling` needs the `spellcheck` feature and is off unless enabled; it
+//! checks against the built-in dictionary and the words the configuration
+//! adds with [`LintConfig::add_words`].
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::parser::{Document, Priority, TaskStatus};
+use crate::schedule::Clock;
+#[cfg(feature = "spellcheck")]
+use crate::spell::Dictionary;
+
+/// A single lint check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintRule {
+    MissingDue,
+    DanglingDependency,
+    DuplicateTitle,
+    Overdue,
+    #[cfg(feature = "spellcheck")]
+    Spelling,
+}
+
+impl LintRule {
+    /// Every rule, in the order they are run.
+    pub const ALL: &'static [LintRule] = &[
+        LintRule::MissingDue,
+        LintRule::DanglingDependency,
+        LintRule::DuplicateTitle,
+        LintRule::Overdue,
+        #[cfg(feature = "spellcheck")]
+        LintRule::Spelling,
+    ];
+
+    /// Kebab-case code of the rule, used in reports and accepted by `FromStr`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            LintRule::MissingDue => "missing-due",
+            LintRule::DanglingDependency => "dangling-dependency",
+            LintRule::DuplicateTitle => "duplicate-title",
+            LintRule::Overdue => "overdue",
+            #[cfg(feature = "spellcheck")]
+            LintRule::Spelling => "spelling",
+        }
+    }
+
+    /// Severity the rule reports with unless configured otherwise.
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            LintRule::DanglingDependency => Severity::Error,
+            #[cfg(feature = "spellcheck")]
+            LintRule::Spelling => Severity::Hint,
+            _ => Severity::Warning,
+        }
+    }
+
+    /// Whether the rule runs without being enabled.
+    pub fn enabled_by_default(&self) -> bool {
+        match self {
+            #[cfg(feature = "spellcheck")]
+            LintRule::Spelling => false,
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for LintRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl FromStr for LintRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LintRule::ALL
+            .iter()
+            .find(|r| r.code() == s)
+            .copied()
+            .ok_or_else(|| {
+                let codes: Vec<_> = LintRule::ALL.iter().map(|r| r.code()).collect();
+                format!(
+                    "unknown rule '{}', expected one of: {}",
+                    s,
+                    codes.join(", ")
+                )
+            })
+    }
+}
+
+/// Which rules run and how severe their findings are.
+///
+/// The default runs every rule that is enabled by default, with its
+/// default severity.
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    /// Per-rule override: `None` disables the rule.
+    overrides: HashMap<LintRule, Option<Severity>>,
+    /// Words `spelling` accepts.
+    #[cfg(feature = "spellcheck")]
+    dictionary: Dictionary,
+}
+
+impl LintConfig {
+    /// Returns the severity `rule` reports with, or `None` if it is disabled.
+    pub fn severity(&self, rule: LintRule) -> Option<Severity> {
+        match self.overrides.get(&rule) {
+            Some(severity) => *severity,
+            None => rule.enabled_by_default().then(|| rule.default_severity()),
+        }
+    }
+
+    /// Turns `rule` off.
+    pub fn disable(&mut self, rule: LintRule) -> &mut Self {
+        self.overrides.insert(rule, None);
+        self
+    }
+
+    /// Turns `rule` on with its default severity.
+    pub fn enable(&mut self, rule: LintRule) -> &mut Self {
+        self.overrides.insert(rule, Some(rule.default_severity()));
+        self
+    }
+
+    /// Turns `rule` on with the given severity.
+    pub fn set_severity(&mut self, rule: LintRule, severity: Severity) -> &mut Self {
+        self.overrides.insert(rule, Some(severity));
+        self
+    }
+
+    /// Makes `spelling` accept `words`, e.g. names and product terms.
+    #[cfg(feature = "spellcheck")]
+    pub fn add_words<I, S>(&mut self, words: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.dictionary.add_words(words);
+        self
+    }
+}
+
+/// Runs the enabled lint rules on `doc`.
+///
+/// Issues are sorted by line.
+///
+/// # Arguments
+/// * `doc` - Document to check
+/// * `config` - Enabled rules and their severities
+/// * `now` - Clock that decides what is overdue
+///
+/// # Example
+/// ```
+/// use chrono::NaiveDate;
+/// use to_do_parcer::ToDoParser;
+/// use to_do_parcer::lint::{LintConfig, lint};
+///
+/// let doc = ToDoParser::parse_document(r#"project "P" { todo: "Ship", @high, }"#)?;
+/// let today = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+/// assert_eq!(lint(&doc, &LintConfig::default(), &today)[0].code, "missing-due");
+/// # Ok::<(), to_do_parcer::ParseError>(())
+/// ```
+pub fn lint(doc: &Document, config: &LintConfig, now: &impl Clock) -> Vec<Diagnostic> {
+    let mut issues = Vec::new();
+    for &rule in LintRule::ALL {
+        let Some(severity) = config.severity(rule) else {
+            continue;
+        };
+        #[cfg(feature = "spellcheck")]
+        if rule == LintRule::Spelling {
+            for (line, message, suggestion) in check_spelling(doc, &config.dictionary) {
+                issues.push(Diagnostic {
+                    severity,
+                    code: rule.code(),
+                    line,
+                    span: None,
+                    suggestion,
+                    fix: None,
+                    message,
+                });
+            }
+            continue;
+        }
+        let mut report = |line: usize, message: String| {
+            issues.push(Diagnostic {
+                severity,
+                code: rule.code(),
+                line,
+                span: None,
+                suggestion: None,
+                fix: None,
+                message,
+            })
+        };
+        match rule {
+            LintRule::MissingDue => check_missing_due(doc, &mut report),
+            LintRule::DanglingDependency => check_dangling_dependencies(doc, &mut report),
+            LintRule::DuplicateTitle => check_duplicate_titles(doc, &mut report),
+            LintRule::Overdue => check_overdue(doc, now, &mut report),
+            #[cfg(feature = "spellcheck")]
+            LintRule::Spelling => unreachable!("reported above with suggestions"),
+        }
+    }
+    issues.sort_by_key(|i| i.line);
+    issues
+}
+
+/// Reports open high-priority tasks without a due date.
+fn check_missing_due(doc: &Document, report: &mut impl FnMut(usize, String)) {
+    for (_, task) in doc.tasks() {
+        if task.status == TaskStatus::Todo
+            && task.priority == Some(Priority::High)
+            && task.due_date.is_none()
+        {
+            report(
+                task.span.line,
+                format!("high-priority task \"{}\" has no due date", task.title),
+            );
+        }
+    }
+}
+
+/// Reports `depends_on` references that match no task.
+fn check_dangling_dependencies(doc: &Document, report: &mut impl FnMut(usize, String)) {
+    let deps = doc.dependencies();
+    for r in doc.task_refs() {
+        let task = r.get(doc).expect("task ref is valid");
+        if let Some(dep) = &task.depends_on
+            && deps.of(r).is_none()
+        {
+            report(
+                task.span.line,
+                format!(
+                    "task \"{}\" depends on unknown task \"{}\"",
+                    task.title, dep
+                ),
+            );
+        }
+    }
+}
+
+/// Reports every task after the first that reuses a title.
+fn check_duplicate_titles(doc: &Document, report: &mut impl FnMut(usize, String)) {
+    let mut first_seen: HashMap<&str, usize> = HashMap::new();
+    for (_, task) in doc.tasks() {
+        match first_seen.get(task.title.as_str()) {
+            Some(line) => report(
+                task.span.line,
+                format!("title \"{}\" is already used on line {}", task.title, line),
+            ),
+            None => {
+                first_seen.insert(&task.title, task.span.line);
+            }
+        }
+    }
+}
+
+/// Reports open tasks whose due date is in the past.
+fn check_overdue(doc: &Document, now: &impl Clock, report: &mut impl FnMut(usize, String)) {
+    for (_, task) in doc.overdue(now) {
+        report(
+            task.span.line,
+            format!(
+                "task \"{}\" was due {}",
+                task.title,
+                task.due_date.as_deref().unwrap_or_default()
+            ),
+        );
+    }
+}
+
+/// Finds unknown words in titles and tags, as `(line, message, suggestion)`.
+#[cfg(feature = "spellcheck")]
+fn check_spelling(doc: &Document, dictionary: &Dictionary) -> Vec<(usize, String, Option<String>)> {
+    let mut found = Vec::new();
+    for (_, task) in doc.tasks() {
+        let places = std::iter::once((&task.title, format!("title \"{}\"", task.title))).chain(
+            task.tags
+                .iter()
+                .map(|tag| (tag, format!("tag \"{}\" of task \"{}\"", tag, task.title))),
+        );
+        for (text, place) in places {
+            for word in dictionary.misspellings(text) {
+                let suggestion = dictionary.suggest(word).map(str::to_string);
+                let hint = match &suggestion {
+                    Some(known) => format!(", did you mean `{}`?", known),
+                    None => String::new(),
+                };
+                found.push((
+                    task.span.line,
+                    format!("\"{}\" in {} may be misspelled{}", word, place, hint),
+                    suggestion,
+                ));
+            }
+        }
+    }
+    found
+}
@@ -1,9 +1,53 @@
-//! Provides commands to parse `.todo` files or strings, show parse trees, and print credits.
+//! Provides commands to parse `.todo` files or strings, show parse trees, validate files,
+//! and print credits.
 
-use clap::{Parser, Subcommand};
+use chrono::NaiveDate;
+use clap::builder::Resettable;
+use clap::parser::ValueSource;
+use clap::{ArgAction, ArgGroup, ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
 use pest::Parser as PestParser;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
 use std::fs;
-use to_do_parcer::parser::{ParseError, ToDoParser};
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::OnceLock;
+use to_do_parcer::archive::archive;
+use to_do_parcer::burndown::{BurndownFormat, burndown, render_burndown};
+use to_do_parcer::cache::DocumentCache;
+use to_do_parcer::color::{ColorChoice, Palette, Style};
+use to_do_parcer::config::Config;
+use to_do_parcer::critical_path::{plan_date, schedule};
+use to_do_parcer::deps::TaskRef;
+use to_do_parcer::diagnostic::{Diagnostic, DiagnosticFormat, DiagnosticReport, Severity};
+use to_do_parcer::diff::Diff;
+use to_do_parcer::edit::{EditError, Editor, TaskChanges, find_task};
+use to_do_parcer::export::{ExportFormat, export};
+use to_do_parcer::fix::{fix_all, suggest_fix};
+use to_do_parcer::graph::{GraphFormat, graph};
+use to_do_parcer::import::{CsvField, CsvMapping, ImportFormat, from_csv, import};
+use to_do_parcer::lint::{LintConfig, LintRule, lint};
+use to_do_parcer::merge::merge;
+use to_do_parcer::notify::{DesktopNotifier, Notifier, due_notifications, parse_window};
+use to_do_parcer::options::{IncludePolicy, ParseOptions};
+use to_do_parcer::parser::{
+    Document, ParseError, Priority, SUPPORTED_VERSIONS, Task, TaskStatus, ToDoParser,
+    decode_source, normalize_source,
+};
+use to_do_parcer::pretty::format_document;
+use to_do_parcer::query::{Filter, Query};
+use to_do_parcer::report::html_report;
+use to_do_parcer::schedule::{AgendaGroup, Clock, SystemClock};
+use to_do_parcer::search::{SearchField, search};
+use to_do_parcer::serve::{Response as ServeResponse, respond};
+use to_do_parcer::sort::SortKey;
+use to_do_parcer::stats::Stats;
+use to_do_parcer::table::{Column, format_columns, format_table_with};
+use to_do_parcer::template::Template;
+use to_do_parcer::tree::{TreeFormat, render_tree};
+use to_do_parcer::validate::{TagStyle, ValidateOptions, parse_roster, validate_with};
+use to_do_parcer::workspace::{Changes, Workspace};
 
 /// Defines CLI root arguments and subcommands.
 #[derive(Parser)]
@@ -15,66 +59,3007 @@ use to_do_parcer::parser::{ParseError, ToDoParser};
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// When to color output; `auto` also honors `NO_COLOR`.
+    #[arg(long, global = true, value_name = "WHEN", default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
+    /// Log what the parser does: -v timing, -vv includes, -vvv every matched rule.
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    verbose: u8,
+
+    /// Only print errors.
+    #[arg(long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Read files that aren't valid UTF-8, replacing the invalid bytes with
+    /// U+FFFD and warning. Commands that rewrite a file never do this.
+    #[arg(long, global = true)]
+    lossy: bool,
+}
+
+/// Writes log records to stderr as `level: message`.
+struct StderrLogger;
+
+impl log::Log for StderrLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let label = match record.level() {
+            log::Level::Error => stderr_palette().paint(Style::Error, "error"),
+            log::Level::Warn => stderr_palette().paint(Style::Warning, "warning"),
+            level => level.as_str().to_lowercase(),
+        };
+        eprintln!("{}: {}", label, record.args());
+    }
+
+    fn flush(&self) {}
+}
+
+/// Maps `-q`/`-v` flags to the most detailed log level shown.
+fn log_level(quiet: bool, verbose: u8) -> log::LevelFilter {
+    match (quiet, verbose) {
+        (true, _) => log::LevelFilter::Error,
+        (false, 0) => log::LevelFilter::Warn,
+        (false, 1) => log::LevelFilter::Info,
+        (false, 2) => log::LevelFilter::Debug,
+        (false, _) => log::LevelFilter::Trace,
+    }
+}
+
+/// Palettes for stdout and stderr, chosen once from `--color` in `main`.
+static PALETTES: OnceLock<(Palette, Palette)> = OnceLock::new();
+
+/// Parse options shared by every command, from the configuration and the
+/// global flags; set once in `main`.
+static PARSE_OPTIONS: OnceLock<ParseOptions> = OnceLock::new();
+
+/// Palette for text printed to stdout.
+fn stdout_palette() -> Palette {
+    PALETTES.get().map(|p| p.0.clone()).unwrap_or_default()
+}
+
+/// Palette for text printed to stderr.
+fn stderr_palette() -> Palette {
+    PALETTES.get().map(|p| p.1.clone()).unwrap_or_default()
+}
+
+/// Exit statuses of the binary, documented under "Exit Codes" in docs.md.
+///
+/// When several things go wrong, the highest status is reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Exit {
+    Ok = 0,
+    /// A file failed to parse, or the command couldn't do what was asked.
+    Failure = 1,
+    /// Validation found errors, or warnings under `--strict`.
+    Validation = 2,
+    /// A file couldn't be read or written.
+    Io = 3,
+    /// `overdue` found tasks past their due date.
+    Overdue = 4,
+    /// `search` found no task.
+    NoMatch = 5,
+}
+
+impl Exit {
+    /// Status for a failed parse.
+    fn of(error: &ParseError) -> Exit {
+        if error.is_io() {
+            Exit::Io
+        } else {
+            Exit::Failure
+        }
+    }
+
+    /// Status for a failed edit.
+    fn of_edit(error: &EditError) -> Exit {
+        match error {
+            EditError::Parse(e) => Exit::of(e),
+            _ => Exit::Failure,
+        }
+    }
+}
+
+impl From<Exit> for ExitCode {
+    fn from(exit: Exit) -> Self {
+        ExitCode::from(exit as u8)
+    }
+}
+
+/// Prints `message` to stderr after a colored `label`.
+fn print_error(label: &str, message: impl std::fmt::Display) {
+    eprintln!(
+        "{}: {}",
+        stderr_palette().paint(Style::Error, label),
+        message
+    );
+}
+
+/// Supported CLI subcommands.
+#[derive(Subcommand)]
+enum Commands {
+    Credits,
+    /// Create a starter `.todo` file with examples of every attribute.
+    Init(InitArgs),
+    Parse(ParseArgs),
+    /// Parse a file and run semantic checks; exits non-zero on errors.
+    Validate(ValidateArgs),
+    /// Run configurable lint rules; exits non-zero on errors.
+    Lint(LintArgs),
+    /// Reformat a file with consistent indentation and attribute order.
+    Fmt(FmtArgs),
+    /// Repair missing commas, unclosed quotes and misspelled date separators.
+    Fix(FixArgs),
+    /// Print task totals, completion rate and per-field counts.
+    Stats(StatsArgs),
+    /// Convert a file to another format and print it.
+    Export(ExportArgs),
+    /// Convert a file from another format to `.todo` text and print it.
+    Import(ImportArgs),
+    /// Print only the tasks matching all given conditions.
+    Filter(FilterArgs),
+    /// Append a task to a project, keeping the rest of the file as is.
+    Add(AddArgs),
+    /// Mark a task as done, keeping the rest of the file as is.
+    Done(DoneArgs),
+    /// Remove a task, keeping the rest of the file as is.
+    Rm(RmArgs),
+    /// Change attributes of a task, keeping the rest of the file as is.
+    Set(SetArgs),
+    /// Move a task to another project, keeping dependency references valid.
+    Mv(MvArgs),
+    /// Move each project into a file of its own and include those files instead.
+    Split(SplitArgs),
+    /// Find tasks with (nearly) the same title in a project; merge them with --write.
+    Dedupe(DedupeArgs),
+    /// Hand every open task of one assignee over to another.
+    Assign(AssignArgs),
+    /// Rename a project and the `Project/Task` references to its tasks.
+    RenameProject(RenameProjectArgs),
+    /// Rewrite a file for another format version, keeping comments and layout.
+    Migrate(MigrateArgs),
+    /// Show how tasks changed between two versions of a file.
+    Diff(DiffArgs),
+    /// Merge two versions of a file with their common ancestor.
+    Merge(MergeArgs),
+    /// Reorder the tasks of each project, keeping the rest of the file as is.
+    Sort(SortArgs),
+    /// Move done tasks and finished projects into an archive file.
+    Archive(ArchiveArgs),
+    /// List open tasks past their due date, latest first; exits with status 4 if any.
+    Overdue(OverdueArgs),
+    /// Show the chain of dependent tasks that decides when all work is done, and each task's slack.
+    CriticalPath(CriticalPathArgs),
+    /// Print the number of open and done tasks for each day of a date range.
+    Burndown(BurndownArgs),
+    /// Show a desktop notification for each open task that is overdue or due soon.
+    Notify(NotifyArgs),
+    /// List open tasks whose dependencies are all done, most urgent first.
+    Next(NextArgs),
+    /// Print the dependency graph of a file for Graphviz or as a Mermaid Gantt chart.
+    Graph(GraphArgs),
+    /// Write a standalone HTML page with progress and tasks per project.
+    Report(ReportArgs),
+    /// Find tasks by title, tag or assignee, ignoring case; exits with status 5 if none match.
+    Search(SearchArgs),
+    /// Show overdue and upcoming tasks grouped by when they are due.
+    Agenda(AgendaArgs),
+    /// Show each assignee's open tasks, high-priority count and nearest deadline.
+    ByAssignee(ByAssigneeArgs),
+    /// List tags with usage counts, or the tasks under one tag.
+    Tags(TagsArgs),
+    /// Answer read-only JSON queries over HTTP (/projects, /tasks, /stats).
+    Serve(ServeArgs),
+    /// Watch a directory and print the diagnostics of files as they change.
+    Watch(WatchArgs),
+    /// Sync tagged tasks with an issue tracker.
+    #[cfg(feature = "github")]
+    Sync(SyncArgs),
+}
+
+/// Input files of commands that read several at once.
+#[derive(clap::Args)]
+struct Inputs {
+    /// File to read; repeat, or quote a glob such as `"tasks/*.todo"`, for several.
+    #[arg(short, long, required_unless_present = "dir")]
+    file: Vec<String>,
+
+    /// Read every `.todo` file under this directory.
+    #[arg(long)]
+    dir: Option<String>,
+}
+
+/// Arguments for the `init` subcommand.
+#[derive(Parser)]
+struct InitArgs {
+    /// File to create.
+    #[arg(short, long, default_value = "tasks.todo")]
+    file: String,
+
+    /// Starter content: sprint, personal or empty.
+    #[arg(long, default_value_t = Template::Sprint)]
+    template: Template,
+
+    /// Replace the file if it already exists.
+    #[arg(long)]
+    force: bool,
+}
+
+/// Arguments for the `parse` subcommand.
+#[derive(Parser)]
+struct ParseArgs {
+    #[command(flatten)]
+    inputs: Inputs,
+
+    /// Print the syntax tree: each rule with its byte span and text.
+    #[arg(long)]
+    tree: bool,
+
+    /// Syntax tree layout: text, or json for editor tooling.
+    #[arg(long, requires = "tree", default_value_t = TreeFormat::Text)]
+    tree_format: TreeFormat,
+
+    /// Print tasks as an aligned table instead of blocks.
+    #[arg(long, conflicts_with = "tree")]
+    table: bool,
+
+    /// Table columns, comma-separated: project, status, title, priority,
+    /// due, assignee, tags.
+    #[arg(long, requires = "table", value_delimiter = ',', value_name = "LIST")]
+    columns: Vec<Column>,
+
+    /// Write the result to this file instead of stdout; the file is
+    /// replaced only once the whole result is written.
+    #[arg(short, long, value_name = "PATH")]
+    output: Option<String>,
+}
+
+/// Arguments for the `validate` subcommand.
+#[derive(Parser)]
+struct ValidateArgs {
+    #[command(flatten)]
+    inputs: Inputs,
+
+    /// Treat warnings as errors: exit with status 2 if there are any, and
+    /// report tasks sharing a title within a project as errors.
+    #[arg(long)]
+    strict: bool,
+
+    /// Report dependencies on unknown tasks as warnings instead of errors.
+    #[arg(long)]
+    lenient: bool,
+
+    /// Warn about open tasks whose due date has passed.
+    #[arg(long)]
+    overdue: bool,
+
+    /// Date (YYYY-MM-DD) used by --overdue instead of today.
+    #[arg(long, value_name = "DATE", requires = "overdue")]
+    as_of: Option<NaiveDate>,
+
+    /// Require every tag to be written in this style: kebab-case,
+    /// snake_case or lowercase.
+    #[arg(long, value_name = "STYLE")]
+    tag_style: Option<TagStyle>,
+
+    /// Only accept these tags, separated by commas.
+    #[arg(long, value_delimiter = ',', value_name = "LIST")]
+    allowed_tags: Vec<String>,
+
+    /// Roster file with one handle per line; `assign:` values not on it
+    /// are errors.
+    #[arg(long, value_name = "FILE")]
+    assignees: Option<String>,
+
+    /// Only report diagnostics at least this severe: hint, warning or error.
+    #[arg(long, value_name = "LEVEL", default_value = "hint")]
+    min_severity: Severity,
+
+    /// Output format: text, or json with code, severity, span, suggestion
+    /// and fix of every diagnostic.
+    #[arg(long, value_name = "FORMAT", default_value_t = DiagnosticFormat::Text)]
+    format: DiagnosticFormat,
+}
+
+/// Arguments for the `lint` subcommand.
+#[derive(Parser)]
+struct LintArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Turn a rule off; repeat for several.
+    #[arg(long, value_name = "RULE")]
+    disable: Vec<LintRule>,
+
+    /// Turn a rule back on; wins over `--disable`.
+    #[arg(long, value_name = "RULE")]
+    enable: Vec<LintRule>,
+
+    /// Report a rule with another severity, e.g. `missing-due=error`.
+    #[arg(long, value_name = "RULE=LEVEL", value_parser = parse_rule_severity)]
+    severity: Vec<(LintRule, Severity)>,
+
+    /// Word list (one word per line, `#` comments) `spelling` accepts.
+    #[cfg(feature = "spellcheck")]
+    #[arg(long, value_name = "FILE")]
+    words: Vec<String>,
+
+    /// Date (YYYY-MM-DD) used to decide what is overdue instead of today.
+    #[arg(long, value_name = "DATE")]
+    as_of: Option<NaiveDate>,
+
+    /// Treat warnings as errors: exit with status 2 if there are any.
+    #[arg(long)]
+    strict: bool,
+
+    /// Only report diagnostics at least this severe: hint, warning or error.
+    #[arg(long, value_name = "LEVEL", default_value = "hint")]
+    min_severity: Severity,
+
+    /// Output format: text, or json with code, severity, span, suggestion
+    /// and fix of every diagnostic.
+    #[arg(long, value_name = "FORMAT", default_value_t = DiagnosticFormat::Text)]
+    format: DiagnosticFormat,
+}
+
+/// Arguments for the `overdue` subcommand.
+#[derive(Parser)]
+struct OverdueArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Date (YYYY-MM-DD) used to decide what is overdue instead of today.
+    #[arg(long, value_name = "DATE")]
+    as_of: Option<NaiveDate>,
+}
+
+/// Arguments for the `critical-path` subcommand.
+#[derive(Parser)]
+struct CriticalPathArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Date (YYYY-MM-DD) the plan starts on instead of today.
+    #[arg(long, value_name = "DATE")]
+    as_of: Option<NaiveDate>,
+}
+
+/// Arguments for the `burndown` subcommand.
+#[derive(Parser)]
+struct BurndownArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// First day of the series (YYYY-MM-DD).
+    #[arg(long, value_name = "DATE")]
+    from: NaiveDate,
+
+    /// Last day of the series (YYYY-MM-DD); defaults to today.
+    #[arg(long, value_name = "DATE")]
+    to: Option<NaiveDate>,
+
+    /// Output format: csv or json.
+    #[arg(long, default_value = "csv")]
+    format: BurndownFormat,
+
+    /// Write the series to this file instead of stdout; the file is
+    /// replaced only once the whole series is written.
+    #[arg(short, long, value_name = "PATH")]
+    output: Option<String>,
+}
+
+/// Arguments for the `notify` subcommand.
+#[derive(Parser)]
+struct NotifyArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// How far ahead to look: hours (24h), days (3d) or weeks (1w).
+    #[arg(long, default_value = "24h", value_parser = parse_window)]
+    within: u32,
+
+    /// Date (YYYY-MM-DD) to look from instead of today.
+    #[arg(long, value_name = "DATE")]
+    as_of: Option<NaiveDate>,
+
+    /// Print the notifications instead of showing them.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// Arguments for the `next` subcommand.
+#[derive(Parser)]
+struct NextArgs {
+    #[arg(short, long)]
+    file: String,
+}
+
+/// Arguments for the `search` subcommand.
+#[derive(Parser)]
+struct SearchArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Text to look for; letters may also match with gaps, e.g. `dbmig`.
+    pattern: String,
+}
+
+/// Arguments for the `agenda` subcommand.
+#[derive(Parser)]
+struct AgendaArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// How many weeks ahead to show.
+    #[arg(long, default_value_t = 1)]
+    weeks: u32,
+
+    /// Date (YYYY-MM-DD) to plan from instead of today.
+    #[arg(long, value_name = "DATE")]
+    as_of: Option<NaiveDate>,
+}
+
+/// Arguments for the `by-assignee` subcommand.
+#[derive(Parser)]
+struct ByAssigneeArgs {
+    #[arg(short, long)]
+    file: String,
+}
+
+/// Arguments for the `tags` subcommand.
+#[derive(Parser)]
+struct TagsArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// List the tasks carrying this tag instead of the counts.
+    #[arg(long, value_name = "TAG")]
+    show: Option<String>,
+}
+
+/// Arguments for the `archive` subcommand.
+#[derive(Parser)]
+struct ArchiveArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Archive file to append to; created if it doesn't exist.
+    #[arg(long, value_name = "PATH")]
+    to: String,
+}
+
+/// Arguments for the `fmt` subcommand.
+#[derive(Parser)]
+struct FmtArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Exit non-zero if the file is not formatted, without changing it.
+    #[arg(long, conflicts_with = "write")]
+    check: bool,
+
+    /// Rewrite the file in place instead of printing the result.
+    #[arg(long)]
+    write: bool,
+}
+
+/// Arguments for the `fix` subcommand.
+#[derive(Parser)]
+struct FixArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Rewrite the file in place instead of printing the result.
+    #[arg(long)]
+    write: bool,
+}
+
+/// Arguments for the `stats` subcommand.
+#[derive(Parser)]
+struct StatsArgs {
+    #[command(flatten)]
+    inputs: Inputs,
+
+    /// Print the statistics as JSON.
+    #[arg(long)]
+    json: bool,
+
+    /// Date (YYYY-MM-DD) used to count overdue tasks instead of today.
+    #[arg(long, value_name = "DATE")]
+    as_of: Option<NaiveDate>,
+
+    /// Write the result to this file instead of stdout; the file is
+    /// replaced only once the whole result is written.
+    #[arg(short, long, value_name = "PATH")]
+    output: Option<String>,
+}
+
+/// Arguments for the `serve` subcommand.
+#[derive(Parser)]
+struct ServeArgs {
+    #[command(flatten)]
+    inputs: Inputs,
+
+    /// Address to listen on; use 0.0.0.0 to accept other machines.
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+
+    /// Port to listen on; 0 picks a free one.
+    #[arg(long, default_value_t = 8080)]
+    port: u16,
+}
+
+/// Arguments for the `watch` subcommand.
+#[derive(Parser)]
+struct WatchArgs {
+    /// Directory whose `.todo` files are watched, subdirectories included.
+    #[arg(long, default_value = ".")]
+    dir: String,
+
+    /// Milliseconds between checks for changes.
+    #[arg(long, value_name = "MS", default_value_t = 500)]
+    interval: u64,
+
+    /// Report tasks sharing a title within a project as errors.
+    #[arg(long)]
+    strict: bool,
+
+    /// Report dependencies on unknown tasks as warnings instead of errors.
+    #[arg(long)]
+    lenient: bool,
+}
+
+/// Arguments for the `sync` subcommand.
+#[cfg(feature = "github")]
+#[derive(Parser)]
+struct SyncArgs {
+    #[command(subcommand)]
+    target: SyncTarget,
+}
+
+/// Issue trackers `sync` can talk to.
+#[cfg(feature = "github")]
+#[derive(Subcommand)]
+enum SyncTarget {
+    /// Create and update GitHub issues from tasks and mark tasks of closed
+    /// issues done. Reads the token from GITHUB_TOKEN.
+    Github(GithubArgs),
+}
+
+/// Arguments for `sync github`.
+#[cfg(feature = "github")]
+#[derive(Parser)]
+struct GithubArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Repository as OWNER/NAME.
+    #[arg(long)]
+    repo: String,
+
+    /// Tag marking the tasks to sync.
+    #[arg(long, default_value = "github")]
+    tag: String,
+
+    /// GitHub login of an assignee, as NAME=LOGIN; repeat for several.
+    #[arg(long, value_name = "NAME=LOGIN", value_parser = parse_mapping)]
+    map_assignee: Vec<(String, String)>,
+
+    /// API base URL, for GitHub Enterprise.
+    #[arg(long, default_value = to_do_parcer::github::API_URL)]
+    api_url: String,
+
+    /// Print what would change without changing anything.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// Arguments for the `export` subcommand.
+#[derive(Parser)]
+struct ExportArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Output format: json, yaml, csv, markdown, org, jira or
+    /// taskwarrior.
+    #[arg(long, default_value = "json")]
+    format: ExportFormat,
+
+    /// Write the result to this file instead of stdout; the file is
+    /// replaced only once the whole result is written.
+    #[arg(short, long, value_name = "PATH")]
+    output: Option<String>,
+}
+
+/// Arguments for the `graph` subcommand.
+#[derive(Parser)]
+struct GraphArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Output format: dot or mermaid-gantt.
+    #[arg(long, default_value = "dot")]
+    format: GraphFormat,
+
+    /// Write the result to this file instead of stdout; the file is
+    /// replaced only once the whole result is written.
+    #[arg(short, long, value_name = "PATH")]
+    output: Option<String>,
+}
+
+/// Arguments for the `report` subcommand.
+#[derive(Parser)]
+struct ReportArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Date (YYYY-MM-DD) used to decide what is overdue instead of today.
+    #[arg(long, value_name = "DATE")]
+    as_of: Option<NaiveDate>,
+
+    /// Write the page to this file instead of stdout; the file is
+    /// replaced only once the whole page is written.
+    #[arg(short, long, value_name = "PATH")]
+    output: Option<String>,
+}
+
+/// Arguments for the `import` subcommand.
+#[derive(Parser)]
+struct ImportArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Input format: markdown, org, trello (board JSON export) or csv.
+    #[arg(long)]
+    format: ImportFormat,
+
+    /// CSV column of a task field, as FIELD=COLUMN; repeat for several.
+    /// Fields: project, title, status, id, priority, start, due, completed,
+    /// assignee, depends_on, tags.
+    #[arg(long, value_name = "FIELD=COLUMN", value_parser = parse_column)]
+    map: Vec<(CsvField, String)>,
+}
+
+/// Arguments for the `filter` subcommand.
+#[derive(Parser)]
+struct FilterArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Task status: todo or done.
+    #[arg(long, value_parser = parse_status)]
+    status: Option<TaskStatus>,
+
+    /// Task priority: high, medium or low.
+    #[arg(long, value_parser = parse_priority)]
+    priority: Option<Priority>,
+
+    /// Required tag; repeat to require several.
+    #[arg(long)]
+    tag: Vec<String>,
+
+    /// Assignee name, with or without the `@`.
+    #[arg(long)]
+    assignee: Option<String>,
+
+    /// Query expression, e.g. `status == todo && (priority == high || tag:"urgent")`.
+    #[arg(short, long)]
+    query: Option<Query>,
+
+    /// Print a table instead of `.todo` text.
+    #[arg(long)]
+    table: bool,
+}
+
+/// Arguments for the `add` subcommand.
+#[derive(Parser)]
+struct AddArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Project to add the task to; created if it doesn't exist.
+    #[arg(short, long)]
+    project: String,
+
+    #[arg(short, long)]
+    title: String,
+
+    /// Identifier for selecting the task later as `#id`.
+    #[arg(long)]
+    id: Option<String>,
+
+    /// Task priority: high, medium or low.
+    #[arg(long, value_parser = parse_priority)]
+    priority: Option<Priority>,
+
+    /// Due date (YYYY-MM-DD).
+    #[arg(long)]
+    due: Option<NaiveDate>,
+
+    /// Assignee name, with or without the `@`.
+    #[arg(long)]
+    assign: Option<String>,
+
+    /// Title of the task this one depends on.
+    #[arg(long)]
+    depends_on: Option<String>,
+
+    /// Tag to add; repeat for several.
+    #[arg(long)]
+    tag: Vec<String>,
+}
+
+/// Arguments for the `done` subcommand.
+#[derive(Parser)]
+struct DoneArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Task title, `Project/Title` path or `#id`.
+    task: String,
+
+    /// Record a completion date: today, or the given `--completed=YYYY-MM-DD`.
+    #[arg(long, num_args = 0..=1, require_equals = true, value_name = "DATE")]
+    completed: Option<Option<NaiveDate>>,
+}
+
+/// Arguments for the `rm` subcommand.
+#[derive(Parser)]
+struct RmArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Task title, `Project/Title` path or `#id`.
+    task: String,
+
+    /// Remove the task even if other tasks depend on it.
+    #[arg(long)]
+    force: bool,
+}
+
+/// Arguments for the `mv` subcommand.
+#[derive(Parser)]
+struct MvArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Task title, `Project/Title` path or `#id`.
+    task: String,
+
+    /// Project to move the task to; created if missing.
+    #[arg(long, value_name = "PROJECT")]
+    to_project: String,
+}
+
+/// Arguments for the `split` subcommand.
+#[derive(Parser)]
+struct SplitArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Directory for the project files; created if missing.
+    #[arg(long, value_name = "DIR")]
+    out_dir: String,
+
+    /// Overwrite project files that already exist.
+    #[arg(long)]
+    force: bool,
+}
+
+/// Arguments for the `dedupe` subcommand.
+#[derive(Parser)]
+struct DedupeArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Merge the duplicates into the first task of each group and save.
+    #[arg(long)]
+    write: bool,
+}
+
+/// Arguments for the `assign` subcommand.
+#[derive(Parser)]
+struct AssignArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Current assignee, with or without `@`.
+    #[arg(long)]
+    from: String,
+
+    /// New assignee, with or without `@`.
+    #[arg(long)]
+    to: String,
+
+    /// Only reassign tasks of this project.
+    #[arg(long)]
+    project: Option<String>,
+
+    /// Also reassign done tasks.
+    #[arg(long)]
+    include_done: bool,
+}
+
+/// Arguments for the `rename-project` subcommand.
+#[derive(Parser)]
+struct RenameProjectArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Current name of the project.
+    old: String,
+
+    /// New name of the project.
+    new: String,
+}
+
+/// Arguments for the `migrate` subcommand.
+#[derive(Parser)]
+struct MigrateArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Format version to write; the latest one by default.
+    #[arg(long, value_name = "VERSION", default_value_t = *SUPPORTED_VERSIONS.end())]
+    to: u32,
+}
+
+/// Arguments for the `set` subcommand.
+///
+/// Each attribute accepts `none` to remove it.
+#[derive(Parser)]
+#[command(group(
+    ArgGroup::new("changes")
+        .required(true)
+        .multiple(true)
+        .args(["priority", "due", "assign", "depends_on"])
+))]
+struct SetArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Task title, `Project/Title` path or `#id`.
+    task: String,
+
+    /// New priority: high, medium, low or none.
+    #[arg(long, value_parser = parse_clearable_priority)]
+    priority: Option<Clearable<Priority>>,
+
+    /// New due date (YYYY-MM-DD) or none.
+    #[arg(long, value_parser = parse_clearable_date)]
+    due: Option<Clearable<NaiveDate>>,
+
+    /// New assignee, with or without the `@`, or none.
+    #[arg(long)]
+    assign: Option<String>,
+
+    /// Title of the task this one depends on, or none.
+    #[arg(long)]
+    depends_on: Option<String>,
+}
+
+/// Arguments for the `sort` subcommand.
+#[derive(Parser)]
+struct SortArgs {
+    #[arg(short, long)]
+    file: String,
+
+    /// Sort key: due, priority or status.
+    #[arg(long)]
+    by: SortKey,
+
+    /// Rewrite the file in place instead of printing the result.
+    #[arg(long)]
+    write: bool,
+}
+
+/// Arguments for the `diff` subcommand.
+#[derive(Parser)]
+struct DiffArgs {
+    /// Old version of the file.
+    old: String,
+
+    /// New version of the file.
+    new: String,
+
+    /// Print the changes as JSON.
+    #[arg(long)]
+    json: bool,
+}
+
+/// Arguments for the `merge` subcommand.
+#[derive(Parser)]
+struct MergeArgs {
+    /// Common ancestor of both versions.
+    base: String,
+
+    /// Our version.
+    ours: String,
+
+    /// Their version.
+    theirs: String,
+
+    /// Write the merged file here instead of printing it.
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+/// A CLI value that is either `none` or a `T`.
+#[derive(Clone)]
+struct Clearable<T>(Option<T>);
+
+fn main() -> ExitCode {
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            print_error("Configuration error", e);
+            return Exit::Failure.into();
+        }
+    };
+    let matches = with_config_defaults(Cli::command(), &config).get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    drop_default_file(&mut cli, &matches);
+
+    let mut stdout = Palette::new(cli.color.enabled(io::stdout().is_terminal()));
+    if let Some(format) = &config.date_format {
+        stdout = stdout.with_date_format(format);
+    }
+    let _ = PALETTES.set((
+        stdout,
+        Palette::new(cli.color.enabled(io::stderr().is_terminal())),
+    ));
+    let _ = PARSE_OPTIONS.set(ParseOptions {
+        ignore_assignee_case: config.ignore_assignee_case.unwrap_or(false),
+        lossy: cli.lossy,
+        ..ParseOptions::default()
+    });
+    if log::set_logger(&StderrLogger).is_ok() {
+        log::set_max_level(log_level(cli.quiet, cli.verbose));
+    }
+
+    let exit = match cli.command {
+        Commands::Credits => {
+            println!("Author: Tetiana Khaimyk");
+            println!("Project: ToDo Parser");
+            println!("Language: Rust");
+            Exit::Ok
+        }
+
+        Commands::Init(args) => run_init(args),
+
+        Commands::Parse(args) => run_parse(args),
+
+        Commands::Validate(args) => run_validate(args, &config),
+
+        Commands::Lint(args) => run_lint(args, config.lint),
+
+        Commands::Fmt(args) => {
+            let file = args.file.clone();
+            run_fmt(args).unwrap_or_else(|e| {
+                print_parse_error("Parsing error", &file, &e);
+                Exit::of(&e)
+            })
+        }
+
+        Commands::Fix(args) => {
+            let file = args.file.clone();
+            run_fix(args).unwrap_or_else(|e| {
+                print_parse_error("Parsing error", &file, &e);
+                Exit::of(&e)
+            })
+        }
+
+        Commands::Stats(args) => run_stats(args),
+
+        Commands::Serve(args) => run_serve(args),
+
+        Commands::Watch(args) => run_watch(args, &config),
+
+        #[cfg(feature = "github")]
+        Commands::Sync(SyncArgs {
+            target: SyncTarget::Github(args),
+        }) => run_sync_github(args),
+
+        Commands::Overdue(args) => run_overdue(args),
+
+        Commands::CriticalPath(args) => run_critical_path(args),
+
+        Commands::Burndown(args) => run_burndown(args),
+
+        Commands::Notify(args) => run_notify(args, &DesktopNotifier),
+
+        Commands::Search(args) => match load_document(&args.file) {
+            Ok(doc) => run_search(&args, &doc),
+            Err(e) => {
+                print_parse_error("Parsing error", &args.file, &e);
+                Exit::of(&e)
+            }
+        },
+
+        Commands::Agenda(args) => match load_document(&args.file) {
+            Ok(doc) => {
+                run_agenda(&args, &doc);
+                Exit::Ok
+            }
+            Err(e) => {
+                print_parse_error("Parsing error", &args.file, &e);
+                Exit::of(&e)
+            }
+        },
+
+        Commands::ByAssignee(args) => match load_document(&args.file) {
+            Ok(doc) => {
+                print_workload(&doc);
+                Exit::Ok
+            }
+            Err(e) => {
+                print_parse_error("Parsing error", &args.file, &e);
+                Exit::of(&e)
+            }
+        },
+
+        Commands::Tags(args) => match load_document(&args.file) {
+            Ok(doc) => run_tags(&args, &doc),
+            Err(e) => {
+                print_parse_error("Parsing error", &args.file, &e);
+                Exit::of(&e)
+            }
+        },
+
+        Commands::Next(args) => match load_document(&args.file) {
+            Ok(doc) => {
+                print!(
+                    "{}",
+                    format_columns(
+                        &doc.actionable(),
+                        &[
+                            Column::Project,
+                            Column::Title,
+                            Column::Priority,
+                            Column::Due
+                        ],
+                        &stdout_palette().with_clock(&SystemClock),
+                    )
+                );
+                Exit::Ok
+            }
+            Err(e) => {
+                print_parse_error("Parsing error", &args.file, &e);
+                Exit::of(&e)
+            }
+        },
+
+        Commands::Import(args) => match run_import(args) {
+            Ok(()) => Exit::Ok,
+            Err(e) => {
+                print_error("File reading error", e);
+                Exit::Io
+            }
+        },
+
+        Commands::Filter(args) => match load_document(&args.file) {
+            Ok(doc) => {
+                run_filter(args, &doc);
+                Exit::Ok
+            }
+            Err(e) => {
+                print_parse_error("Parsing error", &args.file, &e);
+                Exit::of(&e)
+            }
+        },
+
+        Commands::Add(args) => edit_status(run_add(args)),
+
+        Commands::Done(args) => edit_status(run_done(args)),
+
+        Commands::Rm(args) => edit_status(run_rm(args)),
+
+        Commands::Set(args) => edit_status(run_set(args)),
+
+        Commands::Mv(args) => edit_status(run_mv(args)),
+
+        Commands::Split(args) => edit_status(run_split(args)),
+
+        Commands::Dedupe(args) => match run_dedupe(&args) {
+            Ok(found) if found && !args.write => Exit::Failure,
+            result => edit_status(result.map(|_| ())),
+        },
+
+        Commands::Assign(args) => edit_status(run_assign(args)),
+
+        Commands::RenameProject(args) => edit_status(run_rename_project(args)),
+        Commands::Migrate(args) => edit_status(run_migrate(args)),
+
+        Commands::Sort(args) => edit_status(run_sort(args)),
+
+        Commands::Archive(args) => edit_status(run_archive(args)),
+
+        Commands::Diff(args) => match run_diff(args) {
+            Ok(()) => Exit::Ok,
+            Err(e) => {
+                print_error("Parsing error", &e);
+                Exit::of(&e)
+            }
+        },
+
+        Commands::Merge(args) => run_merge(args).unwrap_or_else(|e| {
+            print_error("Parsing error", &e);
+            Exit::of(&e)
+        }),
+
+        Commands::Export(args) => match load_document(&args.file) {
+            Ok(doc) => match emit(args.output.as_deref(), &export(&doc, args.format)) {
+                Ok(()) => Exit::Ok,
+                Err(e) => {
+                    print_error("File writing error", e);
+                    Exit::Io
+                }
+            },
+            Err(e) => {
+                print_parse_error("Parsing error", &args.file, &e);
+                Exit::of(&e)
+            }
+        },
+
+        Commands::Report(args) => match load_document(&args.file) {
+            Ok(doc) => {
+                let today = args.as_of.unwrap_or_else(|| SystemClock.today());
+                let html = html_report(&doc, &args.file, &today);
+                match emit(args.output.as_deref(), &html) {
+                    Ok(()) => Exit::Ok,
+                    Err(e) => {
+                        print_error("File writing error", e);
+                        Exit::Io
+                    }
+                }
+            }
+            Err(e) => {
+                print_parse_error("Parsing error", &args.file, &e);
+                Exit::of(&e)
+            }
+        },
+
+        Commands::Graph(args) => match load_document(&args.file) {
+            Ok(doc) => match emit(args.output.as_deref(), &graph(&doc, args.format)) {
+                Ok(()) => Exit::Ok,
+                Err(e) => {
+                    print_error("File writing error", e);
+                    Exit::Io
+                }
+            },
+            Err(e) => {
+                print_parse_error("Parsing error", &args.file, &e);
+                Exit::of(&e)
+            }
+        },
+    };
+
+    exit.into()
+}
+
+/// Makes the settings of `config` the defaults of the matching arguments,
+/// so that flags given on the command line still win.
+fn with_config_defaults(mut command: clap::Command, config: &Config) -> clap::Command {
+    if let Some(color) = config.color {
+        command = command.mut_arg("color", |arg| arg.default_value(color.as_str()));
+    }
+    if let Some(format) = config.format {
+        command = command.mut_subcommand("export", |sub| {
+            sub.mut_arg("format", |arg| arg.default_value(format.as_str()))
+        });
+    }
+    if let Some(file) = &config.file {
+        let names: Vec<String> = command
+            .get_subcommands()
+            .filter(|sub| sub.get_arguments().any(|arg| arg.get_id() == "file"))
+            .map(|sub| sub.get_name().to_string())
+            .collect();
+        for name in names {
+            command = command.mut_subcommand(name, |sub| {
+                sub.mut_arg("file", |arg| {
+                    arg.default_value(file.clone())
+                        .required(false)
+                        .required_unless_present(Resettable::Reset)
+                })
+            });
+        }
+    }
+    command
+}
+
+/// Forgets the configured default `--file` of commands given `--dir`, which
+/// stands in for `--file` on the command line.
+fn drop_default_file(cli: &mut Cli, matches: &ArgMatches) {
+    let Some((_, sub)) = matches.subcommand() else {
+        return;
+    };
+    let defaulted = sub.try_get_raw("file").is_ok()
+        && sub.value_source("file") == Some(ValueSource::DefaultValue);
+    let inputs = match &mut cli.command {
+        Commands::Parse(args) => &mut args.inputs,
+        Commands::Validate(args) => &mut args.inputs,
+        Commands::Stats(args) => &mut args.inputs,
+        Commands::Serve(args) => &mut args.inputs,
+        _ => return,
+    };
+    if defaulted && inputs.dir.is_some() {
+        inputs.file.clear();
+    }
+}
+
+/// Prints a parse error of the file at `path` after `label`, with the
+/// offending line of the file (or of the included file the error comes
+/// from) and a caret under the error.
+fn print_parse_error(label: &str, path: &str, error: &ParseError) {
+    let (included, inner) = error.innermost();
+    let file = included.map_or_else(|| PathBuf::from(path), Path::to_path_buf);
+    match fs::read_to_string(&file) {
+        Ok(source) if inner.line().is_some() => {
+            let mut rendered = error.render(&source, &file.display().to_string());
+            if let Some(fix) = suggest_fix(&source, &cli_options(IncludePolicy::Ignore)) {
+                rendered = format!(
+                    "{}\n  = help: {} at {}:{} (`fix --write` applies it)",
+                    rendered.trim_end(),
+                    fix,
+                    fix.span.line,
+                    fix.span.column
+                );
+            }
+            print_error(label, rendered.trim_end());
+        }
+        _ => print_error(label, error),
+    }
+}
+
+/// The diagnostic of a parse error of the file at `path`, with the fix
+/// for it when one is recognized.
+fn parse_diagnostic(path: &str, error: &ParseError) -> Diagnostic {
+    let mut diagnostic = error.diagnostic();
+    let (included, _) = error.innermost();
+    let file = included.map_or_else(|| PathBuf::from(path), Path::to_path_buf);
+    if let Ok(source) = fs::read_to_string(&file) {
+        diagnostic.fix = suggest_fix(&source, &cli_options(IncludePolicy::Ignore));
+    }
+    diagnostic
+}
+
+/// Reports the error of an edit command, if any.
+fn edit_status(result: Result<(), EditError>) -> Exit {
+    match result {
+        Ok(()) => Exit::Ok,
+        Err(e) => {
+            print_error("Error", &e);
+            Exit::of_edit(&e)
+        }
+    }
+}
+
+/// Options for parsing a file named on the command line.
+///
+/// Empty and comment-only files are read as documents without projects;
+/// [`warn_if_empty`] tells the user about them.
+fn cli_options(includes: IncludePolicy) -> ParseOptions {
+    ParseOptions {
+        includes,
+        allow_empty: true,
+        ..PARSE_OPTIONS.get().cloned().unwrap_or_default()
+    }
+}
+
+/// Whether `--lossy` was given.
+fn lossy() -> bool {
+    PARSE_OPTIONS.get().is_some_and(|options| options.lossy)
+}
+
+/// Reads a file named on the command line.
+///
+/// Invalid UTF-8 fails with `ParseError::Encoding`, or with `lossy` is
+/// replaced with a warning. Commands that write the file back pass `false`
+/// so that replaced bytes never reach the disk.
+fn read_source(path: &str, lossy: bool) -> Result<String, ParseError> {
+    log::debug!("reading {}", path);
+    let (text, replaced) = decode_source(fs::read(path)?, lossy)?;
+    if let Some(warning) = replaced {
+        log::warn!("{}: {}", path, warning);
+    }
+    Ok(text)
+}
+
+/// Warns that `path` has no projects, which usually means the wrong file.
+fn warn_if_empty(path: &str, doc: &Document) {
+    if doc.projects.is_empty() {
+        log::warn!("{} contains no projects", path);
+    }
+}
+
+/// Parses `path` into a `Document`, resolving includes relative to it.
+fn load_document(path: &str) -> Result<Document, ParseError> {
+    let doc = ToDoParser::parse_with(&read_source(path, lossy())?, &document_options(path))?;
+    warn_if_empty(path, &doc);
+    Ok(doc)
+}
+
+/// Like `load_document`, but only parses `path` when it changed since it
+/// was last put in `cache`.
+fn load_cached(cache: &mut DocumentCache, path: &str) -> Result<Document, ParseError> {
+    let doc = cache.parse_file(path, &document_options(path))?;
+    warn_if_empty(path, doc);
+    Ok(doc.clone())
+}
+
+/// Options for parsing `path`, resolving includes relative to it.
+fn document_options(path: &str) -> ParseOptions {
+    ParseOptions {
+        base_dir: Path::new(path).parent().map(Path::to_path_buf),
+        ..cli_options(IncludePolicy::Resolve)
+    }
+}
+
+impl Inputs {
+    /// Expands globs and `--dir` into the files to read, in command-line
+    /// order and without duplicates.
+    ///
+    /// # Returns
+    /// * `Err` naming the pattern or directory that matches no file.
+    fn paths(&self) -> Result<Vec<String>, String> {
+        // Each pattern with the message to show if it matches nothing.
+        let mut patterns: Vec<(String, String)> = self
+            .file
+            .iter()
+            .map(|f| (f.clone(), format!("no files match '{}'", f)))
+            .collect();
+        if let Some(dir) = &self.dir {
+            let pattern = Path::new(&glob::Pattern::escape(dir)).join("**/*.todo");
+            patterns.push((
+                pattern.to_string_lossy().into_owned(),
+                format!("no .todo files in '{}'", dir),
+            ));
+        }
+
+        let mut paths: Vec<String> = Vec::new();
+        for (pattern, none_found) in patterns {
+            let expanded = if pattern.contains(['*', '?', '[']) {
+                let mut found: Vec<String> = glob::glob(&pattern)
+                    .map_err(|e| format!("invalid pattern '{}': {}", pattern, e))?
+                    .filter_map(|entry| entry.ok())
+                    .filter(|path| path.is_file())
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .collect();
+                found.sort();
+                if found.is_empty() {
+                    return Err(none_found);
+                }
+                found
+            } else {
+                vec![pattern]
+            };
+            for path in expanded {
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+        Ok(paths)
+    }
+}
+
+/// Parses every input and joins their projects into one document.
+///
+/// Errors are printed here, naming the file that failed.
+///
+/// # Returns
+/// * `Err(Exit)` with the status to exit with.
+fn load_combined(inputs: &Inputs) -> Result<Document, Exit> {
+    let paths = inputs.paths().map_err(|e| {
+        print_error("Error", e);
+        Exit::Io
+    })?;
+    join_documents(&paths, load_document).map_err(|(path, e)| {
+        match paths.len() {
+            1 => print_parse_error("Parsing error", path, &e),
+            _ => print_parse_error(&format!("Parsing error in {}", path), path, &e),
+        }
+        Exit::of(&e)
+    })
+}
+
+/// Parses `paths` with `load` and joins their projects into one document.
+///
+/// # Returns
+/// * `Err` with the first file that fails to parse and its error.
+fn join_documents(
+    paths: &[String],
+    mut load: impl FnMut(&str) -> Result<Document, ParseError>,
+) -> Result<Document, (&str, ParseError)> {
+    let mut combined: Option<Document> = None;
+    for path in paths {
+        let doc = load(path).map_err(|e| (path.as_str(), e))?;
+        match &mut combined {
+            None => combined = Some(doc),
+            Some(all) => {
+                all.projects.extend(doc.projects);
+                all.warnings.extend(doc.warnings);
+            }
+        }
+    }
+    Ok(combined.unwrap_or_default())
+}
+
+/// Handles the `parse` command.
+///
+/// With several files, each file's output is preceded by a `==> path <==`
+/// header. A file that fails to parse is reported and the others are still
+/// printed.
+///
+/// # Arguments
+/// * `args` — CLI arguments with input files and tree flag.
+///
+/// # Returns
+/// * `Exit::Io` if an input can't be found, read or written.
+/// * `Exit::Failure` if a file fails to parse.
+/// * `Exit::Ok` otherwise.
+fn run_parse(args: ParseArgs) -> Exit {
+    let paths = match args.inputs.paths() {
+        Ok(paths) => paths,
+        Err(e) => {
+            print_error("Error", e);
+            return Exit::Io;
+        }
+    };
+    // Files get plain text; colors are for terminals.
+    let palette = match args.output {
+        Some(_) => Palette::default(),
+        None => stdout_palette().with_clock(&SystemClock),
+    };
+
+    let mut out = String::new();
+    let mut exit = Exit::Ok;
+    for (i, path) in paths.iter().enumerate() {
+        if paths.len() > 1 {
+            if i > 0 {
+                out.push('\n');
+            }
+            let _ = writeln!(out, "==> {} <==", path);
+        }
+        match parse_one(path, &args, &palette) {
+            Ok(text) => out.push_str(&text),
+            Err(e) => {
+                match paths.len() {
+                    1 => print_parse_error("Parsing error", path, &e),
+                    _ => print_parse_error(&format!("Parsing error in {}", path), path, &e),
+                }
+                exit = exit.max(Exit::of(&e));
+            }
+        }
+    }
+
+    if let Err(e) = emit(args.output.as_deref(), &out) {
+        print_error("File writing error", e);
+        return Exit::Io;
+    }
+    exit
+}
+
+/// Renders the projects, task table or syntax tree of a single file.
+fn parse_one(path: &str, args: &ParseArgs, palette: &Palette) -> Result<String, ParseError> {
+    let content = read_source(path, lossy())?;
+
+    if args.tree {
+        let content = normalize_source(&content);
+        let pairs = ToDoParser::parse(to_do_parcer::parser::Rule::file, &content)
+            .map_err(|e| ParseError::Pest(Box::new(e)))?;
+        Ok(match args.tree_format {
+            TreeFormat::Text => format!("Syntax tree:\n\n{}", render_tree(pairs, TreeFormat::Text)),
+            TreeFormat::Json => render_tree(pairs, TreeFormat::Json),
+        })
+    } else {
+        let doc = ToDoParser::parse_with(&content, &document_options(path))?;
+        warn_if_empty(path, &doc);
+        if args.table {
+            let rows: Vec<_> = doc.tasks().collect();
+            let columns = match args.columns.as_slice() {
+                [] => Column::ALL,
+                columns => columns,
+            };
+            Ok(format_columns(&rows, columns, palette))
+        } else {
+            let mut out = String::new();
+            for project in &doc.projects {
+                let _ = project.render_colored(&mut out, palette);
+                out.push('\n');
+            }
+            Ok(out)
+        }
+    }
+}
+
+/// Handles the `validate` command.
+///
+/// # Arguments
+/// * `args` — CLI arguments with the input files and `--strict` flag.
+/// * `config` — Tag naming rules and roster used when no flag sets them.
+///
+/// # Returns
+/// * `Exit::Io` if an input can't be found or read.
+/// * `Exit::Failure` if a file fails to parse.
+/// * `Exit::Validation` if there are errors, or warnings with `--strict`.
+/// * `Exit::Ok` otherwise.
+fn run_validate(args: ValidateArgs, config: &Config) -> Exit {
+    let paths = match args.inputs.paths() {
+        Ok(paths) => paths,
+        Err(e) => {
+            print_error("Error", e);
+            return Exit::Io;
+        }
+    };
+
+    let assignees = match &args.assignees {
+        Some(path) => match fs::read_to_string(path) {
+            Ok(text) => parse_roster(&text),
+            Err(e) => {
+                print_error("File reading error", format!("{}: {}", path, e));
+                return Exit::Io;
+            }
+        },
+        None => config.assignees.clone().unwrap_or_default(),
+    };
+    let options = ValidateOptions {
+        lenient: args.lenient,
+        strict: args.strict,
+        today: args
+            .overdue
+            .then(|| args.as_of.unwrap_or_else(|| SystemClock.today())),
+        tag_style: args.tag_style.or(config.tag_style),
+        allowed_tags: match args.allowed_tags.as_slice() {
+            [] => config.allowed_tags.clone().unwrap_or_default(),
+            tags => tags.to_vec(),
+        },
+        assignees,
+    };
+    let (mut errors, mut warnings, mut parse_errors) = (0, 0, 0);
+    let mut exit = Exit::Ok;
+    let mut report = DiagnosticReport::default();
+    for path in &paths {
+        let issues = match load_document(path) {
+            Ok(doc) => {
+                let mut issues = validate_with(&doc, &options);
+                issues.retain(|i| i.severity >= args.min_severity);
+                errors += count(&issues, Severity::Error);
+                warnings += count(&issues, Severity::Warning);
+                issues
+            }
+            Err(e) => {
+                parse_errors += 1;
+                exit = exit.max(Exit::of(&e));
+                vec![parse_diagnostic(path, &e)]
+            }
+        };
+        match args.format {
+            DiagnosticFormat::Text => print_issues(path, &issues),
+            DiagnosticFormat::Json => report.push(path, issues),
+        }
+    }
+    match args.format {
+        DiagnosticFormat::Text => print_summary(errors + parse_errors, warnings),
+        DiagnosticFormat::Json => println!("{}", report.to_json()),
+    }
+    exit.max(issues_status(errors, warnings, args.strict))
+}
+
+/// Handles the `lint` command.
+///
+/// # Arguments
+/// * `args` — CLI arguments with the file, rule configuration and date.
+///
+/// # Returns
+/// * `Exit::Io` or `Exit::Failure` if the file or a word list can't be
+///   read or parsed.
+/// * `Exit::Validation` if a rule reports an error, or any issue with `--strict`.
+/// * `Exit::Ok` otherwise.
+fn run_lint(args: LintArgs, mut config: LintConfig) -> Exit {
+    let doc = match load_document(&args.file) {
+        Ok(doc) => doc,
+        Err(e) => {
+            match args.format {
+                DiagnosticFormat::Text => print_parse_error("Parsing error", &args.file, &e),
+                DiagnosticFormat::Json => {
+                    let mut report = DiagnosticReport::default();
+                    report.push(&args.file, [parse_diagnostic(&args.file, &e)]);
+                    println!("{}", report.to_json());
+                }
+            }
+            return Exit::of(&e);
+        }
+    };
+
+    for (rule, severity) in args.severity {
+        config.set_severity(rule, severity);
+    }
+    for rule in args.disable {
+        if !args.enable.contains(&rule) {
+            config.disable(rule);
+        }
+    }
+    for &rule in &args.enable {
+        if config.severity(rule).is_none() {
+            config.enable(rule);
+        }
+    }
+    #[cfg(feature = "spellcheck")]
+    for path in &args.words {
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                config.add_words(to_do_parcer::spell::parse_word_list(&text));
+            }
+            Err(e) => {
+                print_error("File reading error", format!("{}: {}", path, e));
+                return Exit::Io;
+            }
+        }
+    }
+
+    let today = args.as_of.unwrap_or_else(|| SystemClock.today());
+    let mut issues = lint(&doc, &config, &today);
+    issues.retain(|i| i.severity >= args.min_severity);
+    let errors = count(&issues, Severity::Error);
+    let warnings = count(&issues, Severity::Warning);
+    match args.format {
+        DiagnosticFormat::Text => {
+            print_issues(&args.file, &issues);
+            print_summary(errors, warnings);
+        }
+        DiagnosticFormat::Json => {
+            let mut report = DiagnosticReport::default();
+            report.push(&args.file, issues);
+            println!("{}", report.to_json());
+        }
+    }
+    issues_status(errors, warnings, args.strict)
+}
+
+/// Handles the `overdue` command.
+///
+/// Prints one `project/title` line per overdue task, the latest first.
+///
+/// # Arguments
+/// * `args` — CLI arguments with the file path and reference date.
+///
+/// # Returns
+/// * `Exit::Overdue` if any task is overdue.
+/// * `Exit::Failure` if the file fails to parse.
+/// * `Exit::Io` if the file can't be found or read.
+/// * `Exit::Ok` otherwise.
+fn run_overdue(args: OverdueArgs) -> Exit {
+    let doc = match load_document(&args.file) {
+        Ok(doc) => doc,
+        Err(e) => {
+            print_parse_error("Parsing error", &args.file, &e);
+            return Exit::of(&e);
+        }
+    };
+
+    let today = args.as_of.unwrap_or_else(|| SystemClock.today());
+    let mut late: Vec<_> = doc
+        .tasks()
+        .filter_map(|(p, t)| t.days_late(&today).map(|days| (p, t, days)))
+        .collect();
+    late.sort_by_key(|&(_, _, days)| std::cmp::Reverse(days));
+
+    let palette = stdout_palette().with_clock(&today);
+    for (project, task, days) in &late {
+        let due = task.due_date.as_deref().unwrap_or_default();
+        println!(
+            "{}/{}: due {}, {} day{} late",
+            project.name,
+            task.title,
+            palette.due(task, due),
+            days,
+            if *days == 1 { "" } else { "s" }
+        );
+    }
+
+    if late.is_empty() {
+        Exit::Ok
+    } else {
+        Exit::Overdue
+    }
+}
+
+/// Handles the `critical-path` command.
+///
+/// Prints the critical path, then one line per open task with its
+/// projected dates; critical tasks are marked with `*`.
+///
+/// # Arguments
+/// * `args` — CLI arguments with the file path and start date.
+///
+/// # Returns
+/// * `Exit::Failure` if the file fails to parse or has a dependency cycle.
+/// * `Exit::Io` if the file can't be found or read.
+/// * `Exit::Ok` otherwise.
+fn run_critical_path(args: CriticalPathArgs) -> Exit {
+    let doc = match load_document(&args.file) {
+        Ok(doc) => doc,
+        Err(e) => {
+            print_parse_error("Parsing error", &args.file, &e);
+            return Exit::of(&e);
+        }
+    };
+    let plan = match schedule(&doc) {
+        Ok(plan) => plan,
+        Err(e) => {
+            print_error("Error", e);
+            return Exit::Failure;
+        }
+    };
+    if plan.tasks.is_empty() {
+        println!("No open tasks.");
+        return Exit::Ok;
+    }
+
+    let name = |r: TaskRef| {
+        let project = r.project(&doc).expect("task ref is valid");
+        let task = r.get(&doc).expect("task ref is valid");
+        format!("{}/{}", project.name, task.title)
+    };
+    let days = |n: u32| format!("{} day{}", n, if n == 1 { "" } else { "s" });
+    let path: Vec<String> = plan.path.iter().map(|r| name(*r)).collect();
+    println!(
+        "Critical path ({}): {}",
+        days(plan.length),
+        path.join(" -> ")
+    );
+    println!();
+
+    let start = args.as_of.unwrap_or_else(|| SystemClock.today());
+    for placed in &plan.tasks {
+        let task = placed.task.get(&doc).expect("task ref is valid");
+        let finish = plan_date(start, placed.earliest_finish() - 1);
+        let mut line = format!(
+            "{} {}: {}, {} to {}",
+            if placed.is_critical() { "*" } else { " " },
+            name(placed.task),
+            days(placed.duration),
+            plan_date(start, placed.earliest_start),
+            finish
+        );
+        if !placed.is_critical() {
+            line.push_str(&format!(", slack {}", days(placed.slack())));
+        }
+        if let Some(due) = task.due()
+            && finish > due
+        {
+            let late = u32::try_from((finish - due).num_days()).unwrap_or(u32::MAX);
+            line.push_str(&format!(", misses due {} by {}", due, days(late)));
+        }
+        println!("{}", line);
+    }
+    Exit::Ok
+}
+
+/// Handles the `burndown` command.
+///
+/// # Arguments
+/// * `args` — CLI arguments with the file path, date range and format.
+///
+/// # Returns
+/// * `Exit::Failure` if the range is empty or the file fails to parse.
+/// * `Exit::Io` if the file can't be read or the output can't be written.
+/// * `Exit::Ok` otherwise.
+fn run_burndown(args: BurndownArgs) -> Exit {
+    let to = args.to.unwrap_or_else(|| SystemClock.today());
+    if args.from > to {
+        print_error(
+            "Error",
+            format!("--from {} is after --to {}", args.from, to),
+        );
+        return Exit::Failure;
+    }
+    let doc = match load_document(&args.file) {
+        Ok(doc) => doc,
+        Err(e) => {
+            print_parse_error("Parsing error", &args.file, &e);
+            return Exit::of(&e);
+        }
+    };
+
+    let series = render_burndown(&burndown(&doc, args.from, to), args.format);
+    match emit(args.output.as_deref(), &series) {
+        Ok(()) => Exit::Ok,
+        Err(e) => {
+            print_error("File writing error", e);
+            Exit::Io
+        }
+    }
+}
+
+/// Handles the `notify` command.
+///
+/// # Arguments
+/// * `args` — CLI arguments with the file path, window and reference date.
+/// * `notifier` — Where the notifications go unless `--dry-run` is given.
+///
+/// # Returns
+/// * `Exit::Io` if the file can't be read or a notification can't be shown.
+/// * `Exit::Failure` if the file fails to parse.
+/// * `Exit::Ok` otherwise, also when nothing is due.
+fn run_notify(args: NotifyArgs, notifier: &impl Notifier) -> Exit {
+    let doc = match load_document(&args.file) {
+        Ok(doc) => doc,
+        Err(e) => {
+            print_parse_error("Parsing error", &args.file, &e);
+            return Exit::of(&e);
+        }
+    };
+
+    let today = args.as_of.unwrap_or_else(|| SystemClock.today());
+    for notification in due_notifications(&doc, &today, args.within) {
+        if args.dry_run {
+            println!("{}\n  {}", notification.summary, notification.body);
+        } else if let Err(e) = notifier.send(&notification) {
+            print_error("Notification error", e);
+            return Exit::Io;
+        }
+    }
+    Exit::Ok
+}
+
+/// Handles the `agenda` command: one heading per group, then one
+/// `due  project/title` line per task.
+fn run_agenda(args: &AgendaArgs, doc: &Document) {
+    let today = args.as_of.unwrap_or_else(|| SystemClock.today());
+    let palette = stdout_palette().with_clock(&today);
+    let agenda = doc.agenda(&today, args.weeks.saturating_mul(7));
+    for (i, (group, tasks)) in agenda.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let label = group.label();
+        match group {
+            AgendaGroup::Overdue => println!("{}", palette.paint(Style::Overdue, label)),
+            _ => println!("{}", label),
+        }
+        for (project, task) in tasks {
+            let due = task.due_date.as_deref().unwrap_or_default();
+            println!(
+                "  {}  {}/{}",
+                palette.due(task, due),
+                project.name,
+                task.title
+            );
+        }
+    }
+}
+
+/// Handles the `by-assignee` command: a summary line per assignee, then
+/// their open tasks.
+fn print_workload(doc: &Document) {
+    let palette = stdout_palette().with_clock(&SystemClock);
+    for (i, (who, load)) in doc.workload().iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let next_due = load
+            .next_due
+            .map_or_else(|| "none".to_string(), |d| d.to_string());
+        println!(
+            "{}: {} open, {} high priority, next due {}",
+            who,
+            load.open.len(),
+            load.high,
+            next_due
+        );
+        for (project, task) in &load.open {
+            let mut details = Vec::new();
+            if let Some(priority) = task.priority {
+                details.push(palette.paint(Style::priority(priority), priority.as_str()));
+            }
+            if let Some(due) = &task.due_date {
+                details.push(format!("due {}", palette.due(task, due)));
+            }
+            if details.is_empty() {
+                println!("  {}/{}", project.name, task.title);
+            } else {
+                println!("  {}/{} ({})", project.name, task.title, details.join(", "));
+            }
+        }
+    }
+}
+
+/// Handles the `tags` command.
+///
+/// # Returns
+/// * `Exit::Failure` if `--show` names a tag no task carries.
+/// * `Exit::Ok` otherwise.
+fn run_tags(args: &TagsArgs, doc: &Document) -> Exit {
+    let index = doc.tag_index();
+    match &args.show {
+        Some(tag) => {
+            let tasks = index.tasks(tag);
+            if tasks.is_empty() {
+                print_error("Error", format!("no task is tagged '{}'", tag));
+                return Exit::Failure;
+            }
+            for (project, task) in tasks {
+                println!("{}/{}", project.name, task.title);
+            }
+        }
+        None => {
+            let counts = index.counts();
+            let width = counts.iter().map(|(tag, _)| tag.chars().count()).max();
+            for (tag, count) in &counts {
+                println!("{:<width$}  {}", tag, count, width = width.unwrap_or(0));
+            }
+        }
+    }
+    Exit::Ok
+}
+
+/// Handles the `search` command.
+///
+/// Prints one `file:line: project/title` line per match, naming the tag or
+/// assignee that matched when it wasn't the title.
+///
+/// # Returns
+/// * `Exit::NoMatch` if nothing matches, like `grep` does with status 1.
+/// * `Exit::Ok` otherwise.
+fn run_search(args: &SearchArgs, doc: &Document) -> Exit {
+    let hits = search(doc, &args.pattern);
+    for hit in &hits {
+        let file = hit
+            .project
+            .file
+            .as_ref()
+            .map_or_else(|| args.file.clone(), |f| f.display().to_string());
+        let mut line = format!(
+            "{}:{}: {}/{}",
+            file, hit.task.span.line, hit.project.name, hit.task.title
+        );
+        if hit.field != SearchField::Title {
+            let _ = write!(line, " ({}: {})", hit.field.as_str(), hit.text);
+        }
+        println!("{}", line);
+    }
+
+    if hits.is_empty() {
+        Exit::NoMatch
+    } else {
+        Exit::Ok
+    }
+}
+
+/// Prints one line per diagnostic, prefixed with `file` and its location.
+fn print_issues(file: &str, issues: &[Diagnostic]) {
+    for issue in issues {
+        let location = match issue.location() {
+            location if location.is_empty() => file.to_string(),
+            location => format!("{}:{}", file, location),
+        };
+        println!(
+            "{}: {}[{}]: {}",
+            location,
+            stdout_palette().paint(Style::severity(issue.severity), issue.severity.as_str()),
+            issue.code,
+            issue.message
+        );
+        if let Some(fix) = &issue.fix {
+            println!("  help: {} at {}:{}", fix, fix.span.line, fix.span.column);
+        }
+    }
+}
+
+/// Counts the diagnostics of the given severity.
+fn count(issues: &[Diagnostic], severity: Severity) -> usize {
+    issues.iter().filter(|i| i.severity == severity).count()
+}
+
+/// Prints the summary line of a check.
+fn print_summary(errors: usize, warnings: usize) {
+    println!("summary: errors={} warnings={}", errors, warnings);
+}
+
+/// Decides the status of a check from the issues it found.
+///
+/// # Returns
+/// * `Exit::Validation` if there are errors, or warnings when `strict`.
+/// * `Exit::Ok` otherwise.
+fn issues_status(errors: usize, warnings: usize, strict: bool) -> Exit {
+    if errors > 0 || (strict && warnings > 0) {
+        Exit::Validation
+    } else {
+        Exit::Ok
+    }
+}
+
+/// Handles the `fmt` command.
+///
+/// Includes are kept as directives rather than inlined. A byte order mark
+/// and CRLF line endings don't count as changes, and `--write` keeps them.
+///
+/// # Arguments
+/// * `args` — CLI arguments with file path and mode flags.
+///
+/// # Returns
+/// * `Ok(Exit::Failure)` in check mode when the file would change.
+/// * `Ok(Exit::Ok)` otherwise.
+/// * `Err(ParseError)` if reading, parsing or writing fails.
+fn run_fmt(args: FmtArgs) -> Result<Exit, ParseError> {
+    let content = read_source(&args.file, lossy() && !args.write)?;
+    let doc = ToDoParser::parse_with(&content, &cli_options(IncludePolicy::Ignore))?;
+    warn_if_empty(&args.file, &doc);
+    let formatted = format_document(&doc);
+    let unchanged = formatted == normalize_source(&content);
+
+    if args.check {
+        if !unchanged {
+            eprintln!("{}: not formatted", args.file);
+            return Ok(Exit::Failure);
+        }
+    } else if args.write {
+        if !unchanged {
+            write_atomic(&args.file, &with_source_style(&content, formatted))?;
+        }
+    } else {
+        print!("{}", formatted);
+    }
+
+    Ok(Exit::Ok)
+}
+
+/// `text` with the byte order mark and CRLF line endings of `original`,
+/// which `normalize_source` removed, like `Editor::finish` keeps them.
+fn with_source_style(original: &str, mut text: String) -> String {
+    if original.contains("\r\n") {
+        text = text.replace('\n', "\r\n");
+    }
+    if original.starts_with('\u{feff}') {
+        text.insert(0, '\u{feff}');
+    }
+    text
+}
+
+/// Handles the `fix` command.
+///
+/// Each fix is reported on stderr as it is applied, with its position in
+/// the text as the fixes before it left it.
+///
+/// # Arguments
+/// * `args` — CLI arguments with file path and mode flag.
+///
+/// # Returns
+/// * `Ok(Exit::Failure)` if the file still doesn't parse; nothing is written.
+/// * `Ok(Exit::Ok)` otherwise.
+/// * `Err(ParseError)` if reading or writing fails.
+fn run_fix(args: FixArgs) -> Result<Exit, ParseError> {
+    let source = read_source(&args.file, false)?;
+    let fixed = fix_all(&source, &cli_options(IncludePolicy::Ignore));
+    for fix in &fixed.applied {
+        eprintln!(
+            "{}:{}:{}: {}",
+            args.file, fix.span.line, fix.span.column, fix
+        );
+    }
+    if let Some(e) = &fixed.remaining {
+        let rendered = e.render(&fixed.text, &args.file);
+        print_error("Parsing error", rendered.trim_end());
+        return Ok(Exit::of(e));
+    }
+
+    if !args.write {
+        print!("{}", fixed.text);
+    } else if fixed.text != source {
+        write_atomic(&args.file, &fixed.text)?;
+    }
+    Ok(Exit::Ok)
+}
+
+/// Handles the `stats` command.
+///
+/// # Arguments
+/// * `args` — CLI arguments with file path, output flag and reference date.
+///
+/// # Returns
+/// * `Exit::Io` if an input can't be found, read or written.
+/// * `Exit::Failure` if a file fails to parse.
+/// * `Exit::Ok` otherwise.
+fn run_stats(args: StatsArgs) -> Exit {
+    let doc = match load_combined(&args.inputs) {
+        Ok(doc) => doc,
+        Err(exit) => return exit,
+    };
+    let today = args.as_of.unwrap_or_else(|| SystemClock.today());
+    let stats = Stats::compute(&doc, &today);
+
+    let mut out = String::new();
+    if args.json {
+        out = serde_json::to_string_pretty(&stats).expect("stats serialize to JSON");
+        out.push('\n');
+    } else {
+        let _ = writeln!(out, "Projects:   {}", stats.projects);
+        let _ = writeln!(out, "Tasks:      {}", stats.total);
+        let _ = writeln!(out, "Active:     {}", stats.active);
+        let _ = writeln!(out, "Completed:  {}", stats.completed);
+        let _ = writeln!(out, "Completion: {:.1}%", stats.completion_rate * 100.0);
+        let _ = writeln!(out, "Overdue:    {}", stats.overdue);
+        write_counts(&mut out, "By priority", &stats.by_priority, "");
+        write_counts(&mut out, "By assignee", &stats.by_assignee, "@");
+        write_counts(&mut out, "By tag", &stats.by_tag, "");
+    }
+
+    match emit(args.output.as_deref(), &out) {
+        Ok(()) => Exit::Ok,
+        Err(e) => {
+            print_error("File writing error", e);
+            Exit::Io
+        }
+    }
 }
 
-/// Supported CLI subcommands.
-#[derive(Subcommand)]
-enum Commands {
-    Credits,
-    Parse(ParseArgs),
+/// Appends a titled `name: count` list, skipped when `counts` is empty.
+fn write_counts(out: &mut String, title: &str, counts: &BTreeMap<String, usize>, prefix: &str) {
+    if counts.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "\n{}:", title);
+    for (name, count) in counts {
+        let _ = writeln!(out, "  {}{}: {}", prefix, name, count);
+    }
 }
 
-/// Arguments for the `parse` subcommand.
-#[derive(Parser)]
-struct ParseArgs {
-    #[arg(short, long)]
-    file: String,
+/// Prints `text`, or writes it to `output` with `write_atomic` if given.
+fn emit(output: Option<&str>, text: &str) -> io::Result<()> {
+    match output {
+        Some(path) => write_atomic(path, text),
+        None => {
+            print!("{}", text);
+            Ok(())
+        }
+    }
+}
 
-    #[arg(long)]
-    tree: bool,
+/// Replaces the file at `path` with `contents`.
+///
+/// The text goes to a temporary file next to `path` that is renamed over
+/// it, so readers never see a half-written file and a failed write leaves
+/// the old file in place.
+fn write_atomic(path: &str, contents: &str) -> io::Result<()> {
+    let target = Path::new(path);
+    let name = target
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path names no file"))?;
+    let temp = target.with_file_name(format!(
+        ".{}.{}.tmp",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+
+    let result = fs::File::create(&temp)
+        .and_then(|mut file| {
+            io::Write::write_all(&mut file, contents.as_bytes())?;
+            // Keep the permissions of the file being replaced.
+            if let Ok(meta) = fs::metadata(target) {
+                file.set_permissions(meta.permissions())?;
+            }
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp, target));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Handles the `import` command.
+///
+/// The converted document goes to stdout; anything the importer had to
+/// adjust is reported on stderr.
+///
+/// `--map` only applies to CSV; with other formats it is ignored with a
+/// warning.
+///
+/// # Arguments
+/// * `args` — CLI arguments with file path, input format and CSV columns.
+///
+/// # Returns
+/// * `Err(io::Error)` if the file can't be read.
+fn run_import(args: ImportArgs) -> io::Result<()> {
+    let content = fs::read_to_string(&args.file)?;
+    let doc = match args.format {
+        ImportFormat::Csv => {
+            let mut mapping = CsvMapping::default();
+            for (field, column) in args.map {
+                mapping.bind(field, column);
+            }
+            from_csv(&content, &mapping)
+        }
+        format => {
+            if !args.map.is_empty() {
+                log::warn!("--map only applies to --format csv; ignored");
+            }
+            import(&content, format)
+        }
+    };
+    for warning in &doc.warnings {
+        log::warn!("{}: {}", args.file, warning);
+    }
+    print!("{}", format_document(&doc));
+    Ok(())
 }
 
-fn main() {
-    let cli = Cli::parse();
+/// Handles the `filter` command.
+///
+/// Tasks must satisfy both the attribute flags and the query, if given.
+///
+/// # Arguments
+/// * `args` — CLI arguments with the conditions and output flag.
+/// * `doc` — Parsed input file.
+fn run_filter(args: FilterArgs, doc: &Document) {
+    let filter = Filter {
+        status: args.status,
+        priority: args.priority,
+        tags: args.tag,
+        assignee: args.assignee.map(|a| a.trim_start_matches('@').to_string()),
+    };
 
-    match cli.command {
-        Commands::Credits => {
-            println!("Author: Tetiana Khaimyk");
-            println!("Project: ToDo Parser");
-            println!("Language: Rust");
+    let query = args.query;
+    let matches = |t: &Task| filter.matches(t) && query.as_ref().is_none_or(|q| q.matches(t));
+
+    if args.table {
+        print!(
+            "{}",
+            format_table_with(
+                &doc.matching(matches),
+                &stdout_palette().with_clock(&SystemClock),
+            )
+        );
+    } else {
+        print!("{}", format_document(&doc.select(matches)));
+    }
+}
+
+/// Parses a `--status` value.
+fn parse_status(value: &str) -> Result<TaskStatus, String> {
+    TaskStatus::from_name(value).ok_or_else(|| "expected todo or done".to_string())
+}
+
+/// Parses a `NAME=VALUE` pair.
+#[cfg(feature = "github")]
+fn parse_mapping(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, mapped)) if !name.is_empty() && !mapped.is_empty() => {
+            Ok((name.trim_start_matches('@').to_string(), mapped.to_string()))
         }
+        _ => Err("expected NAME=VALUE".to_string()),
+    }
+}
+
+/// Parses a `--map` value of `import`.
+fn parse_column(value: &str) -> Result<(CsvField, String), String> {
+    match value.split_once('=') {
+        Some((field, column)) if !column.is_empty() => Ok((field.parse()?, column.to_string())),
+        _ => Err("expected FIELD=COLUMN".to_string()),
+    }
+}
+
+/// Parses a `--priority` value.
+fn parse_priority(value: &str) -> Result<Priority, String> {
+    Priority::from_name(value).ok_or_else(|| "expected high, medium or low".to_string())
+}
+
+/// Handles the `add` command.
+///
+/// A missing file is created.
+///
+/// # Arguments
+/// * `args` — CLI arguments with the file, target project and task fields.
+///
+/// # Returns
+/// * `Err(EditError)` if the file can't be read, parsed or written, or the
+///   task fields can't be written as `.todo` text.
+fn run_add(args: AddArgs) -> Result<(), EditError> {
+    let source = match read_source(&args.file, false) {
+        Ok(source) => source,
+        Err(ParseError::Io(e)) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut task = Task::new(TaskStatus::Todo, args.title);
+    task.id = args.id;
+    task.priority = args.priority;
+    task.due_date = args.due.map(|d| d.format("%Y-%m-%d").to_string());
+    task.assignee = args.assign.map(|a| a.trim_start_matches('@').to_string());
+    task.depends_on = args.depends_on;
+    task.tags = args.tag.into();
 
-        Commands::Parse(args) => {
-            if let Err(e) = run_parse(args) {
-                eprintln!("Parsing error: {}", e);
+    let file = edit_target(&args.file, |doc| {
+        doc.projects.iter().position(|p| p.name == args.project)
+    });
+    let source = if file == args.file {
+        source
+    } else {
+        read_source(&file, false)?
+    };
+    let mut editor = Editor::new(&source)?;
+    editor.add_task(&args.project, task)?;
+    write_atomic(&file, &editor.finish()).map_err(ParseError::from)?;
+    Ok(())
+}
+
+/// The document of `path` with its includes resolved, to find the file an
+/// edit belongs in; `None` if it can't be read or parsed that way.
+fn resolved_document(path: &str) -> Option<Document> {
+    let source = read_source(path, false).ok()?;
+    ToDoParser::parse_with(&source, &document_options(path)).ok()
+}
+
+/// The file the project with index `project` of `doc`, the document of
+/// `path`, is written in.
+fn file_of(path: &str, doc: &Document, project: usize) -> String {
+    doc.projects[project]
+        .file
+        .as_ref()
+        .map_or_else(|| path.to_string(), |f| f.to_string_lossy().into_owned())
+}
+
+/// The file an edit of `path` should change: the included file of the
+/// project `locate` finds, or `path` itself.
+///
+/// `Editor` only sees the file it edits, so editing `path` would miss a
+/// project that lives in one of its includes, or add a second one.
+fn edit_target(path: &str, locate: impl FnOnce(&Document) -> Option<usize>) -> String {
+    let Some(doc) = resolved_document(path) else {
+        return path.to_string();
+    };
+    let file = locate(&doc).map_or_else(|| path.to_string(), |p| file_of(path, &doc, p));
+    if file != path {
+        log::info!("editing {}, included by {}", file, path);
+    }
+    file
+}
+
+/// The file an edit of `path` selecting a task should change; see
+/// `edit_target`.
+fn task_file(path: &str, selector: &str) -> String {
+    edit_target(path, |doc| find_task(doc, selector).ok().map(|r| r.project))
+}
+
+/// Handles the `done` command.
+///
+/// A task that is already done is left alone, with a note on stderr.
+///
+/// # Arguments
+/// * `args` — CLI arguments with the file, task selector and date option.
+///
+/// # Returns
+/// * `Err(EditError)` if the file can't be read, parsed or written, or the
+///   selector doesn't match exactly one task.
+fn run_done(args: DoneArgs) -> Result<(), EditError> {
+    let file = task_file(&args.file, &args.task);
+    let source = read_source(&file, false)?;
+    let completed = args
+        .completed
+        .map(|date| date.unwrap_or_else(|| SystemClock.today()));
+
+    let mut editor = Editor::new(&source)?;
+    if !editor.complete(&args.task, completed)? {
+        log::warn!("\"{}\" is already done", args.task);
+        return Ok(());
+    }
+    write_atomic(&file, &editor.finish()).map_err(ParseError::from)?;
+    Ok(())
+}
+
+/// Handles the `rm` command.
+///
+/// # Arguments
+/// * `args` — CLI arguments with the file, task selector and force flag.
+///
+/// # Returns
+/// * `Err(EditError)` if the file can't be read, parsed or written, the
+///   selector doesn't match exactly one task, or other tasks depend on it.
+fn run_rm(args: RmArgs) -> Result<(), EditError> {
+    let file = task_file(&args.file, &args.task);
+    let source = read_source(&file, false)?;
+    let mut editor = Editor::new(&source)?;
+    editor.remove_task(&args.task, args.force)?;
+    write_atomic(&file, &editor.finish()).map_err(ParseError::from)?;
+    Ok(())
+}
+
+/// Handles the `mv` command.
+///
+/// A task that is already in the destination project is left alone, with a
+/// note on stderr.
+///
+/// # Arguments
+/// * `args` — CLI arguments with the file, task selector and destination.
+///
+/// # Returns
+/// * `Err(EditError)` if the file can't be read, parsed or written, or the
+///   selector doesn't match exactly one task.
+fn run_mv(args: MvArgs) -> Result<(), EditError> {
+    let mut file = args.file.clone();
+    if let Some(doc) = resolved_document(&args.file) {
+        let task = find_task(&doc, &args.task).ok();
+        let task = task.map(|r| file_of(&args.file, &doc, r.project));
+        let project = doc.projects.iter().position(|p| p.name == args.to_project);
+        let project = project.map(|p| file_of(&args.file, &doc, p));
+        match (task, project) {
+            (Some(task), Some(project)) if task != project => {
+                return Err(EditError::DifferentFiles {
+                    task: args.task,
+                    project: args.to_project,
+                });
             }
+            (Some(task), _) => file = task,
+            (None, _) => {}
         }
     }
+    let source = read_source(&file, false)?;
+    let mut editor = Editor::new(&source)?;
+    if !editor.move_task(&args.task, &args.to_project)? {
+        log::warn!("\"{}\" is already in \"{}\"", args.task, args.to_project);
+        return Ok(());
+    }
+    write_atomic(&file, &editor.finish()).map_err(ParseError::from)?;
+    Ok(())
 }
 
-/// Handles the `parse` command.
+/// Handles the `split` command.
+///
+/// Prints the path of each file written. Nothing is written if any project
+/// file already exists and `--force` isn't given.
 ///
 /// # Arguments
-/// * `args` — CLI arguments with file path and tree flag.
+/// * `args` — CLI arguments with the file, output directory and force flag.
 ///
 /// # Returns
-/// * `Ok(())` if parsed successfully.
-/// * `Err(ParseError)` if parsing fails.
-fn run_parse(args: ParseArgs) -> Result<(), ParseError> {
-    let content = fs::read_to_string(&args.file)?;
+/// * `Err(EditError)` if the file can't be read or parsed, or a file
+///   can't be written.
+fn run_split(args: SplitArgs) -> Result<(), EditError> {
+    let source = read_source(&args.file, false)?;
+    let mut editor = Editor::new(&source)?;
+    let out_dir = Path::new(&args.out_dir);
+    fs::create_dir_all(out_dir).map_err(ParseError::from)?;
+    let files = editor.split(&include_dir(Path::new(&args.file), out_dir));
 
-    if args.tree {
-        let pairs = ToDoParser::parse(to_do_parcer::parser::Rule::file, &content)
-            .map_err(|e| ParseError::Pest(Box::new(e)))?;
-        println!("Syntax tree:\n");
-        to_do_parcer::parser::display_tree(pairs);
-    } else {
-        let projects = ToDoParser::parse_projects(&content)?;
-        for project in projects {
-            project.display();
-            println!();
+    let targets: Vec<PathBuf> = files.iter().map(|f| out_dir.join(&f.file_name)).collect();
+    if !args.force
+        && let Some(existing) = targets.iter().find(|t| t.exists())
+    {
+        let message = format!(
+            "{} already exists; use --force to overwrite",
+            existing.display()
+        );
+        return Err(ParseError::from(io::Error::new(io::ErrorKind::AlreadyExists, message)).into());
+    }
+    for (file, target) in files.iter().zip(&targets) {
+        write_atomic(&target.to_string_lossy(), &file.text).map_err(ParseError::from)?;
+        println!("{}", target.display());
+    }
+    write_atomic(&args.file, &editor.finish()).map_err(ParseError::from)?;
+    Ok(())
+}
+
+/// How an `include` in `file` names the directory `dir`: relative to the
+/// file's directory when `dir` is inside it, absolute otherwise.
+fn include_dir(file: &Path, dir: &Path) -> String {
+    let base = file
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let (Ok(base), Ok(dir)) = (base.canonicalize(), dir.canonicalize()) else {
+        return dir.to_string_lossy().into_owned();
+    };
+    match dir.strip_prefix(&base) {
+        Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+        Err(_) => dir.to_string_lossy().into_owned(),
+    }
+}
+
+/// Handles the `dedupe` command.
+///
+/// Prints each group of duplicates as `Project: "Kept" (line N)` followed
+/// by one indented line per copy; with `--write` the file is rewritten.
+///
+/// # Arguments
+/// * `args` — CLI arguments with the file and the write flag.
+///
+/// # Returns
+/// * `Ok(true)` if duplicates were found.
+/// * `Err(EditError)` if the file can't be read, parsed or written.
+fn run_dedupe(args: &DedupeArgs) -> Result<bool, EditError> {
+    let source = read_source(&args.file, false)?;
+    let mut editor = Editor::new(&source)?;
+    let doc = editor.document().clone();
+    let groups = editor.dedupe();
+
+    let describe = |r: TaskRef| {
+        let task = r.get(&doc).expect("task ref is valid");
+        format!("\"{}\" (line {})", task.title, task.span.line)
+    };
+    for group in &groups {
+        let project = group.keep.project(&doc).expect("task ref is valid");
+        println!("{}: {}", project.name, describe(group.keep));
+        for copy in &group.duplicates {
+            println!("  {}", describe(*copy));
+        }
+    }
+
+    if args.write && !groups.is_empty() {
+        write_atomic(&args.file, &editor.finish()).map_err(ParseError::from)?;
+        let merged: usize = groups.iter().map(|g| g.duplicates.len()).sum();
+        log::info!("Merged {} duplicate task(s)", merged);
+    }
+    Ok(!groups.is_empty())
+}
+
+/// Handles the `assign` command.
+///
+/// # Arguments
+/// * `args` — CLI arguments with the file, both assignees and the scope.
+///
+/// # Returns
+/// * `Err(EditError)` if the file can't be read, parsed or written, the
+///   project doesn't exist, or the new assignee isn't an identifier.
+fn run_assign(args: AssignArgs) -> Result<(), EditError> {
+    let source = read_source(&args.file, false)?;
+    let mut editor = Editor::new(&source)?;
+    let reassigned = editor.reassign(
+        &args.from,
+        &args.to,
+        args.project.as_deref(),
+        args.include_done,
+    )?;
+    write_atomic(&args.file, &editor.finish()).map_err(ParseError::from)?;
+    log::info!("Reassigned {} task(s)", reassigned);
+    Ok(())
+}
+
+/// Handles the `rename-project` command.
+///
+/// # Arguments
+/// * `args` — CLI arguments with the file and the old and new names.
+///
+/// # Returns
+/// * `Err(EditError)` if the file can't be read, parsed or written, the
+///   project doesn't exist, or the new name is taken.
+fn run_rename_project(args: RenameProjectArgs) -> Result<(), EditError> {
+    let file = edit_target(&args.file, |doc| {
+        doc.projects.iter().position(|p| p.name == args.old)
+    });
+    let source = read_source(&file, false)?;
+    let mut editor = Editor::new(&source)?;
+    let rewritten = editor.rename_project(&args.old, &args.new)?;
+    write_atomic(&file, &editor.finish()).map_err(ParseError::from)?;
+    log::info!("Updated {} dependency reference(s)", rewritten);
+    Ok(())
+}
+
+/// Handles the `migrate` command.
+///
+/// # Arguments
+/// * `args` — CLI arguments with the file and the target version.
+///
+/// # Returns
+/// * `Err(EditError)` if the file can't be read, parsed or written, or
+///   the version isn't supported.
+fn run_migrate(args: MigrateArgs) -> Result<(), EditError> {
+    let source = read_source(&args.file, false)?;
+    let mut editor = Editor::new(&source)?;
+    let from = editor.document().version;
+    let rewritten = editor.migrate(args.to)?;
+    write_atomic(&args.file, &editor.finish()).map_err(ParseError::from)?;
+    log::info!(
+        "Migrated from version {} to {}; rewrote {} tag(s)",
+        from,
+        args.to,
+        rewritten
+    );
+    Ok(())
+}
+
+/// Handles the `serve` command.
+///
+/// Requests are answered one at a time. The files are read again for every
+/// request but only parsed when they changed; one that fails to parse
+/// gives a 500 response naming it.
+///
+/// # Returns
+/// * `Exit::Io` if the inputs match no file or the address can't be bound.
+/// * Otherwise it serves until the process is stopped.
+fn run_serve(args: ServeArgs) -> Exit {
+    let paths = match args.inputs.paths() {
+        Ok(paths) => paths,
+        Err(e) => {
+            print_error("Error", e);
+            return Exit::Io;
+        }
+    };
+    let server = match tiny_http::Server::http((args.host.as_str(), args.port)) {
+        Ok(server) => server,
+        Err(e) => {
+            print_error("Server error", e);
+            return Exit::Io;
+        }
+    };
+    match server.server_addr().to_ip() {
+        Some(addr) => println!("Serving {} file(s) on http://{}", paths.len(), addr),
+        None => println!("Serving {} file(s)", paths.len()),
+    }
+
+    let json = tiny_http::Header::from_bytes("Content-Type", "application/json")
+        .expect("content type header is valid");
+    let mut cache = DocumentCache::new();
+    for request in server.incoming_requests() {
+        let response = match join_documents(&paths, |path| load_cached(&mut cache, path)) {
+            Ok(doc) => respond(&doc, request.method().as_str(), request.url(), &SystemClock),
+            Err((path, e)) => {
+                ServeResponse::error(500, format!("Parsing error in {}: {}", path, e))
+            }
+        };
+        log::info!(
+            "{} {} -> {}",
+            request.method(),
+            request.url(),
+            response.status
+        );
+        let reply = tiny_http::Response::from_string(response.body)
+            .with_status_code(response.status)
+            .with_header(json.clone());
+        if let Err(e) = request.respond(reply) {
+            log::warn!("could not send response: {}", e);
+        }
+    }
+    Exit::Ok
+}
+
+/// Handles the `watch` command.
+///
+/// Every file is checked once at the start; after that, each time files
+/// change, the diagnostics of the files that were validated again are
+/// printed (`path: ok` when there are none), followed by the summary of
+/// the whole directory. A `depends_on` that names a task of another file
+/// in the directory is not reported.
+///
+/// # Returns
+/// * `Exit::Io` if the directory can't be searched.
+/// * Otherwise it watches until the process is stopped.
+fn run_watch(args: WatchArgs, config: &Config) -> Exit {
+    if !Path::new(&args.dir).is_dir() {
+        print_error("Error", format!("'{}' is not a directory", args.dir));
+        return Exit::Io;
+    }
+    let options = ValidateOptions {
+        lenient: args.lenient,
+        strict: args.strict,
+        tag_style: config.tag_style,
+        allowed_tags: config.allowed_tags.clone().unwrap_or_default(),
+        assignees: config.assignees.clone().unwrap_or_default(),
+        ..ValidateOptions::default()
+    };
+    let mut workspace = Workspace::new(&args.dir, cli_options(IncludePolicy::Resolve), options);
+    let interval = std::time::Duration::from_millis(args.interval);
+    loop {
+        let changes = match workspace.refresh() {
+            Ok(changes) => changes,
+            Err(e) => {
+                print_error("Error", e);
+                return Exit::Io;
+            }
+        };
+        if !changes.is_empty() {
+            print_changes(&workspace, &changes);
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+/// Prints what a refresh of the watched directory found.
+fn print_changes(workspace: &Workspace, changes: &Changes) {
+    for path in &changes.removed {
+        println!("{}: removed", path.display());
+    }
+    for path in &changes.revalidated {
+        let file = path.display().to_string();
+        match workspace.diagnostics(path) {
+            [] => println!("{}: ok", file),
+            issues => print_issues(&file, issues),
+        }
+    }
+    let (mut errors, mut warnings) = (0, 0);
+    for path in workspace.files() {
+        errors += count(workspace.diagnostics(path), Severity::Error);
+        warnings += count(workspace.diagnostics(path), Severity::Warning);
+    }
+    print_summary(errors, warnings);
+}
+
+/// Handles `sync github`.
+///
+/// Issues are created and updated first; the file is written afterwards,
+/// only if tasks of closed issues were marked done.
+///
+/// # Returns
+/// * `Exit::Failure` if the file doesn't parse.
+/// * `Exit::Io` if the file can't be read or written or GitHub fails.
+/// * `Exit::Ok` otherwise.
+#[cfg(feature = "github")]
+fn run_sync_github(args: GithubArgs) -> Exit {
+    use to_do_parcer::github::{self, Action, Client, SyncOptions};
+
+    let source = match read_source(&args.file, false) {
+        Ok(source) => source,
+        Err(e) => {
+            print_error("Error", &e);
+            return Exit::of(&e);
+        }
+    };
+    let mut editor = match Editor::new(&source) {
+        Ok(editor) => editor,
+        Err(e) => {
+            print_error("Parsing error", &e);
+            return Exit::of_edit(&e);
+        }
+    };
+    let options = SyncOptions {
+        tag: args.tag,
+        assignees: args.map_assignee.into_iter().collect(),
+    };
+    let client = Client::new(
+        &args.api_url,
+        &args.repo,
+        std::env::var("GITHUB_TOKEN").ok(),
+    );
+
+    let issues = match client.issues() {
+        Ok(issues) => issues,
+        Err(e) => {
+            print_error("Sync error", e);
+            return Exit::Io;
+        }
+    };
+    let actions = github::plan(editor.document(), &issues, &options);
+    if args.dry_run {
+        for action in &actions {
+            println!("Would {}", action);
+        }
+        return Exit::Ok;
+    }
+
+    for action in &actions {
+        let result = match action {
+            Action::Create { fields, .. } => client.create(fields),
+            Action::Update { number, fields, .. } => client.update(*number, fields),
+            Action::Complete { .. } => continue,
+        };
+        match result {
+            Ok(issue) => log::info!("{} (#{})", action, issue.number),
+            Err(e) => {
+                print_error("Sync error", e);
+                return Exit::Io;
+            }
+        }
+    }
+    let completed = github::apply(&mut editor, &actions);
+    if completed > 0
+        && let Err(e) = write_atomic(&args.file, &editor.finish())
+    {
+        print_error("File writing error", e);
+        return Exit::Io;
+    }
+
+    let count = |f: fn(&Action) -> bool| actions.iter().filter(|a| f(a)).count();
+    println!(
+        "Synced with {}: {} issue(s) created, {} updated, {} task(s) marked done",
+        args.repo,
+        count(|a| matches!(a, Action::Create { .. })),
+        count(|a| matches!(a, Action::Update { .. })),
+        completed
+    );
+    Exit::Ok
+}
+
+/// Handles the `init` command.
+///
+/// # Returns
+/// * `Exit::Failure` if the file exists and `--force` isn't given.
+/// * `Exit::Io` if the file can't be written.
+/// * `Exit::Ok` otherwise.
+fn run_init(args: InitArgs) -> Exit {
+    if !args.force && Path::new(&args.file).exists() {
+        print_error(
+            "Error",
+            format!("{} already exists; use --force to replace it", args.file),
+        );
+        return Exit::Failure;
+    }
+    match write_atomic(&args.file, args.template.text()) {
+        Ok(()) => {
+            println!("Created {} from the {} template", args.file, args.template);
+            Exit::Ok
+        }
+        Err(e) => {
+            print_error("File writing error", e);
+            Exit::Io
         }
     }
+}
+
+/// Handles the `archive` command.
+///
+/// The archive is written before the active file, so a failure in between
+/// leaves tasks in both files rather than in neither.
+///
+/// # Arguments
+/// * `args` — CLI arguments with the active and archive file paths.
+///
+/// # Returns
+/// * `Err(EditError)` if a file can't be read, parsed or written.
+fn run_archive(args: ArchiveArgs) -> Result<(), EditError> {
+    let source = read_source(&args.file, false)?;
+    let existing = match read_source(&args.to, false) {
+        Ok(text) => text,
+        Err(ParseError::Io(e)) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+
+    let result = archive(&source, &existing)?;
+    for task in &result.kept {
+        log::warn!("kept \"{}\": open tasks depend on it", task);
+    }
+    if result.tasks == 0 {
+        println!("Nothing to archive");
+        return Ok(());
+    }
+
+    write_atomic(&args.to, &result.archive).map_err(ParseError::from)?;
+    write_atomic(&args.file, &result.source).map_err(ParseError::from)?;
+    println!(
+        "Archived {} task{} ({} whole project{}) to {}",
+        result.tasks,
+        if result.tasks == 1 { "" } else { "s" },
+        result.projects,
+        if result.projects == 1 { "" } else { "s" },
+        args.to
+    );
+    Ok(())
+}
+
+/// Handles the `set` command.
+///
+/// # Arguments
+/// * `args` — CLI arguments with the file, task selector and new values.
+///
+/// # Returns
+/// * `Err(EditError)` if the file can't be read, parsed or written, the
+///   selector doesn't match exactly one task, or a value can't be written.
+fn run_set(args: SetArgs) -> Result<(), EditError> {
+    let clearable = |value: Option<String>| value.map(|v| (v != "none").then_some(v));
+    let changes = TaskChanges {
+        priority: args.priority.map(|p| p.0),
+        due: args.due.map(|d| d.0),
+        assignee: clearable(args.assign)
+            .map(|a| a.map(|name| name.trim_start_matches('@').to_string())),
+        depends_on: clearable(args.depends_on),
+    };
+
+    let file = task_file(&args.file, &args.task);
+    let source = read_source(&file, false)?;
+    let mut editor = Editor::new(&source)?;
+    editor.update_task(&args.task, &changes)?;
+    write_atomic(&file, &editor.finish()).map_err(ParseError::from)?;
+    Ok(())
+}
+
+/// Handles the `diff` command.
+///
+/// # Arguments
+/// * `args` — CLI arguments with both file paths and the output mode.
+///
+/// # Returns
+/// * `Err(ParseError)` if either file can't be read or parsed.
+fn run_diff(args: DiffArgs) -> Result<(), ParseError> {
+    let diff = Diff::compute(&load_document(&args.old)?, &load_document(&args.new)?);
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&diff).expect("diff serializes to JSON")
+        );
+    } else {
+        print!("{}", diff);
+    }
+    Ok(())
+}
+
+/// Handles the `merge` command.
+///
+/// Includes are kept as directives rather than inlined. The merged file is
+/// written even when there are conflicts; conflicting tasks are marked with
+/// a comment.
+///
+/// # Arguments
+/// * `args` — CLI arguments with the three input paths and the output path.
+///
+/// # Returns
+/// * `Ok(Exit::Failure)` if there were conflicts, as git expects from
+///   a merge driver.
+/// * `Ok(Exit::Ok)` otherwise.
+/// * `Err(ParseError)` if reading, parsing or writing fails.
+fn run_merge(args: MergeArgs) -> Result<Exit, ParseError> {
+    let options = cli_options(IncludePolicy::Ignore);
+    let load = |path: &str| ToDoParser::parse_with(&read_source(path, lossy())?, &options);
+    let merged = merge(&load(&args.base)?, &load(&args.ours)?, &load(&args.theirs)?);
+
+    let text = format_document(&merged.document);
+    match &args.output {
+        Some(path) => write_atomic(path, &text)?,
+        None => print!("{}", text),
+    }
+
+    for conflict in &merged.conflicts {
+        eprintln!(
+            "{}: {}",
+            stderr_palette().paint(Style::Error, "conflict"),
+            conflict
+        );
+    }
+    Ok(if merged.is_clean() {
+        Exit::Ok
+    } else {
+        Exit::Failure
+    })
+}
+
+/// Handles the `sort` command.
+///
+/// # Arguments
+/// * `args` — CLI arguments with the file, sort key and `--write` flag.
+///
+/// # Returns
+/// * `Err(EditError)` if the file can't be read, parsed or written.
+fn run_sort(args: SortArgs) -> Result<(), EditError> {
+    let source = read_source(&args.file, false)?;
+    let mut editor = Editor::new(&source)?;
+    editor.sort_tasks(args.by);
+    let sorted = editor.finish();
 
+    if !args.write {
+        print!("{}", sorted);
+    } else if sorted != source {
+        write_atomic(&args.file, &sorted).map_err(ParseError::from)?;
+    }
     Ok(())
 }
+
+/// Parses a `--severity RULE=LEVEL` value.
+fn parse_rule_severity(value: &str) -> Result<(LintRule, Severity), String> {
+    let (rule, level) = value
+        .split_once('=')
+        .ok_or_else(|| "expected RULE=LEVEL".to_string())?;
+    Ok((rule.parse()?, level.parse()?))
+}
+
+/// Parses a `--priority` value that may be `none`.
+fn parse_clearable_priority(value: &str) -> Result<Clearable<Priority>, String> {
+    if value == "none" {
+        return Ok(Clearable(None));
+    }
+    parse_priority(value).map(|p| Clearable(Some(p)))
+}
+
+/// Parses a `--due` value that may be `none`.
+fn parse_clearable_date(value: &str) -> Result<Clearable<NaiveDate>, String> {
+    if value == "none" {
+        return Ok(Clearable(None));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|d| Clearable(Some(d)))
+        .map_err(|_| "expected YYYY-MM-DD or none".to_string())
+}
@@ -0,0 +1,397 @@
+//! Three-way semantic merge of a document.
+//!
+//! Both sides are compared with their common ancestor task by task (see
+//! [`crate::diff`] for how tasks are paired up). A change made on one side
+//! only is taken; the same change made on both sides is taken once. When
+//! both sides change the same attribute differently, or one side changes a
+//! task the other removed, the merge keeps the changed version, reports a
+//! [`Conflict`] and marks the task with a `// merge conflict: ...` comment.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::deps::TaskRef;
+use crate::diff::TaskMatching;
+use crate::parser::{Document, Project, Task};
+
+/// How two sides disagree about a task.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConflictKind {
+    /// Both sides changed an attribute to different values; ours is kept.
+    Field {
+        field: &'static str,
+        ours: Option<String>,
+        theirs: Option<String>,
+    },
+    /// We removed the task but they changed it; theirs is kept.
+    RemovedInOurs,
+    /// They removed the task but we changed it; ours is kept.
+    RemovedInTheirs,
+}
+
+/// A task the merge could not reconcile automatically.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    /// Project of the task in the merged document.
+    pub project: String,
+    pub title: String,
+    pub kind: ConflictKind,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}: {}", self.project, self.title, self.kind)
+    }
+}
+
+impl fmt::Display for ConflictKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConflictKind::Field {
+                field,
+                ours,
+                theirs,
+            } => write!(
+                f,
+                "{} is {} in ours and {} in theirs, kept ours",
+                field,
+                ours.as_deref().unwrap_or("none"),
+                theirs.as_deref().unwrap_or("none")
+            ),
+            ConflictKind::RemovedInOurs => write!(f, "removed in ours but changed in theirs, kept"),
+            ConflictKind::RemovedInTheirs => {
+                write!(f, "removed in theirs but changed in ours, kept")
+            }
+        }
+    }
+}
+
+/// Result of a three-way merge.
+#[derive(Debug, Clone)]
+pub struct Merge {
+    /// The merged document; its layout follows ours.
+    pub document: Document,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl Merge {
+    /// Returns `true` if every change could be merged automatically.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Merges the changes `ours` and `theirs` made to `base`.
+///
+/// Tasks keep the order they have in `ours`; tasks only `theirs` added go
+/// after them, in new projects at the end if needed. Spans of the merged
+/// document are meaningless; print it with `format_document`.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::ToDoParser;
+/// use to_do_parcer::merge::merge;
+///
+/// let base = ToDoParser::parse_document(r#"project "P" { todo: "A", }"#)?;
+/// let ours = ToDoParser::parse_document(r#"project "P" { done: "A", }"#)?;
+/// let theirs = ToDoParser::parse_document(r#"project "P" { todo: "A", @high, todo: "B", }"#)?;
+/// let merged = merge(&base, &ours, &theirs);
+/// assert!(merged.is_clean());
+/// let tasks = &merged.document.projects[0].tasks;
+/// assert_eq!(tasks.len(), 2);
+/// assert!(tasks[0].priority.is_some());
+/// # Ok::<(), to_do_parcer::ParseError>(())
+/// ```
+pub fn merge(base: &Document, ours: &Document, theirs: &Document) -> Merge {
+    let ours_of_base = inverse(base, ours);
+    let theirs_of_base = inverse(base, theirs);
+    let ours_matching = TaskMatching::new(base, ours);
+    let theirs_matching = TaskMatching::new(base, theirs);
+
+    let mut document = Document {
+        projects: ours
+            .projects
+            .iter()
+            .map(|p| Project {
+                tasks: Vec::new(),
+                ..p.clone()
+            })
+            .collect(),
+        warnings: Vec::new(),
+        ..ours.clone()
+    };
+    let mut conflicts = Vec::new();
+
+    // Tasks we have, merged with their changes.
+    for (ours_ref, base_ref) in ours.task_refs().zip(&ours_matching.old_for_new) {
+        let ours_side = Side::of(ours, ours_ref);
+        let Some(base_ref) = base_ref else {
+            place(&mut document, ours_side.project, ours_side.task.clone());
+            continue;
+        };
+        let base_side = Side::of(base, *base_ref);
+        match theirs_of_base.get(base_ref) {
+            Some(&theirs_ref) => {
+                let theirs_side = Side::of(theirs, theirs_ref);
+                let (project, task) =
+                    merge_task(&base_side, &ours_side, &theirs_side, &mut conflicts);
+                place(&mut document, &project, task);
+            }
+            None if ours_side.same_as(&base_side) => {}
+            None => {
+                let task =
+                    keep_with_conflict(&ours_side, ConflictKind::RemovedInTheirs, &mut conflicts);
+                place(&mut document, ours_side.project, task);
+            }
+        }
+    }
+
+    for (theirs_ref, base_ref) in theirs.task_refs().zip(&theirs_matching.old_for_new) {
+        let theirs_side = Side::of(theirs, theirs_ref);
+        match base_ref {
+            // Removed in ours: keep it only if they changed it.
+            Some(base_ref) if !ours_of_base.contains_key(base_ref) => {
+                if !theirs_side.same_as(&Side::of(base, *base_ref)) {
+                    let task = keep_with_conflict(
+                        &theirs_side,
+                        ConflictKind::RemovedInOurs,
+                        &mut conflicts,
+                    );
+                    place(&mut document, theirs_side.project, task);
+                }
+            }
+            Some(_) => {}
+            // Added in theirs, unless we added the same task.
+            None => {
+                let duplicate = document
+                    .projects
+                    .iter()
+                    .find(|p| p.name == theirs_side.project)
+                    .is_some_and(|p| {
+                        p.tasks.iter().any(|t| {
+                            Side {
+                                project: &p.name,
+                                task: t,
+                            }
+                            .same_as(&theirs_side)
+                        })
+                    });
+                if !duplicate {
+                    place(&mut document, theirs_side.project, theirs_side.task.clone());
+                }
+            }
+        }
+    }
+
+    // Projects they added stay even if empty; empty projects they removed go.
+    for project in &theirs.projects {
+        let known = |doc: &Document| doc.projects.iter().any(|p| p.name == project.name);
+        if !known(base) && !known(&document) {
+            document.projects.push(Project {
+                tasks: Vec::new(),
+                ..project.clone()
+            });
+        }
+    }
+    document.projects.retain(|p| {
+        let in_doc = |doc: &Document| doc.projects.iter().any(|q| q.name == p.name);
+        !p.tasks.is_empty() || !in_doc(base) || in_doc(theirs)
+    });
+
+    Merge {
+        document,
+        conflicts,
+    }
+}
+
+/// A task together with the name of its project.
+struct Side<'d> {
+    project: &'d str,
+    task: &'d Task,
+}
+
+impl<'d> Side<'d> {
+    fn of(doc: &'d Document, r: TaskRef) -> Side<'d> {
+        Side {
+            project: &doc.projects[r.project].name,
+            task: r.get(doc).expect("handle comes from the same document"),
+        }
+    }
+
+    /// Compares everything but layout: project, status and attributes.
+    fn same_as(&self, other: &Side) -> bool {
+        let (a, b) = (self.task, other.task);
+        self.project == other.project
+            && a.status == b.status
+            && a.title == b.title
+            && a.id == b.id
+            && a.priority == b.priority
+            && a.start_date == b.start_date
+            && a.due_date == b.due_date
+            && a.completed == b.completed
+            && a.assignee == b.assignee
+            && a.depends_on == b.depends_on
+            && a.tags == b.tags
+    }
+}
+
+/// Maps base tasks to the tasks of `other` they were paired with.
+fn inverse(base: &Document, other: &Document) -> HashMap<TaskRef, TaskRef> {
+    other
+        .task_refs()
+        .zip(TaskMatching::new(base, other).old_for_new)
+        .filter_map(|(other_ref, base_ref)| Some((base_ref?, other_ref)))
+        .collect()
+}
+
+/// Appends `task` to the project named `project`, creating it if needed.
+fn place(doc: &mut Document, project: &str, task: Task) {
+    match doc.projects.iter_mut().find(|p| p.name == project) {
+        Some(p) => p.tasks.push(task),
+        None => {
+            let mut p = Project::new(project);
+            p.tasks.push(task);
+            doc.projects.push(p);
+        }
+    }
+}
+
+/// Returns a copy of `side`'s task marked with a conflict comment.
+fn keep_with_conflict(side: &Side, kind: ConflictKind, conflicts: &mut Vec<Conflict>) -> Task {
+    let mut task = side.task.clone();
+    task.comments.push(format!("// merge conflict: {}", kind));
+    conflicts.push(Conflict {
+        project: side.project.to_string(),
+        title: task.title.clone(),
+        kind,
+    });
+    task
+}
+
+/// Merges a task present on all three sides, attribute by attribute.
+fn merge_task(
+    base: &Side,
+    ours: &Side,
+    theirs: &Side,
+    conflicts: &mut Vec<Conflict>,
+) -> (String, Task) {
+    let (b, o, t) = (base.task, ours.task, theirs.task);
+    let mut found = Vec::new();
+    let text = |v: &String| Some(v.clone());
+    let optional = |v: &Option<String>| v.clone();
+
+    let project = pick(
+        "project",
+        &base.project,
+        &ours.project,
+        &theirs.project,
+        |p| Some(p.to_string()),
+        &mut found,
+    )
+    .to_string();
+    let mut task = o.clone();
+    task.status = pick(
+        "status",
+        &b.status,
+        &o.status,
+        &t.status,
+        |s| Some(s.as_str().to_string()),
+        &mut found,
+    );
+    task.title = pick("title", &b.title, &o.title, &t.title, text, &mut found);
+    task.id = pick("id", &b.id, &o.id, &t.id, optional, &mut found);
+    task.priority = pick(
+        "priority",
+        &b.priority,
+        &o.priority,
+        &t.priority,
+        |p| p.map(|p| p.as_str().to_string()),
+        &mut found,
+    );
+    task.start_date = pick(
+        "start",
+        &b.start_date,
+        &o.start_date,
+        &t.start_date,
+        optional,
+        &mut found,
+    );
+    task.due_date = pick(
+        "due",
+        &b.due_date,
+        &o.due_date,
+        &t.due_date,
+        optional,
+        &mut found,
+    );
+    task.completed = pick(
+        "completed",
+        &b.completed,
+        &o.completed,
+        &t.completed,
+        optional,
+        &mut found,
+    );
+    task.assignee = pick(
+        "assignee",
+        &b.assignee,
+        &o.assignee,
+        &t.assignee,
+        optional,
+        &mut found,
+    );
+    task.assignee_as_written = [o, t, b]
+        .into_iter()
+        .find(|side| side.assignee == task.assignee)
+        .and_then(|side| side.assignee_as_written.clone());
+    task.depends_on = pick(
+        "depends_on",
+        &b.depends_on,
+        &o.depends_on,
+        &t.depends_on,
+        optional,
+        &mut found,
+    );
+    task.tags = pick(
+        "tags",
+        &b.tags,
+        &o.tags,
+        &t.tags,
+        |tags| (!tags.is_empty()).then(|| tags.join(", ")),
+        &mut found,
+    );
+
+    for kind in found {
+        task.comments.push(format!("// merge conflict: {}", kind));
+        conflicts.push(Conflict {
+            project: project.clone(),
+            title: task.title.clone(),
+            kind,
+        });
+    }
+    (project, task)
+}
+
+/// Three-way merge of a single value: a change on one side wins, and
+/// conflicting changes keep ours and are recorded in `found`.
+fn pick<T: PartialEq + Clone>(
+    field: &'static str,
+    base: &T,
+    ours: &T,
+    theirs: &T,
+    show: impl Fn(&T) -> Option<String>,
+    found: &mut Vec<ConflictKind>,
+) -> T {
+    if ours == base || ours == theirs {
+        theirs.clone()
+    } else if theirs == base {
+        ours.clone()
+    } else {
+        found.push(ConflictKind::Field {
+            field,
+            ours: show(ours),
+            theirs: show(theirs),
+        });
+        ours.clone()
+    }
+}
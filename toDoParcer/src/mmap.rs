@@ -0,0 +1,156 @@
+//! Parsing files through a memory map instead of reading them into a
+//! `String` first.
+//!
+//! A [`MappedSource`] checks the mapped bytes as UTF-8 in place and lends
+//! them out as a `&str`, so a multi-hundred-megabyte task dump is never
+//! copied as a whole: [`ToDoParser::parse_events`] streams it with project
+//! names borrowed from the map, and [`ToDoParser::parse_file_mmap_with`]
+//! only allocates the strings of the resulting [`Document`].
+//!
+//! The file must not be changed while it is mapped: the text is checked as
+//! UTF-8 once and then trusted, and truncating the file under a running
+//! parse can crash the process. The functions that map a file are `unsafe`
+//! for that reason. Use them for generated dumps and archives, not for
+//! files someone may be editing.
+//!
+//! ```no_run
+//! use to_do_parcer::ToDoParser;
+//! use to_do_parcer::events::Event;
+//! use to_do_parcer::mmap::MappedSource;
+//!
+//! // SAFETY: the dump is written once and never modified afterwards.
+//! let source = unsafe { MappedSource::open("dump.todo")? };
+//! let mut tasks = 0;
+//! ToDoParser::parse_events(source.as_str(), &mut |event: Event<'_>| {
+//!     if let Event::TaskParsed(_) = event {
+//!         tasks += 1;
+//!     }
+//! })?;
+//! println!("{} tasks", tasks);
+//! # Ok::<(), to_do_parcer::ParseError>(())
+//! ```
+
+use std::fs::File;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::options::{IncludePolicy, ParseOptions};
+use crate::parser::{Document, ParseError, Project, ToDoParser, check_file_size, decode_source};
+
+/// Contents of a file mapped into memory, known to be valid UTF-8.
+#[derive(Debug)]
+pub struct MappedSource {
+    /// `None` for an empty file, which can't be mapped on every platform.
+    map: Option<Mmap>,
+}
+
+impl MappedSource {
+    /// Maps the file at `path` and checks that it is UTF-8.
+    ///
+    /// # Safety
+    /// The file must not be modified or truncated, by this process or any
+    /// other, until the `MappedSource` is dropped. [`MappedSource::as_str`]
+    /// relies on the bytes staying as they were checked.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the input file
+    ///
+    /// # Returns
+    /// * `Ok(MappedSource)` on success
+    /// * `Err(ParseError::Io)` if the file can't be opened or mapped
+    /// * `Err(ParseError::Encoding)` if it isn't valid UTF-8
+    pub unsafe fn open(path: impl AsRef<Path>) -> Result<MappedSource, ParseError> {
+        let file = File::open(path)?;
+        if file.metadata()?.len() == 0 {
+            return Ok(MappedSource { map: None });
+        }
+        // SAFETY: the map is only read, and the caller guarantees that the
+        // file doesn't change while it is mapped.
+        let map = unsafe { Mmap::map(&file)? };
+        if let Err(e) = std::str::from_utf8(&map) {
+            let offset = e.valid_up_to();
+            let line = map[..offset].iter().filter(|&&b| b == b'\n').count() + 1;
+            return Err(ParseError::Encoding { line, offset });
+        }
+        Ok(MappedSource { map: Some(map) })
+    }
+
+    /// The text of the file, borrowed from the map.
+    pub fn as_str(&self) -> &str {
+        match &self.map {
+            // SAFETY: `open` checked that the bytes are UTF-8, and its caller
+            // guarantees that they don't change while mapped.
+            Some(map) => unsafe { std::str::from_utf8_unchecked(map) },
+            None => "",
+        }
+    }
+}
+
+impl ToDoParser {
+    /// Parse projects from a file like [`ToDoParser::parse_from_file`],
+    /// mapping it into memory instead of reading it.
+    ///
+    /// # Safety
+    /// Same as [`MappedSource::open`]: the file must not change until the
+    /// call returns.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the input file
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Project>)` on success
+    /// * `Err(ParseError)` if mapping or parsing fails
+    ///
+    /// # Example
+    /// ```no_run
+    /// use to_do_parcer::ToDoParser;
+    ///
+    /// // SAFETY: nothing writes to the dump while it is parsed.
+    /// let projects = unsafe { ToDoParser::parse_from_file_mmap("dump.todo")? };
+    /// # Ok::<(), to_do_parcer::ParseError>(())
+    /// ```
+    pub unsafe fn parse_from_file_mmap(path: &str) -> Result<Vec<Project>, ParseError> {
+        let options = ParseOptions {
+            includes: IncludePolicy::Resolve,
+            ..ParseOptions::default()
+        };
+        // SAFETY: forwarded to the caller.
+        unsafe { Self::parse_file_mmap_with(path, &options) }.map(|doc| doc.projects)
+    }
+
+    /// Parse a file into a `Document` like [`ToDoParser::parse_file_with`],
+    /// mapping it into memory instead of reading it.
+    ///
+    /// Included files are still read normally. With `options.lossy`, a
+    /// file that isn't valid UTF-8 is copied once to replace the invalid
+    /// bytes.
+    ///
+    /// # Safety
+    /// Same as [`MappedSource::open`]: the file must not change until the
+    /// call returns.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the input file
+    /// * `options` - Strictness, allowed priorities, include handling, etc.
+    ///
+    /// # Returns
+    /// * `Ok(Document)` on success
+    /// * `Err(ParseError)` if mapping or parsing fails
+    pub unsafe fn parse_file_mmap_with(
+        path: &str,
+        options: &ParseOptions,
+    ) -> Result<Document, ParseError> {
+        log::debug!("mapping {}", path);
+        check_file_size(Path::new(path), 0, options)?;
+        // SAFETY: forwarded to the caller.
+        match unsafe { MappedSource::open(path) } {
+            Ok(source) => Self::parse_file_contents(path, source.as_str(), None, options),
+            Err(ParseError::Encoding { .. }) if options.lossy => {
+                let (content, replaced) = decode_source(std::fs::read(path)?, true)?;
+                Self::parse_file_contents(path, &content, replaced, options)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
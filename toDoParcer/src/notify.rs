@@ -0,0 +1,196 @@
+//! Desktop notifications for tasks that are due soon or overdue.
+//!
+//! Meant to run from a timer or cron job: [`due_notifications`] picks the
+//! tasks and words the messages, and a [`Notifier`] shows them. The
+//! [`DesktopNotifier`] hands each message to the platform's notification
+//! tool — `notify-send` on Linux and the BSDs, `osascript` on macOS — so no
+//! notification daemon bindings are linked into the binary.
+
+use std::io;
+use std::process::Command;
+
+use chrono::NaiveDate;
+
+use crate::parser::Document;
+use crate::schedule::{AgendaGroup, Clock};
+
+/// How insistently a notification is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+    Normal,
+    /// For overdue tasks; stays on screen until dismissed where supported.
+    Critical,
+}
+
+impl Urgency {
+    /// Name of the level as understood by `notify-send --urgency`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Urgency::Normal => "normal",
+            Urgency::Critical => "critical",
+        }
+    }
+}
+
+/// One message about one task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    pub summary: String,
+    pub body: String,
+    pub urgency: Urgency,
+}
+
+/// Something that can show notifications.
+pub trait Notifier {
+    /// Shows `notification`.
+    fn send(&self, notification: &Notification) -> io::Result<()>;
+}
+
+/// Shows notifications through the desktop's notification tool.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DesktopNotifier;
+
+impl Notifier for DesktopNotifier {
+    fn send(&self, notification: &Notification) -> io::Result<()> {
+        let mut command = if cfg!(target_os = "macos") {
+            let script = format!(
+                "display notification {} with title {}",
+                apple_string(&notification.body),
+                apple_string(&notification.summary)
+            );
+            let mut command = Command::new("osascript");
+            command.arg("-e").arg(script);
+            command
+        } else if cfg!(unix) {
+            let mut command = Command::new("notify-send");
+            command
+                .arg("--app-name=to_do_parcer")
+                .arg(format!("--urgency={}", notification.urgency.as_str()))
+                .arg(&notification.summary)
+                .arg(&notification.body);
+            command
+        } else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "desktop notifications are not supported on this platform",
+            ));
+        };
+
+        let status = command.status().map_err(|e| {
+            let program = command.get_program().to_string_lossy().into_owned();
+            io::Error::new(e.kind(), format!("cannot run {}: {}", program, e))
+        })?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!(
+                "{} failed with {}",
+                command.get_program().to_string_lossy(),
+                status
+            )))
+        }
+    }
+}
+
+/// AppleScript string literal of `text`.
+fn apple_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Notifications for open tasks that are overdue or due within `days`
+/// days, the most urgent first.
+///
+/// # Arguments
+/// * `doc` - Document to look through
+/// * `now` - Clock that provides the current date
+/// * `days` - How far ahead to look; 0 means tasks due today, and a window
+///   past the end of the calendar takes every task with a due date
+///
+/// # Example
+/// ```
+/// use chrono::NaiveDate;
+/// use to_do_parcer::ToDoParser;
+/// use to_do_parcer::notify::{Urgency, due_notifications};
+///
+/// let doc = ToDoParser::parse_document(
+///     r#"project "P" { todo: "Pay rent", due: 2025-01-31, todo: "Later", due: 2025-03-01, }"#,
+/// )?;
+/// let today = NaiveDate::from_ymd_opt(2025, 2, 1).unwrap();
+/// let notes = due_notifications(&doc, &today, 1);
+/// assert_eq!(notes.len(), 1);
+/// assert_eq!(notes[0].summary, "Overdue: Pay rent");
+/// assert_eq!(notes[0].urgency, Urgency::Critical);
+/// # Ok::<(), to_do_parcer::ParseError>(())
+/// ```
+pub fn due_notifications(doc: &Document, now: &impl Clock, days: u32) -> Vec<Notification> {
+    let today = now.today();
+    let mut out = Vec::new();
+    for (group, tasks) in doc.agenda(now, days) {
+        for (project, task) in tasks {
+            let due = task.due().expect("agenda tasks have a due date");
+            let (summary, urgency) = match group {
+                AgendaGroup::Overdue => (format!("Overdue: {}", task.title), Urgency::Critical),
+                AgendaGroup::Today => (format!("Due today: {}", task.title), Urgency::Normal),
+                AgendaGroup::Tomorrow => (format!("Due tomorrow: {}", task.title), Urgency::Normal),
+                _ => (
+                    format!("Due {}: {}", due.format("%a %b %-d"), task.title),
+                    Urgency::Normal,
+                ),
+            };
+            out.push(Notification {
+                summary,
+                body: body(&project.name, task.assignee.as_deref(), due, today),
+                urgency,
+            });
+        }
+    }
+    out
+}
+
+/// Second line of a notification: project, due date, lateness, assignee.
+fn body(project: &str, assignee: Option<&str>, due: NaiveDate, today: NaiveDate) -> String {
+    let mut body = format!("{} · due {}", project, due);
+    let late = (today - due).num_days();
+    if late > 0 {
+        body.push_str(&format!(
+            ", {} day{} late",
+            late,
+            if late == 1 { "" } else { "s" }
+        ));
+    }
+    if let Some(assignee) = assignee {
+        body.push_str(&format!(" · @{}", assignee));
+    }
+    body
+}
+
+/// Parses a look-ahead window such as `24h`, `3d` or `2w` into whole days.
+///
+/// Due dates have no time of day, so hours are rounded up to days; a bare
+/// number counts days.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::notify::parse_window;
+///
+/// assert_eq!(parse_window("24h"), Ok(1));
+/// assert_eq!(parse_window("36h"), Ok(2));
+/// assert_eq!(parse_window("2w"), Ok(14));
+/// assert!(parse_window("soon").is_err());
+/// ```
+pub fn parse_window(value: &str) -> Result<u32, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let error = || format!("invalid window '{}', expected e.g. 24h, 3d or 2w", value);
+    let number: u32 = number.parse().map_err(|_| error())?;
+    let days = match unit {
+        "h" => number.div_ceil(24),
+        "" | "d" => number,
+        "w" => number.checked_mul(7).ok_or_else(error)?,
+        _ => return Err(error()),
+    };
+    Ok(days)
+}
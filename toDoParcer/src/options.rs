@@ -0,0 +1,186 @@
+//! Configuration for `ToDoParser::parse_with`.
+
+use std::path::PathBuf;
+
+use crate::parser::Priority;
+
+/// Knobs controlling how permissive the parser is.
+///
+/// `ParseOptions::default()` reproduces the behavior of
+/// `ToDoParser::parse_document`.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::options::{ParseOptions, UnknownAttributePolicy};
+/// use to_do_parcer::ToDoParser;
+///
+/// let options = ParseOptions {
+///     unknown_attributes: UnknownAttributePolicy::Warn,
+///     ..ParseOptions::default()
+/// };
+/// let doc = ToDoParser::parse_with(r#"project "P" { todo: "A", color: red, }"#, &options)?;
+/// assert_eq!(doc.warnings.len(), 1);
+/// # Ok::<(), to_do_parcer::ParseError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Turn every warning into a hard error.
+    pub strict: bool,
+    /// Priorities a task may use. Others are dropped with a warning.
+    pub allowed_priorities: Vec<Priority>,
+    /// What to do with `name: value` attributes the parser doesn't know.
+    pub unknown_attributes: UnknownAttributePolicy,
+    /// Format of date literals such as `due:`.
+    pub date_format: DateFormat,
+    /// What to do with `include "file"` directives.
+    pub includes: IncludePolicy,
+    /// What to do when several `project` blocks share a name.
+    pub duplicate_projects: DuplicateProjectPolicy,
+    /// Bounds on the size of the input, for parsing untrusted files.
+    pub limits: Limits,
+    /// Accept keywords such as `TODO:`, `Done:` or `Project` in any case.
+    /// Otherwise they fail with `ParseError::KeywordCase`.
+    pub case_insensitive_keywords: bool,
+    /// Accept input that is empty or holds only comments as a document
+    /// without projects. Otherwise it fails like any other syntax error.
+    pub allow_empty: bool,
+    /// Treat `@Alice` and `@alice` as the same assignee: `Task::assignee`
+    /// holds the lowercase handle, and `Task::assignee_as_written` keeps
+    /// the original spelling for display.
+    pub ignore_assignee_case: bool,
+    /// Read files that aren't valid UTF-8 with the invalid bytes replaced
+    /// by U+FFFD, and record a warning. Otherwise they fail with
+    /// `ParseError::Encoding`.
+    pub lossy: bool,
+    /// Directory that relative include paths are resolved against.
+    /// Defaults to the current directory when `None`.
+    pub base_dir: Option<PathBuf>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            strict: false,
+            allowed_priorities: vec![Priority::High, Priority::Medium, Priority::Low],
+            unknown_attributes: UnknownAttributePolicy::Error,
+            date_format: DateFormat::Iso,
+            includes: IncludePolicy::Error,
+            duplicate_projects: DuplicateProjectPolicy::KeepSeparate,
+            limits: Limits::default(),
+            case_insensitive_keywords: false,
+            allow_empty: false,
+            ignore_assignee_case: false,
+            lossy: false,
+            base_dir: None,
+        }
+    }
+}
+
+/// Upper bounds enforced while parsing; `None` means unlimited, which is
+/// the default.
+///
+/// Project blocks can't be nested, so includes are the only nesting a file
+/// can have; `max_include_depth` bounds it.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::options::{Limits, ParseOptions};
+/// use to_do_parcer::{ParseError, ToDoParser};
+///
+/// let options = ParseOptions {
+///     limits: Limits {
+///         max_tasks_per_project: Some(1),
+///         ..Limits::default()
+///     },
+///     ..ParseOptions::default()
+/// };
+/// let result = ToDoParser::parse_with(r#"project "P" { todo: "A", todo: "B", }"#, &options);
+/// assert!(matches!(result, Err(ParseError::TooManyTasks { max: 1, .. })));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// Bytes of input, counting the main input and every included file.
+    pub max_input_bytes: Option<usize>,
+    /// Tasks in a single `project` block.
+    pub max_tasks_per_project: Option<usize>,
+    /// Levels of `include` below the main input; 0 allows no includes.
+    pub max_include_depth: Option<usize>,
+}
+
+/// Handling of attributes the parser doesn't recognize.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownAttributePolicy {
+    /// Fail with `ParseError::UnknownAttribute`.
+    Error,
+    /// Drop the attribute and record a warning.
+    Warn,
+    /// Drop the attribute silently.
+    Ignore,
+}
+
+/// Accepted spelling of date literals.
+///
+/// ISO dates are always accepted; the other formats additionally accept
+/// one local spelling. Dates are stored in the AST as `YYYY-MM-DD` either
+/// way. The parser never guesses: `01/02/2025` is rejected unless
+/// `MonthDayYear` is configured.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::options::{DateFormat, ParseOptions};
+/// use to_do_parcer::ToDoParser;
+///
+/// let options = ParseOptions {
+///     date_format: DateFormat::DayMonthYear,
+///     ..ParseOptions::default()
+/// };
+/// let doc = ToDoParser::parse_with(r#"project "P" { todo: "A", due: 31.12.2025, }"#, &options)?;
+/// assert_eq!(doc.projects[0].tasks[0].due_date.as_deref(), Some("2025-12-31"));
+/// # Ok::<(), to_do_parcer::ParseError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateFormat {
+    /// `YYYY-MM-DD` only.
+    Iso,
+    /// `DD.MM.YYYY`, as in `31.12.2025`.
+    DayMonthYear,
+    /// `MM/DD/YYYY`, as in `12/31/2025`.
+    MonthDayYear,
+}
+
+impl DateFormat {
+    /// The spelling accepted besides ISO, as shown in error messages.
+    pub fn pattern(&self) -> &'static str {
+        match self {
+            DateFormat::Iso => "YYYY-MM-DD",
+            DateFormat::DayMonthYear => "DD.MM.YYYY",
+            DateFormat::MonthDayYear => "MM/DD/YYYY",
+        }
+    }
+}
+
+/// Handling of `include "file"` directives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IncludePolicy {
+    /// Fail with `ParseError::IncludesDisabled`.
+    Error,
+    /// Skip the directive.
+    Ignore,
+    /// Parse the referenced file and splice its projects in place.
+    Resolve,
+}
+
+/// Handling of `project` blocks whose name was used before, in the same
+/// file or an included one.
+///
+/// Only documents built by `ToDoParser::parse_with` and friends are
+/// affected; streamed events always report every block as written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateProjectPolicy {
+    /// Fail with `ParseError::DuplicateProject`.
+    Error,
+    /// Keep every block as a project of its own.
+    KeepSeparate,
+    /// Append the tasks of later blocks to the first project of that name.
+    Merge,
+}
@@ -0,0 +1,181 @@
+//! Parsing the projects of one large input on every core.
+//!
+//! The input is cut before top-level `project` blocks, along with the
+//! comments above them, and the pieces are parsed in parallel with rayon.
+//! Positions, warnings, `Task::order` and comments come out as from
+//! [`ToDoParser::parse_with`]. When any piece fails, the whole input is
+//! parsed again in one go, so errors are reported exactly as usual.
+
+use std::cell::Cell;
+
+use rayon::prelude::*;
+
+use crate::chunked::{BlockScanner, LineKind};
+use crate::events::DocumentBuilder;
+use crate::options::ParseOptions;
+use crate::parser::{
+    Document, Origin, ParseError, ToDoParser, apply_duplicate_project_policy, normalize_source,
+};
+
+/// Smallest piece worth handing to another thread, in bytes.
+const MIN_CHUNK_BYTES: usize = 16 * 1024;
+
+/// A piece of the input, starting at the beginning of a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Chunk {
+    start: usize,
+    end: usize,
+    /// Lines of the input before `start`.
+    lines_before: usize,
+}
+
+impl ToDoParser {
+    /// Parse a whole input string into a `Document`, parsing its projects
+    /// in parallel.
+    ///
+    /// Gives the same result as [`ToDoParser::parse_document`]; it is only
+    /// faster for inputs with many projects.
+    ///
+    /// # Arguments
+    /// * `input` - Text representation of the projects and tasks
+    ///
+    /// # Returns
+    /// * `Ok(Document)` on success
+    /// * `Err(ParseError)` if parsing fails
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::ToDoParser;
+    ///
+    /// let input = "project \"A\" { todo: \"1\", }\nproject \"B\" { done: \"2\", }\n".repeat(1_000);
+    /// let doc = ToDoParser::parse_parallel(&input)?;
+    /// assert_eq!(doc.projects.len(), 2_000);
+    /// assert_eq!(doc.projects[1_999].span.line, 2_000);
+    /// # Ok::<(), to_do_parcer::ParseError>(())
+    /// ```
+    pub fn parse_parallel(input: &str) -> Result<Document, ParseError> {
+        Self::parse_parallel_with(input, &ParseOptions::default())
+    }
+
+    /// Same as [`ToDoParser::parse_parallel`], using custom options.
+    ///
+    /// Inputs with `limits.max_input_bytes` set are parsed in one go, as
+    /// the limit counts the bytes of every included file in order.
+    pub fn parse_parallel_with(
+        input: &str,
+        options: &ParseOptions,
+    ) -> Result<Document, ParseError> {
+        let normalized = normalize_source(input);
+        let text = normalized.as_ref();
+        let chunks = chunks(text, MIN_CHUNK_BYTES);
+        if chunks.len() < 2 || options.limits.max_input_bytes.is_some() {
+            return Self::parse_with(input, options);
+        }
+
+        let parse = |chunk: &Chunk, version| {
+            let origin = Origin {
+                offset: chunk.start,
+                lines_before: chunk.lines_before,
+            };
+            let mut builder = DocumentBuilder::default();
+            let source = &text[chunk.start..chunk.end];
+            let version = Self::parse_chunk_events(
+                source,
+                origin,
+                version,
+                options,
+                &Cell::new(0),
+                &mut builder,
+            )?;
+            let mut doc = builder.finish();
+            doc.version = version;
+            Ok::<_, ParseError>(doc)
+        };
+        // The first chunk holds the `version` pragma the others need.
+        let Ok(head) = parse(&chunks[0], None) else {
+            return Self::parse_with(input, options);
+        };
+        let rest: Result<Vec<Document>, ParseError> = chunks[1..]
+            .par_iter()
+            .map(|chunk| parse(chunk, Some(head.version)))
+            .collect();
+        let Ok(rest) = rest else {
+            return Self::parse_with(input, options);
+        };
+        log::info!("parsed {} bytes in {} chunks", text.len(), chunks.len());
+
+        let mut doc = merge(head, rest);
+        apply_duplicate_project_policy(&mut doc, options.duplicate_projects)?;
+        Ok(doc)
+    }
+}
+
+/// Cuts `input` into pieces of about `min_bytes` or more that each start
+/// with a top-level `project` (or the comments and blank lines above it),
+/// except the first.
+///
+/// Pieces of invalid input fail to parse, and the caller falls back to
+/// parsing the whole.
+fn chunks(input: &str, min_bytes: usize) -> Vec<Chunk> {
+    let mut scanner = BlockScanner::default();
+    let mut cuts = vec![(0, 0)];
+    let mut seen_project = false;
+    // Start and line of the blank and comment lines above the current one.
+    let mut run = None;
+    let mut start = 0;
+
+    for (line, text) in input.split_inclusive('\n').enumerate() {
+        match scanner.line(text.trim_end_matches('\n')) {
+            LineKind::Blank => {
+                run.get_or_insert((start, line));
+            }
+            kind => {
+                if kind == LineKind::Project {
+                    let cut = run.unwrap_or((start, line));
+                    if seen_project && cut.0 - cuts[cuts.len() - 1].0 >= min_bytes {
+                        cuts.push(cut);
+                    }
+                    seen_project = true;
+                }
+                run = None;
+            }
+        }
+        start += text.len();
+    }
+
+    let ends = cuts.iter().skip(1).map(|&(start, _)| start);
+    cuts.iter()
+        .zip(ends.chain([input.len()]))
+        .map(|(&(start, lines_before), end)| Chunk {
+            start,
+            end,
+            lines_before,
+        })
+        .collect()
+}
+
+/// Joins the documents of consecutive chunks into one.
+fn merge(mut doc: Document, rest: Vec<Document>) -> Document {
+    let mut tasks: usize = doc.projects.iter().map(|p| p.tasks.len()).sum();
+    for mut chunk in rest {
+        // Comments at the end of a chunk belong to the next project.
+        if let Some(first) = chunk.projects.first_mut() {
+            let mut comments = std::mem::take(&mut doc.trailing_comments);
+            comments.append(&mut first.comments);
+            first.comments = comments;
+        }
+        for include in &mut chunk.includes {
+            include.position += doc.projects.len();
+        }
+        let before = tasks;
+        for task in chunk.projects.iter_mut().flat_map(|p| &mut p.tasks) {
+            task.order += before;
+            tasks += 1;
+        }
+        doc.projects.append(&mut chunk.projects);
+        doc.includes.append(&mut chunk.includes);
+        doc.warnings.append(&mut chunk.warnings);
+        doc.trailing_comments = chunk.trailing_comments;
+    }
+    doc
+}
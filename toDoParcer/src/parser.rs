@@ -1,7 +1,27 @@
-use pest::iterators::{Pair, Pairs};
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::fmt::{self, Write};
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use chrono::{Months, NaiveDate};
 use pest::Parser;
+use pest::iterators::{Pair, Pairs};
+use serde::Serialize;
 use thiserror::Error;
 
+use crate::color::{Palette, Style};
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::events::{DocumentBuilder, Event, TodoSink};
+use crate::extensions::Extensions;
+use crate::options::{
+    DateFormat, DuplicateProjectPolicy, IncludePolicy, ParseOptions, UnknownAttributePolicy,
+};
+use crate::suggest::closest;
+use crate::tags::Tags;
+pub use crate::tree::format_tree;
+
 /// A parser implementation for the custom file format using Pest.
 ///
 /// This struct implements the `pest::Parser` trait .
@@ -17,9 +37,507 @@ pub enum ParseError {
     #[error("File reading error: {0}")]
     Io(#[from] std::io::Error),
 
+    /// Error returned for a file that isn't valid UTF-8, unless
+    /// `ParseOptions::lossy` is set.
+    #[error("Line {line}: invalid UTF-8 at byte {offset}")]
+    Encoding { line: usize, offset: usize },
+
     /// Error returned when Pest parser fails.
     #[error("Parsing failed: {0}")]
     Pest(#[from] Box<pest::error::Error<Rule>>),
+
+    /// Error returned when the `version` pragma names an unknown format version.
+    #[error(
+        "Unsupported format version {found} (supported: {}-{})",
+        SUPPORTED_VERSIONS.start(),
+        SUPPORTED_VERSIONS.end()
+    )]
+    UnsupportedVersion { found: u32 },
+
+    /// Error returned when syntax is used that the file's format version doesn't allow.
+    #[error("Line {line}: {message}")]
+    Incompatible { line: usize, message: String },
+
+    /// Error returned for an attribute the parser doesn't know, with the
+    /// closest known attribute if it looks like a typo of one.
+    #[error("Line {line}: unknown attribute `{name}`{}", did_you_mean(.suggestion))]
+    UnknownAttribute {
+        line: usize,
+        name: String,
+        suggestion: Option<&'static str>,
+    },
+
+    /// Error returned for a warning when parsing in strict mode.
+    #[error("{0} (strict mode)")]
+    Strict(ParseWarning),
+
+    /// Error returned for a date that has the right shape but doesn't exist,
+    /// such as `2025-02-30`.
+    #[error("Line {}: invalid date `{value}`: {reason}", span.line)]
+    InvalidDate {
+        span: Span,
+        value: String,
+        reason: String,
+    },
+
+    /// Error returned for a keyword that isn't lowercase, such as `TODO:`,
+    /// unless keywords are case-insensitive.
+    #[error("Line {}: keyword `{found}` must be written `{expected}`", span.line)]
+    KeywordCase {
+        span: Span,
+        found: String,
+        expected: String,
+    },
+
+    /// Error returned for a second `project` block with the same name when
+    /// duplicate projects are not allowed.
+    #[error("Line {line}: project \"{name}\" is already defined on line {first_line}")]
+    DuplicateProject {
+        line: usize,
+        name: String,
+        first_line: usize,
+    },
+
+    /// Error returned when the input, with the files it includes, is larger
+    /// than `Limits::max_input_bytes`.
+    #[error("Input is larger than the limit of {max} bytes")]
+    InputTooLarge { max: usize },
+
+    /// Error returned for a project with more tasks than
+    /// `Limits::max_tasks_per_project`.
+    #[error("Line {line}: project \"{project}\" has more than {max} tasks")]
+    TooManyTasks {
+        line: usize,
+        project: String,
+        max: usize,
+    },
+
+    /// Error returned for an `include` nested deeper than
+    /// `Limits::max_include_depth`.
+    #[error("Include of {} is nested deeper than {max} levels", path.display())]
+    IncludeTooDeep { path: PathBuf, max: usize },
+
+    /// Error returned for an `include` directive when includes are disabled.
+    #[error("Line {line}: includes are disabled (`include \"{path}\"`)")]
+    IncludesDisabled { line: usize, path: String },
+
+    /// Error returned when a file includes itself, directly or indirectly.
+    #[error("Include cycle through {}", path.display())]
+    IncludeCycle { path: PathBuf },
+
+    /// Error returned for any failure inside an included file.
+    #[error("In {}: {source}", path.display())]
+    Include {
+        path: PathBuf,
+        source: Box<ParseError>,
+    },
+}
+
+impl ParseError {
+    /// Returns the 1-based line the error points at, if it has one.
+    ///
+    /// For errors inside included files this is the line in that file.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            ParseError::Pest(e) => Some(match e.line_col {
+                pest::error::LineColLocation::Pos((line, _)) => line,
+                pest::error::LineColLocation::Span((line, _), _) => line,
+            }),
+            ParseError::Incompatible { line, .. }
+            | ParseError::UnknownAttribute { line, .. }
+            | ParseError::IncludesDisabled { line, .. }
+            | ParseError::Encoding { line, .. } => Some(*line),
+            ParseError::InvalidDate { span, .. } | ParseError::KeywordCase { span, .. } => {
+                Some(span.line)
+            }
+            ParseError::DuplicateProject { line, .. } | ParseError::TooManyTasks { line, .. } => {
+                Some(*line)
+            }
+            ParseError::Strict(warning) => Some(warning.line),
+            ParseError::Include { source, .. } => source.line(),
+            ParseError::Io(_)
+            | ParseError::UnsupportedVersion { .. }
+            | ParseError::IncludeCycle { .. }
+            | ParseError::InputTooLarge { .. }
+            | ParseError::IncludeTooDeep { .. } => None,
+        }
+    }
+
+    /// Returns the 1-based column the error points at, if it is known.
+    pub fn column(&self) -> Option<usize> {
+        self.position().map(|(_, column, _)| column)
+    }
+
+    /// Line, column and width in characters of the text the error points
+    /// at, for errors that know their column.
+    pub(crate) fn position(&self) -> Option<(usize, usize, usize)> {
+        match self {
+            ParseError::Pest(e) => Some(match e.line_col {
+                pest::error::LineColLocation::Pos((line, column)) => (line, column, 1),
+                pest::error::LineColLocation::Span((line, start), (end_line, end)) => {
+                    let width = if end_line == line && end > start {
+                        end - start
+                    } else {
+                        1
+                    };
+                    (line, start, width)
+                }
+            }),
+            ParseError::InvalidDate { span, value, .. } => {
+                Some((span.line, span.column, value.chars().count()))
+            }
+            ParseError::KeywordCase { span, found, .. } => {
+                Some((span.line, span.column, found.chars().count()))
+            }
+            ParseError::Include { source, .. } => source.position(),
+            _ => None,
+        }
+    }
+
+    /// Returns the error behind any `include` layers, with the included
+    /// file it comes from (`None` if it comes from the main input).
+    pub fn innermost(&self) -> (Option<&Path>, &ParseError) {
+        match self {
+            ParseError::Include { path, source } => match source.innermost() {
+                (None, inner) => (Some(path), inner),
+                found => found,
+            },
+            other => (None, other),
+        }
+    }
+
+    /// Renders the error like a compiler diagnostic: the message, the
+    /// location, the offending line with the line before it, and a caret
+    /// under the error column when it is known.
+    ///
+    /// # Arguments
+    /// * `source` - Text the error points into; for errors inside included
+    ///   files, the text of the file named by [`ParseError::innermost`]
+    /// * `name` - Name of the source shown in the location, e.g. its path
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::ToDoParser;
+    ///
+    /// let source = "project \"P\" {\n  todo: \"A\", due: 2025-02-30,\n}";
+    /// let err = ToDoParser::parse_document(source).unwrap_err();
+    /// assert_eq!(
+    ///     err.render(source, "tasks.todo"),
+    ///     "invalid date `2025-02-30`: February 2025 has 28 days
+    ///  --> tasks.todo:2:19
+    ///   |
+    /// 1 | project \"P\" {
+    /// 2 |   todo: \"A\", due: 2025-02-30,
+    ///   |                   ^^^^^^^^^^
+    /// "
+    /// );
+    /// # Ok::<(), to_do_parcer::ParseError>(())
+    /// ```
+    pub fn render(&self, source: &str, name: &str) -> String {
+        let (_, inner) = self.innermost();
+        let normalized = normalize_source(source);
+        let source = normalized.as_ref();
+        let mut out = format!("{}\n", inner.short_message());
+        let Some(line) = inner.line() else {
+            return out;
+        };
+        let Some(text) = source.lines().nth(line - 1) else {
+            return out;
+        };
+        let position = inner.position();
+        let gutter = " ".repeat(line.to_string().len());
+
+        let _ = match position {
+            Some((_, column, _)) => writeln!(out, "{}--> {}:{}:{}", gutter, name, line, column),
+            None => writeln!(out, "{}--> {}:{}", gutter, name, line),
+        };
+        let _ = writeln!(out, "{} |", gutter);
+        if line > 1
+            && let Some(before) = source.lines().nth(line - 2)
+        {
+            let number = format!("{:>width$}", line - 1, width = gutter.len());
+            let _ = writeln!(out, "{} | {}", number, before);
+        }
+        let _ = writeln!(out, "{} | {}", line, text);
+        if let Some((_, column, width)) = position {
+            // Keep tabs so the caret lines up with the text above it.
+            let padding: String = text
+                .chars()
+                .take(column.saturating_sub(1))
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            let _ = writeln!(out, "{} | {}{}", gutter, padding, "^".repeat(width));
+        }
+        out
+    }
+
+    /// Returns `true` if the error comes from reading a file rather than
+    /// from its contents, including files reached through `include`.
+    pub fn is_io(&self) -> bool {
+        match self {
+            ParseError::Io(_) => true,
+            ParseError::Include { source, .. } => source.is_io(),
+            _ => false,
+        }
+    }
+
+    /// Returns a one-line description of the error without its location,
+    /// for output that reports the line separately.
+    pub fn short_message(&self) -> String {
+        match self {
+            ParseError::Pest(e) => e.variant.message().into_owned(),
+            ParseError::Incompatible { message, .. } => message.clone(),
+            ParseError::UnknownAttribute {
+                name, suggestion, ..
+            } => format!("unknown attribute `{}`{}", name, did_you_mean(suggestion)),
+            ParseError::InvalidDate { value, reason, .. } => {
+                format!("invalid date `{}`: {}", value, reason)
+            }
+            ParseError::KeywordCase {
+                found, expected, ..
+            } => format!("keyword `{}` must be written `{}`", found, expected),
+            ParseError::TooManyTasks { project, max, .. } => {
+                format!("project \"{}\" has more than {} tasks", project, max)
+            }
+            ParseError::DuplicateProject {
+                name, first_line, ..
+            } => format!(
+                "project \"{}\" is already defined on line {}",
+                name, first_line
+            ),
+            ParseError::Strict(warning) => format!("{} (strict mode)", warning.message),
+            ParseError::IncludesDisabled { path, .. } => {
+                format!("includes are disabled (`include \"{}\"`)", path)
+            }
+            ParseError::Encoding { offset, .. } => format!("invalid UTF-8 at byte {}", offset),
+            ParseError::Include { path, source } => {
+                format!("in {}: {}", path.display(), source.short_message())
+            }
+            other => other.to_string(),
+        }
+    }
+
+    /// The error as a [`Diagnostic`] with code `parse`, for reporting it
+    /// alongside the findings of `validate` and `lint`.
+    ///
+    /// Spans and lines of errors inside included files point into those
+    /// files.
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::ToDoParser;
+    ///
+    /// let err = ToDoParser::parse_document("project \"P\" { todo: \"A\", asign: @me, }")
+    ///     .unwrap_err();
+    /// let diagnostic = err.diagnostic();
+    /// assert_eq!(diagnostic.suggestion.as_deref(), Some("assign"));
+    /// assert_eq!(
+    ///     diagnostic.to_string(),
+    ///     "1: error[parse]: unknown attribute `asign`, did you mean `assign`?"
+    /// );
+    /// ```
+    pub fn diagnostic(&self) -> Diagnostic {
+        let (_, inner) = self.innermost();
+        let suggestion = match inner {
+            ParseError::UnknownAttribute { suggestion, .. } => suggestion.map(str::to_string),
+            ParseError::KeywordCase { expected, .. } => Some(expected.clone()),
+            _ => None,
+        };
+        let span = match inner {
+            ParseError::InvalidDate { span, .. } | ParseError::KeywordCase { span, .. } => {
+                Some(*span)
+            }
+            ParseError::Pest(e) => {
+                let (start, end) = match e.location {
+                    pest::error::InputLocation::Pos(pos) => (pos, pos),
+                    pest::error::InputLocation::Span(span) => span,
+                };
+                self.position().map(|(line, column, _)| Span {
+                    start,
+                    end,
+                    line,
+                    column,
+                })
+            }
+            _ => None,
+        };
+        Diagnostic {
+            severity: Severity::Error,
+            code: "parse",
+            line: self.line().unwrap_or(0),
+            span,
+            message: self.short_message(),
+            suggestion,
+            fix: None,
+        }
+    }
+}
+
+/// `, did you mean `x`?` for a suggested keyword, or nothing.
+fn did_you_mean(suggestion: &Option<&str>) -> String {
+    match suggestion {
+        Some(keyword) => format!(", did you mean `{}`?", keyword),
+        None => String::new(),
+    }
+}
+
+/// A recoverable problem found while parsing.
+///
+/// Warnings are collected in `Document::warnings`, or turned into
+/// `ParseError::Strict` when `ParseOptions::strict` is set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseWarning {
+    /// Included file the warning comes from; `None` for the main input.
+    pub file: Option<PathBuf>,
+    /// 1-based line number.
+    pub line: usize,
+    pub message: String,
+}
+
+impl ParseWarning {
+    /// The warning as a [`Diagnostic`] with code `parse-warning`.
+    pub fn diagnostic(&self) -> Diagnostic {
+        let message = match &self.file {
+            Some(file) => format!("in {}: {}", file.display(), self.message),
+            None => self.message.clone(),
+        };
+        Diagnostic::new(Severity::Warning, "parse-warning", self.line, message)
+    }
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(
+                f,
+                "{}, line {}: {}",
+                file.display(),
+                self.line,
+                self.message
+            ),
+            None => write!(f, "Line {}: {}", self.line, self.message),
+        }
+    }
+}
+
+/// Format version assumed for files without a `version` pragma.
+pub const DEFAULT_VERSION: u32 = 1;
+
+/// Format versions this parser understands.
+pub const SUPPORTED_VERSIONS: std::ops::RangeInclusive<u32> = 1..=2;
+
+/// Location of a node in the source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct Span {
+    /// Byte offset of the first character.
+    pub start: usize,
+    /// Byte offset just past the last character.
+    pub end: usize,
+    /// 1-based line of `start`.
+    pub line: usize,
+    /// 1-based column of `start`.
+    pub column: usize,
+}
+
+impl From<pest::Span<'_>> for Span {
+    /// Finds the line and column by scanning the input from its start;
+    /// converting a [`Pair`] is much faster for positions deep in a file.
+    fn from(span: pest::Span<'_>) -> Self {
+        let (line, column) = span.start_pos().line_col();
+        Span {
+            start: span.start(),
+            end: span.end(),
+            line,
+            column,
+        }
+    }
+}
+
+impl From<&Pair<'_, Rule>> for Span {
+    /// Location of `pair`, with the line and column looked up in the
+    /// line index its parse built.
+    fn from(pair: &Pair<'_, Rule>) -> Self {
+        let span = pair.as_span();
+        let (line, column) = pair.line_col();
+        Span {
+            start: span.start(),
+            end: span.end(),
+            line,
+            column,
+        }
+    }
+}
+
+/// A matched grammar rule as the walk over a parsed file sees it: a pest
+/// [`Pair`], or with the `fast-parser` feature a node of the hand-written
+/// parser. The methods mirror those of `Pair`.
+pub(crate) trait SyntaxNode<'i>: Clone {
+    /// Iterator over the rules matched inside this one.
+    type Inner: Iterator<Item = Self>;
+
+    fn as_rule(&self) -> Rule;
+    fn as_str(&self) -> &'i str;
+    /// Byte offset of the start of the match.
+    fn start(&self) -> usize;
+    /// Byte offset just past the end of the match.
+    fn end(&self) -> usize;
+    /// The whole text that was parsed.
+    fn input(&self) -> &'i str;
+    fn into_inner(self) -> Self::Inner;
+}
+
+impl<'i> SyntaxNode<'i> for Pair<'i, Rule> {
+    type Inner = Pairs<'i, Rule>;
+
+    fn as_rule(&self) -> Rule {
+        Pair::as_rule(self)
+    }
+
+    fn as_str(&self) -> &'i str {
+        Pair::as_str(self)
+    }
+
+    fn start(&self) -> usize {
+        self.as_span().start()
+    }
+
+    fn end(&self) -> usize {
+        self.as_span().end()
+    }
+
+    fn input(&self) -> &'i str {
+        self.get_input()
+    }
+
+    fn into_inner(self) -> Pairs<'i, Rule> {
+        Pair::into_inner(self)
+    }
+}
+
+/// The root node of the AST: everything parsed from a single input.
+#[derive(Debug, Clone)]
+pub struct Document {
+    /// Format version from the `version` pragma, or `DEFAULT_VERSION`.
+    pub version: u32,
+    pub projects: Vec<Project>,
+    /// `include` directives of the main input, in source order.
+    pub includes: Vec<IncludeDirective>,
+    /// Comments after the last project.
+    pub trailing_comments: Vec<String>,
+    /// Recoverable problems found while parsing.
+    pub warnings: Vec<ParseWarning>,
+}
+
+/// An `include "file"` directive of the main input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncludeDirective {
+    /// Path as written in the directive.
+    pub path: String,
+    /// Number of entries in `Document::projects` that precede the directive.
+    pub position: usize,
+    /// Comments on the lines before the directive.
+    pub comments: Vec<String>,
+    pub span: Span,
 }
 
 /// A project node in the AST containing the main things: a
@@ -28,6 +546,16 @@ pub enum ParseError {
 pub struct Project {
     pub name: String,
     pub tasks: Vec<Task>,
+    /// Location of the whole `project` block.
+    pub span: Span,
+    /// Included file the project comes from; `None` for the main input.
+    pub file: Option<PathBuf>,
+    /// Comments on the lines before the project.
+    pub comments: Vec<String>,
+    /// Comments after the last task, before the closing brace.
+    pub footer_comments: Vec<String>,
+    /// Typed data attached by analyzers; empty after parsing.
+    pub extensions: Extensions,
 }
 
 /// A task node in the AST representing an individual task with its attributes.
@@ -35,71 +563,238 @@ pub struct Project {
 pub struct Task {
     pub status: TaskStatus,
     pub title: String,
+    /// Identifier from `id:`, selectable as `#id` on the command line.
+    pub id: Option<String>,
     pub priority: Option<Priority>,
+    /// Start date from `start:`.
+    pub start_date: Option<String>,
+    /// Location of the `start:` attribute, if it comes from source text.
+    pub start_span: Option<Span>,
     pub due_date: Option<String>,
+    /// Location of the `due:` attribute, if it comes from source text.
+    pub due_span: Option<Span>,
+    /// Completion date from `completed:`.
+    pub completed: Option<String>,
+    /// Handle from `assign:`, without `@`; lowercase when parsed with
+    /// `ParseOptions::ignore_assignee_case`.
     pub assignee: Option<String>,
+    /// The handle as the file spells it, when that differs from `assignee`.
+    pub assignee_as_written: Option<String>,
     pub depends_on: Option<String>,
-    pub tags: Vec<String>,
+    /// Location of the `depends_on` attribute, if it comes from source text.
+    pub depends_on_span: Option<Span>,
+    pub tags: Tags,
+    /// Location of the task, from its keyword to its trailing comma.
+    pub span: Span,
+    /// 0-based position of the task among all tasks of the document, in
+    /// the order they appear in the source, with the tasks of included
+    /// files where the `include` is. Sorting, grouping and merging
+    /// duplicate projects move tasks but never change it; 0 for tasks not
+    /// parsed from text.
+    pub order: usize,
+    /// Comments on the lines before the task (and any inside it).
+    pub comments: Vec<String>,
+    /// Comment following the task on the same line.
+    pub trailing_comment: Option<String>,
+    /// Typed data attached by analyzers; empty after parsing.
+    pub extensions: Extensions,
 }
 
 /// The status of a task, either Todo or Done.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum TaskStatus {
     Todo,
     Done,
 }
 
 /// The priority level of a task.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Priority {
     High,
     Medium,
     Low,
 }
 
-impl Project {
-    /// Display the project and its tasks in a normal format.
+impl TaskStatus {
+    /// Looks up a status by keyword (`todo` or `done`), ignoring case.
+    pub fn from_name(name: &str) -> Option<TaskStatus> {
+        [TaskStatus::Todo, TaskStatus::Done]
+            .into_iter()
+            .find(|s| s.as_str().eq_ignore_ascii_case(name))
+    }
+
+    /// Keyword of the status as written in the DSL, without the colon.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskStatus::Todo => "todo",
+            TaskStatus::Done => "done",
+        }
+    }
+}
+
+impl Priority {
+    /// Looks up a priority by name (`high`, `medium` or `low`), ignoring case.
+    pub fn from_name(name: &str) -> Option<Priority> {
+        [Priority::High, Priority::Medium, Priority::Low]
+            .into_iter()
+            .find(|p| p.as_str().eq_ignore_ascii_case(name))
+    }
+
+    /// Name of the priority as written in the DSL, without the `@`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::High => "high",
+            Priority::Medium => "medium",
+            Priority::Low => "low",
+        }
+    }
+}
+
+impl Task {
+    /// Creates a task without attributes that doesn't come from any source text.
+    pub fn new(status: TaskStatus, title: impl Into<String>) -> Task {
+        Task {
+            status,
+            title: title.into(),
+            id: None,
+            priority: None,
+            start_date: None,
+            start_span: None,
+            due_date: None,
+            due_span: None,
+            completed: None,
+            assignee: None,
+            assignee_as_written: None,
+            depends_on: None,
+            depends_on_span: None,
+            tags: Tags::new(),
+            span: Span::default(),
+            order: 0,
+            comments: Vec::new(),
+            trailing_comment: None,
+            extensions: Extensions::default(),
+        }
+    }
+
+    /// The assignee as it should be shown: spelled as in the file.
     ///
     /// # Example
-    /// ``````
-    /// project.display();
     /// ```
-    pub fn display(&self) {
-        println!("Project: {}\n", self.name);
+    /// use to_do_parcer::options::ParseOptions;
+    /// use to_do_parcer::ToDoParser;
+    ///
+    /// let options = ParseOptions {
+    ///     ignore_assignee_case: true,
+    ///     ..ParseOptions::default()
+    /// };
+    /// let doc = ToDoParser::parse_with(r#"project "P" { todo: "A", assign: @Alice, }"#, &options)?;
+    /// let task = &doc.projects[0].tasks[0];
+    /// assert_eq!(task.assignee.as_deref(), Some("alice"));
+    /// assert_eq!(task.assignee_display(), Some("Alice"));
+    /// # Ok::<(), to_do_parcer::ParseError>(())
+    /// ```
+    pub fn assignee_display(&self) -> Option<&str> {
+        self.assignee_as_written
+            .as_deref()
+            .or(self.assignee.as_deref())
+    }
+}
 
-        for task in &self.tasks {
-            let status = match task.status {
-                TaskStatus::Todo => "[TODO]",
-                TaskStatus::Done => "[DONE]",
-            };
-            println!("{} {}", status, task.title);
+impl Default for Document {
+    fn default() -> Self {
+        Document {
+            version: DEFAULT_VERSION,
+            projects: Vec::new(),
+            includes: Vec::new(),
+            trailing_comments: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+}
 
-            if let Some(priority) = &task.priority {
-                let p = match priority {
-                    Priority::High => "High",
-                    Priority::Medium => "Medium",
-                    Priority::Low => "Low",
-                };
-                println!("       Priority: {}", p);
-            }
+impl Document {
+    /// Iterates over every task in the document together with its project,
+    /// in source order.
+    pub fn tasks(&self) -> impl Iterator<Item = (&Project, &Task)> {
+        self.projects
+            .iter()
+            .flat_map(|p| p.tasks.iter().map(move |t| (p, t)))
+    }
+}
 
-            if let Some(due) = &task.due_date {
-                println!("       Due: {}", due);
-            }
+impl Project {
+    /// Creates an empty project that doesn't come from any source text.
+    pub fn new(name: impl Into<String>) -> Project {
+        Project {
+            name: name.into(),
+            tasks: Vec::new(),
+            span: Span::default(),
+            file: None,
+            comments: Vec::new(),
+            footer_comments: Vec::new(),
+            extensions: Extensions::default(),
+        }
+    }
 
-            if let Some(assignee) = &task.assignee {
-                println!("       Assigned to: @{}", assignee);
-            }
+    /// Display the project and its tasks in a normal format.
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::ToDoParser;
+    ///
+    /// let projects = ToDoParser::parse_projects(r#"project "Demo" { todo: "Task", }"#).unwrap();
+    /// projects[0].display();
+    /// ```
+    pub fn display(&self) {
+        self.display_with(&Palette::default());
+    }
 
-            if let Some(depends) = &task.depends_on {
-                println!("       Depends on: {}", depends);
-            }
+    /// Display the project like `display`, coloring statuses, priorities
+    /// and overdue dates with `palette`.
+    ///
+    /// Stdout is locked and buffered once for the whole project.
+    pub fn display_with(&self, palette: &Palette) {
+        let mut out = io::BufWriter::new(io::stdout().lock());
+        let _ = write!(out, "{}", Rendered(self, palette));
+        let _ = out.flush();
+    }
 
-            for tag in &task.tags {
-                println!("       Tag: {}", tag);
-            }
+    /// Returns the text `display_with` prints.
+    pub fn render_with(&self, palette: &Palette) -> String {
+        let mut out = String::new();
+        let _ = self.render_colored(&mut out, palette);
+        out
+    }
 
-            println!();
+    /// Writes the text `display` prints to `w`, without building
+    /// intermediate strings.
+    ///
+    /// # Arguments
+    /// * `w` - Destination, such as a `String` or a `fmt::Formatter`
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::ToDoParser;
+    ///
+    /// let projects = ToDoParser::parse_projects(r#"project "Demo" { todo: "Task", }"#).unwrap();
+    /// let mut out = String::new();
+    /// projects[0].render(&mut out)?;
+    /// assert!(out.starts_with("Project: Demo\n\n[TODO] Task\n"));
+    /// # Ok::<(), std::fmt::Error>(())
+    /// ```
+    pub fn render(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        self.render_colored(w, &Palette::default())
+    }
+
+    /// Writes the text `display_with` prints to `w`.
+    ///
+    /// # Arguments
+    /// * `w` - Destination, such as a `String` or a `fmt::Formatter`
+    /// * `palette` - Colors and date format to use
+    pub fn render_colored(&self, w: &mut impl fmt::Write, palette: &Palette) -> fmt::Result {
+        writeln!(w, "Project: {}\n", self.name)?;
+        for task in &self.tasks {
+            task.render_colored(w, palette)?;
         }
 
         let total = self.tasks.len();
@@ -110,11 +805,73 @@ impl Project {
             .count();
         let active = total - completed;
 
-        println!("-----------------------------------");
-        println!(
+        writeln!(w, "-----------------------------------")?;
+        writeln!(
+            w,
             "Total: {} tasks ({} active, {} completed)",
             total, active, completed
-        );
+        )
+    }
+}
+
+impl Task {
+    /// Writes the status line and attributes of the task, as part of
+    /// [`Project::render_colored`].
+    fn render_colored(&self, w: &mut impl fmt::Write, palette: &Palette) -> fmt::Result {
+        let status = match self.status {
+            TaskStatus::Todo => "[TODO]",
+            TaskStatus::Done => "[DONE]",
+        };
+        palette.write_painted(w, Style::status(self.status), status)?;
+        writeln!(w, " {}", self.title)?;
+
+        if let Some(priority) = &self.priority {
+            let p = match priority {
+                Priority::High => "High",
+                Priority::Medium => "Medium",
+                Priority::Low => "Low",
+            };
+            w.write_str("       Priority: ")?;
+            palette.write_painted(w, Style::priority(*priority), p)?;
+            w.write_char('\n')?;
+        }
+
+        if let Some(start) = &self.start_date {
+            writeln!(w, "       Start: {}", start)?;
+        }
+
+        if let Some(due) = &self.due_date {
+            w.write_str("       Due: ")?;
+            palette.write_due(w, self, due)?;
+            if palette.is_overdue(self) {
+                w.write_str(" (overdue)")?;
+            }
+            w.write_char('\n')?;
+        }
+
+        if let Some(assignee) = &self.assignee {
+            writeln!(w, "       Assigned to: @{}", assignee)?;
+        }
+
+        if let Some(depends) = &self.depends_on {
+            writeln!(w, "       Depends on: {}", depends)?;
+        }
+
+        for tag in &self.tags {
+            writeln!(w, "       Tag: {}", tag)?;
+        }
+
+        w.write_char('\n')
+    }
+}
+
+/// A project as `display_with` shows it, for writing straight to an
+/// [`io::Write`] with `write!`.
+struct Rendered<'a>(&'a Project, &'a Palette);
+
+impl fmt::Display for Rendered<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.render_colored(f, self.1)
     }
 }
 
@@ -131,29 +888,124 @@ impl ToDoParser {
     ///
     /// # Example
     /// ```
-    /// let projects = ToDoParser::parse_projects(input)?;
+    /// use to_do_parcer::ToDoParser;
+    ///
+    /// let projects = ToDoParser::parse_projects(r#"project "Demo" { todo: "Task", }"#)?;
+    /// assert_eq!(projects[0].tasks.len(), 1);
+    /// # Ok::<(), to_do_parcer::ParseError>(())
     /// ```
     pub fn parse_projects(input: &str) -> Result<Vec<Project>, ParseError> {
-        let pairs = Self::parse(Rule::file, input).map_err(|e| ParseError::Pest(Box::new(e)))?;
-        let mut projects = Vec::new();
-
-        for pair in pairs {
-            match pair.as_rule() {
-                Rule::file => {
-                    for inner in pair.into_inner() {
-                        if inner.as_rule() == Rule::project {
-                            projects.push(parse_project_pair(inner));
-                        }
-                    }
-                }
-                Rule::project => {
-                    projects.push(parse_project_pair(pair));
-                }
-                _ => {}
-            }
-        }
+        Self::parse_document(input).map(|doc| doc.projects)
+    }
+
+    /// Parse a whole input string into a `Document`.
+    ///
+    /// # Arguments
+    /// * `input` - Text representation of the projects and tasks
+    ///
+    /// # Returns
+    /// * `Ok(Document)` on success
+    /// * `Err(ParseError)` if parsing fails
+    pub fn parse_document(input: &str) -> Result<Document, ParseError> {
+        Self::parse_with(input, &ParseOptions::default())
+    }
+
+    /// Parse a whole input string into a `Document` using custom options.
+    ///
+    /// # Arguments
+    /// * `input` - Text representation of the projects and tasks
+    /// * `options` - Strictness, allowed priorities, include handling, etc.
+    ///
+    /// # Returns
+    /// * `Ok(Document)` on success
+    /// * `Err(ParseError)` if parsing fails
+    pub fn parse_with(input: &str, options: &ParseOptions) -> Result<Document, ParseError> {
+        let mut builder = DocumentBuilder::default();
+        Self::parse_events_with(input, options, &mut builder)?;
+        let mut doc = builder.finish();
+        apply_duplicate_project_policy(&mut doc, options.duplicate_projects)?;
+        Ok(doc)
+    }
+
+    /// Parse an input string, streaming projects and tasks to `sink`
+    /// instead of building a `Document`.
+    ///
+    /// # Arguments
+    /// * `input` - Text representation of the projects and tasks
+    /// * `sink` - Receiver of `Version`/`ProjectStart`/`TaskParsed`/`ProjectEnd` events
+    ///
+    /// # Returns
+    /// * `Ok(())` once the whole input was streamed
+    /// * `Err(ParseError)` if parsing fails. Syntax errors are reported
+    ///   before any event is emitted; semantic errors (such as syntax not
+    ///   allowed by the file's version) may follow earlier events.
+    pub fn parse_events(input: &str, sink: &mut impl TodoSink) -> Result<(), ParseError> {
+        Self::parse_events_with(input, &ParseOptions::default(), sink)
+    }
 
-        Ok(projects)
+    /// Same as [`ToDoParser::parse_events`], using custom options.
+    pub fn parse_events_with(
+        input: &str,
+        options: &ParseOptions,
+        sink: &mut impl TodoSink,
+    ) -> Result<(), ParseError> {
+        let bytes_read = Cell::new(0);
+        let mut ctx = Context {
+            options,
+            version: DEFAULT_VERSION,
+            file: None,
+            base_dir: options.base_dir.clone().unwrap_or_default(),
+            include_stack: Vec::new(),
+            bytes_read: &bytes_read,
+            origin: Origin::default(),
+            locator: Cell::default(),
+        };
+        let started = Instant::now();
+        let result = emit_file(input, &mut ctx, sink, true);
+        log::info!(
+            "parsed {} bytes in {:.2?}{}",
+            input.len(),
+            started.elapsed(),
+            if result.is_ok() { "" } else { " (failed)" }
+        );
+        result
+    }
+
+    /// Parses one chunk of a file, streaming its events to `sink` with
+    /// positions and warnings as in the whole file.
+    ///
+    /// # Arguments
+    /// * `chunk` - Text starting at the beginning of a line of the file,
+    ///   with no `version` pragma unless it is the first chunk
+    /// * `origin` - Where `chunk` starts in the file
+    /// * `version` - Format version of the file, or `None` for the first
+    ///   chunk, which reports it with a `Version` event
+    /// * `options` - Strictness, allowed priorities, include handling, etc.
+    /// * `bytes_read` - Bytes of input parsed so far, for `Limits::max_input_bytes`
+    /// * `sink` - Receiver of the events
+    ///
+    /// # Returns
+    /// * The format version of the file
+    pub(crate) fn parse_chunk_events(
+        chunk: &str,
+        origin: Origin,
+        version: Option<u32>,
+        options: &ParseOptions,
+        bytes_read: &Cell<usize>,
+        sink: &mut impl TodoSink,
+    ) -> Result<u32, ParseError> {
+        let mut ctx = Context {
+            options,
+            version: version.unwrap_or(DEFAULT_VERSION),
+            file: None,
+            base_dir: options.base_dir.clone().unwrap_or_default(),
+            include_stack: Vec::new(),
+            bytes_read,
+            origin,
+            locator: Cell::default(),
+        };
+        emit_file(chunk, &mut ctx, sink, version.is_none())?;
+        Ok(ctx.version)
     }
 
     /// Parse projects from a file into structured data.
@@ -166,37 +1018,701 @@ impl ToDoParser {
     /// * `Err(ParseError)` if reading or parsing fails
     ///
     /// # Example
-    /// ```
+    /// ```no_run
+    /// use to_do_parcer::ToDoParser;
+    ///
     /// let projects = ToDoParser::parse_from_file("tasks.txt")?;
+    /// # Ok::<(), to_do_parcer::ParseError>(())
     /// ```
     pub fn parse_from_file(path: &str) -> Result<Vec<Project>, ParseError> {
-        let content = std::fs::read_to_string(path)?;
-        Self::parse_projects(&content)
+        let options = ParseOptions {
+            includes: IncludePolicy::Resolve,
+            ..ParseOptions::default()
+        };
+        Self::parse_file_with(path, &options).map(|doc| doc.projects)
+    }
+
+    /// Parse a file into a `Document` using custom options.
+    ///
+    /// Relative includes are resolved against the file's directory unless
+    /// `options.base_dir` is set.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the input file
+    /// * `options` - Strictness, allowed priorities, include handling, etc.
+    ///
+    /// # Returns
+    /// * `Ok(Document)` on success
+    /// * `Err(ParseError)` if reading or parsing fails
+    pub fn parse_file_with(path: &str, options: &ParseOptions) -> Result<Document, ParseError> {
+        log::debug!("reading {}", path);
+        check_file_size(Path::new(path), 0, options)?;
+        let (content, replaced) = decode_source(std::fs::read(path)?, options.lossy)?;
+        Self::parse_file_contents(path, &content, replaced, options)
+    }
+
+    /// Parses `content`, read from `path`, the way `parse_file_with` does.
+    ///
+    /// # Arguments
+    /// * `path` - File the text was read from; includes are resolved
+    ///   against its directory unless `options.base_dir` is set
+    /// * `content` - Text of the file
+    /// * `replaced` - Warning from [`decode_source`], if it replaced
+    ///   invalid UTF-8
+    /// * `options` - Strictness, allowed priorities, include handling, etc.
+    pub(crate) fn parse_file_contents(
+        path: &str,
+        content: &str,
+        replaced: Option<ParseWarning>,
+        options: &ParseOptions,
+    ) -> Result<Document, ParseError> {
+        let mut options = options.clone();
+        if options.base_dir.is_none() {
+            options.base_dir = Path::new(path).parent().map(Path::to_path_buf);
+        }
+        let mut doc = Self::parse_with(content, &options)?;
+        if let Some(warning) = replaced {
+            if options.strict {
+                return Err(ParseError::Strict(warning));
+            }
+            doc.warnings.insert(0, warning);
+        }
+        Ok(doc)
     }
 }
 
-/// Converts a Pest `project` pair into a `Project` struct.
+/// Decodes the contents of a file as UTF-8.
 ///
 /// # Arguments
-/// * `pair` - A Pest `Pair` representing a project
+/// * `bytes` - Contents of the file
+/// * `lossy` - Replace invalid sequences with U+FFFD instead of failing
 ///
 /// # Returns
-/// * `Project` -- struct with parsed data
-fn parse_project_pair(pair: Pair<Rule>) -> Project {
-    let mut project_name = String::new();
-    let mut tasks = Vec::new();
+/// * The text, with a warning pointing at the first invalid byte when
+///   `lossy` replaced any
+/// * `Err(ParseError::Encoding)` for invalid UTF-8 unless `lossy`
+///
+/// # Example
+/// ```
+/// use to_do_parcer::parser::decode_source;
+/// use to_do_parcer::ParseError;
+///
+/// let bytes = b"project \"Caf\xe9\" {\n}\n".to_vec();
+/// assert!(matches!(
+///     decode_source(bytes.clone(), false),
+///     Err(ParseError::Encoding { line: 1, offset: 12 })
+/// ));
+/// let (text, warning) = decode_source(bytes, true)?;
+/// assert_eq!(text, "project \"Caf\u{fffd}\" {\n}\n");
+/// assert_eq!(warning.unwrap().line, 1);
+/// # Ok::<(), ParseError>(())
+/// ```
+pub fn decode_source(
+    bytes: Vec<u8>,
+    lossy: bool,
+) -> Result<(String, Option<ParseWarning>), ParseError> {
+    let error = match String::from_utf8(bytes) {
+        Ok(text) => return Ok((text, None)),
+        Err(e) => e,
+    };
+    let offset = error.utf8_error().valid_up_to();
+    let bytes = error.into_bytes();
+    let line = bytes[..offset].iter().filter(|&&b| b == b'\n').count() + 1;
+    if !lossy {
+        return Err(ParseError::Encoding { line, offset });
+    }
+    let warning = ParseWarning {
+        file: None,
+        line,
+        message: format!("invalid UTF-8 from byte {} replaced with U+FFFD", offset),
+    };
+    Ok((String::from_utf8_lossy(&bytes).into_owned(), Some(warning)))
+}
+
+/// Fails on, or merges, `project` blocks that reuse an earlier name.
+pub(crate) fn apply_duplicate_project_policy(
+    doc: &mut Document,
+    policy: DuplicateProjectPolicy,
+) -> Result<(), ParseError> {
+    if policy == DuplicateProjectPolicy::KeepSeparate {
+        return Ok(());
+    }
+    let mut index = 1;
+    while index < doc.projects.len() {
+        let Some(first) = doc.projects[..index]
+            .iter()
+            .position(|p| p.name == doc.projects[index].name)
+        else {
+            index += 1;
+            continue;
+        };
+        let duplicate = doc.projects.remove(index);
+        if policy == DuplicateProjectPolicy::Error {
+            return Err(ParseError::DuplicateProject {
+                line: duplicate.span.line,
+                name: duplicate.name,
+                first_line: doc.projects[first].span.line,
+            });
+        }
+        log::debug!(
+            "merging project \"{}\" from line {} into line {}",
+            duplicate.name,
+            duplicate.span.line,
+            doc.projects[first].span.line
+        );
+        let target = &mut doc.projects[first];
+        let mut tasks = duplicate.tasks;
+        // Comments above the block go with its first task, or the footer.
+        match tasks.first_mut() {
+            Some(task) => {
+                let mut comments = duplicate.comments;
+                comments.append(&mut task.comments);
+                task.comments = comments;
+            }
+            None => target.footer_comments.extend(duplicate.comments),
+        }
+        target.tasks.append(&mut tasks);
+        target.footer_comments.extend(duplicate.footer_comments);
+        for include in &mut doc.includes {
+            if include.position > index {
+                include.position -= 1;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// State shared by the helpers while walking one file.
+struct Context<'o> {
+    options: &'o ParseOptions,
+    /// Format version of the file being walked.
+    version: u32,
+    /// Included file being walked; `None` for the main input.
+    file: Option<PathBuf>,
+    /// Directory relative includes are resolved against.
+    base_dir: PathBuf,
+    /// Canonical paths of the files currently being included, outermost first.
+    include_stack: Vec<PathBuf>,
+    /// Bytes of input walked so far, over all files.
+    bytes_read: &'o Cell<usize>,
+    /// Where the text being walked starts in the main input, when it is
+    /// one chunk of it.
+    origin: Origin,
+    /// Position of the last node located in the text being walked.
+    locator: Cell<Locator>,
+}
+
+/// Finds the line and column of positions looked up mostly in order,
+/// scanning from the previous position rather than from the start of the
+/// line. `Pair::line_col` counts from the start of the line, which made
+/// walking a line with thousands of attributes quadratic.
+#[derive(Debug, Clone, Copy)]
+struct Locator {
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Default for Locator {
+    fn default() -> Self {
+        Locator {
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+}
+
+impl Locator {
+    /// 1-based line and column of `offset` in `input`, counted like
+    /// `Pair::line_col`.
+    fn seek(&mut self, input: &str, offset: usize) -> (usize, usize) {
+        if offset >= self.offset {
+            let skipped = &input[self.offset..offset];
+            match skipped.rfind('\n') {
+                Some(last) => {
+                    self.line += skipped.bytes().filter(|&b| b == b'\n').count();
+                    self.column = skipped[last + 1..].chars().count() + 1;
+                }
+                None => self.column += skipped.chars().count(),
+            }
+        } else {
+            let skipped = &input[offset..self.offset];
+            let newlines = skipped.bytes().filter(|&b| b == b'\n').count();
+            if newlines == 0 {
+                self.column -= skipped.chars().count();
+            } else {
+                self.line -= newlines;
+                let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+                self.column = input[line_start..offset].chars().count() + 1;
+            }
+        }
+        self.offset = offset;
+        (self.line, self.column)
+    }
+}
+
+/// Where a chunk of a file starts, for parsing it on its own.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Origin {
+    /// Bytes of the file before the chunk.
+    pub(crate) offset: usize,
+    /// Lines of the file before the chunk.
+    pub(crate) lines_before: usize,
+}
+
+impl Context<'_> {
+    /// Line and column of `pair` in the text being walked.
+    fn line_col<'i>(&self, pair: &impl SyntaxNode<'i>) -> (usize, usize) {
+        let mut locator = self.locator.get();
+        let line_col = locator.seek(pair.input(), pair.start());
+        self.locator.set(locator);
+        line_col
+    }
+
+    /// Location of `pair` in the whole file.
+    fn span<'i>(&self, pair: &impl SyntaxNode<'i>) -> Span {
+        let (line, column) = self.line_col(pair);
+        Span {
+            start: pair.start() + self.origin.offset,
+            end: pair.end() + self.origin.offset,
+            line: line + self.origin.lines_before,
+            column,
+        }
+    }
+
+    /// Line of `pair` in the whole file.
+    fn line<'i>(&self, pair: &impl SyntaxNode<'i>) -> usize {
+        self.line_col(pair).0 + self.origin.lines_before
+    }
+
+    /// Moves a syntax error in the text being walked to where it is in
+    /// the whole file.
+    fn locate(&self, error: &mut pest::error::Error<Rule>) {
+        use pest::error::{InputLocation, LineColLocation};
+
+        let Origin {
+            offset,
+            lines_before,
+        } = self.origin;
+        match &mut error.location {
+            InputLocation::Pos(pos) => *pos += offset,
+            InputLocation::Span((start, end)) => {
+                *start += offset;
+                *end += offset;
+            }
+        }
+        match &mut error.line_col {
+            LineColLocation::Pos((line, _)) => *line += lines_before,
+            LineColLocation::Span((start, _), (end, _)) => {
+                *start += lines_before;
+                *end += lines_before;
+            }
+        }
+    }
+
+    /// Adds `len` bytes of input to the total, failing if that goes over
+    /// the limit.
+    fn count_bytes(&self, len: usize) -> Result<(), ParseError> {
+        let total = self.bytes_read.get().saturating_add(len);
+        self.bytes_read.set(total);
+        match self.options.limits.max_input_bytes {
+            Some(max) if total > max => Err(ParseError::InputTooLarge { max }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Records a warning, or fails if parsing in strict mode.
+    fn warn(
+        &self,
+        sink: &mut impl TodoSink,
+        line: usize,
+        message: String,
+    ) -> Result<(), ParseError> {
+        let warning = ParseWarning {
+            file: self.file.clone(),
+            line,
+            message,
+        };
+        if self.options.strict {
+            return Err(ParseError::Strict(warning));
+        }
+        sink.event(Event::Warning(warning));
+        Ok(())
+    }
+}
+
+/// Parses one file (the main input or an included one) and emits its events.
+///
+/// # Arguments
+/// * `input` - Text of the file
+/// * `ctx` - Parsing state; its `version` is set from the file's pragma
+/// * `sink` - Receiver of the emitted events
+/// * `root` - Whether this is the main input, which reports its `Version`
+fn emit_file(
+    input: &str,
+    ctx: &mut Context,
+    sink: &mut impl TodoSink,
+    root: bool,
+) -> Result<(), ParseError> {
+    ctx.count_bytes(input.len())?;
+    let normalized = normalize_source(input);
+    let input = normalized.as_ref();
+    #[cfg(feature = "fast-parser")]
+    match crate::fast::parse(input) {
+        Some(tree) => return emit_items(tree.root(), input, ctx, sink, root),
+        None => log::debug!(
+            "{}: reparsing with pest to report the error",
+            describe_file(ctx)
+        ),
+    }
+    if ctx.options.allow_empty && ToDoParser::parse(Rule::empty_file, input).is_ok() {
+        ctx.version = DEFAULT_VERSION;
+        if root {
+            sink.event(Event::Version(ctx.version));
+        }
+        CommentCursor::new(input, ctx.origin.lines_before).flush_before(input.len(), None, sink);
+        return Ok(());
+    }
+    let mut pairs = ToDoParser::parse(Rule::file, input).map_err(|mut e| {
+        log::debug!(
+            "{}: syntax error: {}",
+            describe_file(ctx),
+            e.variant.message()
+        );
+        ctx.locate(&mut e);
+        ParseError::Pest(Box::new(e))
+    })?;
+    let file = pairs
+        .next()
+        .expect("a successful parse matches the file rule");
+    emit_items(file, input, ctx, sink, root)
+}
+
+/// Walks the top-level items of a file that parsed, from the `version`
+/// pragma to `EOI`, and emits their events.
+///
+/// # Arguments
+/// * `file` - The match of the `file` rule
+/// * `input` - Normalized text of the file
+/// * `ctx` - Parsing state; its `version` is set from the file's pragma
+/// * `sink` - Receiver of the emitted events
+/// * `root` - Whether this is the main input, which reports its `Version`
+fn emit_items<'i, N: SyntaxNode<'i>>(
+    file: N,
+    input: &str,
+    ctx: &mut Context,
+    sink: &mut impl TodoSink,
+    root: bool,
+) -> Result<(), ParseError> {
+    if log::log_enabled!(log::Level::Trace) {
+        trace_pairs(std::iter::once(file.clone()), ctx);
+    }
+    let mut items = file.into_inner().peekable();
+
+    // The grammar only allows the pragma before everything else.
+    ctx.version = match items.next_if(|p| p.as_rule() == Rule::version) {
+        Some(pragma) => {
+            check_keyword(&pragma, "version".len(), ctx)?;
+            parse_version(pragma)?
+        }
+        // A chunk after the first keeps the version of its file.
+        None if ctx.origin.offset > 0 => ctx.version,
+        None => DEFAULT_VERSION,
+    };
+    if root {
+        sink.event(Event::Version(ctx.version));
+    }
+
+    let mut comments = CommentCursor::new(input, ctx.origin.lines_before);
+    for pair in items {
+        comments.flush_before(pair.start(), None, sink);
+        match pair.as_rule() {
+            Rule::project => emit_project(pair, ctx, &mut comments, sink)?,
+            Rule::include => emit_include(pair, ctx, sink)?,
+            _ => {}
+        }
+    }
+    comments.flush_before(input.len(), None, sink);
+
+    Ok(())
+}
+
+/// Names the file being walked in log messages.
+fn describe_file(ctx: &Context) -> String {
+    match &ctx.file {
+        Some(file) => file.display().to_string(),
+        None => "<input>".to_string(),
+    }
+}
+
+/// Logs every matched grammar rule with its position, indented by depth.
+fn trace_pairs<'i, N: SyntaxNode<'i>>(pairs: impl Iterator<Item = N>, ctx: &Context) {
+    fn trace_pair<'i, N: SyntaxNode<'i>>(pair: N, file: &str, locator: &mut Locator, depth: usize) {
+        let (line, column) = locator.seek(pair.input(), pair.start());
+        log::trace!(
+            "{}:{}:{}: {:depth$}matched {:?}",
+            file,
+            line,
+            column,
+            "",
+            pair.as_rule(),
+            depth = depth * 2
+        );
+        for inner in pair.into_inner() {
+            trace_pair(inner, file, locator, depth + 1);
+        }
+    }
+
+    let file = describe_file(ctx);
+    let mut locator = Locator::default();
+    for pair in pairs {
+        trace_pair(pair, &file, &mut locator, 0);
+    }
+}
+
+/// Fails before reading the file at `path` if it would take the input past
+/// the size limit, `already` bytes having been read.
+pub(crate) fn check_file_size(
+    path: &Path,
+    already: usize,
+    options: &ParseOptions,
+) -> Result<(), ParseError> {
+    let Some(max) = options.limits.max_input_bytes else {
+        return Ok(());
+    };
+    let size = std::fs::metadata(path)?.len();
+    if usize::try_from(size).map_or(true, |size| already.saturating_add(size) > max) {
+        return Err(ParseError::InputTooLarge { max });
+    }
+    Ok(())
+}
+
+/// Handles an `include "file"` directive according to the include policy.
+///
+/// # Arguments
+/// * `pair` - A Pest `Pair` representing the directive
+/// * `ctx` - Parsing state of the including file
+/// * `sink` - Receiver of the included file's events
+fn emit_include<'i>(
+    pair: impl SyntaxNode<'i>,
+    ctx: &mut Context,
+    sink: &mut impl TodoSink,
+) -> Result<(), ParseError> {
+    check_keyword(&pair, "include".len(), ctx)?;
+    let span = ctx.span(&pair);
+    let line = span.line;
+    let target = pair
+        .into_inner()
+        .find(|p| p.as_rule() == Rule::quoted)
+        .map(parse_quoted)
+        .unwrap_or_default();
+
+    log::debug!(
+        "{}:{}: include \"{}\" ({:?})",
+        describe_file(ctx),
+        line,
+        target,
+        ctx.options.includes
+    );
+    if ctx.options.includes == IncludePolicy::Error {
+        return Err(ParseError::IncludesDisabled { line, path: target });
+    }
+    if ctx.file.is_none() {
+        sink.event(Event::Include {
+            path: &target,
+            span,
+        });
+    }
+
+    match ctx.options.includes {
+        IncludePolicy::Error | IncludePolicy::Ignore => Ok(()),
+        IncludePolicy::Resolve => {
+            let path = ctx.base_dir.join(&target);
+            let wrap = |source: ParseError| ParseError::Include {
+                path: path.clone(),
+                source: Box::new(source),
+            };
+            let canonical = path.canonicalize().map_err(|e| wrap(e.into()))?;
+            if ctx.include_stack.contains(&canonical) {
+                return Err(ParseError::IncludeCycle { path });
+            }
+            if let Some(max) = ctx.options.limits.max_include_depth
+                && ctx.include_stack.len() >= max
+            {
+                return Err(ParseError::IncludeTooDeep { path, max });
+            }
+            check_file_size(&path, ctx.bytes_read.get(), ctx.options).map_err(wrap)?;
+            let bytes = std::fs::read(&path).map_err(|e| wrap(e.into()))?;
+            let (content, replaced) = decode_source(bytes, ctx.options.lossy).map_err(wrap)?;
+            log::debug!("resolved include to {}", canonical.display());
 
+            let mut nested = Context {
+                options: ctx.options,
+                version: DEFAULT_VERSION,
+                file: Some(path.clone()),
+                base_dir: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+                include_stack: ctx.include_stack.clone(),
+                bytes_read: ctx.bytes_read,
+                origin: Origin::default(),
+                locator: Cell::default(),
+            };
+            nested.include_stack.push(canonical);
+            if let Some(warning) = replaced {
+                nested.warn(sink, warning.line, warning.message)?;
+            }
+            emit_file(&content, &mut nested, sink, false).map_err(|e| match e {
+                // Report cycles once, not wrapped by every file on the way.
+                cycle @ (ParseError::IncludeCycle { .. } | ParseError::IncludeTooDeep { .. }) => {
+                    cycle
+                }
+                other => wrap(other),
+            })
+        }
+    }
+}
+
+/// Walks a Pest `project` pair and emits its events.
+///
+/// # Arguments
+/// * `pair` - A Pest `Pair` representing a project
+/// * `ctx` - Parsing state of the current file
+/// * `comments` - Comments of the current file not emitted yet
+/// * `sink` - Receiver of the emitted events
+fn emit_project<'i>(
+    pair: impl SyntaxNode<'i>,
+    ctx: &Context,
+    comments: &mut CommentCursor,
+    sink: &mut impl TodoSink,
+) -> Result<(), ParseError> {
+    check_keyword(&pair, "project".len(), ctx)?;
+    let span = ctx.span(&pair);
+    let end = pair.end();
+    if let Some(max) = ctx.options.limits.max_tasks_per_project {
+        let tasks = pair
+            .clone()
+            .into_inner()
+            .filter(|p| p.as_rule() == Rule::task);
+        if tasks.count() > max {
+            let name = pair
+                .clone()
+                .into_inner()
+                .find(|p| p.as_rule() == Rule::quoted);
+            return Err(ParseError::TooManyTasks {
+                line: span.line,
+                project: name.map(parse_quoted).unwrap_or_default(),
+                max,
+            });
+        }
+    }
+    let mut last_task_line = None;
     for inner in pair.into_inner() {
         match inner.as_rule() {
-            Rule::quoted => project_name = parse_quoted(inner),
-            Rule::task => tasks.push(parse_task(inner)),
+            Rule::quoted => sink.event(Event::ProjectStart {
+                name: inner.as_str().trim_matches('"'),
+                span,
+                file: ctx.file.as_deref(),
+            }),
+            Rule::task => {
+                // Comments inside a task are moved in front of it.
+                comments.flush_before(inner.end(), last_task_line, sink);
+                let lines = inner.as_str().bytes().filter(|&b| b == b'\n').count();
+                last_task_line = Some(ctx.line(&inner) + lines);
+                let task = parse_task(inner, ctx, sink)?;
+                sink.event(Event::TaskParsed(task));
+            }
             _ => {}
         }
     }
+    comments.flush_before(end, last_task_line, sink);
+    sink.event(Event::ProjectEnd);
+    Ok(())
+}
 
-    Project {
-        name: project_name,
-        tasks,
+/// `// comments` of one file, emitted in source order as the walk passes them.
+///
+/// Comments are silent in the grammar (so they never show up in syntax
+/// errors), so they are found by scanning the text instead. This is only
+/// done after a successful parse, when every `//` outside a quoted string
+/// is known to start a comment.
+struct CommentCursor<'i> {
+    /// Byte offset, line and text of every comment.
+    comments: Vec<(usize, usize, &'i str)>,
+    next: usize,
+}
+
+impl<'i> CommentCursor<'i> {
+    /// Finds the comments of `input`, which follows `lines_before` lines
+    /// of its file.
+    fn new(input: &'i str, lines_before: usize) -> Self {
+        let bytes = input.as_bytes();
+        let mut comments = Vec::new();
+        let (mut i, mut line, mut in_quote) = (0, lines_before + 1, false);
+
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\n' => line += 1,
+                b'"' => in_quote = !in_quote,
+                b'/' if !in_quote && bytes.get(i + 1) == Some(&b'/') => {
+                    let end = input[i..].find('\n').map_or(input.len(), |n| i + n);
+                    comments.push((i, line, input[i..end].trim_end()));
+                    i = end;
+                    continue;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+
+        CommentCursor { comments, next: 0 }
+    }
+
+    /// Emits every comment starting before `offset`.
+    ///
+    /// # Arguments
+    /// * `offset` - Byte offset to stop at
+    /// * `last_task_line` - Line the previous task ended on, to detect trailing comments
+    /// * `sink` - Receiver of the events
+    fn flush_before(
+        &mut self,
+        offset: usize,
+        last_task_line: Option<usize>,
+        sink: &mut impl TodoSink,
+    ) {
+        while let Some(&(start, line, text)) = self.comments.get(self.next) {
+            if start >= offset {
+                break;
+            }
+            sink.event(Event::Comment {
+                text,
+                line,
+                trailing: last_task_line == Some(line),
+            });
+            self.next += 1;
+        }
+    }
+}
+
+/// Reads the number from a `version` pragma and checks that it is supported.
+///
+/// # Arguments
+/// * `pair` - Pest pair for the pragma
+///
+/// # Returns
+/// * `Ok(u32)` with the version
+/// * `Err(ParseError::UnsupportedVersion)` for unknown versions
+fn parse_version<'i>(pair: impl SyntaxNode<'i>) -> Result<u32, ParseError> {
+    let text = pair
+        .into_inner()
+        .find(|p| p.as_rule() == Rule::version_number)
+        .map(|p| p.as_str())
+        .unwrap_or_default();
+    // Anything too large for u32 is unsupported anyway.
+    let found = text.parse().unwrap_or(u32::MAX);
+    if SUPPORTED_VERSIONS.contains(&found) {
+        Ok(found)
+    } else {
+        Err(ParseError::UnsupportedVersion { found })
     }
 }
 
@@ -207,7 +1723,7 @@ fn parse_project_pair(pair: Pair<Rule>) -> Project {
 ///
 /// # Returns
 /// * `String` representing the raw content
-fn parse_quoted(pair: Pair<Rule>) -> String {
+fn parse_quoted<'i>(pair: impl SyntaxNode<'i>) -> String {
     pair.as_str().trim_matches('"').to_string()
 }
 
@@ -215,134 +1731,387 @@ fn parse_quoted(pair: Pair<Rule>) -> String {
 ///
 /// # Arguments
 /// * `pair` - A Pest `Pair` representing a task
+/// * `ctx` - Parsing state of the current file
+/// * `sink` - Receiver of warnings
 ///
 /// # Returns
-/// * `Task` -- struct with parsed data
-fn parse_task(pair: Pair<Rule>) -> Task {
-    let mut task = Task {
-        status: TaskStatus::Todo,
-        title: String::new(),
-        priority: None,
-        due_date: None,
-        assignee: None,
-        depends_on: None,
-        tags: Vec::new(),
-    };
+/// * `Ok(Task)` -- struct with parsed data
+/// * `Err(ParseError)` if the task is rejected by the version or options
+fn parse_task<'i>(
+    pair: impl SyntaxNode<'i>,
+    ctx: &Context,
+    sink: &mut impl TodoSink,
+) -> Result<Task, ParseError> {
+    let mut task = Task::new(TaskStatus::Todo, String::new());
+    task.span = ctx.span(&pair);
 
     for inner in pair.into_inner() {
         match inner.as_rule() {
             Rule::todo_task | Rule::done_task => {
+                check_keyword(&inner, "todo:".len(), ctx)?;
                 task.status = if inner.as_rule() == Rule::done_task {
                     TaskStatus::Done
                 } else {
                     TaskStatus::Todo
                 };
-                parse_task_details(
-                    inner,
-                    &mut task.title,
-                    &mut task.priority,
-                    &mut task.due_date,
-                    &mut task.assignee,
-                    &mut task.depends_on,
-                    &mut task.tags,
-                );
+                parse_task_details(inner, &mut task, ctx, sink)?;
             }
             _ => {}
         }
     }
 
-    task
+    Ok(task)
 }
 
-/// Parses details and attributes of a single task.`.
+/// Parses details and attributes of a single task.
 ///
 /// # Arguments
 /// * `pair` — Pest pair for the task block.
-/// * `title`, `priority`, `due_date`, `assignee`, `depends_on`, `tags` — Mutable references to fill parsed data.
-fn parse_task_details(
-    pair: Pair<Rule>,
-    title: &mut String,
-    priority: &mut Option<Priority>,
-    due_date: &mut Option<String>,
-    assignee: &mut Option<String>,
-    depends_on: &mut Option<String>,
-    tags: &mut Vec<String>,
-) {
+/// * `task` — Task to fill with parsed data.
+/// * `ctx` — Parsing state of the current file.
+/// * `sink` — Receiver of warnings.
+fn parse_task_details<'i>(
+    pair: impl SyntaxNode<'i>,
+    task: &mut Task,
+    ctx: &Context,
+    sink: &mut impl TodoSink,
+) -> Result<(), ParseError> {
     for item in pair.into_inner() {
         match item.as_rule() {
-            Rule::quoted => *title = parse_quoted(item),
+            Rule::quoted => task.title = parse_quoted(item),
             Rule::attribute_list => {
+                let mut seen = Vec::new();
                 for attr in item.into_inner().filter(|a| a.as_rule() == Rule::attribute) {
-                    parse_attribute(attr, priority, due_date, assignee, depends_on, tags);
+                    // An attribute wraps exactly one item.
+                    let Some(attr) = attr.into_inner().next() else {
+                        continue;
+                    };
+                    parse_attribute(attr.clone(), task, ctx, sink)?;
+                    check_repeated_attribute(&attr, &mut seen, ctx, sink)?;
                 }
             }
             _ => {}
         }
     }
+    Ok(())
 }
 
 /// Parses a single attribute of a task (priority, due date, etc.).
 ///
 /// # Arguments
-/// * `pair` — Pest pair for the attribute.
-/// * `priority`, `due_date`, `assignee`, `depends_on`, `tags` — Mutable references to fill parsed data.
-fn parse_attribute(
-    pair: Pair<Rule>,
-    priority: &mut Option<Priority>,
-    due_date: &mut Option<String>,
-    assignee: &mut Option<String>,
-    depends_on: &mut Option<String>,
-    tags: &mut Vec<String>,
-) {
-    for item in pair.into_inner() {
-        match item.as_rule() {
-            Rule::priority => {
-                *priority = match item.as_str() {
-                    "@high" => Some(Priority::High),
-                    "@medium" => Some(Priority::Medium),
-                    "@low" => Some(Priority::Low),
-                    _ => None,
-                };
+/// * `item` — Pest pair inside the attribute.
+/// * `task` — Task to fill with parsed data.
+/// * `ctx` — Parsing state of the current file.
+/// * `sink` — Receiver of warnings.
+fn parse_attribute<'i>(
+    item: impl SyntaxNode<'i>,
+    task: &mut Task,
+    ctx: &Context,
+    sink: &mut impl TodoSink,
+) -> Result<(), ParseError> {
+    let keyword = match item.as_rule() {
+        Rule::priority => item.as_str().len(),
+        Rule::unknown_attribute | Rule::tag => 0,
+        _ => item.as_str().find(':').map_or(0, |colon| colon + 1),
+    };
+    check_keyword(&item, keyword, ctx)?;
+    match item.as_rule() {
+        Rule::priority => {
+            let priority = match item.as_str().to_ascii_lowercase().as_str() {
+                "@high" => Priority::High,
+                "@medium" => Priority::Medium,
+                _ => Priority::Low,
+            };
+            if ctx.options.allowed_priorities.contains(&priority) {
+                task.priority = Some(priority);
+            } else {
+                let message = format!("priority `{}` is not allowed", item.as_str());
+                ctx.warn(sink, ctx.line(&item), message)?;
             }
-            Rule::due_date => {
-                if let Some(date) = item.into_inner().find(|i| i.as_rule() == Rule::date) {
-                    *due_date = Some(date.as_str().to_string());
-                }
+        }
+        Rule::id => {
+            if let Some(id) = item.into_inner().find(|i| i.as_rule() == Rule::identifier) {
+                task.id = Some(id.as_str().to_string());
+            }
+        }
+        Rule::start_date => {
+            task.start_span = Some(ctx.span(&item));
+            if let Some(date) = item.into_inner().find(|i| i.as_rule() == Rule::date) {
+                task.start_date = Some(parse_date(&date, ctx)?);
+            }
+        }
+        Rule::due_date => {
+            task.due_span = Some(ctx.span(&item));
+            if let Some(date) = item.into_inner().find(|i| i.as_rule() == Rule::date) {
+                task.due_date = Some(parse_date(&date, ctx)?);
+            }
+        }
+        Rule::completed => {
+            if let Some(date) = item.into_inner().find(|i| i.as_rule() == Rule::date) {
+                task.completed = Some(parse_date(&date, ctx)?);
             }
-            Rule::assignee => {
-                if let Some(id) = item.into_inner().find(|i| i.as_rule() == Rule::identifier) {
-                    *assignee = Some(id.as_str().to_string());
+        }
+        Rule::assignee => {
+            if let Some(id) = item.into_inner().find(|i| i.as_rule() == Rule::identifier) {
+                let handle = id.as_str();
+                if ctx.options.ignore_assignee_case && handle != handle.to_lowercase() {
+                    task.assignee = Some(handle.to_lowercase());
+                    task.assignee_as_written = Some(handle.to_string());
+                } else {
+                    task.assignee = Some(handle.to_string());
+                    task.assignee_as_written = None;
                 }
             }
-            Rule::depends_on => {
-                if let Some(dep) = item.into_inner().find(|i| i.as_rule() == Rule::quoted) {
-                    *depends_on = Some(parse_quoted(dep));
+        }
+        Rule::depends_on => {
+            task.depends_on_span = Some(ctx.span(&item));
+            if let Some(dep) = item.into_inner().find(|i| i.as_rule() == Rule::quoted) {
+                task.depends_on = Some(parse_quoted(dep));
+            }
+        }
+        Rule::tag => {
+            for tag_item in item.into_inner() {
+                match tag_item.as_rule() {
+                    Rule::tag_marker => {
+                        check_keyword(&tag_item, tag_item.as_str().len(), ctx)?;
+                        check_tag_marker(&tag_item, ctx)?
+                    }
+                    Rule::quoted => task.tags.push(parse_quoted(tag_item)),
+                    _ => {}
                 }
             }
-            Rule::tag => {
-                for tag_item in item.into_inner().filter(|i| i.as_rule() == Rule::quoted) {
-                    tags.push(parse_quoted(tag_item));
+        }
+        Rule::unknown_attribute => {
+            let line = ctx.line(&item);
+            let name = item
+                .into_inner()
+                .find(|i| i.as_rule() == Rule::attribute_name)
+                .map(|i| i.as_str().to_string())
+                .unwrap_or_default();
+            let suggestion = closest(&name, ATTRIBUTE_NAMES).map(|keyword| match keyword {
+                "tag" => tag_keyword(ctx.version).trim_end_matches(':'),
+                other => other,
+            });
+            match ctx.options.unknown_attributes {
+                UnknownAttributePolicy::Error => {
+                    return Err(ParseError::UnknownAttribute {
+                        line,
+                        name,
+                        suggestion,
+                    });
+                }
+                UnknownAttributePolicy::Warn => {
+                    let message = format!(
+                        "unknown attribute `{}` ignored{}",
+                        name,
+                        did_you_mean(&suggestion)
+                    );
+                    ctx.warn(sink, line, message)?;
                 }
+                UnknownAttributePolicy::Ignore => {}
             }
-            _ => {}
         }
+        _ => {}
     }
+    Ok(())
 }
 
-/// Debug utility: prints a tree of parsed rules (only in debug mode).
+/// Warns when a task gives an attribute that holds a single value, such
+/// as `due:` or a priority, a second time with a different value. The last
+/// value wins, as it always has.
 ///
 /// # Arguments
-/// * `pairs` - Pest parse tree to display.   
-#[cfg(debug_assertions)]
-pub fn display_tree(pairs: Pairs<Rule>) {
-    fn print_pair(pair: Pair<Rule>, indent: usize) {
-        println!("{:indent$}- {:?}", "", pair.as_rule(), indent = indent * 2);
-        for inner in pair.into_inner() {
-            print_pair(inner, indent + 1);
+/// * `pair` — Pest pair for the attribute.
+/// * `seen` — Name, value and location of the attributes before it.
+/// * `ctx` — Parsing state of the current file.
+/// * `sink` — Receiver of warnings.
+fn check_repeated_attribute<'i>(
+    item: &impl SyntaxNode<'i>,
+    seen: &mut Vec<(&'static str, &'i str, Span)>,
+    ctx: &Context,
+    sink: &mut impl TodoSink,
+) -> Result<(), ParseError> {
+    let name = match item.as_rule() {
+        Rule::priority => "priority",
+        Rule::id => "id:",
+        Rule::start_date => "start:",
+        Rule::due_date => "due:",
+        Rule::completed => "completed:",
+        Rule::assignee => "assign:",
+        Rule::depends_on => "depends_on:",
+        _ => return Ok(()),
+    };
+    let text = item.as_str();
+    let value = match text.find(':') {
+        Some(colon) if name != "priority" => text[colon + 1..].trim(),
+        _ => text,
+    };
+    let span = ctx.span(item);
+    let previous = seen.iter().position(|(n, _, _)| *n == name);
+    if let Some(index) = previous {
+        let (_, first, first_span) = seen.remove(index);
+        let same = match name {
+            "priority" => first.eq_ignore_ascii_case(value),
+            _ => first == value,
+        };
+        if !same {
+            let what = match name {
+                "priority" => "priorities".to_string(),
+                keyword => format!("`{}` values", keyword),
+            };
+            let message = format!(
+                "task has two {}, `{}` at {}:{} and `{}` at {}:{}; the last one is used",
+                what, first, first_span.line, first_span.column, value, span.line, span.column
+            );
+            ctx.warn(sink, span.line, message)?;
         }
     }
+    seen.push((name, value, span));
+    Ok(())
+}
 
-    for pair in pairs {
-        print_pair(pair, 0);
+/// Names of the `name:` attributes, with tags as `tag` in every version.
+const ATTRIBUTE_NAMES: [&str; 7] = [
+    "id",
+    "start",
+    "due",
+    "completed",
+    "assign",
+    "depends_on",
+    "tag",
+];
+
+/// Keyword that introduces a tag in the given format version.
+pub fn tag_keyword(version: u32) -> &'static str {
+    if version >= 2 { "tag:" } else { "@tag:" }
+}
+
+/// Strips a leading UTF-8 byte order mark and turns Windows (`\r\n`) line
+/// endings into `\n`, so files saved on Windows parse like any other.
+///
+/// Spans of parsed items point into the normalized text.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::parser::normalize_source;
+///
+/// assert_eq!(normalize_source("\u{feff}project \"P\" {\r\n}\r\n"), "project \"P\" {\n}\n");
+/// assert!(matches!(normalize_source("plain\n"), std::borrow::Cow::Borrowed(_)));
+/// ```
+pub fn normalize_source(input: &str) -> Cow<'_, str> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    if input.contains("\r\n") {
+        Cow::Owned(input.replace("\r\n", "\n"))
+    } else {
+        Cow::Borrowed(input)
     }
 }
+
+/// Returns the text of a `date` pair after checking that the day exists:
+/// months run from 01 to 12, and days up to the length of the month,
+/// counting February 29 in leap years only.
+fn parse_date<'i>(date: &impl SyntaxNode<'i>, ctx: &Context) -> Result<String, ParseError> {
+    let format = ctx.options.date_format;
+    let value = date.as_str();
+    let number = |range: std::ops::Range<usize>| value[range].parse::<u32>().unwrap_or(0);
+    let invalid = |reason: String| ParseError::InvalidDate {
+        span: ctx.span(date),
+        value: value.to_string(),
+        reason,
+    };
+    let (year, month, day) = match (value.as_bytes()[2], format) {
+        (b'.', DateFormat::DayMonthYear) => (number(6..10), number(3..5), number(0..2)),
+        (b'/', DateFormat::MonthDayYear) => (number(6..10), number(0..2), number(3..5)),
+        (b'.' | b'/', _) => {
+            let expected = match format {
+                DateFormat::Iso => "YYYY-MM-DD".to_string(),
+                local => format!("YYYY-MM-DD or {}", local.pattern()),
+            };
+            return Err(invalid(format!("expected {}", expected)));
+        }
+        _ => (number(0..4), number(5..7), number(8..10)),
+    };
+    let year = year as i32;
+
+    let Some(first) = NaiveDate::from_ymd_opt(year, month, 1) else {
+        return Err(invalid(format!(
+            "month {:02} is not between 01 and 12",
+            month
+        )));
+    };
+    let next = first
+        .checked_add_months(Months::new(1))
+        .expect("a four-digit year has a next month");
+    let days = next.signed_duration_since(first).num_days() as u32;
+    if day == 0 {
+        return Err(invalid("day 00 does not exist".to_string()));
+    }
+    if day > days {
+        return Err(invalid(format!(
+            "{} has {} days",
+            first.format("%B %Y"),
+            days
+        )));
+    }
+    Ok(format!("{:04}-{:02}-{:02}", year, month, day))
+}
+
+/// Checks that the keyword at the start of `pair`, `len` bytes long, is
+/// lowercase, unless keywords are case-insensitive.
+fn check_keyword<'i>(
+    pair: &impl SyntaxNode<'i>,
+    len: usize,
+    ctx: &Context,
+) -> Result<(), ParseError> {
+    let found = &pair.as_str()[..len];
+    let expected = found.to_ascii_lowercase();
+    if found == expected || ctx.options.case_insensitive_keywords {
+        return Ok(());
+    }
+    let span = ctx.span(pair);
+    Err(ParseError::KeywordCase {
+        span: Span {
+            end: span.start + len,
+            ..span
+        },
+        found: found.to_string(),
+        expected,
+    })
+}
+
+/// Checks that a tag is spelled the way the file's version expects:
+/// `@tag:` in version 1, `tag:` from version 2 on.
+fn check_tag_marker<'i>(marker: &impl SyntaxNode<'i>, ctx: &Context) -> Result<(), ParseError> {
+    let version = ctx.version;
+    let expected = tag_keyword(version);
+    if marker.as_str().eq_ignore_ascii_case(expected) {
+        return Ok(());
+    }
+    let message = if version >= 2 {
+        format!(
+            "`{}` was replaced by `{}` in version {}",
+            marker.as_str(),
+            expected,
+            version
+        )
+    } else {
+        format!(
+            "`{}` requires `version 2`; use `{}` instead",
+            marker.as_str(),
+            expected
+        )
+    };
+    Err(ParseError::Incompatible {
+        line: ctx.line(marker),
+        message,
+    })
+}
+
+/// Debug utility: prints a tree of parsed rules (only in debug mode).
+///
+/// # Arguments
+/// * `pairs` - Pest parse tree to display.   
+#[cfg(debug_assertions)]
+pub fn display_tree(pairs: Pairs<Rule>) {
+    print!("{}", format_tree(pairs));
+}
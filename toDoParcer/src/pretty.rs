@@ -0,0 +1,144 @@
+//! Pretty-printer turning a `Document` back into canonical DSL text.
+//!
+//! The output uses two-space indentation, one task per line, attributes in
+//! a fixed order (id, priority, due date, completion date, assignee,
+//! dependency, tags) and a trailing comma after every task. Comments and
+//! `include` directives are kept; projects that came from included files
+//! are not inlined.
+
+use std::fmt::Write;
+
+use crate::parser::{DEFAULT_VERSION, Document, Project, Task, tag_keyword};
+
+/// Indentation of tasks inside a project.
+const INDENT: &str = "  ";
+
+/// Formats a whole document.
+///
+/// A `version` pragma is written only for versions other than the default.
+/// Formatting is idempotent: parsing the output and formatting it again
+/// yields the same text.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::ToDoParser;
+/// use to_do_parcer::pretty::format_document;
+///
+/// let doc = ToDoParser::parse_document(r#"project "P"{todo:"A",assign:@bob,@high,}"#)?;
+/// assert_eq!(
+///     format_document(&doc),
+///     "project \"P\" {\n  todo: \"A\", @high, assign: @bob,\n}\n"
+/// );
+/// # Ok::<(), to_do_parcer::ParseError>(())
+/// ```
+pub fn format_document(doc: &Document) -> String {
+    let mut out = String::new();
+    if doc.version != DEFAULT_VERSION {
+        let _ = writeln!(out, "version {}", doc.version);
+    }
+
+    let mut previous_was_include = false;
+    for i in 0..=doc.projects.len() {
+        for include in doc.includes.iter().filter(|inc| inc.position == i) {
+            if !out.is_empty() && !previous_was_include {
+                out.push('\n');
+            }
+            write_comments(&mut out, &include.comments, "");
+            let _ = writeln!(out, "include \"{}\"", include.path);
+            previous_was_include = true;
+        }
+        if let Some(project) = doc.projects.get(i).filter(|p| p.file.is_none()) {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            format_project_into(&mut out, project, doc.version);
+            previous_was_include = false;
+        }
+    }
+
+    if !doc.trailing_comments.is_empty() {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        write_comments(&mut out, &doc.trailing_comments, "");
+    }
+    out
+}
+
+/// Formats a single project block, including its leading comments.
+///
+/// # Arguments
+/// * `project` - Project to format
+/// * `version` - Format version, which decides how tags are spelled
+pub fn format_project(project: &Project, version: u32) -> String {
+    let mut out = String::new();
+    format_project_into(&mut out, project, version);
+    out
+}
+
+/// Formats a single task as one line without indentation or comments,
+/// e.g. `todo: "A", @high, due: 2025-11-15,`.
+///
+/// # Arguments
+/// * `task` - Task to format
+/// * `version` - Format version, which decides how tags are spelled
+pub fn format_task(task: &Task, version: u32) -> String {
+    let mut line = format!("{}: \"{}\"", task.status.as_str(), task.title);
+
+    if let Some(id) = &task.id {
+        let _ = write!(line, ", id: {}", id);
+    }
+    if let Some(priority) = &task.priority {
+        let _ = write!(line, ", @{}", priority.as_str());
+    }
+    if let Some(start) = &task.start_date {
+        let _ = write!(line, ", start: {}", start);
+    }
+    if let Some(due) = &task.due_date {
+        let _ = write!(line, ", due: {}", due);
+    }
+    if let Some(completed) = &task.completed {
+        let _ = write!(line, ", completed: {}", completed);
+    }
+    if let Some(assignee) = task.assignee_display() {
+        let _ = write!(line, ", assign: @{}", assignee);
+    }
+    if let Some(dep) = &task.depends_on {
+        let _ = write!(line, ", depends_on: \"{}\"", dep);
+    }
+    for tag in &task.tags {
+        let _ = write!(line, ", {} \"{}\"", tag_keyword(version), tag);
+    }
+
+    line.push(',');
+    line
+}
+
+/// Appends a formatted project to `out`.
+fn format_project_into(out: &mut String, project: &Project, version: u32) {
+    write_comments(out, &project.comments, "");
+
+    if project.tasks.is_empty() && project.footer_comments.is_empty() {
+        let _ = writeln!(out, "project \"{}\" {{}}", project.name);
+        return;
+    }
+
+    let _ = writeln!(out, "project \"{}\" {{", project.name);
+    for task in &project.tasks {
+        write_comments(out, &task.comments, INDENT);
+        let _ = write!(out, "{}{}", INDENT, format_task(task, version));
+        if let Some(comment) = &task.trailing_comment {
+            let _ = write!(out, " {}", comment);
+        }
+        out.push('\n');
+    }
+    write_comments(out, &project.footer_comments, INDENT);
+    out.push_str("}\n");
+}
+
+/// Appends one comment per line with the given indentation.
+fn write_comments(out: &mut String, comments: &[String], indent: &str) {
+    for comment in comments {
+        let _ = writeln!(out, "{}{}", indent, comment);
+    }
+}
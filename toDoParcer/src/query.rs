@@ -0,0 +1,405 @@
+//! Selection of tasks by their attributes.
+//!
+//! Two ways to describe a selection are offered: [`Filter`], a plain struct
+//! of required values, and [`Query`], a boolean expression parsed from text
+//! such as `status == todo && (priority == high || tag:"urgent")`.
+//!
+//! # Query syntax
+//!
+//! * Comparisons: `field op value`, with fields `status`, `priority`, `due`,
+//!   `assignee`, `title` and `tag`, and operators `==`, `!=`, `<`, `<=`,
+//!   `>`, `>=` and `~` (case-insensitive "contains").
+//! * Values: bare words (`todo`, `high`, `@alice`), dates (`2025-12-01`) or
+//!   double-quoted text. `none` matches a missing priority, due date or
+//!   assignee.
+//! * `tag:"x"` is short for `tag == "x"`; `tag != "x"` means the task lacks
+//!   the tag.
+//! * `&&` binds tighter than `||`; `!` negates; parentheses group.
+//!
+//! Ordering operators apply to `priority` (`high` is greatest, a missing
+//! priority ranks below `low`) and `due` (tasks without a due date never
+//! match). `~` applies to `title`, `assignee` and `tag`.
+
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use pest::Parser;
+use pest::iterators::Pair;
+use thiserror::Error;
+
+use crate::parser::{Document, Priority, Project, Task, TaskStatus};
+
+/// Parser for query expressions, generated from `query.pest`.
+#[derive(pest_derive::Parser)]
+#[grammar = "query.pest"]
+pub struct QueryParser;
+
+/// Errors from parsing a query expression.
+#[derive(Debug, Error)]
+pub enum QueryError {
+    /// The text doesn't match the query grammar.
+    #[error("Invalid query: {0}")]
+    Syntax(#[from] Box<pest::error::Error<Rule>>),
+
+    /// The expression is well-formed but makes no sense, e.g. `status < todo`.
+    #[error("Invalid query: {0}")]
+    Invalid(String),
+}
+
+/// A parsed query expression.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::ToDoParser;
+/// use to_do_parcer::query::Query;
+///
+/// let doc = ToDoParser::parse_document(
+///     r#"project "P" { todo: "A", @high, due: 2025-11-01, todo: "B", @tag: "urgent", }"#,
+/// )?;
+/// let query: Query = r#"priority == high && due < 2025-12-01 || tag:"urgent""#.parse()?;
+/// assert_eq!(doc.matching(|t| query.matches(t)).len(), 2);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Not(Box<Query>),
+    Status(CompareOp, TaskStatus),
+    Priority(CompareOp, Option<Priority>),
+    Due(CompareOp, Option<NaiveDate>),
+    Assignee(CompareOp, Option<String>),
+    Title(CompareOp, String),
+    Tag(CompareOp, String),
+}
+
+/// Comparison operator of a query condition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// Case-insensitive substring match.
+    Contains,
+}
+
+impl CompareOp {
+    /// Applies an ordering operator to the result of comparing two values.
+    fn holds(self, ordering: std::cmp::Ordering) -> bool {
+        use std::cmp::Ordering::*;
+        match self {
+            CompareOp::Eq => ordering == Equal,
+            CompareOp::Ne => ordering != Equal,
+            CompareOp::Lt => ordering == Less,
+            CompareOp::Le => ordering != Greater,
+            CompareOp::Gt => ordering == Greater,
+            CompareOp::Ge => ordering != Less,
+            CompareOp::Contains => false,
+        }
+    }
+
+    fn is_ordering(self) -> bool {
+        matches!(
+            self,
+            CompareOp::Lt | CompareOp::Le | CompareOp::Gt | CompareOp::Ge
+        )
+    }
+}
+
+impl Query {
+    /// Parses a query expression.
+    ///
+    /// # Returns
+    /// * `Ok(Query)` on success
+    /// * `Err(QueryError)` if the text is malformed or compares values in
+    ///   an unsupported way
+    pub fn parse(input: &str) -> Result<Query, QueryError> {
+        let mut pairs = QueryParser::parse(Rule::query, input).map_err(Box::new)?;
+        let or_expr = pairs
+            .next()
+            .and_then(|q| q.into_inner().next())
+            .expect("query contains an expression");
+        build_or(or_expr)
+    }
+
+    /// Whether `task` satisfies the expression.
+    pub fn matches(&self, task: &Task) -> bool {
+        match self {
+            Query::And(a, b) => a.matches(task) && b.matches(task),
+            Query::Or(a, b) => a.matches(task) || b.matches(task),
+            Query::Not(q) => !q.matches(task),
+            Query::Status(op, status) => op.holds(task.status.cmp(status)),
+            Query::Priority(op, priority) => {
+                op.holds(priority_rank(task.priority).cmp(&priority_rank(*priority)))
+            }
+            Query::Due(op, date) => match (task.due(), date) {
+                (Some(due), Some(date)) => op.holds(due.cmp(date)),
+                (due, None) => (*op == CompareOp::Eq) == due.is_none(),
+                (None, Some(_)) => *op == CompareOp::Ne,
+            },
+            Query::Assignee(op, name) => match op {
+                CompareOp::Contains => task
+                    .assignee
+                    .as_ref()
+                    .is_some_and(|a| contains_ignore_case(a, name.as_deref().unwrap_or_default())),
+                _ => op.holds(task.assignee.cmp(name)),
+            },
+            Query::Title(op, title) => match op {
+                CompareOp::Contains => contains_ignore_case(&task.title, title),
+                _ => op.holds(task.title.as_str().cmp(title)),
+            },
+            Query::Tag(op, tag) => match op {
+                CompareOp::Eq => task.tags.contains(tag),
+                CompareOp::Ne => !task.tags.contains(tag),
+                _ => task.tags.iter().any(|t| contains_ignore_case(t, tag)),
+            },
+        }
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Query::parse(s)
+    }
+}
+
+/// Orders priorities from a missing one (0) up to high (3).
+fn priority_rank(priority: Option<Priority>) -> u8 {
+    match priority {
+        None => 0,
+        Some(Priority::Low) => 1,
+        Some(Priority::Medium) => 2,
+        Some(Priority::High) => 3,
+    }
+}
+
+fn contains_ignore_case(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+fn build_or(pair: Pair<Rule>) -> Result<Query, QueryError> {
+    let mut parts = pair.into_inner().map(build_and);
+    let first = parts.next().expect("or_expr has an operand")?;
+    parts.try_fold(first, |acc, next| {
+        Ok(Query::Or(Box::new(acc), Box::new(next?)))
+    })
+}
+
+fn build_and(pair: Pair<Rule>) -> Result<Query, QueryError> {
+    let mut parts = pair.into_inner().map(build_unary);
+    let first = parts.next().expect("and_expr has an operand")?;
+    parts.try_fold(first, |acc, next| {
+        Ok(Query::And(Box::new(acc), Box::new(next?)))
+    })
+}
+
+fn build_unary(pair: Pair<Rule>) -> Result<Query, QueryError> {
+    let mut negations = 0;
+    let mut query = None;
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::not => negations += 1,
+            Rule::or_expr => query = Some(build_or(inner)?),
+            Rule::tag_test => {
+                let value = inner.into_inner().next().expect("tag_test has a value");
+                query = Some(Query::Tag(CompareOp::Eq, value_text(&value)));
+            }
+            Rule::comparison => query = Some(build_comparison(inner)?),
+            _ => unreachable!("unexpected rule in unary: {:?}", inner.as_rule()),
+        }
+    }
+    let mut query = query.expect("unary has an operand");
+    for _ in 0..negations {
+        query = Query::Not(Box::new(query));
+    }
+    Ok(query)
+}
+
+fn build_comparison(pair: Pair<Rule>) -> Result<Query, QueryError> {
+    let mut inner = pair.into_inner();
+    let field = inner.next().expect("comparison has a field").as_str();
+    let op = match inner.next().expect("comparison has an operator").as_str() {
+        "==" => CompareOp::Eq,
+        "!=" => CompareOp::Ne,
+        "<" => CompareOp::Lt,
+        "<=" => CompareOp::Le,
+        ">" => CompareOp::Gt,
+        ">=" => CompareOp::Ge,
+        _ => CompareOp::Contains,
+    };
+    let value_pair = inner.next().expect("comparison has a value");
+    let value = value_text(&value_pair);
+    let is_none = value_pair.as_rule() == Rule::word && value == "none";
+
+    let unsupported = || {
+        QueryError::Invalid(format!(
+            "operator '{}' is not supported for '{}'",
+            op_symbol(op),
+            field
+        ))
+    };
+
+    match field {
+        "status" => {
+            if !matches!(op, CompareOp::Eq | CompareOp::Ne) {
+                return Err(unsupported());
+            }
+            let status = TaskStatus::from_name(&value).ok_or_else(|| {
+                QueryError::Invalid(format!(
+                    "unknown status '{}' (expected todo or done)",
+                    value
+                ))
+            })?;
+            Ok(Query::Status(op, status))
+        }
+        "priority" => {
+            if op == CompareOp::Contains {
+                return Err(unsupported());
+            }
+            let priority = if is_none {
+                None
+            } else {
+                Some(Priority::from_name(&value).ok_or_else(|| {
+                    QueryError::Invalid(format!(
+                        "unknown priority '{}' (expected high, medium, low or none)",
+                        value
+                    ))
+                })?)
+            };
+            Ok(Query::Priority(op, priority))
+        }
+        "due" => {
+            if op == CompareOp::Contains || (is_none && op.is_ordering()) {
+                return Err(unsupported());
+            }
+            let date = if is_none {
+                None
+            } else {
+                Some(NaiveDate::parse_from_str(&value, "%Y-%m-%d").map_err(|_| {
+                    QueryError::Invalid(format!("invalid date '{}' (expected YYYY-MM-DD)", value))
+                })?)
+            };
+            Ok(Query::Due(op, date))
+        }
+        "assignee" => {
+            if op.is_ordering() || (is_none && op == CompareOp::Contains) {
+                return Err(unsupported());
+            }
+            let name = (!is_none).then(|| value.trim_start_matches('@').to_string());
+            Ok(Query::Assignee(op, name))
+        }
+        "title" => {
+            if op.is_ordering() {
+                return Err(unsupported());
+            }
+            Ok(Query::Title(op, value))
+        }
+        _ => {
+            if op.is_ordering() {
+                return Err(unsupported());
+            }
+            Ok(Query::Tag(op, value))
+        }
+    }
+}
+
+/// Text of a value: the contents of a quoted string, or the word or date.
+fn value_text(pair: &Pair<Rule>) -> String {
+    match pair.as_rule() {
+        Rule::quoted => pair.clone().into_inner().as_str().to_string(),
+        _ => pair.as_str().to_string(),
+    }
+}
+
+fn op_symbol(op: CompareOp) -> &'static str {
+    match op {
+        CompareOp::Eq => "==",
+        CompareOp::Ne => "!=",
+        CompareOp::Lt => "<",
+        CompareOp::Le => "<=",
+        CompareOp::Gt => ">",
+        CompareOp::Ge => ">=",
+        CompareOp::Contains => "~",
+    }
+}
+
+/// Attribute filter: a task matches if it satisfies every field that is set.
+///
+/// `Filter::default()` matches every task.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::query::Filter;
+/// use to_do_parcer::{Priority, ToDoParser};
+///
+/// let doc = ToDoParser::parse_document(r#"project "P" { todo: "A", @high, todo: "B", }"#)?;
+/// let filter = Filter {
+///     priority: Some(Priority::High),
+///     ..Filter::default()
+/// };
+/// assert_eq!(doc.matching(|t| filter.matches(t)).len(), 1);
+/// # Ok::<(), to_do_parcer::ParseError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filter {
+    pub status: Option<TaskStatus>,
+    pub priority: Option<Priority>,
+    /// Tags the task must all have.
+    pub tags: Vec<String>,
+    /// Assignee name, without the `@`.
+    pub assignee: Option<String>,
+}
+
+impl Filter {
+    /// Whether `task` satisfies the filter.
+    pub fn matches(&self, task: &Task) -> bool {
+        self.status.is_none_or(|s| task.status == s)
+            && self.priority.is_none_or(|p| task.priority == Some(p))
+            && self.tags.iter().all(|tag| task.tags.contains(tag))
+            && self
+                .assignee
+                .as_ref()
+                .is_none_or(|a| task.assignee.as_ref() == Some(a))
+    }
+}
+
+impl Document {
+    /// Returns every task accepted by `predicate`, with its project, in
+    /// source order.
+    pub fn matching(&self, predicate: impl Fn(&Task) -> bool) -> Vec<(&Project, &Task)> {
+        self.tasks().filter(|(_, t)| predicate(t)).collect()
+    }
+
+    /// Copies the document, keeping only tasks accepted by `predicate`.
+    ///
+    /// Projects left without tasks are dropped. Projects from included
+    /// files are kept inline (their `file` is cleared) and `include`
+    /// directives are removed, so that formatting the result shows every
+    /// match.
+    pub fn select(&self, predicate: impl Fn(&Task) -> bool) -> Document {
+        let projects = self
+            .projects
+            .iter()
+            .filter(|project| project.tasks.iter().any(&predicate))
+            .map(|project| {
+                let mut copy = project.clone();
+                copy.tasks.retain(&predicate);
+                copy.file = None;
+                copy
+            })
+            .collect();
+
+        Document {
+            version: self.version,
+            projects,
+            includes: Vec::new(),
+            trailing_comments: Vec::new(),
+            warnings: self.warnings.clone(),
+        }
+    }
+}
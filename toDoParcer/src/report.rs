@@ -0,0 +1,135 @@
+//! Static HTML reports for readers who don't use the CLI.
+//!
+//! The page is a single self-contained file: styles are inline and nothing
+//! is loaded from elsewhere, so it can be mailed or attached as is. Each
+//! project gets a heading, a progress bar of done tasks and a table of its
+//! tasks; rows of overdue tasks are highlighted.
+
+use std::fmt::Write;
+
+use crate::parser::{Document, Project, TaskStatus};
+use crate::schedule::Clock;
+
+/// Styles embedded in every report.
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em auto; max-width: 60em; color: #222; }
+h1 { font-size: 1.6em; }
+h2 { font-size: 1.2em; margin-top: 2em; }
+.bar { background: #eee; border-radius: 4px; height: 0.8em; overflow: hidden; }
+.bar div { background: #4caf50; height: 100%; }
+.progress { color: #555; font-size: 0.9em; margin: 0.3em 0 0.8em; }
+table { border-collapse: collapse; width: 100%; }
+th, td { border-bottom: 1px solid #ddd; padding: 0.3em 0.6em; text-align: left; }
+tr.done td { color: #888; }
+tr.overdue td { background: #fdecea; }
+tr.overdue td.due { color: #c62828; font-weight: bold; }
+";
+
+/// Renders `doc` as a standalone HTML page.
+///
+/// # Arguments
+/// * `doc` - Document to report on
+/// * `title` - Page title and top heading
+/// * `now` - Clock that decides what is overdue
+///
+/// # Example
+/// ```
+/// use chrono::NaiveDate;
+/// use to_do_parcer::ToDoParser;
+/// use to_do_parcer::report::html_report;
+///
+/// let doc = ToDoParser::parse_document(r#"project "P" { todo: "Late", due: 2025-01-01, }"#)?;
+/// let today = NaiveDate::from_ymd_opt(2025, 2, 1).unwrap();
+/// let html = html_report(&doc, "Plan", &today);
+/// assert!(html.contains(r#"<tr class="overdue">"#));
+/// # Ok::<(), to_do_parcer::ParseError>(())
+/// ```
+pub fn html_report(doc: &Document, title: &str, now: &impl Clock) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "<!DOCTYPE html>");
+    let _ = writeln!(out, "<html lang=\"en\">");
+    let _ = writeln!(out, "<head>");
+    let _ = writeln!(out, "<meta charset=\"utf-8\">");
+    let _ = writeln!(out, "<title>{}</title>", escape_html(title));
+    let _ = writeln!(out, "<style>\n{}</style>", STYLE);
+    let _ = writeln!(out, "</head>");
+    let _ = writeln!(out, "<body>");
+    let _ = writeln!(out, "<h1>{}</h1>", escape_html(title));
+    for project in &doc.projects {
+        write_project(&mut out, project, now);
+    }
+    let _ = writeln!(out, "</body>");
+    let _ = writeln!(out, "</html>");
+    out
+}
+
+/// Appends the heading, progress bar and task table of one project.
+fn write_project(out: &mut String, project: &Project, now: &impl Clock) {
+    let total = project.tasks.len();
+    let done = project
+        .tasks
+        .iter()
+        .filter(|t| t.status == TaskStatus::Done)
+        .count();
+    let percent = (done * 100).checked_div(total).unwrap_or(0);
+
+    let _ = writeln!(out, "<h2>{}</h2>", escape_html(&project.name));
+    let _ = writeln!(
+        out,
+        "<div class=\"bar\"><div style=\"width: {}%\"></div></div>",
+        percent
+    );
+    let _ = writeln!(
+        out,
+        "<p class=\"progress\">{} of {} done ({}%)</p>",
+        done, total, percent
+    );
+    if project.tasks.is_empty() {
+        return;
+    }
+
+    let _ = writeln!(out, "<table>");
+    let _ = writeln!(
+        out,
+        "<tr><th>Status</th><th>Title</th><th>Priority</th><th>Due</th><th>Assignee</th><th>Tags</th></tr>"
+    );
+    for task in &project.tasks {
+        let class = if task.days_late(now).is_some() {
+            "overdue"
+        } else if task.status == TaskStatus::Done {
+            "done"
+        } else {
+            "todo"
+        };
+        let _ = writeln!(
+            out,
+            "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td class=\"due\">{}</td><td>{}</td><td>{}</td></tr>",
+            class,
+            task.status.as_str(),
+            escape_html(&task.title),
+            task.priority.map_or("", |p| p.as_str()),
+            task.due_date.as_deref().unwrap_or(""),
+            task.assignee
+                .as_deref()
+                .map(|a| format!("@{}", escape_html(a)))
+                .unwrap_or_default(),
+            escape_html(&task.tags.join(", "))
+        );
+    }
+    let _ = writeln!(out, "</table>");
+}
+
+/// Escapes the characters HTML treats as markup.
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
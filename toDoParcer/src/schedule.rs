@@ -0,0 +1,206 @@
+//! Deadline queries over a parsed `Document`.
+//!
+//! All queries take a [`Clock`] instead of reading the system time directly,
+//! so callers (and tests) decide what "today" means.
+
+use chrono::{Datelike, Local, NaiveDate};
+
+use crate::parser::{Document, Project, Task, TaskStatus};
+
+/// Source of the current calendar date.
+pub trait Clock {
+    /// Returns today's date.
+    fn today(&self) -> NaiveDate;
+}
+
+/// Clock backed by the system time in the local timezone.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn today(&self) -> NaiveDate {
+        Local::now().date_naive()
+    }
+}
+
+/// A fixed date can be used directly as a clock.
+impl Clock for NaiveDate {
+    fn today(&self) -> NaiveDate {
+        *self
+    }
+}
+
+/// Section of an agenda, by how soon a task is due.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AgendaGroup {
+    Overdue,
+    Today,
+    Tomorrow,
+    /// After tomorrow, up to and including Sunday.
+    ThisWeek,
+    Later,
+}
+
+impl AgendaGroup {
+    /// Heading of the section.
+    pub fn label(&self) -> &'static str {
+        match self {
+            AgendaGroup::Overdue => "Overdue",
+            AgendaGroup::Today => "Today",
+            AgendaGroup::Tomorrow => "Tomorrow",
+            AgendaGroup::ThisWeek => "This Week",
+            AgendaGroup::Later => "Later",
+        }
+    }
+
+    /// Returns the section of a task due on `due`, as seen on `today`.
+    pub fn of(due: NaiveDate, today: NaiveDate) -> AgendaGroup {
+        let days = (due - today).num_days();
+        let to_sunday = i64::from(6 - today.weekday().num_days_from_monday());
+        match days {
+            ..0 => AgendaGroup::Overdue,
+            0 => AgendaGroup::Today,
+            1 => AgendaGroup::Tomorrow,
+            d if d <= to_sunday => AgendaGroup::ThisWeek,
+            _ => AgendaGroup::Later,
+        }
+    }
+}
+
+impl Task {
+    /// Returns the start date as a calendar date, if the task has a valid one.
+    pub fn start(&self) -> Option<NaiveDate> {
+        self.start_date
+            .as_deref()
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+    }
+
+    /// Returns the due date as a calendar date, if the task has a valid one.
+    pub fn due(&self) -> Option<NaiveDate> {
+        self.due_date
+            .as_deref()
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+    }
+
+    /// Returns the completion date as a calendar date, if the task has a
+    /// valid one.
+    pub fn completed_on(&self) -> Option<NaiveDate> {
+        self.completed
+            .as_deref()
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+    }
+
+    /// Returns how many days an open task is past its due date, or `None`
+    /// if it is done, undated or not late yet.
+    ///
+    /// # Arguments
+    /// * `now` - Clock that provides the current date
+    pub fn days_late(&self, now: &impl Clock) -> Option<u64> {
+        if self.status != TaskStatus::Todo {
+            return None;
+        }
+        let late = (now.today() - self.due()?).num_days();
+        u64::try_from(late).ok().filter(|&days| days > 0)
+    }
+}
+
+impl Document {
+    /// Returns all open tasks whose due date is before today.
+    ///
+    /// # Arguments
+    /// * `now` - Clock that provides the current date
+    ///
+    /// # Example
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use to_do_parcer::ToDoParser;
+    ///
+    /// let doc = ToDoParser::parse_document(r#"project "P" { todo: "Late", due: 2025-01-01, }"#)?;
+    /// let today = NaiveDate::from_ymd_opt(2025, 2, 1).unwrap();
+    /// assert_eq!(doc.overdue(&today).len(), 1);
+    /// # Ok::<(), to_do_parcer::ParseError>(())
+    /// ```
+    pub fn overdue(&self, now: &impl Clock) -> Vec<(&Project, &Task)> {
+        let today = now.today();
+        self.open_tasks_due()
+            .filter(|(_, _, due)| *due < today)
+            .map(|(p, t, _)| (p, t))
+            .collect()
+    }
+
+    /// Returns all open tasks due between today and `days` days from now,
+    /// both ends inclusive.
+    ///
+    /// # Arguments
+    /// * `now` - Clock that provides the current date
+    /// * `days` - Size of the window in days; a window past the end of the
+    ///   calendar takes every task due from today on
+    pub fn due_within(&self, now: &impl Clock, days: u32) -> Vec<(&Project, &Task)> {
+        let today = now.today();
+        let until = window_end(today, days);
+        self.open_tasks_due()
+            .filter(|(_, _, due)| (today..=until).contains(due))
+            .map(|(p, t, _)| (p, t))
+            .collect()
+    }
+
+    /// Returns overdue tasks and open tasks due within `days` days, grouped
+    /// into agenda sections and ordered by due date inside each one.
+    /// Sections without tasks are left out.
+    ///
+    /// # Arguments
+    /// * `now` - Clock that provides the current date
+    /// * `days` - How far ahead to look, in days; a window past the end of
+    ///   the calendar takes every open task with a due date
+    ///
+    /// # Example
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use to_do_parcer::ToDoParser;
+    /// use to_do_parcer::schedule::AgendaGroup;
+    ///
+    /// let doc = ToDoParser::parse_document(r#"project "P" { todo: "Call", due: 2025-02-02, }"#)?;
+    /// let today = NaiveDate::from_ymd_opt(2025, 2, 1).unwrap();
+    /// let agenda = doc.agenda(&today, 14);
+    /// assert_eq!(agenda[0].0, AgendaGroup::Tomorrow);
+    /// # Ok::<(), to_do_parcer::ParseError>(())
+    /// ```
+    pub fn agenda(
+        &self,
+        now: &impl Clock,
+        days: u32,
+    ) -> Vec<(AgendaGroup, Vec<(&Project, &Task)>)> {
+        let today = now.today();
+        let until = window_end(today, days);
+        let mut due: Vec<_> = self
+            .open_tasks_due()
+            .filter(|(_, _, due)| *due <= until)
+            .collect();
+        due.sort_by_key(|(_, _, due)| *due);
+
+        let mut groups: Vec<(AgendaGroup, Vec<(&Project, &Task)>)> = Vec::new();
+        for (project, task, date) in due {
+            let group = AgendaGroup::of(date, today);
+            match groups.last_mut() {
+                Some((last, tasks)) if *last == group => tasks.push((project, task)),
+                _ => groups.push((group, vec![(project, task)])),
+            }
+        }
+        groups
+    }
+
+    /// Open tasks that have a valid due date, along with that date.
+    fn open_tasks_due(&self) -> impl Iterator<Item = (&Project, &Task, NaiveDate)> {
+        self.tasks()
+            .filter(|(_, t)| t.status == TaskStatus::Todo)
+            .filter_map(|(p, t)| t.due().map(|d| (p, t, d)))
+    }
+}
+
+/// The last day of a window of `days` days from `today`, clamped to the
+/// last date `NaiveDate` can hold.
+fn window_end(today: NaiveDate, days: u32) -> NaiveDate {
+    today
+        .checked_add_days(chrono::Days::new(u64::from(days)))
+        .unwrap_or(NaiveDate::MAX)
+}
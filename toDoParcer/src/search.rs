@@ -0,0 +1,96 @@
+//! Free-text search over task titles, tags and assignees.
+//!
+//! Matching ignores case. A task matches exactly when a field contains the
+//! pattern, and fuzzily when the pattern's characters appear in a field in
+//! order with gaps allowed (`"dbmig"` finds `"DB migration"`). Exact
+//! matches are listed before fuzzy ones.
+
+use crate::parser::{Document, Project, Task};
+
+/// Task field a search pattern was found in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SearchField {
+    Title,
+    Tag,
+    Assignee,
+}
+
+impl SearchField {
+    /// Name of the field as shown next to matches.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchField::Title => "title",
+            SearchField::Tag => "tag",
+            SearchField::Assignee => "assignee",
+        }
+    }
+}
+
+/// A task found by `search`.
+#[derive(Debug, Clone, Copy)]
+pub struct Hit<'d> {
+    pub project: &'d Project,
+    pub task: &'d Task,
+    /// First field that matched, in title, tag, assignee order.
+    pub field: SearchField,
+    /// The text of that field.
+    pub text: &'d str,
+    /// Whether the field contains the pattern rather than only its letters.
+    pub exact: bool,
+}
+
+/// Finds tasks whose title, tags or assignee match `pattern`.
+///
+/// Each task is listed once: exact matches first, then fuzzy ones, each in
+/// source order.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::ToDoParser;
+/// use to_do_parcer::search::search;
+///
+/// let doc = ToDoParser::parse_document(
+///     r#"project "P" { todo: "Fix login", todo: "Add OAuth", @tag: "auth", }"#,
+/// )?;
+/// let hits = search(&doc, "AUTH");
+/// assert_eq!(hits[0].task.title, "Add OAuth");
+/// assert!(hits[0].exact);
+/// # Ok::<(), to_do_parcer::ParseError>(())
+/// ```
+pub fn search<'d>(doc: &'d Document, pattern: &str) -> Vec<Hit<'d>> {
+    let pattern = pattern.to_lowercase();
+    let mut hits: Vec<Hit<'d>> = doc
+        .tasks()
+        .filter_map(|(project, task)| {
+            let fields = std::iter::once((SearchField::Title, task.title.as_str()))
+                .chain(task.tags.iter().map(|t| (SearchField::Tag, t.as_str())))
+                .chain(task.assignee.as_deref().map(|a| (SearchField::Assignee, a)));
+            let mut best: Option<Hit<'d>> = None;
+            for (field, text) in fields {
+                let text_lower = text.to_lowercase();
+                let exact = text_lower.contains(&pattern);
+                if !exact && !is_subsequence(&pattern, &text_lower) {
+                    continue;
+                }
+                if best.is_none_or(|b| exact && !b.exact) {
+                    best = Some(Hit {
+                        project,
+                        task,
+                        field,
+                        text,
+                        exact,
+                    });
+                }
+            }
+            best
+        })
+        .collect();
+    hits.sort_by_key(|hit| !hit.exact);
+    hits
+}
+
+/// Whether the characters of `needle` appear in `haystack` in order.
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut rest = haystack.chars();
+    needle.chars().all(|c| rest.any(|h| h == c))
+}
@@ -0,0 +1,160 @@
+//! Read-only HTTP API over a set of documents, for dashboards and scripts.
+//!
+//! This module only maps a request to a response; the `serve` command owns
+//! the socket and reloads the files for every request, so answers always
+//! reflect what is on disk. Every body is JSON:
+//!
+//! * `GET /projects` — projects with their tasks, in the layout of
+//!   `export --format json`
+//! * `GET /tasks` — every task, with a `project` field added; narrowed by
+//!   the query parameters `status`, `priority`, `assignee` (with or without
+//!   `@`) and `tag` (repeatable, all must match)
+//! * `GET /stats` — the numbers of `stats --json`
+//!
+//! Errors are `{"error": "..."}` with status 400 for bad parameters, 404
+//! for unknown paths and 405 for methods other than `GET`.
+
+use serde::Serialize;
+use serde_json::json;
+
+use crate::export::{ProjectView, TaskView};
+use crate::parser::{Document, Priority, TaskStatus};
+use crate::query::Filter;
+use crate::schedule::Clock;
+use crate::stats::Stats;
+
+/// Status code and JSON body of an answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    /// A 200 response with `value` as its body.
+    fn ok(value: &impl Serialize) -> Response {
+        Response {
+            status: 200,
+            body: to_json(value),
+        }
+    }
+
+    /// An error response with the message in an `error` field.
+    pub fn error(status: u16, message: impl Into<String>) -> Response {
+        Response {
+            status,
+            body: to_json(&json!({ "error": message.into() })),
+        }
+    }
+}
+
+/// A task listed by `GET /tasks`.
+#[derive(Serialize)]
+struct TaskRow<'a> {
+    project: &'a str,
+    #[serde(flatten)]
+    task: TaskView<'a>,
+}
+
+/// Answers one request against `doc`.
+///
+/// # Arguments
+/// * `doc` - Documents to answer from, already joined into one
+/// * `method` - HTTP method, e.g. `GET`
+/// * `url` - Path with optional query string, e.g. `/tasks?status=todo`
+/// * `now` - Clock for the overdue count of `/stats`
+///
+/// # Example
+/// ```
+/// use chrono::NaiveDate;
+/// use to_do_parcer::ToDoParser;
+/// use to_do_parcer::serve::respond;
+///
+/// let doc = ToDoParser::parse_document(r#"project "P" { todo: "A", done: "B", }"#)?;
+/// let today = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+/// let response = respond(&doc, "GET", "/tasks?status=done", &today);
+/// assert_eq!(response.status, 200);
+/// assert!(response.body.contains(r#""title": "B""#));
+/// assert!(!response.body.contains(r#""title": "A""#));
+/// # Ok::<(), to_do_parcer::ParseError>(())
+/// ```
+pub fn respond(doc: &Document, method: &str, url: &str, now: &impl Clock) -> Response {
+    if method != "GET" {
+        return Response::error(405, format!("method {} is not allowed", method));
+    }
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    match path.trim_end_matches('/') {
+        "/projects" => {
+            let projects: Vec<ProjectView> = doc.projects.iter().map(ProjectView::from).collect();
+            Response::ok(&projects)
+        }
+        "/tasks" => match task_filter(query) {
+            Ok(filter) => {
+                let rows: Vec<TaskRow> = doc
+                    .matching(|t| filter.matches(t))
+                    .into_iter()
+                    .map(|(project, task)| TaskRow {
+                        project: &project.name,
+                        task: TaskView::from(task),
+                    })
+                    .collect();
+                Response::ok(&rows)
+            }
+            Err(message) => Response::error(400, message),
+        },
+        "/stats" => Response::ok(&Stats::compute(doc, now)),
+        _ => Response::error(404, format!("no endpoint at {}", path)),
+    }
+}
+
+/// Builds the filter of `GET /tasks` from its query string.
+fn task_filter(query: &str) -> Result<Filter, String> {
+    let mut filter = Filter::default();
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = decode(value).ok_or_else(|| format!("bad escape in '{}'", pair))?;
+        match key {
+            "status" => {
+                filter.status = Some(
+                    TaskStatus::from_name(&value)
+                        .ok_or_else(|| format!("unknown status '{}'", value))?,
+                )
+            }
+            "priority" => {
+                filter.priority = Some(
+                    Priority::from_name(&value)
+                        .ok_or_else(|| format!("unknown priority '{}'", value))?,
+                )
+            }
+            "assignee" => filter.assignee = Some(value.trim_start_matches('@').to_string()),
+            "tag" => filter.tags.push(value),
+            _ => return Err(format!("unknown parameter '{}'", key)),
+        }
+    }
+    Ok(filter)
+}
+
+/// Decodes a query string value: `+` is a space and `%XX` a byte.
+fn decode(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.bytes();
+    while let Some(b) = rest.next() {
+        match b {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = [rest.next()?, rest.next()?];
+                let hex = std::str::from_utf8(&hex).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            }
+            _ => bytes.push(b),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Pretty JSON with a trailing newline, like the other JSON outputs.
+fn to_json(value: &impl Serialize) -> String {
+    let mut out = serde_json::to_string_pretty(value).expect("response serializes to JSON");
+    out.push('\n');
+    out
+}
@@ -0,0 +1,116 @@
+//! Ordering of tasks within a project.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::parser::{Priority, Project, Task, TaskStatus};
+
+/// What to order tasks by. Sorting is stable, so tasks that compare equal
+/// keep their relative order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// Earliest due date first; tasks without one last.
+    Due,
+    /// `@high` first, then `@medium`, `@low` and tasks without a priority.
+    Priority,
+    /// Open tasks before done ones.
+    Status,
+}
+
+impl SortKey {
+    /// Every key, in the order shown in help texts.
+    pub const ALL: &'static [SortKey] = &[SortKey::Due, SortKey::Priority, SortKey::Status];
+
+    /// Name of the key as accepted by `FromStr`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortKey::Due => "due",
+            SortKey::Priority => "priority",
+            SortKey::Status => "status",
+        }
+    }
+
+    /// Compares two tasks by this key.
+    pub fn compare(&self, a: &Task, b: &Task) -> Ordering {
+        match self {
+            SortKey::Due => match (a.due(), b.due()) {
+                (Some(x), Some(y)) => x.cmp(&y),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+            SortKey::Priority => priority_order(a.priority).cmp(&priority_order(b.priority)),
+            SortKey::Status => status_order(a.status).cmp(&status_order(b.status)),
+        }
+    }
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SortKey::ALL
+            .iter()
+            .find(|k| k.as_str().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<_> = SortKey::ALL.iter().map(|k| k.as_str()).collect();
+                format!(
+                    "unknown sort key '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl Project {
+    /// Sorts the project's tasks by `key`, keeping ties in their order.
+    pub fn sort_tasks(&mut self, key: SortKey) {
+        self.tasks.sort_by(|a, b| key.compare(a, b));
+    }
+
+    /// Puts the project's tasks back in the order of the source file,
+    /// by `Task::order`.
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::ToDoParser;
+    /// use to_do_parcer::sort::SortKey;
+    ///
+    /// let mut projects =
+    ///     ToDoParser::parse_projects(r#"project "P" { todo: "A", todo: "B", @high, }"#)?;
+    /// let project = &mut projects[0];
+    /// project.sort_tasks(SortKey::Priority);
+    /// assert_eq!(project.tasks[0].title, "B");
+    /// project.sort_by_source_order();
+    /// assert_eq!(project.tasks[0].title, "A");
+    /// # Ok::<(), to_do_parcer::ParseError>(())
+    /// ```
+    pub fn sort_by_source_order(&mut self) {
+        self.tasks.sort_by_key(|task| task.order);
+    }
+}
+
+fn priority_order(priority: Option<Priority>) -> u8 {
+    match priority {
+        Some(Priority::High) => 0,
+        Some(Priority::Medium) => 1,
+        Some(Priority::Low) => 2,
+        None => 3,
+    }
+}
+
+fn status_order(status: TaskStatus) -> u8 {
+    match status {
+        TaskStatus::Todo => 0,
+        TaskStatus::Done => 1,
+    }
+}
@@ -0,0 +1,192 @@
+//! Word lists for the `spelling` lint.
+//!
+//! The built-in list (`dictionaries/en.txt`) holds common English words in
+//! their base form; plurals, `-ed`, `-ing`, `-er` and `-ly` forms and a
+//! `re-` prefix are derived from it. Names, product words and jargon a
+//! project uses go in a project word list, read with [`parse_word_list`].
+//!
+//! Only words that look like prose are checked: at least three letters,
+//! no digits, and lowercase apart from the first letter, so acronyms
+//! (`API`) and identifiers (`GitHub`, `utf8`) are left alone.
+
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use crate::suggest::closest;
+
+/// The built-in word list, one word per line.
+const BUILTIN: &str = include_str!("../dictionaries/en.txt");
+
+/// Shortest word that is checked.
+const MIN_LEN: usize = 3;
+
+/// Endings that are stripped to find the base form, with what replaces them.
+const SUFFIXES: &[(&str, &str)] = &[
+    ("ies", "y"),
+    ("ied", "y"),
+    ("ing", ""),
+    ("ing", "e"),
+    ("ers", ""),
+    ("ers", "e"),
+    ("es", ""),
+    ("ed", ""),
+    ("ed", "e"),
+    ("er", ""),
+    ("er", "e"),
+    ("ly", ""),
+    ("s", ""),
+];
+
+/// Words of the built-in list.
+fn builtin() -> &'static HashSet<&'static str> {
+    static WORDS: OnceLock<HashSet<&'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| parse_word_list(BUILTIN).collect())
+}
+
+/// Words of a word list: one per line, `#` starts a comment.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::spell::parse_word_list;
+///
+/// let words: Vec<_> = parse_word_list("# team\nkubernetes\n\nOleksii  # lead\n").collect();
+/// assert_eq!(words, ["kubernetes", "Oleksii"]);
+/// ```
+pub fn parse_word_list(text: &str) -> impl Iterator<Item = &str> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|word| !word.is_empty())
+}
+
+/// The built-in words plus the words a project adds.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dictionary {
+    /// Project words, lowercased.
+    extra: HashSet<String>,
+}
+
+impl Dictionary {
+    /// A dictionary with the built-in words only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accepts `words` too, in any case.
+    pub fn add_words<I, S>(&mut self, words: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.extra
+            .extend(words.into_iter().map(|w| w.as_ref().to_lowercase()));
+        self
+    }
+
+    /// Whether `word`, or the base form it is derived from, is known.
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::spell::Dictionary;
+    ///
+    /// let mut dictionary = Dictionary::new();
+    /// assert!(dictionary.contains("Deployed"));
+    /// assert!(dictionary.contains("planning"));
+    /// assert!(!dictionary.contains("kubernetes"));
+    /// dictionary.add_words(["Kubernetes"]);
+    /// assert!(dictionary.contains("kubernetes"));
+    /// ```
+    pub fn contains(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        let word = word.strip_suffix("'s").unwrap_or(&word);
+        self.known(word)
+            || word
+                .strip_prefix("re")
+                .is_some_and(|rest| rest.len() >= MIN_LEN && self.known_base(rest))
+            || self.known_base(word)
+    }
+
+    /// The known word closest to `word`, if it is close enough to be
+    /// what was meant.
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::spell::Dictionary;
+    ///
+    /// assert_eq!(Dictionary::new().suggest("recieve"), Some("receive"));
+    /// assert_eq!(Dictionary::new().suggest("teh"), Some("the"));
+    /// ```
+    pub fn suggest(&self, word: &str) -> Option<&str> {
+        let mut words: Vec<&str> = builtin()
+            .iter()
+            .copied()
+            .chain(self.extra.iter().map(String::as_str))
+            .collect();
+        // `closest` breaks ties by order: put words with the same letters
+        // first, so a swap (`teh`) wins over a substitution.
+        let letters = sorted_letters(&word.to_lowercase());
+        words.sort_by_cached_key(|w| (sorted_letters(w) != letters, *w));
+        closest(word, words)
+    }
+
+    /// Words of `text` that look like prose but aren't known, in order.
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::spell::Dictionary;
+    ///
+    /// let dictionary = Dictionary::new();
+    /// let words = dictionary.misspellings("Fix teh login API for utf8 on GitHub, v2 soon-ish");
+    /// assert_eq!(words, ["teh", "ish"]);
+    /// ```
+    pub fn misspellings<'t>(&self, text: &'t str) -> Vec<&'t str> {
+        text.split(|c: char| !(c.is_alphanumeric() || c == '\''))
+            .map(|token| token.trim_matches('\''))
+            .filter(|token| looks_like_prose(token) && !self.contains(token))
+            .collect()
+    }
+
+    /// Whether `word`, lowercase, is listed.
+    fn known(&self, word: &str) -> bool {
+        builtin().contains(word) || self.extra.contains(word)
+    }
+
+    /// Whether `word`, lowercase, is a listed word with one of [`SUFFIXES`].
+    fn known_base(&self, word: &str) -> bool {
+        if self.known(word) {
+            return true;
+        }
+        SUFFIXES.iter().any(|(suffix, replacement)| {
+            let Some(stem) = word.strip_suffix(suffix) else {
+                return false;
+            };
+            if stem.len() < 2 {
+                return false;
+            }
+            let base = format!("{}{}", stem, replacement);
+            if self.known(&base) {
+                return true;
+            }
+            // `planned`, `running`: the last consonant is doubled.
+            let bytes = stem.as_bytes();
+            replacement.is_empty()
+                && bytes.len() >= 2
+                && bytes[bytes.len() - 1] == bytes[bytes.len() - 2]
+                && self.known(&stem[..stem.len() - 1])
+        })
+    }
+}
+
+/// Whether `token` is checked: long enough, letters only, and lowercase
+/// after its first letter.
+fn looks_like_prose(token: &str) -> bool {
+    token.chars().count() >= MIN_LEN
+        && token.chars().all(|c| c.is_alphabetic() || c == '\'')
+        && token.chars().skip(1).all(|c| !c.is_uppercase())
+}
+
+/// Letters of `word` in sorted order.
+fn sorted_letters(word: &str) -> Vec<char> {
+    let mut letters: Vec<char> = word.chars().collect();
+    letters.sort_unstable();
+    letters
+}
@@ -0,0 +1,109 @@
+//! Breaking a file up into one file per project.
+//!
+//! Each project block is copied verbatim into a file named after the
+//! project, and replaced in the original by an `include` of that file, so
+//! the original still parses to the same projects. Comments above a
+//! project stay in the original, above its `include`. Files of documents
+//! with a `version` pragma start with the same pragma.
+
+use crate::edit::Editor;
+use crate::parser::DEFAULT_VERSION;
+
+/// A file produced by [`Editor::split`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitFile {
+    /// Name of the project in it.
+    pub project: String,
+    /// File name, without directory.
+    pub file_name: String,
+    /// Contents of the file.
+    pub text: String,
+}
+
+/// File name for a project: its name in lowercase, with every run of
+/// other characters than letters and digits turned into `-`.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::split::file_name;
+///
+/// assert_eq!(file_name("Q4 Launch / Web"), "q4-launch-web.todo");
+/// assert_eq!(file_name("!!!"), "project.todo");
+/// ```
+pub fn file_name(project: &str) -> String {
+    let mut stem = String::new();
+    for c in project.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            stem.push(c);
+        } else if !stem.is_empty() && !stem.ends_with('-') {
+            stem.push('-');
+        }
+    }
+    let stem = stem.trim_end_matches('-');
+    format!("{}.todo", if stem.is_empty() { "project" } else { stem })
+}
+
+impl Editor<'_> {
+    /// Moves every project into a file of its own and includes it instead.
+    ///
+    /// File names come from [`file_name`]; when two projects would share
+    /// one, later ones get `-2`, `-3`, ... appended.
+    ///
+    /// # Arguments
+    /// * `dir` - Directory of the new files as the `include` paths should
+    ///   name it, relative to the original file; empty for the same
+    ///   directory
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::edit::Editor;
+    ///
+    /// let source = "// Work\nproject \"Web\" {\n  todo: \"A\",\n}\n";
+    /// let mut editor = Editor::new(source)?;
+    /// let files = editor.split("projects");
+    /// assert_eq!(files[0].file_name, "web.todo");
+    /// assert_eq!(files[0].text, "project \"Web\" {\n  todo: \"A\",\n}\n");
+    /// assert_eq!(editor.finish(), "// Work\ninclude \"projects/web.todo\"\n");
+    /// # Ok::<(), to_do_parcer::edit::EditError>(())
+    /// ```
+    pub fn split(&mut self, dir: &str) -> Vec<SplitFile> {
+        let version = self.document().version;
+        let header = if version == DEFAULT_VERSION {
+            String::new()
+        } else {
+            format!("version {}\n\n", version)
+        };
+        let dir = dir.trim_end_matches('/');
+
+        let mut files: Vec<SplitFile> = Vec::new();
+        let projects: Vec<_> = self
+            .document()
+            .projects
+            .iter()
+            .map(|p| (p.name.clone(), p.span))
+            .collect();
+        for (index, (name, span)) in projects.into_iter().enumerate() {
+            let base = file_name(&name);
+            let mut unique = base.clone();
+            let mut n = 1;
+            while files.iter().any(|f| f.file_name == unique) {
+                n += 1;
+                unique = format!("{}-{}.todo", base.trim_end_matches(".todo"), n);
+            }
+
+            let path = if dir.is_empty() {
+                unique.clone()
+            } else {
+                format!("{}/{}", dir, unique)
+            };
+            let text = format!("{}{}\n", header, self.text(span.start..span.end));
+            self.replace_project_at(index, format!("include \"{}\"", path));
+            files.push(SplitFile {
+                project: name,
+                file_name: unique,
+                text,
+            });
+        }
+        files
+    }
+}
@@ -0,0 +1,83 @@
+//! Summary statistics over a parsed `Document`.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::parser::{Document, TaskStatus};
+use crate::schedule::Clock;
+
+/// Task counts for a document.
+///
+/// Serializes to JSON with the field names below; maps are sorted by key.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Stats {
+    pub projects: usize,
+    pub total: usize,
+    pub active: usize,
+    pub completed: usize,
+    /// Share of completed tasks, from 0.0 to 1.0 (0.0 for no tasks).
+    pub completion_rate: f64,
+    /// Open tasks past their due date.
+    pub overdue: usize,
+    /// Tasks per priority (`high`, `medium`, `low`, `none`).
+    pub by_priority: BTreeMap<String, usize>,
+    /// Tasks per assignee, without the `@`. Unassigned tasks are not listed.
+    pub by_assignee: BTreeMap<String, usize>,
+    /// Tasks per tag. A task with several tags counts once for each.
+    pub by_tag: BTreeMap<String, usize>,
+}
+
+impl Stats {
+    /// Computes statistics for `doc`, using `now` to decide what is overdue.
+    ///
+    /// # Example
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use to_do_parcer::ToDoParser;
+    /// use to_do_parcer::stats::Stats;
+    ///
+    /// let doc = ToDoParser::parse_document(r#"project "P" { todo: "A", done: "B", }"#)?;
+    /// let stats = Stats::compute(&doc, &NaiveDate::from_ymd_opt(2025, 1, 1).unwrap());
+    /// assert_eq!(stats.completion_rate, 0.5);
+    /// # Ok::<(), to_do_parcer::ParseError>(())
+    /// ```
+    pub fn compute(doc: &Document, now: &impl Clock) -> Stats {
+        let mut stats = Stats {
+            projects: doc.projects.len(),
+            total: 0,
+            active: 0,
+            completed: 0,
+            completion_rate: 0.0,
+            overdue: doc.overdue(now).len(),
+            by_priority: ["high", "medium", "low", "none"]
+                .iter()
+                .map(|p| (p.to_string(), 0))
+                .collect(),
+            by_assignee: BTreeMap::new(),
+            by_tag: BTreeMap::new(),
+        };
+
+        for (_, task) in doc.tasks() {
+            stats.total += 1;
+            match task.status {
+                TaskStatus::Todo => stats.active += 1,
+                TaskStatus::Done => stats.completed += 1,
+            }
+
+            let priority = task.priority.as_ref().map_or("none", |p| p.as_str());
+            *stats.by_priority.entry(priority.to_string()).or_default() += 1;
+            if let Some(assignee) = &task.assignee {
+                *stats.by_assignee.entry(assignee.clone()).or_default() += 1;
+            }
+            for tag in &task.tags {
+                *stats.by_tag.entry(tag.clone()).or_default() += 1;
+            }
+        }
+
+        if stats.total > 0 {
+            stats.completion_rate = stats.completed as f64 / stats.total as f64;
+        }
+        stats
+    }
+}
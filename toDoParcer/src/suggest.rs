@@ -0,0 +1,62 @@
+//! "Did you mean" hints for misspelled names.
+//!
+//! Distances count insertions, deletions, substitutions and swaps of two
+//! neighbouring characters, so `deu` is one edit away from `due`.
+
+/// Number of single-character edits that turn `a` into `b`.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::suggest::edit_distance;
+///
+/// assert_eq!(edit_distance("asign", "assign"), 1);
+/// assert_eq!(edit_distance("deu", "due"), 1);
+/// assert_eq!(edit_distance("kitten", "sitting"), 3);
+/// ```
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // Rows for the prefixes of `a` of length i - 2, i - 1 and i.
+    let mut before: Vec<usize> = Vec::new();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (previous[j] + 1)
+                .min(row[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(before[j - 2] + 1);
+            }
+        }
+        before = std::mem::replace(&mut previous, row);
+    }
+    previous[b.len()]
+}
+
+/// Picks the candidate closest to `word`, ignoring case, if it is close
+/// enough to be a likely typo: one edit for every three characters of
+/// `word`, and at least one.
+///
+/// Ties go to the earliest candidate.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::suggest::closest;
+///
+/// let keywords = ["assign", "due", "depends_on"];
+/// assert_eq!(closest("Asign", keywords), Some("assign"));
+/// assert_eq!(closest("dependson", keywords), Some("depends_on"));
+/// assert_eq!(closest("color", keywords), None);
+/// ```
+pub fn closest<'c>(word: &str, candidates: impl IntoIterator<Item = &'c str>) -> Option<&'c str> {
+    let word = word.to_lowercase();
+    let limit = (word.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .map(|c| (edit_distance(&word, &c.to_lowercase()), c))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, c)| c)
+}
@@ -0,0 +1,196 @@
+//! Aligned plain-text tables of tasks.
+
+use std::fmt;
+use std::str::FromStr;
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::color::{Palette, Style};
+use crate::parser::{Project, Task};
+
+/// A column of a task table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Project,
+    Status,
+    Title,
+    Priority,
+    Due,
+    Assignee,
+    Tags,
+}
+
+impl Column {
+    /// Every column, in the default display order.
+    pub const ALL: &'static [Column] = &[
+        Column::Project,
+        Column::Status,
+        Column::Title,
+        Column::Priority,
+        Column::Due,
+        Column::Assignee,
+        Column::Tags,
+    ];
+
+    /// Name of the column as accepted by `FromStr`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Column::Project => "project",
+            Column::Status => "status",
+            Column::Title => "title",
+            Column::Priority => "priority",
+            Column::Due => "due",
+            Column::Assignee => "assignee",
+            Column::Tags => "tags",
+        }
+    }
+
+    /// Text of this column's cell for `task`; `-` if the value is missing.
+    fn cell(&self, project: &Project, task: &Task, palette: &Palette) -> String {
+        match self {
+            Column::Project => project.name.clone(),
+            Column::Status => task.status.as_str().to_string(),
+            Column::Title => task.title.clone(),
+            Column::Priority => task.priority.map_or("-", |p| p.as_str()).to_string(),
+            Column::Due => task
+                .due_date
+                .as_deref()
+                .map_or_else(|| "-".to_string(), |d| palette.date(d)),
+            Column::Assignee => task
+                .assignee
+                .as_ref()
+                .map_or_else(|| "-".to_string(), |a| format!("@{}", a)),
+            Column::Tags => {
+                if task.tags.is_empty() {
+                    "-".to_string()
+                } else {
+                    task.tags.join(",")
+                }
+            }
+        }
+    }
+
+    /// Colors an already padded cell of this column.
+    fn paint(&self, palette: &Palette, task: &Task, cell: &str) -> String {
+        match (self, task.priority) {
+            (Column::Status, _) => palette.paint(Style::status(task.status), cell),
+            (Column::Priority, Some(p)) => palette.paint(Style::priority(p), cell),
+            (Column::Due, _) => palette.overdue(task, cell),
+            _ => cell.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Column::ALL
+            .iter()
+            .find(|c| c.as_str().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<_> = Column::ALL.iter().map(|c| c.as_str()).collect();
+                format!(
+                    "unknown column '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Renders tasks as a table with one row per task and a header row.
+///
+/// Columns are separated by two spaces and padded to their widest cell, as
+/// it shows in a terminal (a CJK character or an emoji takes two columns);
+/// missing values are shown as `-`.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::ToDoParser;
+/// use to_do_parcer::table::format_table;
+///
+/// let doc = ToDoParser::parse_document(r#"project "P" { todo: "A", @high, }"#)?;
+/// let rows: Vec<_> = doc.tasks().collect();
+/// assert_eq!(
+///     format_table(&rows),
+///     "PROJECT  STATUS  TITLE  PRIORITY  DUE  ASSIGNEE  TAGS\n\
+///      P        todo    A      high      -    -         -\n"
+/// );
+/// # Ok::<(), to_do_parcer::ParseError>(())
+/// ```
+pub fn format_table(rows: &[(&Project, &Task)]) -> String {
+    format_table_with(rows, &Palette::default())
+}
+
+/// Renders tasks like `format_table`, coloring statuses, priorities and
+/// overdue dates with `palette`.
+pub fn format_table_with(rows: &[(&Project, &Task)], palette: &Palette) -> String {
+    format_columns(rows, Column::ALL, palette)
+}
+
+/// Renders tasks as a table of the given columns, in the given order.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::ToDoParser;
+/// use to_do_parcer::color::Palette;
+/// use to_do_parcer::table::{Column, format_columns};
+///
+/// let doc = ToDoParser::parse_document(r#"project "P" { todo: "A", due: 2025-01-01, }"#)?;
+/// let rows: Vec<_> = doc.tasks().collect();
+/// assert_eq!(
+///     format_columns(&rows, &[Column::Due, Column::Title], &Palette::default()),
+///     "DUE         TITLE\n2025-01-01  A\n"
+/// );
+/// # Ok::<(), to_do_parcer::ParseError>(())
+/// ```
+pub fn format_columns(rows: &[(&Project, &Task)], columns: &[Column], palette: &Palette) -> String {
+    let mut cells: Vec<Vec<String>> =
+        vec![columns.iter().map(|c| c.as_str().to_uppercase()).collect()];
+    for (project, task) in rows {
+        cells.push(
+            columns
+                .iter()
+                .map(|c| c.cell(project, task, palette))
+                .collect(),
+        );
+    }
+
+    let mut widths = vec![0; columns.len()];
+    for row in &cells {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.width());
+        }
+    }
+
+    let mut out = String::new();
+    for (r, row) in cells.iter().enumerate() {
+        // The header row has no task.
+        let task = r.checked_sub(1).map(|r| rows[r].1);
+        let mut line = String::new();
+        for (i, cell) in row.iter().enumerate() {
+            if i > 0 {
+                line.push_str("  ");
+            }
+            match task {
+                Some(task) => line.push_str(&columns[i].paint(palette, task, cell)),
+                None => line.push_str(cell),
+            }
+            if i + 1 < row.len() {
+                let pad = widths[i] - cell.width();
+                line.extend(std::iter::repeat_n(' ', pad));
+            }
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,155 @@
+//! The tags of a task, stored inline while there are few of them.
+//!
+//! Most tasks have no tag or one or two, so [`Tags`] keeps up to
+//! [`INLINE_TAGS`] of them inside the task itself and only allocates a
+//! separate buffer for longer lists. It dereferences to `[String]`, so
+//! slice methods like `iter`, `len`, `contains` and `join` work as on a
+//! `Vec<String>`.
+//!
+//! ```
+//! use to_do_parcer::tags::Tags;
+//!
+//! let mut tags = Tags::new();
+//! tags.push("bug".to_string());
+//! tags.extend(["ui".to_string()]);
+//! assert_eq!(tags, ["bug", "ui"]);
+//! assert_eq!(tags.join(", "), "bug, ui");
+//! assert!(tags.is_inline());
+//! ```
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use smallvec::SmallVec;
+
+/// Tags kept inline before a [`Tags`] allocates.
+pub const INLINE_TAGS: usize = 2;
+
+/// The tags of a task, in source order.
+#[derive(Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Tags(SmallVec<[String; INLINE_TAGS]>);
+
+impl Tags {
+    /// Creates an empty list, without allocating.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `tag` at the end.
+    pub fn push(&mut self, tag: String) {
+        self.0.push(tag);
+    }
+
+    /// Removes every tag.
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Whether the tags are stored inside the task, without a heap buffer.
+    pub fn is_inline(&self) -> bool {
+        !self.0.spilled()
+    }
+
+    /// The tags as a `Vec`.
+    pub fn into_vec(self) -> Vec<String> {
+        self.0.into_vec()
+    }
+}
+
+impl fmt::Debug for Tags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl Deref for Tags {
+    type Target = [String];
+
+    fn deref(&self) -> &[String] {
+        &self.0
+    }
+}
+
+impl DerefMut for Tags {
+    fn deref_mut(&mut self) -> &mut [String] {
+        &mut self.0
+    }
+}
+
+impl From<Vec<String>> for Tags {
+    fn from(tags: Vec<String>) -> Self {
+        Tags(SmallVec::from_vec(tags))
+    }
+}
+
+impl From<Tags> for Vec<String> {
+    fn from(tags: Tags) -> Self {
+        tags.into_vec()
+    }
+}
+
+impl FromIterator<String> for Tags {
+    fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
+        Tags(iter.into_iter().collect())
+    }
+}
+
+impl Extend<String> for Tags {
+    fn extend<I: IntoIterator<Item = String>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+impl IntoIterator for Tags {
+    type Item = String;
+    type IntoIter = smallvec::IntoIter<[String; INLINE_TAGS]>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Tags {
+    type Item = &'a String;
+    type IntoIter = std::slice::Iter<'a, String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Tags {
+    type Item = &'a mut String;
+    type IntoIter = std::slice::IterMut<'a, String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter_mut()
+    }
+}
+
+impl<T> PartialEq<Vec<T>> for Tags
+where
+    String: PartialEq<T>,
+{
+    fn eq(&self, other: &Vec<T>) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T, const N: usize> PartialEq<[T; N]> for Tags
+where
+    String: PartialEq<T>,
+{
+    fn eq(&self, other: &[T; N]) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T> PartialEq<[T]> for Tags
+where
+    String: PartialEq<T>,
+{
+    fn eq(&self, other: &[T]) -> bool {
+        self[..] == *other
+    }
+}
@@ -0,0 +1,66 @@
+//! Starter files written by `init`.
+//!
+//! Each template is a well-formed version 2 file whose header comment lists
+//! every task attribute, so new users can learn the syntax by editing it.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A starter `.todo` file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Template {
+    /// A sprint with ids, dates, assignees and dependencies between tasks.
+    Sprint,
+    /// Household tasks and a trip plan.
+    Personal,
+    /// A single project with one task.
+    Empty,
+}
+
+impl Template {
+    /// Every template, in the order shown in help texts.
+    pub const ALL: &'static [Template] = &[Template::Sprint, Template::Personal, Template::Empty];
+
+    /// Name of the template as accepted by `FromStr`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Template::Sprint => "sprint",
+            Template::Personal => "personal",
+            Template::Empty => "empty",
+        }
+    }
+
+    /// Text of the template.
+    pub fn text(&self) -> &'static str {
+        match self {
+            Template::Sprint => include_str!("../templates/sprint.todo"),
+            Template::Personal => include_str!("../templates/personal.todo"),
+            Template::Empty => include_str!("../templates/empty.todo"),
+        }
+    }
+}
+
+impl fmt::Display for Template {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Template {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Template::ALL
+            .iter()
+            .find(|t| t.as_str().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<_> = Template::ALL.iter().map(|t| t.as_str()).collect();
+                format!(
+                    "unknown template '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
@@ -0,0 +1,167 @@
+//! Dumps of the pest syntax tree, for debugging the grammar and for editor
+//! tooling.
+//!
+//! The text form shows one rule per line, indented by depth, with its byte
+//! span and the start of the text it matched:
+//!
+//! ```text
+//! - file 0..26 "project \"P\" { todo: \"A\", }"
+//!   - project 0..26 "project \"P\" { todo: \"A\", }"
+//!     - quoted 8..11 "\"P\""
+//!     - task 14..24 "todo: \"A\","
+//! ```
+//!
+//! The JSON form is an array of root nodes, each with `rule`, `start` and
+//! `end` (byte offsets), `line` and `column` (1-based, of `start`), the
+//! full matched `text` and its `children`.
+
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+use pest::iterators::{Pair, Pairs};
+use serde::Serialize;
+
+use crate::parser::Rule;
+
+/// Characters of matched text shown per node in the text form.
+const SNIPPET_CHARS: usize = 40;
+
+/// Output format of `--tree`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeFormat {
+    Text,
+    Json,
+}
+
+impl TreeFormat {
+    /// Every supported format, in the order shown in help texts.
+    pub const ALL: &'static [TreeFormat] = &[TreeFormat::Text, TreeFormat::Json];
+
+    /// Name of the format as accepted by `FromStr`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TreeFormat::Text => "text",
+            TreeFormat::Json => "json",
+        }
+    }
+}
+
+impl fmt::Display for TreeFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TreeFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TreeFormat::ALL
+            .iter()
+            .find(|f| f.as_str().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<_> = TreeFormat::ALL.iter().map(|f| f.as_str()).collect();
+                format!(
+                    "unknown format '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// One rule match in the JSON form.
+#[derive(Debug, Serialize)]
+struct Node<'i> {
+    rule: String,
+    start: usize,
+    end: usize,
+    line: usize,
+    column: usize,
+    text: &'i str,
+    children: Vec<Node<'i>>,
+}
+
+impl<'i> From<Pair<'i, Rule>> for Node<'i> {
+    fn from(pair: Pair<'i, Rule>) -> Self {
+        let span = pair.as_span();
+        let (line, column) = pair.line_col();
+        Node {
+            rule: format!("{:?}", pair.as_rule()),
+            start: span.start(),
+            end: span.end(),
+            line,
+            column,
+            text: span.as_str(),
+            children: pair.into_inner().map(Node::from).collect(),
+        }
+    }
+}
+
+/// Renders a parse tree in the given format.
+///
+/// # Example
+/// ```
+/// use pest::Parser;
+/// use to_do_parcer::ToDoParser;
+/// use to_do_parcer::parser::Rule;
+/// use to_do_parcer::tree::{TreeFormat, render_tree};
+///
+/// let pairs = ToDoParser::parse(Rule::quoted, "\"P\"").unwrap();
+/// assert_eq!(render_tree(pairs, TreeFormat::Text), "- quoted 0..3 \"\\\"P\\\"\"\n");
+/// ```
+pub fn render_tree(pairs: Pairs<Rule>, format: TreeFormat) -> String {
+    match format {
+        TreeFormat::Text => format_tree(pairs),
+        TreeFormat::Json => tree_json(pairs),
+    }
+}
+
+/// Renders a tree of parsed rules in the text form described above.
+///
+/// # Arguments
+/// * `pairs` - Pest parse tree to render.
+pub fn format_tree(pairs: Pairs<Rule>) -> String {
+    fn write_pair(out: &mut String, pair: Pair<Rule>, indent: usize) {
+        let span = pair.as_span();
+        let _ = writeln!(
+            out,
+            "{:indent$}- {:?} {}..{} {:?}",
+            "",
+            pair.as_rule(),
+            span.start(),
+            span.end(),
+            snippet(span.as_str()),
+            indent = indent * 2
+        );
+        for inner in pair.into_inner() {
+            write_pair(out, inner, indent + 1);
+        }
+    }
+
+    let mut out = String::new();
+    for pair in pairs {
+        write_pair(&mut out, pair, 0);
+    }
+    out
+}
+
+/// Renders a tree of parsed rules as the JSON array described above.
+pub fn tree_json(pairs: Pairs<Rule>) -> String {
+    let nodes: Vec<Node> = pairs.map(Node::from).collect();
+    let mut out = serde_json::to_string_pretty(&nodes).expect("syntax tree serializes to JSON");
+    out.push('\n');
+    out
+}
+
+/// First line of `text`, cut to `SNIPPET_CHARS` characters with `…` when
+/// anything is left out.
+fn snippet(text: &str) -> String {
+    let line = text.lines().next().unwrap_or("");
+    let mut out: String = line.chars().take(SNIPPET_CHARS).collect();
+    if out.len() < text.len() {
+        out.push('…');
+    }
+    out
+}
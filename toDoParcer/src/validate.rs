@@ -0,0 +1,541 @@
+//! Semantic checks run on a successfully parsed `Document`.
+//!
+//! The grammar only guarantees that a file is well-formed; these checks
+//! catch files that parse but are probably not what the author meant.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::parser::{Document, ParseWarning, Span, TaskStatus};
+use crate::suggest::closest;
+
+/// Spelling that tags must follow, checked by `tag-name`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagStyle {
+    /// Lowercase words joined by `-`, e.g. `front-end`.
+    KebabCase,
+    /// Lowercase words joined by `_`, e.g. `front_end`.
+    SnakeCase,
+    /// No uppercase letters.
+    Lowercase,
+}
+
+impl TagStyle {
+    /// Every style, in the order shown in help texts.
+    pub const ALL: &'static [TagStyle] = &[
+        TagStyle::KebabCase,
+        TagStyle::SnakeCase,
+        TagStyle::Lowercase,
+    ];
+
+    /// Name of the style as accepted by `FromStr`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TagStyle::KebabCase => "kebab-case",
+            TagStyle::SnakeCase => "snake_case",
+            TagStyle::Lowercase => "lowercase",
+        }
+    }
+
+    /// Whether `tag` is written in this style.
+    ///
+    /// # Example
+    /// ```
+    /// use to_do_parcer::validate::TagStyle;
+    ///
+    /// assert!(TagStyle::KebabCase.matches("front-end"));
+    /// assert!(!TagStyle::KebabCase.matches("Front End"));
+    /// assert!(!TagStyle::KebabCase.matches("front--end"));
+    /// assert!(TagStyle::Lowercase.matches("front end"));
+    /// ```
+    pub fn matches(&self, tag: &str) -> bool {
+        match self {
+            TagStyle::Lowercase => !tag.chars().any(char::is_uppercase),
+            TagStyle::KebabCase | TagStyle::SnakeCase => self.convert(tag) == tag,
+        }
+    }
+
+    /// `tag` rewritten in this style: lowercased and, for kebab-case and
+    /// snake_case, with every run of other characters than letters and
+    /// digits turned into one separator.
+    pub fn convert(&self, tag: &str) -> String {
+        let separator = match self {
+            TagStyle::KebabCase => "-",
+            TagStyle::SnakeCase => "_",
+            TagStyle::Lowercase => return tag.to_lowercase(),
+        };
+        tag.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+}
+
+impl fmt::Display for TagStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TagStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TagStyle::ALL
+            .iter()
+            .find(|style| style.as_str().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<_> = TagStyle::ALL.iter().map(|style| style.as_str()).collect();
+                format!(
+                    "unknown tag style '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Settings for [`validate_with`].
+#[derive(Debug, Clone, Default)]
+pub struct ValidateOptions {
+    /// Report dependencies on unknown tasks as warnings instead of errors.
+    pub lenient: bool,
+    /// Report tasks sharing a title within a project as errors instead of
+    /// warnings.
+    pub strict: bool,
+    /// Warn about open tasks due before this day; `None` skips the check.
+    pub today: Option<NaiveDate>,
+    /// Spelling every tag must follow; `None` allows any.
+    pub tag_style: Option<TagStyle>,
+    /// The only tags allowed; empty allows any.
+    pub allowed_tags: Vec<String>,
+    /// Handles `assign:` may name, without `@`; empty allows any.
+    pub assignees: Vec<String>,
+}
+
+/// Reads the handles of a roster file: one per line, with or without `@`.
+/// Blank lines and lines starting with `#` are skipped.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::validate::parse_roster;
+///
+/// assert_eq!(parse_roster("# team\n@tanya\n\noleksii\n"), ["tanya", "oleksii"]);
+/// ```
+pub fn parse_roster(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.trim_start_matches('@').to_string())
+        .collect()
+}
+
+/// Runs every semantic check on `doc` with the default options.
+///
+/// Parse warnings collected in `doc.warnings` are reported as well, with
+/// code `parse-warning`. Issues are sorted by line.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::ToDoParser;
+/// use to_do_parcer::validate::validate;
+///
+/// let doc = ToDoParser::parse_document(r#"project "Empty" {}"#)?;
+/// assert_eq!(validate(&doc)[0].code, "empty-project");
+/// # Ok::<(), to_do_parcer::ParseError>(())
+/// ```
+pub fn validate(doc: &Document) -> Vec<Diagnostic> {
+    validate_with(doc, &ValidateOptions::default())
+}
+
+/// Runs every semantic check on `doc`.
+///
+/// # Example
+/// ```
+/// use to_do_parcer::ToDoParser;
+/// use to_do_parcer::diagnostic::Severity;
+/// use to_do_parcer::validate::{ValidateOptions, validate_with};
+///
+/// let doc = ToDoParser::parse_document(r#"project "P" { todo: "A", depends_on: "Typo", }"#)?;
+/// let options = ValidateOptions {
+///     lenient: true,
+///     ..ValidateOptions::default()
+/// };
+/// let issues = validate_with(&doc, &options);
+/// assert_eq!(issues[0].code, "unknown-dependency");
+/// assert_eq!(issues[0].severity, Severity::Warning);
+/// # Ok::<(), to_do_parcer::ParseError>(())
+/// ```
+pub fn validate_with(doc: &Document, options: &ValidateOptions) -> Vec<Diagnostic> {
+    let mut issues = Vec::new();
+
+    issues.extend(doc.warnings.iter().map(ParseWarning::diagnostic));
+
+    check_names(doc, &mut issues);
+    check_empty_projects(doc, &mut issues);
+    check_duplicate_tags(doc, &mut issues);
+    check_tag_names(doc, options, &mut issues);
+    check_assignees(doc, options, &mut issues);
+    check_duplicate_titles(doc, options, &mut issues);
+    check_dependencies(doc, options, &mut issues);
+    check_dependency_cycles(doc, &mut issues);
+    check_done_dependencies(doc, &mut issues);
+    check_date_order(doc, &mut issues);
+    if let Some(today) = options.today {
+        check_overdue(doc, today, &mut issues);
+    }
+
+    issues.sort_by_key(|i| i.line);
+    issues
+}
+
+/// Keywords of the format, which make confusing names on their own.
+const RESERVED_NAMES: [&str; 5] = ["project", "todo", "done", "include", "version"];
+
+/// Why `name` is not usable as a project or task name, if it isn't.
+fn name_problem(name: &str) -> Option<&'static str> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Some(if name.is_empty() { "empty" } else { "blank" });
+    }
+    let word = trimmed.trim_end_matches(':');
+    RESERVED_NAMES
+        .iter()
+        .any(|keyword| keyword.eq_ignore_ascii_case(word))
+        .then_some("a reserved keyword")
+}
+
+/// Reports project names and task titles that are empty, only whitespace,
+/// or nothing but a keyword such as `todo`, as errors.
+fn check_names(doc: &Document, issues: &mut Vec<Diagnostic>) {
+    for project in &doc.projects {
+        if let Some(problem) = name_problem(&project.name) {
+            issues.push(Diagnostic {
+                severity: Severity::Error,
+                code: "invalid-name",
+                line: project.span.line,
+                span: None,
+                suggestion: None,
+                fix: None,
+                message: format!("project name \"{}\" is {}", project.name, problem),
+            });
+        }
+        for task in &project.tasks {
+            if let Some(problem) = name_problem(&task.title) {
+                issues.push(Diagnostic {
+                    severity: Severity::Error,
+                    code: "invalid-name",
+                    line: task.span.line,
+                    span: None,
+                    suggestion: None,
+                    fix: None,
+                    message: format!(
+                        "title \"{}\" of a task in project \"{}\" is {}",
+                        task.title, project.name, problem
+                    ),
+                });
+            }
+        }
+    }
+}
+
+/// Warns about projects without any task.
+fn check_empty_projects(doc: &Document, issues: &mut Vec<Diagnostic>) {
+    for project in doc.projects.iter().filter(|p| p.tasks.is_empty()) {
+        issues.push(Diagnostic {
+            severity: Severity::Warning,
+            code: "empty-project",
+            line: project.span.line,
+            span: None,
+            suggestion: None,
+            fix: None,
+            message: format!("project \"{}\" has no tasks", project.name),
+        });
+    }
+}
+
+/// Warns about a tag repeated on the same task.
+fn check_duplicate_tags(doc: &Document, issues: &mut Vec<Diagnostic>) {
+    for (_, task) in doc.tasks() {
+        for (i, tag) in task.tags.iter().enumerate() {
+            if task.tags[..i].contains(tag) {
+                issues.push(Diagnostic {
+                    severity: Severity::Warning,
+                    code: "duplicate-tag",
+                    line: task.span.line,
+                    span: None,
+                    suggestion: None,
+                    fix: None,
+                    message: format!("task \"{}\" has tag \"{}\" more than once", task.title, tag),
+                });
+            }
+        }
+    }
+}
+
+/// Reports tags that break the naming rules of `options`, as errors.
+fn check_tag_names(doc: &Document, options: &ValidateOptions, issues: &mut Vec<Diagnostic>) {
+    for (_, task) in doc.tasks() {
+        for tag in &task.tags {
+            let (message, suggestion) = if let Some(style) = options.tag_style
+                && !style.matches(tag)
+            {
+                let fixed = style.convert(tag);
+                let message = format!(
+                    "tag \"{}\" on task \"{}\" is not {}, write it as \"{}\"",
+                    tag, task.title, style, fixed
+                );
+                (message, Some(fixed))
+            } else if !options.allowed_tags.is_empty() && !options.allowed_tags.contains(tag) {
+                let suggestion = closest(tag, options.allowed_tags.iter().map(String::as_str));
+                let message = format!(
+                    "tag \"{}\" on task \"{}\" is not an allowed tag{}",
+                    tag,
+                    task.title,
+                    suggestion
+                        .map(|name| format!(", did you mean \"{}\"?", name))
+                        .unwrap_or_default()
+                );
+                (message, suggestion.map(str::to_string))
+            } else {
+                continue;
+            };
+            issues.push(Diagnostic {
+                severity: Severity::Error,
+                code: "tag-name",
+                line: task.span.line,
+                span: None,
+                message,
+                suggestion,
+                fix: None,
+            });
+        }
+    }
+}
+
+/// Reports `assign:` handles missing from the roster in `options`, as errors.
+fn check_assignees(doc: &Document, options: &ValidateOptions, issues: &mut Vec<Diagnostic>) {
+    if options.assignees.is_empty() {
+        return;
+    }
+    for (_, task) in doc.tasks() {
+        let Some(assignee) = &task.assignee else {
+            continue;
+        };
+        if options.assignees.contains(assignee) {
+            continue;
+        }
+        let suggestion = closest(assignee, options.assignees.iter().map(String::as_str));
+        issues.push(Diagnostic {
+            severity: Severity::Error,
+            code: "unknown-assignee",
+            line: task.span.line,
+            span: None,
+            message: format!(
+                "task \"{}\" is assigned to @{}, who is not on the roster{}",
+                task.title,
+                assignee,
+                suggestion
+                    .map(|name| format!(", did you mean @{}?", name))
+                    .unwrap_or_default()
+            ),
+            suggestion: suggestion.map(str::to_string),
+            fix: None,
+        });
+    }
+}
+
+/// Reports every task after the first of its project with the same title:
+/// title references to either can only reach the first.
+fn check_duplicate_titles(doc: &Document, options: &ValidateOptions, issues: &mut Vec<Diagnostic>) {
+    for project in &doc.projects {
+        for (i, task) in project.tasks.iter().enumerate() {
+            let Some(first) = project.tasks[..i].iter().find(|t| t.title == task.title) else {
+                continue;
+            };
+            issues.push(Diagnostic {
+                severity: if options.strict {
+                    Severity::Error
+                } else {
+                    Severity::Warning
+                },
+                code: "duplicate-title",
+                line: task.span.line,
+                span: None,
+                suggestion: None,
+                fix: None,
+                message: format!(
+                    "project \"{}\" already has a task \"{}\" on line {}",
+                    project.name, task.title, first.span.line
+                ),
+            });
+        }
+    }
+}
+
+/// Reports `depends_on` references that don't resolve to any task, as
+/// errors, or as warnings in lenient mode.
+fn check_dependencies(doc: &Document, options: &ValidateOptions, issues: &mut Vec<Diagnostic>) {
+    let deps = doc.dependencies();
+    for r in doc.task_refs() {
+        let task = r.get(doc).expect("task ref is valid");
+        let Some(reference) = &task.depends_on else {
+            continue;
+        };
+        if deps.of(r).is_some() {
+            continue;
+        }
+        let suggestion = closest_task(doc, reference, r.project);
+        issues.push(Diagnostic {
+            severity: if options.lenient {
+                Severity::Warning
+            } else {
+                Severity::Error
+            },
+            code: "unknown-dependency",
+            line: task.depends_on_span.map_or(task.span.line, |s| s.line),
+            span: task.depends_on_span,
+            message: format!(
+                "task \"{}\" depends on unknown task \"{}\"{}",
+                task.title,
+                reference,
+                suggestion
+                    .as_ref()
+                    .map(|name| format!(", did you mean \"{}\"?", name))
+                    .unwrap_or_default()
+            ),
+            suggestion,
+            fix: None,
+        });
+    }
+}
+
+/// The existing task reference closest to a misspelled one: a
+/// `Project/Title` path if `reference` is one, a title otherwise, looking
+/// in the project with index `home` first.
+fn closest_task(doc: &Document, reference: &str, home: usize) -> Option<String> {
+    let projects = std::iter::once(home).chain((0..doc.projects.len()).filter(|&p| p != home));
+    let names: Vec<String> = projects
+        .flat_map(|p| {
+            let project = &doc.projects[p];
+            project.tasks.iter().map(move |t| {
+                if reference.contains('/') {
+                    format!("{}/{}", project.name, t.title)
+                } else {
+                    t.title.clone()
+                }
+            })
+        })
+        .collect();
+    closest(reference, names.iter().map(String::as_str)).map(str::to_string)
+}
+
+/// Reports every loop of `depends_on` references once, at its first task.
+fn check_dependency_cycles(doc: &Document, issues: &mut Vec<Diagnostic>) {
+    for cycle in doc.dependency_cycles() {
+        let tasks: Vec<_> = cycle
+            .iter()
+            .map(|r| r.get(doc).expect("task ref is valid"))
+            .collect();
+        let mut chain: Vec<String> = tasks.iter().map(|t| format!("\"{}\"", t.title)).collect();
+        chain.push(chain[0].clone());
+        let first = tasks[0];
+        issues.push(Diagnostic {
+            severity: Severity::Error,
+            code: "dependency-cycle",
+            line: first.depends_on_span.map_or(first.span.line, |s| s.line),
+            span: first.depends_on_span,
+            suggestion: None,
+            fix: None,
+            message: format!("dependency cycle: {}", chain.join(" -> ")),
+        });
+    }
+}
+
+/// Warns about done tasks that depend on a task that is still open.
+fn check_done_dependencies(doc: &Document, issues: &mut Vec<Diagnostic>) {
+    let deps = doc.dependencies();
+    for r in doc.task_refs() {
+        let task = r.get(doc).expect("task ref is valid");
+        if task.status != TaskStatus::Done {
+            continue;
+        }
+        let Some(target) = deps.of(r).and_then(|d| d.get(doc)) else {
+            continue;
+        };
+        if target.status == TaskStatus::Todo {
+            issues.push(Diagnostic {
+                severity: Severity::Warning,
+                code: "done-before-dependency",
+                line: task.depends_on_span.map_or(task.span.line, |s| s.line),
+                span: task.depends_on_span,
+                suggestion: None,
+                fix: None,
+                message: format!(
+                    "task \"{}\" is done but depends on \"{}\", which is not",
+                    task.title, target.title
+                ),
+            });
+        }
+    }
+}
+
+/// Reports tasks due before they start, as errors at their `due:`.
+fn check_date_order(doc: &Document, issues: &mut Vec<Diagnostic>) {
+    for (_, task) in doc.tasks() {
+        let (Some(start), Some(due)) = (task.start(), task.due()) else {
+            continue;
+        };
+        if start <= due {
+            continue;
+        }
+        let at = |span: Option<Span>| {
+            span.map(|s| format!(" ({}:{})", s.line, s.column))
+                .unwrap_or_default()
+        };
+        issues.push(Diagnostic {
+            severity: Severity::Error,
+            code: "due-before-start",
+            line: task.due_span.map_or(task.span.line, |s| s.line),
+            span: task.due_span,
+            message: format!(
+                "task \"{}\" is due {}{} before it starts {}{}",
+                task.title,
+                due,
+                at(task.due_span),
+                start,
+                at(task.start_span)
+            ),
+            suggestion: None,
+            fix: None,
+        });
+    }
+}
+
+/// Warns about open tasks whose due date is before `today`.
+fn check_overdue(doc: &Document, today: NaiveDate, issues: &mut Vec<Diagnostic>) {
+    for (_, task) in doc.overdue(&today) {
+        let due = task.due().expect("overdue tasks have a due date");
+        let days = (today - due).num_days();
+        issues.push(Diagnostic {
+            severity: Severity::Warning,
+            code: "overdue",
+            line: task.span.line,
+            span: None,
+            suggestion: None,
+            fix: None,
+            message: format!(
+                "task \"{}\" was due {} ({} day{} ago)",
+                task.title,
+                due,
+                days,
+                if days == 1 { "" } else { "s" }
+            ),
+        });
+    }
+}
@@ -0,0 +1,326 @@
+//! A directory of `.todo` files kept parsed and validated as they change.
+//!
+//! [`Workspace::refresh`] looks for `.todo` files under the root, parses
+//! the ones that were added or whose content changed (through a
+//! [`DocumentCache`]) and validates them again. A `depends_on` reference
+//! that names no task of its own file resolves against the tasks of the
+//! other files: when a change adds or removes a task name, only the files
+//! referring to that name are validated again, so a refresh after editing
+//! one file of a large workspace does work in proportion to that file.
+//!
+//! Dependency cycles and dependencies on done tasks are still checked
+//! within each file only.
+//!
+//! ```no_run
+//! use to_do_parcer::options::ParseOptions;
+//! use to_do_parcer::validate::ValidateOptions;
+//! use to_do_parcer::workspace::Workspace;
+//!
+//! let mut workspace = Workspace::new("tasks", ParseOptions::default(), ValidateOptions::default());
+//! let changes = workspace.refresh()?;
+//! for path in &changes.revalidated {
+//!     for issue in workspace.diagnostics(path) {
+//!         println!("{}:{}: {}", path.display(), issue.line, issue.message);
+//!     }
+//! }
+//! // Nothing changed on disk: nothing is parsed or validated.
+//! assert!(workspace.refresh()?.is_empty());
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::cache::DocumentCache;
+use crate::diagnostic::Diagnostic;
+use crate::options::ParseOptions;
+use crate::parser::{Document, ParseError};
+use crate::validate::{ValidateOptions, validate_with};
+
+/// The `.todo` files under a directory, with their diagnostics.
+#[derive(Debug)]
+pub struct Workspace {
+    root: PathBuf,
+    parse: ParseOptions,
+    validate: ValidateOptions,
+    cache: DocumentCache,
+    files: BTreeMap<PathBuf, FileState>,
+    /// Files defining each task name: titles, `Project/Title` paths and
+    /// `#id`s.
+    names: HashMap<String, BTreeSet<PathBuf>>,
+}
+
+/// What a [`Workspace::refresh`] did, each list in path order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Changes {
+    /// Files parsed because they are new or their content changed.
+    pub reparsed: Vec<PathBuf>,
+    /// Files that disappeared.
+    pub removed: Vec<PathBuf>,
+    /// Files whose diagnostics were computed again: the reparsed ones and
+    /// those referring to a task name another file added or removed.
+    pub revalidated: Vec<PathBuf>,
+}
+
+impl Changes {
+    /// Whether the refresh found nothing to do.
+    pub fn is_empty(&self) -> bool {
+        self.reparsed.is_empty() && self.removed.is_empty() && self.revalidated.is_empty()
+    }
+}
+
+/// One file of a [`Workspace`].
+#[derive(Debug)]
+struct FileState {
+    /// Why the file failed to parse, if it did.
+    error: Option<ParseError>,
+    /// Task names the file defines.
+    names: BTreeSet<String>,
+    /// Diagnostics of the file checked on its own.
+    local: Vec<Diagnostic>,
+    /// `depends_on` references that no task of the file matches, with the
+    /// index of their `unknown-dependency` diagnostic in `local`.
+    unresolved: Vec<(String, usize)>,
+    /// `local` without the unknown dependencies another file defines.
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Workspace {
+    /// Creates a workspace for the `.todo` files under `root`, in
+    /// subdirectories too. Nothing is read until [`Workspace::refresh`].
+    ///
+    /// # Arguments
+    /// * `root` - Directory to look for files in
+    /// * `parse` - Options every file is parsed with; includes resolve
+    ///   relative to the including file unless `base_dir` is set
+    /// * `validate` - Options every file is validated with
+    pub fn new(root: impl Into<PathBuf>, parse: ParseOptions, validate: ValidateOptions) -> Self {
+        Workspace {
+            root: root.into(),
+            parse,
+            validate,
+            cache: DocumentCache::new(),
+            files: BTreeMap::new(),
+            names: HashMap::new(),
+        }
+    }
+
+    /// Brings the workspace up to date with the files on disk.
+    ///
+    /// Every file is read, but only new and changed ones are parsed and
+    /// validated, along with the files whose cross-file references the
+    /// change affects. A file that fails to parse gets the error as its
+    /// only diagnostic; it contributes no task names until it parses.
+    ///
+    /// # Returns
+    /// * The files parsed, removed and validated again
+    /// * `Err` if the root can't be searched
+    pub fn refresh(&mut self) -> io::Result<Changes> {
+        let paths = self.scan()?;
+        let mut changes = Changes::default();
+        // Names that appeared or disappeared: references to them may
+        // resolve differently now.
+        let mut touched = BTreeSet::new();
+
+        let gone: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter(|path| !paths.contains(*path))
+            .cloned()
+            .collect();
+        for path in gone {
+            let state = self.files.remove(&path).expect("path is a workspace file");
+            self.cache.invalidate(&path);
+            self.unindex(&path, &state.names);
+            touched.extend(state.names);
+            changes.removed.push(path);
+        }
+
+        for path in paths {
+            let Some(state) = self.load(&path) else {
+                continue;
+            };
+            let old = self
+                .files
+                .remove(&path)
+                .map(|old| old.names)
+                .unwrap_or_default();
+            self.unindex(&path, &old);
+            for name in &state.names {
+                self.names
+                    .entry(name.clone())
+                    .or_default()
+                    .insert(path.clone());
+            }
+            touched.extend(old.symmetric_difference(&state.names).cloned());
+            self.files.insert(path.clone(), state);
+            changes.reparsed.push(path);
+        }
+
+        for (path, state) in &mut self.files {
+            let affected = changes.reparsed.contains(path)
+                || state
+                    .unresolved
+                    .iter()
+                    .any(|(name, _)| touched.contains(name));
+            if affected {
+                state.resolve(&self.names);
+                changes.revalidated.push(path.clone());
+            }
+        }
+        if !changes.is_empty() {
+            log::debug!(
+                "workspace refresh: {} reparsed, {} removed, {} revalidated",
+                changes.reparsed.len(),
+                changes.removed.len(),
+                changes.revalidated.len()
+            );
+        }
+        Ok(changes)
+    }
+
+    /// Paths of the files found by the last refresh, in order.
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.keys().map(PathBuf::as_path)
+    }
+
+    /// Diagnostics of `path`, by line; empty for a file not in the
+    /// workspace.
+    pub fn diagnostics(&self, path: impl AsRef<Path>) -> &[Diagnostic] {
+        self.files
+            .get(path.as_ref())
+            .map_or(&[], |state| &state.diagnostics)
+    }
+
+    /// Why `path` failed to parse, if it did.
+    pub fn error(&self, path: impl AsRef<Path>) -> Option<&ParseError> {
+        self.files.get(path.as_ref())?.error.as_ref()
+    }
+
+    /// The document parsed from `path`, if it parsed.
+    pub fn document(&self, path: impl AsRef<Path>) -> Option<&Document> {
+        self.cache.get(path)
+    }
+
+    /// Every `.todo` file under the root.
+    fn scan(&self) -> io::Result<BTreeSet<PathBuf>> {
+        let pattern = Path::new(&glob::Pattern::escape(&self.root.to_string_lossy()))
+            .join("**/*.todo")
+            .to_string_lossy()
+            .into_owned();
+        let entries = glob::glob(&pattern).map_err(io::Error::other)?;
+        Ok(entries
+            .filter_map(Result::ok)
+            .filter(|path| path.is_file())
+            .collect())
+    }
+
+    /// Parses and checks `path` on its own.
+    ///
+    /// # Returns
+    /// * `None` if neither the file nor the error it fails with changed
+    fn load(&mut self, path: &Path) -> Option<FileState> {
+        let misses = self.cache.stats().misses;
+        let parsed = self.cache.parse_file(path, &self.parse).map(|_| ());
+        let cached = self.cache.stats().misses == misses;
+        match parsed {
+            Ok(()) if cached && self.files.contains_key(path) => None,
+            Ok(()) => {
+                let doc = self.cache.get(path).expect("a parsed file is cached");
+                Some(FileState::checked(doc, &self.validate))
+            }
+            Err(e) => {
+                let unchanged = self
+                    .files
+                    .get(path)
+                    .and_then(|state| state.error.as_ref())
+                    .is_some_and(|old| old.to_string() == e.to_string());
+                (!unchanged).then(|| FileState::failed(e))
+            }
+        }
+    }
+
+    /// Forgets that `path` defines `names`.
+    fn unindex(&mut self, path: &Path, names: &BTreeSet<String>) {
+        for name in names {
+            if let Some(files) = self.names.get_mut(name) {
+                files.remove(path);
+                if files.is_empty() {
+                    self.names.remove(name);
+                }
+            }
+        }
+    }
+}
+
+impl FileState {
+    /// State of a file that parsed into `doc`, before cross-file
+    /// resolution.
+    fn checked(doc: &Document, options: &ValidateOptions) -> Self {
+        let mut names = BTreeSet::new();
+        let mut references = Vec::new();
+        let deps = doc.dependencies();
+        for r in doc.task_refs() {
+            let project = r.project(doc).expect("task ref is valid");
+            let task = r.get(doc).expect("task ref is valid");
+            names.insert(task.title.clone());
+            names.insert(format!("{}/{}", project.name, task.title));
+            if let Some(id) = &task.id {
+                names.insert(format!("#{}", id));
+            }
+            if let Some(reference) = &task.depends_on
+                && deps.of(r).is_none()
+            {
+                let line = task.depends_on_span.map_or(task.span.line, |s| s.line);
+                references.push((reference.clone(), line, task.depends_on_span));
+            }
+        }
+
+        let local = validate_with(doc, options);
+        let unresolved = local
+            .iter()
+            .enumerate()
+            .filter(|(_, issue)| issue.code == "unknown-dependency")
+            .filter_map(|(index, issue)| {
+                let (reference, _, _) = references
+                    .iter()
+                    .find(|(_, line, span)| *line == issue.line && *span == issue.span)?;
+                Some((reference.clone(), index))
+            })
+            .collect();
+        FileState {
+            error: None,
+            names,
+            local,
+            unresolved,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// State of a file that failed to parse.
+    fn failed(error: ParseError) -> Self {
+        FileState {
+            local: vec![error.diagnostic()],
+            error: Some(error),
+            names: BTreeSet::new(),
+            unresolved: Vec::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Computes the diagnostics, dropping the unknown dependencies that
+    /// another file defines.
+    fn resolve(&mut self, names: &HashMap<String, BTreeSet<PathBuf>>) {
+        let resolved: BTreeSet<usize> = self
+            .unresolved
+            .iter()
+            .filter(|(name, _)| names.contains_key(name))
+            .map(|&(_, index)| index)
+            .collect();
+        self.diagnostics = (self.local.iter().enumerate())
+            .filter(|(index, _)| !resolved.contains(index))
+            .map(|(_, issue)| issue.clone())
+            .collect();
+    }
+}
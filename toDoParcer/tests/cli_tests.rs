@@ -0,0 +1,2629 @@
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// Runs the CLI binary with the given arguments, without reading the
+/// user's configuration file.
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_to_do_parcer"))
+        .args(args)
+        .env(
+            "XDG_CONFIG_HOME",
+            std::env::temp_dir().join("to_do_parcer_no_config"),
+        )
+        .output()
+        .expect("failed to run binary")
+}
+
+/// Writes `content` to a fresh temp file named after the test.
+fn temp_file(name: &str, content: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("to_do_parcer_cli_tests");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+mod validate_command {
+    use super::*;
+
+    #[test]
+    fn valid_file_succeeds() {
+        let path = temp_file(
+            "validate_ok.todo",
+            "project \"P\" {\n  todo: \"A\",\n  todo: \"B\", depends_on: \"A\",\n}\n",
+        );
+        let out = run(&["validate", "--file", path.to_str().unwrap()]);
+        assert!(out.status.success());
+        assert_eq!(stdout(&out).trim(), "summary: errors=0 warnings=0");
+    }
+
+    #[test]
+    fn example_has_unknown_dependencies() {
+        let out = run(&["validate", "--file", "examples/project.txt"]);
+        assert_eq!(out.status.code(), Some(2));
+        assert_eq!(
+            stdout(&out),
+            "examples/project.txt:9:32: error[unknown-dependency]: task \"Implement task list\" depends on unknown task \"Create UI mockups\"\n\
+             examples/project.txt:15:25: error[unknown-dependency]: task \"Cargo clippy\" depends on unknown task \"Design database schema\"\n\
+             summary: errors=2 warnings=0\n"
+        );
+
+        let out = run(&["validate", "--file", "examples/project.txt", "--lenient"]);
+        assert!(out.status.success());
+        assert_eq!(
+            stdout(&out).lines().last(),
+            Some("summary: errors=0 warnings=2")
+        );
+    }
+
+    #[test]
+    fn syntax_error_fails_with_location() {
+        let path = temp_file(
+            "validate_bad.todo",
+            "project \"A\" {}\nproject \"B\" { todo: \"x\" }",
+        );
+        let out = run(&["validate", "--file", path.to_str().unwrap()]);
+        assert!(!out.status.success());
+        let text = stdout(&out);
+        assert!(
+            text.contains(":2:15: error[parse]: expected task"),
+            "{}",
+            text
+        );
+        assert!(text.ends_with("summary: errors=1 warnings=0\n"));
+    }
+
+    #[test]
+    fn warnings_do_not_fail() {
+        let path = temp_file("validate_warn.todo", r#"project "A" {}"#);
+        let out = run(&["validate", "--file", path.to_str().unwrap()]);
+        assert!(out.status.success());
+        assert!(stdout(&out).contains("warning[empty-project]"));
+    }
+
+    #[test]
+    fn duplicate_title_is_an_error_with_strict() {
+        let path = temp_file(
+            "validate_dup_title.todo",
+            "project \"P\" {\n  todo: \"A\",\n  todo: \"A\",\n}\n",
+        );
+        let out = run(&["validate", "--file", path.to_str().unwrap()]);
+        assert!(out.status.success());
+        assert!(stdout(&out).contains(":3: warning[duplicate-title]"));
+
+        let out = run(&["validate", "--file", path.to_str().unwrap(), "--strict"]);
+        assert_eq!(out.status.code(), Some(2));
+        assert!(stdout(&out).contains(":3: error[duplicate-title]"));
+    }
+
+    #[test]
+    fn overdue_with_as_of() {
+        let path = temp_file(
+            "validate_overdue.todo",
+            "project \"P\" {\n  todo: \"A\", due: 2025-01-01,\n}\n",
+        );
+        let file = path.to_str().unwrap();
+        assert!(!stdout(&run(&["validate", "--file", file])).contains("overdue"));
+
+        let out = run(&[
+            "validate",
+            "--file",
+            file,
+            "--overdue",
+            "--as-of",
+            "2025-01-11",
+        ]);
+        assert!(out.status.success());
+        assert!(
+            stdout(&out)
+                .contains(":2: warning[overdue]: task \"A\" was due 2025-01-01 (10 days ago)"),
+            "{}",
+            stdout(&out)
+        );
+    }
+
+    #[test]
+    fn unknown_dependency_fails_unless_lenient() {
+        let path = temp_file(
+            "validate_dep.todo",
+            "project \"P\" {\n  todo: \"A\",\n  todo: \"B\", depends_on: \"Aa\",\n}\n",
+        );
+        let out = run(&["validate", "--file", path.to_str().unwrap()]);
+        assert_eq!(out.status.code(), Some(2));
+        let text = stdout(&out);
+        assert!(
+            text.contains(
+                ":3:14: error[unknown-dependency]: task \"B\" depends on unknown task \"Aa\""
+            ),
+            "{}",
+            text
+        );
+
+        let out = run(&["validate", "--file", path.to_str().unwrap(), "--lenient"]);
+        assert!(out.status.success());
+        assert!(stdout(&out).contains("warning[unknown-dependency]"));
+    }
+
+    #[test]
+    fn tag_rules() {
+        let path = temp_file(
+            "validate_tags.todo",
+            "project \"P\" {\n  todo: \"A\", @tag: \"Backend\",\n}\n",
+        );
+        let file = path.to_str().unwrap();
+        assert!(run(&["validate", "--file", file]).status.success());
+
+        let out = run(&["validate", "--file", file, "--tag-style", "kebab-case"]);
+        assert_eq!(out.status.code(), Some(2));
+        assert!(stdout(&out).contains(
+            ":2: error[tag-name]: tag \"Backend\" on task \"A\" is not kebab-case, write it as \"backend\""
+        ));
+
+        let out = run(&["validate", "--file", file, "--allowed-tags", "api,Backend"]);
+        assert!(out.status.success());
+        let out = run(&["validate", "--file", file, "--allowed-tags", "api,web"]);
+        assert_eq!(out.status.code(), Some(2));
+        assert!(stdout(&out).contains("is not an allowed tag"));
+    }
+
+    #[test]
+    fn assignee_roster() {
+        let path = temp_file(
+            "validate_roster.todo",
+            "project \"P\" {\n  todo: \"A\", assign: @bob,\n  todo: \"B\", assign: @alcie,\n}\n",
+        );
+        let roster = temp_file("validate_roster.txt", "@alice\nbob\n");
+        let out = run(&[
+            "validate",
+            "--file",
+            path.to_str().unwrap(),
+            "--assignees",
+            roster.to_str().unwrap(),
+        ]);
+        assert_eq!(out.status.code(), Some(2));
+        assert!(
+            stdout(&out).contains(":3: error[unknown-assignee]: task \"B\" is assigned to @alcie, who is not on the roster, did you mean @alice?"),
+            "{}",
+            stdout(&out)
+        );
+
+        let out = run(&[
+            "validate",
+            "--file",
+            path.to_str().unwrap(),
+            "--assignees",
+            "missing-roster.txt",
+        ]);
+        assert_eq!(out.status.code(), Some(3));
+    }
+
+    #[test]
+    fn prints_json_reports_for_every_file() {
+        let good = temp_file(
+            "validate_json_good.todo",
+            "project \"P\" {\n  todo: \"A\", depends_on: \"Bee\",\n  todo: \"Be\",\n}\n",
+        );
+        let bad = temp_file(
+            "validate_json_bad.todo",
+            "project \"P\" {\n  todo: \"A\"\n}\n",
+        );
+        let out = run(&[
+            "validate",
+            "--file",
+            good.to_str().unwrap(),
+            "--file",
+            bad.to_str().unwrap(),
+            "--format",
+            "json",
+        ]);
+        assert!(!out.status.success());
+        let stdout = stdout(&out);
+        assert!(!stdout.contains("summary:"));
+        let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let diagnostics = json["diagnostics"].as_array().unwrap();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0]["file"], good.to_str().unwrap());
+        assert_eq!(diagnostics[0]["code"], "unknown-dependency");
+        assert_eq!(diagnostics[0]["suggestion"], "Be");
+        assert_eq!(diagnostics[1]["file"], bad.to_str().unwrap());
+        assert_eq!(diagnostics[1]["code"], "parse");
+        assert_eq!(diagnostics[1]["fix"]["replacement"], ",");
+        assert_eq!(json["summary"]["errors"], 2);
+    }
+}
+
+mod fmt_command {
+    use super::*;
+
+    const MESSY: &str = "project \"P\"{todo:\"A\",@high,}\n";
+    const TIDY: &str = "project \"P\" {\n  todo: \"A\", @high,\n}\n";
+
+    #[test]
+    fn prints_formatted() {
+        let path = temp_file("fmt_print.todo", MESSY);
+        let out = run(&["fmt", "--file", path.to_str().unwrap()]);
+        assert!(out.status.success());
+        assert_eq!(stdout(&out), TIDY);
+    }
+
+    #[test]
+    fn check_mode() {
+        let messy = temp_file("fmt_check_messy.todo", MESSY);
+        assert!(
+            !run(&["fmt", "--file", messy.to_str().unwrap(), "--check"])
+                .status
+                .success()
+        );
+        assert_eq!(std::fs::read_to_string(&messy).unwrap(), MESSY);
+
+        let tidy = temp_file("fmt_check_tidy.todo", TIDY);
+        assert!(
+            run(&["fmt", "--file", tidy.to_str().unwrap(), "--check"])
+                .status
+                .success()
+        );
+    }
+
+    #[test]
+    fn keeps_crlf_and_bom() {
+        let windows = |text: &str| format!("\u{feff}{}", text.replace('\n', "\r\n"));
+        let tidy = temp_file("fmt_crlf_tidy.todo", &windows(TIDY));
+        assert!(
+            run(&["fmt", "--file", tidy.to_str().unwrap(), "--check"])
+                .status
+                .success()
+        );
+
+        let messy = temp_file("fmt_crlf_messy.todo", &windows(MESSY));
+        let out = run(&["fmt", "--file", messy.to_str().unwrap(), "--write"]);
+        assert!(out.status.success());
+        assert_eq!(std::fs::read_to_string(&messy).unwrap(), windows(TIDY));
+    }
+
+    #[test]
+    fn write_mode() {
+        let path = temp_file("fmt_write.todo", MESSY);
+        assert!(
+            run(&["fmt", "--file", path.to_str().unwrap(), "--write"])
+                .status
+                .success()
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), TIDY);
+    }
+}
+
+mod fix_command {
+    use super::*;
+
+    const BROKEN: &str = "project \"P\" {\n  todo: \"A\", due: 2025/12/31\n}\n";
+    const FIXED: &str = "project \"P\" {\n  todo: \"A\", due: 2025-12-31,\n}\n";
+
+    #[test]
+    fn prints_fixed() {
+        let path = temp_file("fix_print.todo", BROKEN);
+        let out = run(&["fix", "--file", path.to_str().unwrap()]);
+        assert!(out.status.success());
+        assert_eq!(stdout(&out), FIXED);
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        assert!(stderr.contains("fix_print.todo:2:19: replace with `2025-12-31,`"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), BROKEN);
+    }
+
+    #[test]
+    fn write_mode() {
+        let path = temp_file("fix_write.todo", BROKEN);
+        let out = run(&["fix", "--file", path.to_str().unwrap(), "--write"]);
+        assert!(out.status.success());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), FIXED);
+    }
+
+    #[test]
+    fn unfixable_files_are_left_alone() {
+        let broken = "project \"P\" { todo: \"A\", @urgent, }\n";
+        let path = temp_file("fix_unfixable.todo", broken);
+        let out = run(&["fix", "--file", path.to_str().unwrap(), "--write"]);
+        assert_eq!(out.status.code(), Some(1));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), broken);
+    }
+
+    #[test]
+    fn errors_show_the_fix() {
+        let path = temp_file("fix_hint.todo", BROKEN);
+        let out = run(&["validate", "--file", path.to_str().unwrap()]);
+        assert!(stdout(&out).contains("  help: replace with `2025-12-31,` at 2:19\n"));
+        let out = run(&["parse", "--file", path.to_str().unwrap()]);
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        assert!(stderr.contains("= help: replace with `2025-12-31,` at 2:19"));
+    }
+}
+
+mod stats_command {
+    use super::*;
+
+    const INPUT: &str = r#"project "P" {
+  todo: "A", @high, due: 2025-01-01, assign: @bob,
+  done: "B", @tag: "x",
+}
+"#;
+
+    #[test]
+    fn prints_report() {
+        let path = temp_file("stats_text.todo", INPUT);
+        let out = run(&[
+            "stats",
+            "--file",
+            path.to_str().unwrap(),
+            "--as-of",
+            "2025-06-01",
+        ]);
+        assert!(out.status.success());
+        let text = stdout(&out);
+        assert!(text.contains("Tasks:      2"));
+        assert!(text.contains("Completion: 50.0%"));
+        assert!(text.contains("Overdue:    1"));
+        assert!(text.contains("  @bob: 1"));
+    }
+
+    #[test]
+    fn prints_json() {
+        let path = temp_file("stats_json.todo", INPUT);
+        let out = run(&[
+            "stats",
+            "--file",
+            path.to_str().unwrap(),
+            "--as-of",
+            "2025-06-01",
+            "--json",
+        ]);
+        assert!(out.status.success());
+        let json: serde_json::Value = serde_json::from_str(&stdout(&out)).unwrap();
+        assert_eq!(json["total"], 2);
+        assert_eq!(json["overdue"], 1);
+        assert_eq!(json["by_priority"]["high"], 1);
+        assert_eq!(json["by_tag"]["x"], 1);
+    }
+}
+
+mod export_command {
+    use super::*;
+
+    #[test]
+    fn exports_json() {
+        let path = temp_file("export_json.todo", r#"project "P" { todo: "A", }"#);
+        let out = run(&[
+            "export",
+            "--file",
+            path.to_str().unwrap(),
+            "--format",
+            "json",
+        ]);
+        assert!(out.status.success());
+        let json: serde_json::Value = serde_json::from_str(&stdout(&out)).unwrap();
+        assert_eq!(json["projects"][0]["tasks"][0]["title"], "A");
+    }
+
+    #[test]
+    fn exports_yaml() {
+        let path = temp_file("export_yaml.todo", r#"project "P" { todo: "A", }"#);
+        let out = run(&[
+            "export",
+            "--file",
+            path.to_str().unwrap(),
+            "--format",
+            "yaml",
+        ]);
+        assert!(out.status.success());
+        assert!(stdout(&out).starts_with("version: 1\nprojects:\n- name: P\n"));
+    }
+
+    #[test]
+    fn exports_jira() {
+        let path = temp_file(
+            "export_jira.todo",
+            "project \"P\" {\n  todo: \"A\",\n  todo: \"B\", depends_on: \"A\",\n}\n",
+        );
+        let out = run(&[
+            "export",
+            "--file",
+            path.to_str().unwrap(),
+            "--format",
+            "jira",
+        ]);
+        assert!(out.status.success());
+        assert!(stdout(&out).ends_with("\n2,B,Task,To Do,,,,P,,1\n"));
+    }
+
+    #[test]
+    fn rejects_unknown_format() {
+        let path = temp_file("export_bad.todo", r#"project "P" { todo: "A", }"#);
+        let out = run(&[
+            "export",
+            "--file",
+            path.to_str().unwrap(),
+            "--format",
+            "xml",
+        ]);
+        assert!(!out.status.success());
+    }
+}
+
+mod import_command {
+    use super::*;
+
+    #[test]
+    fn imports_markdown() {
+        let path = temp_file("import.md", "## Home\n- [ ] Fix sink\n- [x] Paint wall\n");
+        let out = run(&[
+            "import",
+            "--file",
+            path.to_str().unwrap(),
+            "--format",
+            "markdown",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(
+            stdout(&out),
+            "project \"Home\" {\n  todo: \"Fix sink\",\n  done: \"Paint wall\",\n}\n"
+        );
+    }
+
+    #[test]
+    fn imports_trello() {
+        let path = temp_file(
+            "import_trello.json",
+            r#"{"lists": [{"id": "l", "name": "Todo"}], "cards": [{"id": "c", "name": "Card", "idList": "l"}]}"#,
+        );
+        let out = run(&[
+            "import",
+            "--file",
+            path.to_str().unwrap(),
+            "--format",
+            "trello",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(stdout(&out), "project \"Todo\" {\n  todo: \"Card\",\n}\n");
+    }
+
+    #[test]
+    fn imports_csv_with_mapped_columns() {
+        let path = temp_file(
+            "import.csv",
+            "Summary,List,Done\nBuy milk,Home,no\nCall mom,Home,yes\n",
+        );
+        let out = run(&[
+            "import",
+            "--file",
+            path.to_str().unwrap(),
+            "--format",
+            "csv",
+            "--map",
+            "title=Summary",
+            "--map",
+            "project=List",
+            "--map",
+            "status=Done",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(
+            stdout(&out),
+            "project \"Home\" {\n  todo: \"Buy milk\",\n  done: \"Call mom\",\n}\n"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_mapped_field() {
+        let path = temp_file("import_bad_map.csv", "title\nA\n");
+        let out = run(&[
+            "import",
+            "--file",
+            path.to_str().unwrap(),
+            "--format",
+            "csv",
+            "--map",
+            "owner=Owner",
+        ]);
+        assert!(!out.status.success());
+        assert!(String::from_utf8_lossy(&out.stderr).contains("unknown field 'owner'"));
+    }
+}
+
+mod filter_command {
+    use super::*;
+
+    const INPUT: &str = r#"project "A" {
+  todo: "One", @high, assign: @bob, @tag: "bug",
+  done: "Two", @high,
+}
+
+project "B" {
+  todo: "Three", @low,
+}
+"#;
+
+    #[test]
+    fn prints_matching_tasks() {
+        let path = temp_file("filter.todo", INPUT);
+        let out = run(&[
+            "filter",
+            "--file",
+            path.to_str().unwrap(),
+            "--status",
+            "todo",
+            "--assignee",
+            "@bob",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(
+            stdout(&out),
+            "project \"A\" {\n  todo: \"One\", @high, assign: @bob, @tag: \"bug\",\n}\n"
+        );
+    }
+
+    #[test]
+    fn table_output() {
+        let path = temp_file("filter_table.todo", INPUT);
+        let out = run(&[
+            "filter",
+            "--file",
+            path.to_str().unwrap(),
+            "--priority",
+            "low",
+            "--table",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(stdout(&out).lines().count(), 2);
+        assert!(stdout(&out).contains("Three"));
+    }
+
+    #[test]
+    fn query_expression() {
+        let path = temp_file("filter_query.todo", INPUT);
+        let out = run(&[
+            "filter",
+            "--file",
+            path.to_str().unwrap(),
+            "--query",
+            r#"priority == high && !tag:"bug""#,
+            "--table",
+        ]);
+        assert!(out.status.success());
+        let text = stdout(&out);
+        assert!(text.contains("Two"));
+        assert!(!text.contains("One"));
+    }
+
+    #[test]
+    fn rejects_bad_query() {
+        let path = temp_file("filter_bad_query.todo", INPUT);
+        let out = run(&[
+            "filter",
+            "--file",
+            path.to_str().unwrap(),
+            "--query",
+            "due <",
+        ]);
+        assert!(!out.status.success());
+    }
+
+    #[test]
+    fn rejects_bad_status() {
+        let path = temp_file("filter_bad.todo", INPUT);
+        let out = run(&[
+            "filter",
+            "--file",
+            path.to_str().unwrap(),
+            "--status",
+            "later",
+        ]);
+        assert!(!out.status.success());
+    }
+}
+
+mod add_command {
+    use super::*;
+
+    #[test]
+    fn appends_task() {
+        let path = temp_file(
+            "add.todo",
+            "project \"Sprint\" {\n  todo: \"A\", // keep\n}\n",
+        );
+        let out = run(&[
+            "add",
+            "--file",
+            path.to_str().unwrap(),
+            "--project",
+            "Sprint",
+            "--title",
+            "Fix login",
+            "--priority",
+            "high",
+            "--due",
+            "2025-12-01",
+            "--assign",
+            "@bob",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "project \"Sprint\" {\n  todo: \"A\", // keep\n  todo: \"Fix login\", @high, due: 2025-12-01, assign: @bob,\n}\n"
+        );
+    }
+
+    #[test]
+    fn creates_missing_file() {
+        let path = std::env::temp_dir()
+            .join("to_do_parcer_cli_tests")
+            .join("add_new.todo");
+        let _ = std::fs::remove_file(&path);
+        let out = run(&[
+            "add",
+            "--file",
+            path.to_str().unwrap(),
+            "--project",
+            "Inbox",
+            "--title",
+            "First",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "project \"Inbox\" {\n  todo: \"First\",\n}\n"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_date() {
+        let path = temp_file("add_bad.todo", "project \"P\" {}\n");
+        let out = run(&[
+            "add",
+            "--file",
+            path.to_str().unwrap(),
+            "-p",
+            "P",
+            "-t",
+            "A",
+            "--due",
+            "tomorrow",
+        ]);
+        assert!(!out.status.success());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "project \"P\" {}\n"
+        );
+    }
+}
+
+mod done_command {
+    use super::*;
+
+    #[test]
+    fn marks_task_done() {
+        let path = temp_file(
+            "done.todo",
+            "project \"P\" {\n  todo: \"Fix login\", id: login,\n}\n",
+        );
+        let out = run(&[
+            "done",
+            "--file",
+            path.to_str().unwrap(),
+            "#login",
+            "--completed=2025-11-02",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "project \"P\" {\n  done: \"Fix login\", id: login, completed: 2025-11-02,\n}\n"
+        );
+    }
+
+    #[test]
+    fn unknown_task_fails() {
+        let path = temp_file("done_missing.todo", "project \"P\" {\n  todo: \"A\",\n}\n");
+        let out = run(&["done", "--file", path.to_str().unwrap(), "B"]);
+        assert!(!out.status.success());
+        assert!(String::from_utf8_lossy(&out.stderr).contains("No task matches \"B\""));
+    }
+}
+
+mod rm_command {
+    use super::*;
+
+    #[test]
+    fn removes_task() {
+        let path = temp_file(
+            "rm.todo",
+            "project \"P\" {\n  todo: \"A\", id: a,\n  todo: \"B\",\n}\n",
+        );
+        let out = run(&["rm", "--file", path.to_str().unwrap(), "#a"]);
+        assert!(out.status.success());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "project \"P\" {\n  todo: \"B\",\n}\n"
+        );
+    }
+
+    #[test]
+    fn refuses_when_depended_on() {
+        let source = "project \"P\" {\n  todo: \"A\",\n  todo: \"B\", depends_on: \"A\",\n}\n";
+        let path = temp_file("rm_dep.todo", source);
+        let out = run(&["rm", "--file", path.to_str().unwrap(), "A"]);
+        assert!(!out.status.success());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), source);
+
+        let out = run(&["rm", "--file", path.to_str().unwrap(), "A", "--force"]);
+        assert!(out.status.success());
+    }
+}
+
+mod mv_command {
+    use super::*;
+
+    #[test]
+    fn moves_task() {
+        let path = temp_file(
+            "mv.todo",
+            "project \"A\" {\n  todo: \"X\", id: x,\n  todo: \"Y\",\n}\n\nproject \"B\" {\n  todo: \"Z\", depends_on: \"A/X\",\n}\n",
+        );
+        let out = run(&[
+            "mv",
+            "--file",
+            path.to_str().unwrap(),
+            "#x",
+            "--to-project",
+            "B",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "project \"A\" {\n  todo: \"Y\",\n}\n\nproject \"B\" {\n  todo: \"Z\", depends_on: \"B/X\",\n  todo: \"X\", id: x,\n}\n"
+        );
+    }
+
+    #[test]
+    fn fails_for_unknown_task() {
+        let source = "project \"A\" {\n  todo: \"X\",\n}\n";
+        let path = temp_file("mv_missing.todo", source);
+        let out = run(&[
+            "mv",
+            "--file",
+            path.to_str().unwrap(),
+            "Q",
+            "--to-project",
+            "B",
+        ]);
+        assert_eq!(out.status.code(), Some(1));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), source);
+    }
+}
+
+mod split_command {
+    use super::*;
+
+    #[test]
+    fn writes_project_files_and_includes() {
+        let file = temp_file(
+            "split.todo",
+            "project \"Web\" {\n  todo: \"A\",\n}\n\nproject \"Api\" {\n  todo: \"B\", depends_on: \"A\",\n}\n",
+        );
+        let out_dir = file.with_file_name("split_projects");
+        let _ = std::fs::remove_dir_all(&out_dir);
+        let out = run(&[
+            "split",
+            "--file",
+            file.to_str().unwrap(),
+            "--out-dir",
+            out_dir.to_str().unwrap(),
+        ]);
+        assert!(out.status.success());
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            "include \"split_projects/web.todo\"\n\ninclude \"split_projects/api.todo\"\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(out_dir.join("api.todo")).unwrap(),
+            "project \"Api\" {\n  todo: \"B\", depends_on: \"A\",\n}\n"
+        );
+
+        let validate = run(&["validate", "--file", file.to_str().unwrap()]);
+        assert!(validate.status.success());
+    }
+
+    #[test]
+    fn edits_the_included_files() {
+        let file = temp_file(
+            "split_edit.todo",
+            "project \"Web\" {\n  todo: \"Deploy\",\n}\n\nproject \"Api\" {\n  todo: \"B\",\n}\n",
+        );
+        let out_dir = file.with_file_name("split_edit");
+        let _ = std::fs::remove_dir_all(&out_dir);
+        let path = file.to_str().unwrap();
+        let out = run(&[
+            "split",
+            "--file",
+            path,
+            "--out-dir",
+            out_dir.to_str().unwrap(),
+        ]);
+        assert!(out.status.success());
+        let stub = std::fs::read_to_string(&file).unwrap();
+
+        let parse = run(&["parse", "--file", path]);
+        assert!(parse.status.success());
+        let stdout = String::from_utf8_lossy(&parse.stdout);
+        assert!(stdout.contains("Web") && stdout.contains("Deploy"));
+
+        let add = run(&["add", "--file", path, "--project", "Web", "--title", "New"]);
+        assert!(add.status.success());
+        let done = run(&["done", "--file", path, "Deploy"]);
+        assert!(done.status.success());
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), stub);
+        let web = std::fs::read_to_string(out_dir.join("web.todo")).unwrap();
+        assert!(web.contains("done: \"Deploy\""));
+        assert!(web.contains("todo: \"New\""));
+
+        let mv = run(&["mv", "--file", path, "Deploy", "--to-project", "Api"]);
+        assert!(!mv.status.success());
+        assert!(String::from_utf8_lossy(&mv.stderr).contains("different files"));
+    }
+
+    #[test]
+    fn keeps_existing_files() {
+        let source = "project \"Web\" {\n  todo: \"A\",\n}\n";
+        let file = temp_file("split_existing.todo", source);
+        let out_dir = file.with_file_name("split_existing");
+        std::fs::create_dir_all(&out_dir).unwrap();
+        std::fs::write(out_dir.join("web.todo"), "mine").unwrap();
+        let out = run(&[
+            "split",
+            "--file",
+            file.to_str().unwrap(),
+            "--out-dir",
+            out_dir.to_str().unwrap(),
+        ]);
+        assert!(!out.status.success());
+        assert!(String::from_utf8_lossy(&out.stderr).contains("use --force"));
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), source);
+        assert_eq!(
+            std::fs::read_to_string(out_dir.join("web.todo")).unwrap(),
+            "mine"
+        );
+    }
+}
+
+mod dedupe_command {
+    use super::*;
+
+    const SOURCE: &str = "project \"P\" {\n  todo: \"Fix login bug\", @tag: \"web\",\n  done: \"fix login bugs\", @high,\n  todo: \"Other\",\n}\n";
+
+    #[test]
+    fn reports_and_fails() {
+        let path = temp_file("dedupe_report.todo", SOURCE);
+        let out = run(&["dedupe", "--file", path.to_str().unwrap()]);
+        assert_eq!(out.status.code(), Some(1));
+        assert_eq!(
+            stdout(&out),
+            "P: \"Fix login bug\" (line 2)\n  \"fix login bugs\" (line 3)\n"
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), SOURCE);
+    }
+
+    #[test]
+    fn write_merges() {
+        let path = temp_file("dedupe_write.todo", SOURCE);
+        let out = run(&["dedupe", "--file", path.to_str().unwrap(), "--write"]);
+        assert!(out.status.success());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "project \"P\" {\n  done: \"Fix login bug\", @high, @tag: \"web\",\n  todo: \"Other\",\n}\n"
+        );
+    }
+
+    #[test]
+    fn succeeds_without_duplicates() {
+        let path = temp_file(
+            "dedupe_none.todo",
+            r#"project "P" { todo: "A", todo: "B", }"#,
+        );
+        let out = run(&["dedupe", "--file", path.to_str().unwrap()]);
+        assert!(out.status.success());
+        assert_eq!(stdout(&out), "");
+    }
+}
+
+mod assign_command {
+    use super::*;
+
+    #[test]
+    fn reassigns_within_project() {
+        let path = temp_file(
+            "assign.todo",
+            "project \"Sprint\" {\n  todo: \"A\", assign: @alice,\n}\nproject \"Later\" {\n  todo: \"B\", assign: @alice,\n}\n",
+        );
+        let out = run(&[
+            "assign",
+            "--file",
+            path.to_str().unwrap(),
+            "--from",
+            "alice",
+            "--to",
+            "bob",
+            "--project",
+            "Sprint",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "project \"Sprint\" {\n  todo: \"A\", assign: @bob,\n}\nproject \"Later\" {\n  todo: \"B\", assign: @alice,\n}\n"
+        );
+    }
+
+    #[test]
+    fn fails_for_unknown_project() {
+        let source = "project \"P\" {\n  todo: \"A\", assign: @alice,\n}\n";
+        let path = temp_file("assign_missing.todo", source);
+        let out = run(&[
+            "assign",
+            "--file",
+            path.to_str().unwrap(),
+            "--from",
+            "alice",
+            "--to",
+            "bob",
+            "--project",
+            "X",
+        ]);
+        assert_eq!(out.status.code(), Some(1));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), source);
+    }
+}
+
+mod migrate_command {
+    use super::*;
+
+    #[test]
+    fn upgrades_to_the_latest_version() {
+        let path = temp_file(
+            "migrate.todo",
+            "// Sprint\nproject \"P\" {\n  todo: \"A\", @tag: \"ui\", // soon\n}\n",
+        );
+        let out = run(&["migrate", "--file", path.to_str().unwrap()]);
+        assert!(out.status.success());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "version 2\n\n// Sprint\nproject \"P\" {\n  todo: \"A\", tag: \"ui\", // soon\n}\n"
+        );
+
+        let out = run(&["migrate", "--file", path.to_str().unwrap(), "--to", "1"]);
+        assert!(out.status.success());
+        assert!(
+            std::fs::read_to_string(&path)
+                .unwrap()
+                .starts_with("version 1\n\n// Sprint\n")
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        let source = "project \"P\" {\n  todo: \"A\",\n}\n";
+        let path = temp_file("migrate_unknown.todo", source);
+        let out = run(&["migrate", "--file", path.to_str().unwrap(), "--to", "7"]);
+        assert!(!out.status.success());
+        assert!(String::from_utf8_lossy(&out.stderr).contains("Unsupported format version 7"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), source);
+    }
+}
+
+mod rename_project_command {
+    use super::*;
+
+    #[test]
+    fn renames_project_and_references() {
+        let path = temp_file(
+            "rename_project.todo",
+            "project \"Old\" {\n  todo: \"A\",\n}\n\nproject \"Q\" {\n  todo: \"B\", depends_on: \"Old/A\",\n}\n",
+        );
+        let out = run(&[
+            "rename-project",
+            "--file",
+            path.to_str().unwrap(),
+            "Old",
+            "New",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "project \"New\" {\n  todo: \"A\",\n}\n\nproject \"Q\" {\n  todo: \"B\", depends_on: \"New/A\",\n}\n"
+        );
+    }
+
+    #[test]
+    fn fails_for_unknown_project() {
+        let source = "project \"P\" {\n  todo: \"A\",\n}\n";
+        let path = temp_file("rename_project_missing.todo", source);
+        let out = run(&["rename-project", "--file", path.to_str().unwrap(), "X", "Y"]);
+        assert_eq!(out.status.code(), Some(1));
+        assert!(String::from_utf8_lossy(&out.stderr).contains("No project named \"X\""));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), source);
+    }
+}
+
+mod set_command {
+    use super::*;
+
+    #[test]
+    fn sets_attributes() {
+        let path = temp_file(
+            "set.todo",
+            "project \"P\" {\n  todo: \"Fix login\", @high,\n}\n",
+        );
+        let out = run(&[
+            "set",
+            "--file",
+            path.to_str().unwrap(),
+            "Fix login",
+            "--due",
+            "2025-12-15",
+            "--assign",
+            "bob",
+            "--priority",
+            "none",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "project \"P\" {\n  todo: \"Fix login\", due: 2025-12-15, assign: @bob,\n}\n"
+        );
+    }
+
+    #[test]
+    fn requires_a_change() {
+        let path = temp_file("set_none.todo", "project \"P\" {\n  todo: \"A\",\n}\n");
+        let out = run(&["set", "--file", path.to_str().unwrap(), "A"]);
+        assert!(!out.status.success());
+    }
+}
+
+mod sort_command {
+    use super::*;
+
+    const SOURCE: &str = "project \"P\" {\n  todo: \"A\",\n  todo: \"B\", @high,\n}\n";
+    const SORTED: &str = "project \"P\" {\n  todo: \"B\", @high,\n  todo: \"A\",\n}\n";
+
+    #[test]
+    fn prints_sorted_file() {
+        let path = temp_file("sort_print.todo", SOURCE);
+        let out = run(&["sort", "--file", path.to_str().unwrap(), "--by", "priority"]);
+        assert!(out.status.success());
+        assert_eq!(String::from_utf8_lossy(&out.stdout), SORTED);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), SOURCE);
+    }
+
+    #[test]
+    fn writes_in_place() {
+        let path = temp_file("sort_write.todo", SOURCE);
+        let out = run(&[
+            "sort",
+            "--file",
+            path.to_str().unwrap(),
+            "--by",
+            "priority",
+            "--write",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), SORTED);
+    }
+}
+
+mod lint_command {
+    use super::*;
+
+    const SOURCE: &str =
+        "project \"P\" {\n  todo: \"Ship\", @high,\n  todo: \"Test\", depends_on: \"Nope\",\n}\n";
+
+    #[test]
+    fn reports_issues_and_fails_on_errors() {
+        let path = temp_file("lint.todo", SOURCE);
+        let out = run(&["lint", "--file", path.to_str().unwrap()]);
+        assert!(!out.status.success());
+        let stdout = String::from_utf8_lossy(&out.stdout);
+        assert!(stdout.contains(":2: warning[missing-due]: "));
+        assert!(stdout.contains(":3: error[dangling-dependency]: "));
+        assert!(stdout.contains("summary: errors=1 warnings=1"));
+    }
+
+    #[test]
+    fn rules_are_configurable() {
+        let path = temp_file("lint_config.todo", SOURCE);
+        let out = run(&[
+            "lint",
+            "--file",
+            path.to_str().unwrap(),
+            "--disable",
+            "dangling-dependency",
+            "--severity",
+            "missing-due=error",
+        ]);
+        assert!(!out.status.success());
+        let stdout = String::from_utf8_lossy(&out.stdout);
+        assert!(stdout.contains("summary: errors=1 warnings=0"));
+
+        let out = run(&[
+            "lint",
+            "--file",
+            path.to_str().unwrap(),
+            "--disable",
+            "dangling-dependency",
+        ]);
+        assert!(out.status.success());
+    }
+
+    #[test]
+    fn hints_and_min_severity() {
+        let path = temp_file("lint_hint.todo", SOURCE);
+        let file = path.to_str().unwrap();
+        let out = run(&["lint", "--file", file, "--severity", "missing-due=hint"]);
+        let text = stdout(&out);
+        assert!(text.contains(":2: hint[missing-due]: "), "{}", text);
+        assert!(text.contains("summary: errors=1 warnings=0"), "{}", text);
+
+        let out = run(&["lint", "--file", file, "--min-severity", "error"]);
+        let text = stdout(&out);
+        assert!(!text.contains("missing-due"), "{}", text);
+        assert!(
+            text.contains(":3: error[dangling-dependency]: "),
+            "{}",
+            text
+        );
+
+        let out = run(&["lint", "--file", file, "--min-severity", "fatal"]);
+        assert_eq!(out.status.code(), Some(2));
+        assert!(
+            String::from_utf8_lossy(&out.stderr)
+                .contains("unknown severity 'fatal', expected one of: hint, warning, error")
+        );
+    }
+
+    #[test]
+    fn prints_json_reports() {
+        let path = temp_file("lint_json.todo", SOURCE);
+        let out = run(&["lint", "--file", path.to_str().unwrap(), "--format", "json"]);
+        assert_eq!(out.status.code(), Some(2));
+        let json: serde_json::Value = serde_json::from_str(&stdout(&out)).unwrap();
+        let codes: Vec<_> = json["diagnostics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|d| (d["line"].as_u64().unwrap(), d["code"].as_str().unwrap()))
+            .collect();
+        assert_eq!(codes, [(2, "missing-due"), (3, "dangling-dependency")]);
+        assert_eq!(json["summary"]["errors"], 1);
+    }
+}
+
+#[cfg(feature = "spellcheck")]
+mod spelling_command {
+    use super::*;
+
+    const SOURCE: &str = "project \"P\" {\n  todo: \"Recieve invoices from Oleksii\",\n}\n";
+
+    #[test]
+    fn reports_hints_when_enabled() {
+        let path = temp_file("spelling.todo", SOURCE);
+        let out = run(&["lint", "--file", path.to_str().unwrap()]);
+        assert!(!stdout(&out).contains("spelling"));
+
+        let out = run(&[
+            "lint",
+            "--file",
+            path.to_str().unwrap(),
+            "--enable",
+            "spelling",
+        ]);
+        assert!(out.status.success());
+        let stdout = stdout(&out);
+        assert!(stdout.contains(":2: hint[spelling]: \"Recieve\" in title"));
+        assert!(stdout.contains("did you mean `receive`?"));
+        assert!(stdout.contains("\"Oleksii\" in title"));
+    }
+
+    #[test]
+    fn reads_project_word_lists() {
+        let path = temp_file("spelling_words.todo", SOURCE);
+        let words = temp_file("spelling_words.txt", "# team\noleksii\n");
+        let out = run(&[
+            "lint",
+            "--file",
+            path.to_str().unwrap(),
+            "--enable",
+            "spelling",
+            "--words",
+            words.to_str().unwrap(),
+        ]);
+        let stdout = stdout(&out);
+        assert!(stdout.contains("\"Recieve\" in title"));
+        assert!(!stdout.contains("\"Oleksii\" in title"));
+    }
+}
+
+mod diff_command {
+    use super::*;
+
+    #[test]
+    fn prints_semantic_changes() {
+        let old = temp_file(
+            "diff_old.todo",
+            "project \"P\" {\n  todo: \"A\", @high,\n}\n",
+        );
+        let new = temp_file(
+            "diff_new.todo",
+            "project \"P\" {\n  done: \"A\", @medium,\n  todo: \"B\",\n}\n",
+        );
+        let out = run(&["diff", old.to_str().unwrap(), new.to_str().unwrap()]);
+        assert!(out.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&out.stdout),
+            "x P/A\n~ P/A: priority high -> medium\n+ P/B\n"
+        );
+
+        let out = run(&[
+            "diff",
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--json",
+        ]);
+        let json: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+        assert_eq!(json["changes"][1]["field"], "priority");
+    }
+}
+
+mod merge_command {
+    use super::*;
+
+    #[test]
+    fn writes_merged_file() {
+        let base = temp_file("merge_base.todo", "project \"P\" {\n  todo: \"A\",\n}\n");
+        let ours = temp_file("merge_ours.todo", "project \"P\" {\n  done: \"A\",\n}\n");
+        let theirs = temp_file(
+            "merge_theirs.todo",
+            "project \"P\" {\n  todo: \"A\",\n  todo: \"B\",\n}\n",
+        );
+        let out = run(&[
+            "merge",
+            base.to_str().unwrap(),
+            ours.to_str().unwrap(),
+            theirs.to_str().unwrap(),
+            "-o",
+            ours.to_str().unwrap(),
+        ]);
+        assert!(out.status.success());
+        assert_eq!(
+            std::fs::read_to_string(&ours).unwrap(),
+            "project \"P\" {\n  done: \"A\",\n  todo: \"B\",\n}\n"
+        );
+    }
+
+    #[test]
+    fn conflicts_fail() {
+        let base = temp_file("merge_c_base.todo", "project \"P\" {\n  todo: \"A\",\n}\n");
+        let ours = temp_file(
+            "merge_c_ours.todo",
+            "project \"P\" {\n  todo: \"A\", @high,\n}\n",
+        );
+        let theirs = temp_file(
+            "merge_c_theirs.todo",
+            "project \"P\" {\n  todo: \"A\", @low,\n}\n",
+        );
+        let out = run(&[
+            "merge",
+            base.to_str().unwrap(),
+            ours.to_str().unwrap(),
+            theirs.to_str().unwrap(),
+        ]);
+        assert!(!out.status.success());
+        assert!(String::from_utf8_lossy(&out.stderr).contains("conflict: P/A: priority"));
+        assert!(String::from_utf8_lossy(&out.stdout).contains("// merge conflict"));
+    }
+}
+
+mod multiple_files {
+    use super::*;
+
+    /// Creates a fresh directory with one file per `(name, content)` pair.
+    fn temp_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join("to_do_parcer_cli_tests")
+            .join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (file, content) in files {
+            std::fs::write(dir.join(file), content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn validate_attributes_issues_to_files() {
+        let dir = temp_dir(
+            "validate_many",
+            &[
+                ("a.todo", r#"project "A" {}"#),
+                ("b.todo", "project \"B\" {\n  todo: \"x\"\n}"),
+                ("notes.txt", "not a todo file"),
+            ],
+        );
+        let out = run(&["validate", "--dir", dir.to_str().unwrap()]);
+        assert!(!out.status.success());
+        let text = stdout(&out);
+        assert!(
+            text.contains("a.todo:1: warning[empty-project]"),
+            "{}",
+            text
+        );
+        assert!(text.contains("b.todo:2:3: error[parse]"), "{}", text);
+        assert!(!text.contains("notes.txt"));
+        assert!(text.ends_with("summary: errors=1 warnings=1\n"));
+    }
+
+    #[test]
+    fn stats_aggregate_files_and_globs() {
+        let dir = temp_dir(
+            "stats_many",
+            &[
+                ("a.todo", r#"project "A" { todo: "x", }"#),
+                ("b.todo", r#"project "B" { done: "y", todo: "z", }"#),
+            ],
+        );
+        let pattern = dir.join("*.todo");
+        let out = run(&["stats", "--file", pattern.to_str().unwrap(), "--json"]);
+        assert!(out.status.success());
+        let json: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+        assert_eq!(json["projects"], 2);
+        assert_eq!(json["total"], 3);
+
+        let out = run(&[
+            "stats",
+            "--file",
+            dir.join("a.todo").to_str().unwrap(),
+            "--file",
+            dir.join("b.todo").to_str().unwrap(),
+            "--json",
+        ]);
+        let json: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+        assert_eq!(json["total"], 3);
+    }
+
+    #[test]
+    fn parse_prints_a_header_per_file() {
+        let dir = temp_dir(
+            "parse_many",
+            &[
+                ("a.todo", r#"project "A" { todo: "x", }"#),
+                ("b.todo", r#"project "B" { todo: "y", }"#),
+            ],
+        );
+        let out = run(&["parse", "--dir", dir.to_str().unwrap()]);
+        assert!(out.status.success());
+        let text = stdout(&out);
+        let a = text.find("a.todo <==").unwrap();
+        let b = text.find("b.todo <==").unwrap();
+        assert!(a < b);
+    }
+
+    #[test]
+    fn unmatched_glob_fails() {
+        let dir = temp_dir("empty_glob", &[]);
+        let pattern = dir.join("*.todo");
+        let out = run(&["stats", "--file", pattern.to_str().unwrap()]);
+        assert!(!out.status.success());
+        assert!(String::from_utf8_lossy(&out.stderr).contains("no files match"));
+    }
+}
+
+mod color_option {
+    use super::*;
+
+    const SOURCE: &str = "project \"P\" {\n  todo: \"A\", @high,\n}\n";
+
+    #[test]
+    fn always_and_never() {
+        let path = temp_file("color.todo", SOURCE);
+        let out = run(&[
+            "parse",
+            "--file",
+            path.to_str().unwrap(),
+            "--color",
+            "always",
+        ]);
+        assert!(stdout(&out).contains("\x1b[33m[TODO]\x1b[0m"));
+
+        let out = run(&[
+            "--color",
+            "never",
+            "parse",
+            "--file",
+            path.to_str().unwrap(),
+        ]);
+        assert!(!stdout(&out).contains('\x1b'));
+    }
+
+    #[test]
+    fn auto_does_not_color_pipes() {
+        let path = temp_file("color_auto.todo", SOURCE);
+        let out = run(&["parse", "--file", path.to_str().unwrap()]);
+        assert!(!stdout(&out).contains('\x1b'));
+    }
+
+    #[test]
+    fn colors_error_labels() {
+        let out = run(&["stats", "--file", "missing.todo", "--color", "always"]);
+        assert!(
+            String::from_utf8_lossy(&out.stderr).starts_with("\x1b[1;31mParsing error\x1b[0m: ")
+        );
+    }
+}
+
+mod output_option {
+    use super::*;
+
+    const SOURCE: &str = "project \"P\" {\n  todo: \"A\", @high,\n}\n";
+
+    #[test]
+    fn export_writes_file() {
+        let path = temp_file("output_export.todo", SOURCE);
+        let target = path.with_file_name("output_export.csv");
+        let out = run(&[
+            "export",
+            "--file",
+            path.to_str().unwrap(),
+            "--format",
+            "csv",
+            "-o",
+            target.to_str().unwrap(),
+        ]);
+        assert!(out.status.success());
+        assert!(out.stdout.is_empty());
+        assert_eq!(
+            std::fs::read_to_string(&target).unwrap(),
+            "project,title,status,priority,due,assignee,tags\nP,A,todo,high,,,\n"
+        );
+    }
+
+    #[test]
+    fn parse_and_stats_write_plain_text() {
+        let path = temp_file("output_parse.todo", SOURCE);
+        let target = path.with_file_name("output_parse.txt");
+        let out = run(&[
+            "parse",
+            "--file",
+            path.to_str().unwrap(),
+            "--color",
+            "always",
+            "--output",
+            target.to_str().unwrap(),
+        ]);
+        assert!(out.status.success());
+        let text = std::fs::read_to_string(&target).unwrap();
+        assert!(text.starts_with("Project: P\n"));
+        assert!(!text.contains('\x1b'));
+
+        let target = path.with_file_name("output_stats.json");
+        let out = run(&[
+            "stats",
+            "--file",
+            path.to_str().unwrap(),
+            "--json",
+            "-o",
+            target.to_str().unwrap(),
+        ]);
+        assert!(out.status.success());
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&target).unwrap()).unwrap();
+        assert_eq!(json["total"], 1);
+    }
+
+    #[test]
+    fn failed_write_reports_error() {
+        let path = temp_file("output_fail.todo", SOURCE);
+        let target = path.with_file_name("no_such_dir").join("out.json");
+        let out = run(&[
+            "export",
+            "--file",
+            path.to_str().unwrap(),
+            "-o",
+            target.to_str().unwrap(),
+        ]);
+        assert!(!out.status.success());
+        assert!(String::from_utf8_lossy(&out.stderr).contains("File writing error"));
+    }
+}
+
+mod parse_tree {
+    use super::*;
+
+    #[test]
+    fn prints_spans_and_json() {
+        let path = temp_file("parse_tree.todo", "project \"P\" { todo: \"A\", }\n");
+        let out = run(&["parse", "--file", path.to_str().unwrap(), "--tree"]);
+        assert!(stdout(&out).contains("\n    - task 14..24 \"todo: \\\"A\\\",\"\n"));
+
+        let out = run(&[
+            "parse",
+            "--file",
+            path.to_str().unwrap(),
+            "--tree",
+            "--tree-format",
+            "json",
+        ]);
+        assert!(out.status.success());
+        assert!(stdout(&out).starts_with("[\n  {\n    \"rule\": \"file\","));
+    }
+
+    #[test]
+    fn tree_format_requires_tree() {
+        let path = temp_file(
+            "parse_tree_requires.todo",
+            "project \"P\" { todo: \"A\", }\n",
+        );
+        let out = run(&[
+            "parse",
+            "--file",
+            path.to_str().unwrap(),
+            "--tree-format",
+            "json",
+        ]);
+        assert_eq!(out.status.code(), Some(2));
+    }
+}
+
+mod parse_table {
+    use super::*;
+
+    const SOURCE: &str =
+        "project \"P\" {\n  todo: \"A\", @high,\n  done: \"Longer title\", due: 2025-01-01,\n}\n";
+
+    #[test]
+    fn prints_all_columns_by_default() {
+        let path = temp_file("parse_table.todo", SOURCE);
+        let out = run(&["parse", "--file", path.to_str().unwrap(), "--table"]);
+        assert!(out.status.success());
+        assert!(stdout(&out).starts_with("PROJECT  STATUS  TITLE         PRIORITY"));
+    }
+
+    #[test]
+    fn selects_columns() {
+        let path = temp_file("parse_columns.todo", SOURCE);
+        let out = run(&[
+            "parse",
+            "--file",
+            path.to_str().unwrap(),
+            "--table",
+            "--columns",
+            "title,due",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(
+            stdout(&out),
+            "TITLE         DUE\nA             -\nLonger title  2025-01-01\n"
+        );
+    }
+
+    #[test]
+    fn aligns_wide_characters() {
+        let path = temp_file(
+            "parse_table_wide.todo",
+            "project \"P\" {\n  todo: \"日本語\",\n  todo: \"Longer title\",\n}\n",
+        );
+        let out = run(&[
+            "parse",
+            "--file",
+            path.to_str().unwrap(),
+            "--table",
+            "--columns",
+            "title,status",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(
+            stdout(&out),
+            "TITLE         STATUS\n日本語        todo\nLonger title  todo\n"
+        );
+    }
+
+    #[test]
+    fn columns_require_table() {
+        let path = temp_file("parse_columns_only.todo", SOURCE);
+        let out = run(&[
+            "parse",
+            "--file",
+            path.to_str().unwrap(),
+            "--columns",
+            "title",
+        ]);
+        assert!(!out.status.success());
+    }
+}
+
+mod parse_error_output {
+    use super::*;
+
+    fn stderr(output: &Output) -> String {
+        String::from_utf8_lossy(&output.stderr).into_owned()
+    }
+
+    #[test]
+    fn shows_line_with_caret() {
+        let path = temp_file(
+            "snippet_bad.todo",
+            "project \"A\" {\n  todo: \"x\", due: 2025-13-01,\n}\n",
+        );
+        let out = run(&["parse", "--file", path.to_str().unwrap()]);
+        assert_eq!(out.status.code(), Some(1));
+        let text = stderr(&out);
+        assert!(text.contains("invalid date `2025-13-01`"), "{}", text);
+        assert!(text.contains("snippet_bad.todo:2:19\n"), "{}", text);
+        assert!(text.contains("1 | project \"A\" {\n"), "{}", text);
+        assert!(
+            text.contains(
+                "2 |   todo: \"x\", due: 2025-13-01,\n  |                   ^^^^^^^^^^\n"
+            ),
+            "{}",
+            text
+        );
+    }
+
+    #[test]
+    fn points_into_included_file() {
+        let inner = temp_file(
+            "snippet_inner.todo",
+            "project \"B\" {\n  todo: \"y\" @high,\n}\n",
+        );
+        let outer = temp_file("snippet_outer.todo", "include \"snippet_inner.todo\"\n");
+        let out = run(&["stats", "--file", outer.to_str().unwrap()]);
+        assert_eq!(out.status.code(), Some(1));
+        let text = stderr(&out);
+        let location = format!("{}:2:", inner.display());
+        assert!(text.contains(&location), "{}", text);
+        assert!(text.contains("2 |   todo: \"y\" @high,"), "{}", text);
+    }
+}
+
+mod empty_files {
+    use super::*;
+
+    fn stderr(output: &Output) -> String {
+        String::from_utf8_lossy(&output.stderr).into_owned()
+    }
+
+    #[test]
+    fn parse_with_a_warning() {
+        let path = temp_file("empty_parse.todo", "// nothing yet\n");
+        let out = run(&["parse", "--file", path.to_str().unwrap()]);
+        assert!(out.status.success(), "{}", stderr(&out));
+        assert_eq!(stdout(&out), "");
+        assert!(stderr(&out).contains("warning: "), "{}", stderr(&out));
+        assert!(stderr(&out).contains("empty_parse.todo contains no projects"));
+
+        let out = run(&["parse", "--file", path.to_str().unwrap(), "--quiet"]);
+        assert!(out.status.success());
+        assert_eq!(stderr(&out), "");
+    }
+
+    #[test]
+    fn tasks_can_be_added() {
+        let path = temp_file("empty_add.todo", "");
+        let out = run(&["stats", "--file", path.to_str().unwrap()]);
+        assert!(out.status.success(), "{}", stderr(&out));
+        let out = run(&[
+            "add",
+            "--file",
+            path.to_str().unwrap(),
+            "--project",
+            "P",
+            "--title",
+            "A",
+        ]);
+        assert!(out.status.success(), "{}", stderr(&out));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "project \"P\" {\n  todo: \"A\",\n}\n"
+        );
+    }
+}
+
+mod invalid_utf8 {
+    use super::*;
+
+    fn stderr(output: &Output) -> String {
+        String::from_utf8_lossy(&output.stderr).into_owned()
+    }
+
+    fn latin1_file(name: &str) -> PathBuf {
+        let path = temp_file(name, "");
+        std::fs::write(&path, b"project \"P\" {\n  todo: \"Caf\xe9\",\n}\n").unwrap();
+        path
+    }
+
+    #[test]
+    fn fails_with_the_byte_offset() {
+        let path = latin1_file("latin1_parse.todo");
+        let out = run(&["parse", "--file", path.to_str().unwrap()]);
+        assert_eq!(out.status.code(), Some(1));
+        assert!(
+            stderr(&out).contains("Line 2: invalid UTF-8 at byte 26"),
+            "{}",
+            stderr(&out)
+        );
+    }
+
+    #[test]
+    fn lossy_replaces_and_warns() {
+        let path = latin1_file("latin1_lossy.todo");
+        let out = run(&["parse", "--file", path.to_str().unwrap(), "--lossy"]);
+        assert!(out.status.success(), "{}", stderr(&out));
+        assert!(stdout(&out).contains("Caf\u{fffd}"));
+        assert!(stderr(&out).contains("invalid UTF-8 from byte 26 replaced with U+FFFD"));
+    }
+
+    #[test]
+    fn edits_never_replace_bytes() {
+        let path = latin1_file("latin1_done.todo");
+        let out = run(&["done", "--file", path.to_str().unwrap(), "--lossy", "P/A"]);
+        assert_eq!(out.status.code(), Some(1));
+        assert!(std::fs::read(&path).unwrap().contains(&0xe9));
+    }
+}
+
+mod verbosity {
+    use super::*;
+
+    fn stderr(output: &Output) -> String {
+        String::from_utf8_lossy(&output.stderr).into_owned()
+    }
+
+    #[test]
+    fn silent_by_default() {
+        let out = run(&["validate", "--file", "examples/project.txt"]);
+        assert_eq!(stderr(&out), "");
+    }
+
+    #[test]
+    fn verbose_levels() {
+        let out = run(&["validate", "--file", "examples/project.txt", "-v"]);
+        let text = stderr(&out);
+        assert!(text.starts_with("info: parsed "), "{}", text);
+        assert!(!text.contains("trace:"));
+
+        let out = run(&["validate", "--file", "examples/project.txt", "-vvv"]);
+        let text = stderr(&out);
+        assert!(
+            text.contains("debug: reading examples/project.txt"),
+            "{}",
+            text
+        );
+        assert!(
+            text.contains("trace: <input>:1:1:   matched project"),
+            "{}",
+            text
+        );
+    }
+
+    #[test]
+    fn quiet_hides_warnings() {
+        let path = temp_file("quiet.todo", "project \"P\" {\n  done: \"A\",\n}\n");
+        let out = run(&["done", "--file", path.to_str().unwrap(), "A"]);
+        assert!(stderr(&out).contains("warning: \"A\" is already done"));
+
+        let out = run(&["done", "--file", path.to_str().unwrap(), "A", "--quiet"]);
+        assert!(out.status.success());
+        assert_eq!(stderr(&out), "");
+    }
+
+    #[test]
+    fn quiet_conflicts_with_verbose() {
+        let out = run(&[
+            "validate",
+            "--file",
+            "examples/project.txt",
+            "-v",
+            "--quiet",
+        ]);
+        assert!(!out.status.success());
+    }
+}
+
+mod exit_codes {
+    use super::*;
+
+    fn code(args: &[&str]) -> i32 {
+        run(args).status.code().expect("exited normally")
+    }
+
+    #[test]
+    fn ok_and_parse_errors() {
+        let ok = temp_file("exit_ok.todo", r#"project "A" { todo: "x", }"#);
+        assert_eq!(code(&["validate", "--file", ok.to_str().unwrap()]), 0);
+        let bad = temp_file("exit_bad.todo", "project \"A\" { todo: \"x\" }");
+        assert_eq!(code(&["parse", "--file", bad.to_str().unwrap()]), 1);
+        assert_eq!(code(&["validate", "--file", bad.to_str().unwrap()]), 1);
+    }
+
+    #[test]
+    fn warnings_fail_only_with_strict() {
+        let path = temp_file("exit_warn.todo", r#"project "A" {}"#);
+        assert_eq!(code(&["validate", "--file", path.to_str().unwrap()]), 0);
+        assert_eq!(
+            code(&["validate", "--file", path.to_str().unwrap(), "--strict"]),
+            2
+        );
+    }
+
+    #[test]
+    fn lint_errors_are_validation_failures() {
+        let path = temp_file(
+            "exit_lint.todo",
+            r#"project "A" { todo: "x", depends_on: "nope", }"#,
+        );
+        assert_eq!(code(&["lint", "--file", path.to_str().unwrap()]), 2);
+    }
+
+    #[test]
+    fn io_errors() {
+        assert_eq!(code(&["validate", "--file", "no/such/file.todo"]), 3);
+        assert_eq!(code(&["stats", "--file", "no/such/file.todo"]), 3);
+        let path = temp_file("exit_io.todo", r#"project "A" { todo: "x", }"#);
+        assert_eq!(
+            code(&[
+                "export",
+                "--file",
+                path.to_str().unwrap(),
+                "-o",
+                "no/such/dir/out.json"
+            ]),
+            3
+        );
+    }
+
+    #[test]
+    fn highest_status_wins() {
+        let bad = temp_file("exit_mix_bad.todo", "project \"A\" { todo: \"x\" }");
+        let invalid = temp_file(
+            "exit_mix_invalid.todo",
+            r#"project "A" { todo: "x", @tag: "t", @tag: "t", }"#,
+        );
+        assert_eq!(
+            code(&[
+                "validate",
+                "--file",
+                bad.to_str().unwrap(),
+                "--file",
+                invalid.to_str().unwrap(),
+                "--strict"
+            ]),
+            2
+        );
+    }
+}
+
+mod critical_path_command {
+    use super::*;
+
+    #[test]
+    fn prints_path_and_slack() {
+        let path = temp_file(
+            "critical.todo",
+            "project \"P\" {\n  todo: \"Design\", start: 2025-11-01, due: 2025-11-03,\n  todo: \"Build\", depends_on: \"Design\", due: 2025-11-03,\n  todo: \"Docs\",\n}\n",
+        );
+        let out = run(&[
+            "critical-path",
+            "--file",
+            path.to_str().unwrap(),
+            "--as-of",
+            "2025-11-01",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(
+            stdout(&out),
+            "Critical path (4 days): P/Design -> P/Build\n\n\
+             * P/Design: 3 days, 2025-11-01 to 2025-11-03\n\
+             * P/Build: 1 day, 2025-11-04 to 2025-11-04, misses due 2025-11-03 by 1 day\n  \
+             P/Docs: 1 day, 2025-11-01 to 2025-11-01, slack 3 days\n"
+        );
+    }
+
+    #[test]
+    fn fails_on_cycle() {
+        let path = temp_file(
+            "critical_cycle.todo",
+            r#"project "P" { todo: "A", depends_on: "B", todo: "B", depends_on: "A", }"#,
+        );
+        let out = run(&["critical-path", "--file", path.to_str().unwrap()]);
+        assert_eq!(out.status.code(), Some(1));
+        assert!(String::from_utf8_lossy(&out.stderr).contains("dependency cycle through \"A\""));
+    }
+}
+
+mod burndown_command {
+    use super::*;
+
+    #[test]
+    fn prints_csv_series() {
+        let path = temp_file(
+            "burndown.todo",
+            "project \"P\" {\n  todo: \"A\",\n  done: \"B\", completed: 2025-11-02,\n}\n",
+        );
+        let out = run(&[
+            "burndown",
+            "--file",
+            path.to_str().unwrap(),
+            "--from",
+            "2025-11-01",
+            "--to",
+            "2025-11-03",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(
+            stdout(&out),
+            "date,open,done,total\n2025-11-01,2,0,2\n2025-11-02,1,1,2\n2025-11-03,1,1,2\n"
+        );
+    }
+
+    #[test]
+    fn rejects_reversed_range() {
+        let path = temp_file("burndown_reversed.todo", r#"project "P" { todo: "A", }"#);
+        let out = run(&[
+            "burndown",
+            "--file",
+            path.to_str().unwrap(),
+            "--from",
+            "2025-11-02",
+            "--to",
+            "2025-11-01",
+        ]);
+        assert_eq!(out.status.code(), Some(1));
+        assert!(String::from_utf8_lossy(&out.stderr).contains("is after --to"));
+    }
+}
+
+mod notify_command {
+    use super::*;
+
+    #[test]
+    fn dry_run_prints_notifications() {
+        let path = temp_file(
+            "notify.todo",
+            "project \"P\" {\n  todo: \"Late\", due: 2025-11-30,\n  todo: \"Soon\", due: 2025-12-02,\n  todo: \"Far\", due: 2025-12-20,\n}\n",
+        );
+        let out = run(&[
+            "notify",
+            "--file",
+            path.to_str().unwrap(),
+            "--within",
+            "24h",
+            "--as-of",
+            "2025-12-01",
+            "--dry-run",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(
+            stdout(&out),
+            "Overdue: Late\n  P · due 2025-11-30, 1 day late\nDue tomorrow: Soon\n  P · due 2025-12-02\n"
+        );
+    }
+
+    #[test]
+    fn rejects_bad_window() {
+        let path = temp_file("notify_bad.todo", r#"project "P" { todo: "x", }"#);
+        let out = run(&[
+            "notify",
+            "--file",
+            path.to_str().unwrap(),
+            "--within",
+            "soon",
+        ]);
+        assert!(!out.status.success());
+        assert!(String::from_utf8_lossy(&out.stderr).contains("invalid window 'soon'"));
+    }
+
+    #[test]
+    fn accepts_huge_window() {
+        let path = temp_file(
+            "notify_huge.todo",
+            "project \"P\" {\n  todo: \"Far\", due: 2999-01-01,\n}\n",
+        );
+        let out = run(&[
+            "notify",
+            "--file",
+            path.to_str().unwrap(),
+            "--within",
+            "4000000000d",
+            "--as-of",
+            "2025-12-01",
+            "--dry-run",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(stdout(&out), "Due Tue Jan 1: Far\n  P · due 2999-01-01\n");
+    }
+}
+
+mod overdue_command {
+    use super::*;
+
+    #[test]
+    fn lists_latest_first_and_fails() {
+        let path = temp_file(
+            "overdue.todo",
+            "project \"P\" {\n  todo: \"Recent\", due: 2025-11-30,\n  todo: \"Old\", due: 2025-11-01,\n  todo: \"Future\", due: 2025-12-31,\n  done: \"Closed\", due: 2025-10-01,\n}\n",
+        );
+        let out = run(&[
+            "overdue",
+            "--file",
+            path.to_str().unwrap(),
+            "--as-of",
+            "2025-12-01",
+        ]);
+        assert_eq!(out.status.code(), Some(4));
+        assert_eq!(
+            stdout(&out),
+            "P/Old: due 2025-11-01, 30 days late\nP/Recent: due 2025-11-30, 1 day late\n"
+        );
+    }
+
+    #[test]
+    fn succeeds_when_nothing_is_late() {
+        let path = temp_file(
+            "overdue_none.todo",
+            r#"project "P" { todo: "x", due: 2025-12-31, }"#,
+        );
+        let out = run(&[
+            "overdue",
+            "--file",
+            path.to_str().unwrap(),
+            "--as-of",
+            "2025-12-01",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(stdout(&out), "");
+    }
+}
+
+mod next_command {
+    use super::*;
+
+    #[test]
+    fn lists_unblocked_tasks_by_urgency() {
+        let path = temp_file(
+            "next.todo",
+            "project \"P\" {\n  done: \"Design\",\n  todo: \"Build\", depends_on: \"Design\",\n  todo: \"Test\", depends_on: \"Build\",\n  todo: \"Fix\", @high, due: 2025-12-01,\n}\n",
+        );
+        let out = run(&["next", "--file", path.to_str().unwrap()]);
+        assert!(out.status.success());
+        let text = stdout(&out);
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("PROJECT"));
+        assert!(lines[1].contains("Fix") && lines[1].contains("2025-12-01"));
+        assert!(lines[2].contains("Build"));
+    }
+}
+
+mod graph_command {
+    use super::*;
+
+    #[test]
+    fn prints_dot() {
+        let path = temp_file(
+            "graph.todo",
+            r#"project "P" { done: "A", todo: "B", depends_on: "A", }"#,
+        );
+        let out = run(&["graph", "--file", path.to_str().unwrap(), "--format", "dot"]);
+        assert!(out.status.success());
+        let text = stdout(&out);
+        assert!(text.starts_with("digraph tasks {"));
+        assert!(text.contains("t0_0 -> t0_1;"));
+    }
+
+    #[test]
+    fn prints_mermaid_gantt() {
+        let path = temp_file(
+            "graph_gantt.todo",
+            r#"project "P" { todo: "A", start: 2025-11-01, due: 2025-11-05, }"#,
+        );
+        let out = run(&[
+            "graph",
+            "--file",
+            path.to_str().unwrap(),
+            "--format",
+            "mermaid-gantt",
+        ]);
+        assert!(out.status.success());
+        assert!(stdout(&out).contains("A :t0_0, 2025-11-01, 2025-11-05\n"));
+    }
+}
+
+mod report_command {
+    use super::*;
+
+    #[test]
+    fn writes_html_file() {
+        let path = temp_file("report.todo", r#"project "P" { done: "A", todo: "B", }"#);
+        let output = path.with_extension("html");
+        let out = run(&[
+            "report",
+            "--file",
+            path.to_str().unwrap(),
+            "-o",
+            output.to_str().unwrap(),
+        ]);
+        assert!(out.status.success());
+        let html = std::fs::read_to_string(&output).unwrap();
+        assert!(html.contains("1 of 2 done (50%)"));
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+}
+
+mod search_command {
+    use super::*;
+
+    #[test]
+    fn prints_matches_with_lines() {
+        let path = temp_file(
+            "search.todo",
+            "project \"P\" {\n  todo: \"Fix login\",\n  todo: \"Add OAuth\", @tag: \"auth\",\n}\n",
+        );
+        let file = path.to_str().unwrap();
+        let out = run(&["search", "--file", file, "Auth"]);
+        assert!(out.status.success());
+        assert_eq!(stdout(&out), format!("{}:3: P/Add OAuth\n", file));
+
+        let out = run(&["search", "--file", file, "nothing"]);
+        assert_eq!(out.status.code(), Some(5));
+
+        let broken = temp_file("search_broken.todo", "project \"P\" { todo: \"A\" ");
+        let out = run(&["search", "--file", broken.to_str().unwrap(), "A"]);
+        assert_eq!(out.status.code(), Some(1));
+    }
+}
+
+mod agenda_command {
+    use super::*;
+
+    #[test]
+    fn groups_upcoming_tasks() {
+        let path = temp_file(
+            "agenda.todo",
+            "project \"P\" {\n  todo: \"Late\", due: 2025-11-01,\n  todo: \"Call\", due: 2025-11-13,\n  todo: \"Plan\", due: 2025-11-12,\n  todo: \"Far\", due: 2026-01-01,\n}\n",
+        );
+        let out = run(&[
+            "agenda",
+            "--file",
+            path.to_str().unwrap(),
+            "--as-of",
+            "2025-11-12",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(
+            stdout(&out),
+            "Overdue\n  2025-11-01  P/Late\n\nToday\n  2025-11-12  P/Plan\n\nTomorrow\n  2025-11-13  P/Call\n"
+        );
+    }
+
+    #[test]
+    fn accepts_huge_windows() {
+        let path = temp_file(
+            "agenda_huge.todo",
+            "project \"P\" {\n  todo: \"Far\", due: 2999-01-01,\n}\n",
+        );
+        let out = run(&[
+            "agenda",
+            "--file",
+            path.to_str().unwrap(),
+            "--as-of",
+            "2025-11-12",
+            "--weeks",
+            "4000000000",
+        ]);
+        assert!(out.status.success());
+        assert_eq!(stdout(&out), "Later\n  2999-01-01  P/Far\n");
+    }
+}
+
+mod by_assignee_command {
+    use super::*;
+
+    #[test]
+    fn prints_workload_per_assignee() {
+        let path = temp_file(
+            "by_assignee.todo",
+            r#"project "P" { todo: "A", assign: @bob, @high, due: 2025-11-20, todo: "B", done: "C", assign: @ann, }"#,
+        );
+        let out = run(&["by-assignee", "--file", path.to_str().unwrap()]);
+        assert!(out.status.success());
+        assert_eq!(
+            stdout(&out),
+            "@bob: 1 open, 1 high priority, next due 2025-11-20\n  P/A (high, due 2025-11-20)\n\n(unassigned): 1 open, 0 high priority, next due none\n  P/B\n"
+        );
+    }
+}
+
+mod tags_command {
+    use super::*;
+
+    const SOURCE: &str =
+        r#"project "P" { todo: "A", @tag: "bug", todo: "B", @tag: "bug", @tag: "frontend", }"#;
+
+    #[test]
+    fn lists_counts() {
+        let path = temp_file("tags.todo", SOURCE);
+        let out = run(&["tags", "--file", path.to_str().unwrap()]);
+        assert!(out.status.success());
+        assert_eq!(stdout(&out), "bug       2\nfrontend  1\n");
+    }
+
+    #[test]
+    fn shows_tasks_of_a_tag() {
+        let path = temp_file("tags_show.todo", SOURCE);
+        let file = path.to_str().unwrap();
+        let out = run(&["tags", "--file", file, "--show", "bug"]);
+        assert_eq!(stdout(&out), "P/A\nP/B\n");
+        let out = run(&["tags", "--file", file, "--show", "nope"]);
+        assert_eq!(out.status.code(), Some(1));
+    }
+}
+
+mod archive_command {
+    use super::*;
+
+    #[test]
+    fn rewrites_both_files() {
+        let path = temp_file(
+            "archive.todo",
+            "project \"P\" {\n  todo: \"A\",\n  done: \"B\",\n}\n",
+        );
+        let target = path.with_file_name("archive_target.todo");
+        let _ = std::fs::remove_file(&target);
+        let out = run(&[
+            "archive",
+            "--file",
+            path.to_str().unwrap(),
+            "--to",
+            target.to_str().unwrap(),
+        ]);
+        assert!(out.status.success());
+        assert!(stdout(&out).starts_with("Archived 1 task (0 whole projects)"));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "project \"P\" {\n  todo: \"A\",\n}\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(&target).unwrap(),
+            "project \"P\" {\n  done: \"B\",\n}\n"
+        );
+    }
+}
+
+mod init_command {
+    use super::*;
+
+    #[test]
+    fn creates_file_once() {
+        let path = temp_file("init.todo", "");
+        std::fs::remove_file(&path).unwrap();
+        let file = path.to_str().unwrap();
+
+        let out = run(&["init", "--file", file, "--template", "personal"]);
+        assert!(out.status.success());
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.contains("project \"Home\""));
+        assert!(run(&["validate", "--file", file]).status.success());
+
+        let out = run(&["init", "--file", file]);
+        assert_eq!(out.status.code(), Some(1));
+        assert!(run(&["init", "--file", file, "--force"]).status.success());
+        assert!(std::fs::read_to_string(&path).unwrap().contains("Sprint 1"));
+    }
+}
+
+mod config_file {
+    use super::*;
+
+    /// Runs the binary in `dir`, which holds a `.todoparser.toml`.
+    fn run_in(dir: &std::path::Path, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_to_do_parcer"))
+            .args(args)
+            .current_dir(dir)
+            .env("XDG_CONFIG_HOME", dir.join("xdg"))
+            .output()
+            .expect("failed to run binary")
+    }
+
+    fn project_dir(name: &str, config: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join("to_do_parcer_cli_tests")
+            .join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("tasks.todo"),
+            "project \"P\" {\n  todo: \"A\", due: 2025-12-01,\n}\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join(".todoparser.toml"), config).unwrap();
+        dir
+    }
+
+    #[test]
+    fn settings_are_defaults() {
+        let dir = project_dir(
+            "config_defaults",
+            "file = \"tasks.todo\"\nformat = \"csv\"\ndate-format = \"%d.%m.%Y\"\n",
+        );
+        let out = run_in(&dir, &["export"]);
+        assert!(out.status.success());
+        assert!(stdout(&out).starts_with("project,title,status"));
+
+        let out = run_in(&dir, &["parse", "--table", "--columns", "title,due"]);
+        assert_eq!(stdout(&out), "TITLE  DUE\nA      01.12.2025\n");
+    }
+
+    #[test]
+    fn flags_override_settings() {
+        let dir = project_dir(
+            "config_override",
+            "file = \"missing.todo\"\nformat = \"csv\"\n\n[lint]\ndisable = [\"overdue\"]\n",
+        );
+        let out = run_in(
+            &dir,
+            &["export", "--file", "tasks.todo", "--format", "json"],
+        );
+        assert!(stdout(&out).starts_with("{"));
+
+        let out = run_in(
+            &dir,
+            &["lint", "--file", "tasks.todo", "--as-of", "2026-01-01"],
+        );
+        assert!(stdout(&out).contains("warnings=0"));
+        let out = run_in(
+            &dir,
+            &[
+                "lint",
+                "--file",
+                "tasks.todo",
+                "--as-of",
+                "2026-01-01",
+                "--enable",
+                "overdue",
+            ],
+        );
+        assert!(stdout(&out).contains("warning[overdue]"));
+    }
+
+    #[test]
+    fn assignee_case_is_ignored() {
+        let dir = project_dir("config_assignee_case", "ignore-assignee-case = true\n");
+        std::fs::write(
+            dir.join("tasks.todo"),
+            "project \"P\" {\n  todo: \"A\", assign: @Alice,\n  todo: \"B\", assign: @alice,\n}\n",
+        )
+        .unwrap();
+        let out = run_in(&dir, &["stats", "--file", "tasks.todo"]);
+        assert!(stdout(&out).contains("By assignee:\n  @alice: 2\n"));
+
+        let out = run_in(&dir, &["fmt", "--file", "tasks.todo"]);
+        assert!(stdout(&out).contains("assign: @Alice,"));
+    }
+
+    #[cfg(feature = "spellcheck")]
+    #[test]
+    fn lint_words_are_accepted() {
+        let dir = project_dir(
+            "config_lint_words",
+            "[lint]\nenable = [\"spelling\"]\nwords = [\"Oleksii\"]\n",
+        );
+        std::fs::write(
+            dir.join("tasks.todo"),
+            "project \"P\" {\n  todo: \"Call Oleksii\", @tag: \"custmer\",\n}\n",
+        )
+        .unwrap();
+        let out = run_in(&dir, &["lint", "--file", "tasks.todo"]);
+        let stdout = stdout(&out);
+        assert!(stdout.contains("\"custmer\" in tag"));
+        assert!(!stdout.contains("Oleksii\" in"));
+    }
+
+    #[test]
+    fn bad_settings_are_reported() {
+        let dir = project_dir("config_bad", "color = \"sometimes\"\n");
+        let out = run_in(&dir, &["parse", "--file", "tasks.todo"]);
+        assert_eq!(out.status.code(), Some(1));
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        assert!(stderr.contains(".todoparser.toml: color: unknown color choice 'sometimes'"));
+    }
+}
+
+mod serve_command {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpStream;
+    use std::process::Stdio;
+
+    #[test]
+    fn answers_http_requests() {
+        let path = temp_file(
+            "serve.todo",
+            "project \"P\" {\n  todo: \"A\",\n  done: \"B\",\n}\n",
+        );
+        let mut child = Command::new(env!("CARGO_BIN_EXE_to_do_parcer"))
+            .args(["serve", "--file", path.to_str().unwrap(), "--port", "0"])
+            .env(
+                "XDG_CONFIG_HOME",
+                std::env::temp_dir().join("to_do_parcer_no_config"),
+            )
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("failed to run binary");
+        let mut banner = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut banner)
+            .unwrap();
+        let addr = banner.trim().rsplit("http://").next().unwrap().to_string();
+
+        let mut stream = TcpStream::connect(&addr).unwrap();
+        write!(
+            stream,
+            "GET /tasks?status=done HTTP/1.0\r\nHost: {}\r\n\r\n",
+            addr
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        assert!(response.starts_with("HTTP/1.0 200"), "{}", response);
+        assert!(response.contains("application/json"));
+        assert!(response.contains("\"title\": \"B\""));
+        assert!(!response.contains("\"title\": \"A\""));
+    }
+}
+
+mod watch_command {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+
+    /// Reads the lines printed by one check, up to its summary.
+    fn round(lines: &mut impl Iterator<Item = std::io::Result<String>>) -> Vec<String> {
+        let mut out = Vec::new();
+        for line in lines {
+            let line = line.unwrap();
+            let done = line.starts_with("summary:");
+            out.push(line);
+            if done {
+                break;
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn reports_changed_files() {
+        let dir = std::env::temp_dir().join("to_do_parcer_cli_watch");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let (a, b) = (dir.join("a.todo"), dir.join("b.todo"));
+        std::fs::write(
+            &a,
+            "project \"A\" { todo: \"Ship\", depends_on: \"Design\", }\n",
+        )
+        .unwrap();
+        std::fs::write(&b, "project \"B\" { todo: \"Design\", }\n").unwrap();
+
+        let mut child = Command::new(env!("CARGO_BIN_EXE_to_do_parcer"))
+            .args(["watch", "--dir", dir.to_str().unwrap(), "--interval", "50"])
+            .env(
+                "XDG_CONFIG_HOME",
+                std::env::temp_dir().join("to_do_parcer_no_config"),
+            )
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("failed to run binary");
+        let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+
+        let first = round(&mut lines);
+        std::fs::write(&b, "project \"B\" { todo: \"Draft\", }\n").unwrap();
+        let second = round(&mut lines);
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        let (a, b) = (a.display().to_string(), b.display().to_string());
+        assert_eq!(
+            first,
+            [
+                format!("{}: ok", a),
+                format!("{}: ok", b),
+                "summary: errors=0 warnings=0".to_string()
+            ]
+        );
+        assert_eq!(second.len(), 3, "{:?}", second);
+        assert!(second[0].starts_with(&format!("{}:1:29: error[unknown-dependency]", a)));
+        assert_eq!(second[1], format!("{}: ok", b));
+        assert_eq!(second[2], "summary: errors=1 warnings=0");
+    }
+}
+
+#[cfg(feature = "github")]
+mod sync_github_command {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Serves canned GitHub answers and records each request as
+    /// "METHOD url body".
+    fn mock_github() -> (String, Arc<Mutex<Vec<String>>>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        std::thread::spawn(move || {
+            for mut request in server.incoming_requests() {
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body).unwrap();
+                log.lock().unwrap().push(format!(
+                    "{} {} {}",
+                    request.method(),
+                    request.url(),
+                    body
+                ));
+                let answer = if request.method().as_str() == "GET" {
+                    r#"[{"number": 3, "title": "Done there", "state": "closed", "closed_at": "2025-05-06T08:00:00Z"}]"#
+                } else {
+                    r#"{"number": 4, "title": "New", "state": "open"}"#
+                };
+                request
+                    .respond(tiny_http::Response::from_string(answer))
+                    .unwrap();
+            }
+        });
+        (format!("http://{}", addr), seen)
+    }
+
+    #[test]
+    fn pushes_tasks_and_pulls_closed_issues() {
+        let path = temp_file(
+            "sync_github.todo",
+            "project \"P\" {\n  todo: \"New\", @high, @tag: \"github\",\n  todo: \"Done there\", @tag: \"github\",\n}\n",
+        );
+        let (api, seen) = mock_github();
+        let out = run(&[
+            "sync",
+            "github",
+            "--file",
+            path.to_str().unwrap(),
+            "--repo",
+            "me/app",
+            "--api-url",
+            &api,
+        ]);
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+        assert_eq!(
+            stdout(&out),
+            "Synced with me/app: 1 issue(s) created, 0 updated, 1 task(s) marked done\n"
+        );
+        assert!(
+            std::fs::read_to_string(&path)
+                .unwrap()
+                .contains("done: \"Done there\", @tag: \"github\", completed: 2025-05-06,")
+        );
+
+        let seen = seen.lock().unwrap();
+        assert!(seen[0].starts_with("GET /repos/me/app/issues?state=all"));
+        assert!(seen[1].starts_with("POST /repos/me/app/issues "));
+        assert!(seen[1].contains(r#""labels":["priority: high"]"#));
+    }
+}
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use chrono::NaiveDate;
 use pest::Parser;
 use to_do_parcer::parser::Rule;
 use to_do_parcer::{ParseError, Priority, TaskStatus, ToDoParser};
@@ -411,6 +412,34 @@ mod integration_tests {
             .count();
         assert_eq!(completed, 1);
     }
+
+    #[test]
+    fn windows_line_endings_and_bom() {
+        let parse = |source: &str| format!("{:?}", ToDoParser::parse_document(source).unwrap());
+        let unix = "// Work\nproject \"P\" {\n  todo: \"A\", @high, // first\n  done: \"B\",\n}\n";
+        let crlf = unix.replace('\n', "\r\n");
+        assert_eq!(parse(&crlf), parse(unix));
+        assert_eq!(parse(&format!("\u{feff}{}", unix)), parse(unix));
+        assert_eq!(parse(&format!("\u{feff}{}", crlf)), parse(unix));
+
+        let doc = ToDoParser::parse_document(&crlf).unwrap();
+        assert_eq!(doc.projects[0].comments, ["// Work"]);
+        assert_eq!(
+            doc.projects[0].tasks[0].trailing_comment.as_deref(),
+            Some("// first")
+        );
+    }
+
+    #[test]
+    fn errors_in_windows_files_point_at_the_same_place() {
+        let source = "\u{feff}project \"P\" {\r\n  todo: \"A\", due: 2025-02-30,\r\n}\r\n";
+        let err = ToDoParser::parse_document(source).unwrap_err();
+        assert_eq!((err.line(), err.column()), (Some(2), Some(19)));
+        assert!(
+            err.render(source, "t.todo")
+                .contains("\n2 |   todo: \"A\", due: 2025-02-30,\n")
+        );
+    }
 }
 
 mod error_tests {
@@ -441,6 +470,105 @@ mod error_tests {
         );
     }
 
+    #[test]
+    fn calendar_invalid_dates() {
+        let input = "project \"T\" {\n  todo: \"X\", due: 2025-02-30,\n}";
+        let Err(ParseError::InvalidDate {
+            span,
+            value,
+            reason,
+        }) = ToDoParser::parse_document(input)
+        else {
+            panic!("expected an invalid date");
+        };
+        assert_eq!(value, "2025-02-30");
+        assert_eq!(reason, "February 2025 has 28 days");
+        assert_eq!((span.line, span.column), (2, 19));
+        assert_eq!(&input[span.start..span.end], "2025-02-30");
+
+        for (date, valid) in [
+            ("2024-02-29", true),
+            ("2000-02-29", true),
+            ("1900-02-29", false),
+            ("2025-04-31", false),
+            ("2025-13-01", false),
+            ("2025-00-10", false),
+            ("2025-01-00", false),
+        ] {
+            let input = format!(r#"project "T" {{ todo: "X", start: {}, }}"#, date);
+            assert_eq!(
+                ToDoParser::parse_document(&input).is_ok(),
+                valid,
+                "{}",
+                date
+            );
+        }
+        let err =
+            ToDoParser::parse_document(r#"project "T" { done: "X", completed: 2025-13-01, }"#)
+                .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Line 1: invalid date `2025-13-01`: month 13 is not between 01 and 12"
+        );
+    }
+
+    #[test]
+    fn unknown_attribute_suggests_keyword() {
+        let err = ToDoParser::parse_document("project \"T\" {\n  todo: \"X\", asign: @bob,\n}")
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Line 2: unknown attribute `asign`, did you mean `assign`?"
+        );
+        for (input, expected) in [
+            (
+                r#"project "T" { todo: "X", deu: 2025-01-01, }"#,
+                Some("due"),
+            ),
+            (
+                r#"project "T" { todo: "X", dependson: "Y", }"#,
+                Some("depends_on"),
+            ),
+            (
+                r#"version 2 project "T" { todo: "X", tags: "y", }"#,
+                Some("tag"),
+            ),
+            (r#"project "T" { todo: "X", tags: "y", }"#, Some("@tag")),
+            (r#"project "T" { todo: "X", color: red, }"#, None),
+        ] {
+            match ToDoParser::parse_document(input) {
+                Err(ParseError::UnknownAttribute { suggestion, .. }) => {
+                    assert_eq!(suggestion, expected, "{}", input)
+                }
+                other => panic!("expected an unknown attribute, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn render_shows_snippet_and_caret() {
+        let source = "project \"T\" {\n\ttodo: \"X\" @high,\n}";
+        let err = ToDoParser::parse_document(source).unwrap_err();
+        assert_eq!(err.column(), Some(2));
+        let rendered = err.render(source, "t.todo");
+        let lines: Vec<_> = rendered.lines().collect();
+        assert_eq!(lines[1], " --> t.todo:2:2");
+        assert_eq!(lines[3], "1 | project \"T\" {");
+        assert_eq!(lines[4], "2 | \ttodo: \"X\" @high,");
+        assert_eq!(lines[5], "  | \t^");
+
+        let first = ToDoParser::parse_document("project").unwrap_err();
+        let rendered = first.render("project", "t.todo");
+        assert!(
+            rendered.ends_with("  |\n1 | project\n  |        ^\n"),
+            "{}",
+            rendered
+        );
+
+        let io = ParseError::Io(std::io::Error::other("gone"));
+        assert_eq!(io.render("", "t.todo"), "File reading error: gone\n");
+    }
+
     #[test]
     fn unclosed_quote() {
         assert!(
@@ -484,4 +612,4539 @@ mod error_tests {
             _ => panic!("Expected IO error"),
         }
     }
+
+    #[test]
+    fn io_errors_are_recognized() {
+        assert!(
+            ToDoParser::parse_from_file("no/such/file.todo")
+                .unwrap_err()
+                .is_io()
+        );
+        assert!(!ToDoParser::parse_document("project").unwrap_err().is_io());
+    }
+}
+
+mod schedule_tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    const INPUT: &str = r#"project "P" {
+        todo: "Late", due: 2025-11-10,
+        todo: "Today", due: 2025-11-15,
+        todo: "Soon", due: 2025-11-20,
+        todo: "Later", due: 2025-12-31,
+        done: "Finished late", due: 2025-11-01,
+        todo: "No date",
+    }"#;
+
+    #[test]
+    fn overdue_skips_done_and_undated() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let late = doc.overdue(&date(2025, 11, 15));
+        assert_eq!(late.len(), 1);
+        assert_eq!(late[0].1.title, "Late");
+    }
+
+    #[test]
+    fn days_late_counts_only_open_past_due_tasks() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let today = date(2025, 11, 15);
+        let late: Vec<_> = doc.tasks().map(|(_, t)| t.days_late(&today)).collect();
+        assert_eq!(late, [Some(5), None, None, None, None, None]);
+    }
+
+    #[test]
+    fn agenda_groups_by_due_date() {
+        use to_do_parcer::schedule::AgendaGroup;
+
+        // 2025-11-15 is a Saturday, so "This Week" ends tomorrow.
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let groups: Vec<_> = doc
+            .agenda(&date(2025, 11, 15), 14)
+            .into_iter()
+            .map(|(g, tasks)| (g, tasks.len()))
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                (AgendaGroup::Overdue, 1),
+                (AgendaGroup::Today, 1),
+                (AgendaGroup::Later, 1),
+            ]
+        );
+
+        let wednesday = date(2025, 11, 12);
+        assert_eq!(
+            AgendaGroup::of(date(2025, 11, 16), wednesday),
+            AgendaGroup::ThisWeek
+        );
+        assert_eq!(
+            AgendaGroup::of(date(2025, 11, 17), wednesday),
+            AgendaGroup::Later
+        );
+        assert_eq!(
+            AgendaGroup::of(date(2025, 11, 13), wednesday),
+            AgendaGroup::Tomorrow
+        );
+    }
+
+    #[test]
+    fn due_within_is_inclusive() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let titles: Vec<_> = doc
+            .due_within(&date(2025, 11, 15), 5)
+            .into_iter()
+            .map(|(_, t)| t.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["Today", "Soon"]);
+    }
+
+    #[test]
+    fn due_within_clamps_huge_windows() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let titles: Vec<_> = doc
+            .due_within(&date(2025, 11, 15), u32::MAX)
+            .into_iter()
+            .map(|(_, t)| t.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["Today", "Soon", "Later"]);
+    }
+
+    #[test]
+    fn custom_clock() {
+        struct Frozen;
+        impl to_do_parcer::Clock for Frozen {
+            fn today(&self) -> NaiveDate {
+                NaiveDate::from_ymd_opt(2026, 1, 1).unwrap()
+            }
+        }
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        assert_eq!(doc.overdue(&Frozen).len(), 4);
+    }
+}
+
+mod event_tests {
+    use super::*;
+    use to_do_parcer::events::{Event, TodoSink};
+
+    #[derive(Default)]
+    struct Recorder {
+        log: Vec<String>,
+    }
+
+    impl TodoSink for Recorder {
+        fn event(&mut self, event: Event<'_>) {
+            self.log.push(match event {
+                Event::Version(v) => format!("version {}", v),
+                Event::ProjectStart { name, .. } => format!("start {}", name),
+                Event::TaskParsed(task) => format!("task {}", task.title),
+                Event::ProjectEnd => "end".to_string(),
+                Event::Warning(w) => format!("warning {}", w),
+                Event::Comment { text, .. } => format!("comment {}", text),
+                Event::Include { path, .. } => format!("include {}", path),
+            });
+        }
+    }
+
+    #[test]
+    fn events_in_source_order() {
+        let mut sink = Recorder::default();
+        ToDoParser::parse_events(
+            r#"
+        project "P1" { todo: "A", @high, }
+        project "P2" { }
+        "#,
+            &mut sink,
+        )
+        .unwrap();
+        assert_eq!(
+            sink.log,
+            vec!["version 1", "start P1", "task A", "end", "start P2", "end"]
+        );
+    }
+
+    #[test]
+    fn no_events_on_error() {
+        let mut sink = Recorder::default();
+        assert!(ToDoParser::parse_events(r#"project "P" { todo: "A" }"#, &mut sink).is_err());
+        assert!(sink.log.is_empty());
+    }
+}
+
+mod extension_tests {
+    use super::*;
+    use to_do_parcer::extensions::Extensions;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Slack(u32);
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Note(&'static str);
+
+    #[test]
+    fn insert_get_remove() {
+        let mut ext = Extensions::new();
+        assert!(ext.is_empty());
+        assert_eq!(ext.insert(Slack(1)), None);
+        assert_eq!(ext.insert(Slack(2)), Some(Slack(1)));
+        ext.insert(Note("x"));
+        assert_eq!(ext.len(), 2);
+        ext.get_mut::<Slack>().unwrap().0 += 1;
+        assert_eq!(ext.get::<Slack>(), Some(&Slack(3)));
+        assert_eq!(ext.remove::<Note>(), Some(Note("x")));
+        assert!(!ext.contains::<Note>());
+    }
+
+    #[test]
+    fn annotate_parsed_nodes() {
+        let mut doc = ToDoParser::parse_document(r#"project "P" { todo: "A", }"#).unwrap();
+        assert!(doc.projects[0].tasks[0].extensions.is_empty());
+        doc.projects[0].tasks[0].extensions.insert(Slack(5));
+        let copy = doc.clone();
+        assert_eq!(
+            copy.projects[0].tasks[0].extensions.get::<Slack>(),
+            Some(&Slack(5))
+        );
+    }
+}
+
+mod group_tests {
+    use super::*;
+    use to_do_parcer::group::{Group, GroupKey};
+
+    const INPUT: &str = r#"project "P" {
+        todo: "A", assign: @bob, @tag: "ui", due: 2025-11-12,
+        done: "B", assign: @alice, @tag: "ui", @tag: "api", due: 2025-11-16,
+        todo: "C", due: 2025-11-17,
+    }"#;
+
+    fn titles(tasks: &[(&to_do_parcer::Project, &to_do_parcer::Task)]) -> Vec<String> {
+        tasks.iter().map(|(_, t)| t.title.clone()).collect()
+    }
+
+    #[test]
+    fn by_assignee_is_ordered() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let groups = doc.group_by(GroupKey::Assignee);
+        let keys: Vec<_> = groups.keys().map(|g| g.to_string()).collect();
+        assert_eq!(keys, vec!["@alice", "@bob", "(unassigned)"]);
+    }
+
+    #[test]
+    fn by_tag_lists_task_per_tag() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let groups = doc.group_by(GroupKey::Tag);
+        assert_eq!(titles(&groups[&Group::Tag("ui".into())]), vec!["A", "B"]);
+        assert_eq!(titles(&groups[&Group::Tag("api".into())]), vec!["B"]);
+        assert_eq!(titles(&groups[&Group::Untagged]), vec!["C"]);
+    }
+
+    #[test]
+    fn by_status_and_due_week() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let status = doc.group_by(GroupKey::Status);
+        assert_eq!(status[&Group::Status(TaskStatus::Todo)].len(), 2);
+
+        let weeks = doc.group_by(GroupKey::DueWeek);
+        let monday = NaiveDate::from_ymd_opt(2025, 11, 10).unwrap();
+        assert_eq!(titles(&weeks[&Group::DueWeek(monday)]), vec!["A", "B"]);
+        assert_eq!(weeks.len(), 2);
+    }
+
+    #[test]
+    fn tag_index_counts_and_lists() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let index = doc.tag_index();
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.counts(), [("ui", 2), ("api", 1)]);
+        assert_eq!(titles(index.tasks("ui")), vec!["A", "B"]);
+        assert!(index.tasks("missing").is_empty());
+    }
+
+    #[test]
+    fn workload_counts_open_tasks() {
+        let doc = ToDoParser::parse_document(
+            r#"project "P" {
+                todo: "A", assign: @bob, @high, due: 2025-11-20,
+                todo: "B", assign: @bob, due: 2025-11-12,
+                done: "C", assign: @alice,
+                todo: "D",
+            }"#,
+        )
+        .unwrap();
+        let load = doc.workload();
+        let keys: Vec<_> = load.keys().map(|g| g.to_string()).collect();
+        assert_eq!(keys, vec!["@bob", "(unassigned)"]);
+        let bob = &load[&Group::Assignee("bob".into())];
+        assert_eq!(titles(&bob.open), vec!["A", "B"]);
+        assert_eq!(bob.high, 1);
+        assert_eq!(bob.next_due, NaiveDate::from_ymd_opt(2025, 11, 12));
+        assert_eq!(load[&Group::Unassigned].next_due, None);
+    }
+}
+
+mod version_tests {
+    use super::*;
+
+    #[test]
+    fn default_version_is_one() {
+        let doc = ToDoParser::parse_document(r#"project "P" { todo: "A", @tag: "x", }"#).unwrap();
+        assert_eq!(doc.version, 1);
+        assert_eq!(doc.projects[0].tasks[0].tags, vec!["x"]);
+    }
+
+    #[test]
+    fn version_two_tags() {
+        let doc = ToDoParser::parse_document(
+            r#"version 2
+            project "P" { todo: "A", tag: "x", }"#,
+        )
+        .unwrap();
+        assert_eq!(doc.version, 2);
+        assert_eq!(doc.projects[0].tasks[0].tags, vec!["x"]);
+    }
+
+    #[test]
+    fn mismatched_tag_spelling() {
+        let v1 = ToDoParser::parse_document(r#"project "P" { todo: "A", tag: "x", }"#);
+        assert!(matches!(v1, Err(ParseError::Incompatible { line: 1, .. })));
+
+        let v2 = ToDoParser::parse_document(
+            r#"version 2
+            project "P" { todo: "A", @tag: "x", }"#,
+        );
+        match v2 {
+            Err(ParseError::Incompatible { line, message }) => {
+                assert_eq!(line, 2);
+                assert!(message.contains("replaced by `tag:`"));
+            }
+            other => panic!("Expected incompatibility error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let err = ToDoParser::parse_document(r#"version 7 project "P" {}"#).unwrap_err();
+        assert!(matches!(err, ParseError::UnsupportedVersion { found: 7 }));
+        assert_eq!(
+            err.to_string(),
+            "Unsupported format version 7 (supported: 1-2)"
+        );
+    }
+}
+
+mod options_tests {
+    use super::*;
+    use std::fs;
+    use to_do_parcer::options::{
+        DateFormat, DuplicateProjectPolicy, IncludePolicy, Limits, ParseOptions,
+        UnknownAttributePolicy,
+    };
+
+    #[test]
+    fn unknown_attribute_policies() {
+        let input = r#"project "P" { todo: "A", color: red, todo: "B", }"#;
+        assert!(matches!(
+            ToDoParser::parse_document(input),
+            Err(ParseError::UnknownAttribute { ref name, .. }) if name == "color"
+        ));
+
+        let ignore = ParseOptions {
+            unknown_attributes: UnknownAttributePolicy::Ignore,
+            ..ParseOptions::default()
+        };
+        let doc = ToDoParser::parse_with(input, &ignore).unwrap();
+        assert_eq!(doc.projects[0].tasks.len(), 2);
+        assert!(doc.warnings.is_empty());
+
+        let warn = ParseOptions {
+            unknown_attributes: UnknownAttributePolicy::Warn,
+            ..ParseOptions::default()
+        };
+        let doc = ToDoParser::parse_with(input, &warn).unwrap();
+        assert_eq!(doc.warnings.len(), 1);
+        assert_eq!(doc.warnings[0].line, 1);
+    }
+
+    #[test]
+    fn repeated_attributes() {
+        let input = "project \"P\" {\n  todo: \"A\", @high, due: 2025-01-01, @low,\n    due: 2025-02-01, due: 2025-02-01,\n  todo: \"B\", @tag: \"x\", @tag: \"y\", @high, @high,\n}";
+        let doc = ToDoParser::parse_document(input).unwrap();
+        let task = &doc.projects[0].tasks[0];
+        assert_eq!(task.priority, Some(Priority::Low));
+        assert_eq!(task.due_date.as_deref(), Some("2025-02-01"));
+        let messages: Vec<_> = doc
+            .warnings
+            .iter()
+            .map(|w| (w.line, w.message.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                (
+                    2,
+                    "task has two priorities, `@high` at 2:14 and `@low` at 2:38; the last one is used"
+                ),
+                (
+                    3,
+                    "task has two `due:` values, `2025-01-01` at 2:21 and `2025-02-01` at 3:5; the last one is used"
+                ),
+            ]
+        );
+
+        let strict = ParseOptions {
+            strict: true,
+            ..ParseOptions::default()
+        };
+        assert!(matches!(
+            ToDoParser::parse_with(input, &strict),
+            Err(ParseError::Strict(ref w)) if w.line == 2
+        ));
+    }
+
+    #[test]
+    fn allowed_priorities_and_strict() {
+        let input = r#"project "P" { todo: "A", @low, todo: "B", @high, }"#;
+        let mut options = ParseOptions {
+            allowed_priorities: vec![Priority::High, Priority::Medium],
+            ..ParseOptions::default()
+        };
+        let doc = ToDoParser::parse_with(input, &options).unwrap();
+        assert_eq!(doc.projects[0].tasks[0].priority, None);
+        assert_eq!(doc.projects[0].tasks[1].priority, Some(Priority::High));
+        assert!(doc.warnings[0].message.contains("@low"));
+
+        options.strict = true;
+        assert!(matches!(
+            ToDoParser::parse_with(input, &options),
+            Err(ParseError::Strict(_))
+        ));
+    }
+
+    #[test]
+    fn priority_needs_word_boundary() {
+        assert!(ToDoParser::parse_document(r#"project "P" { todo: "A", @highest, }"#).is_err());
+    }
+
+    #[test]
+    fn include_policies() {
+        let dir = std::env::temp_dir().join("to_do_parcer_include_policies");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("team.todo"), r#"project "Team" { todo: "Sync", }"#).unwrap();
+        let input = r#"project "Main" { todo: "A", } include "team.todo""#;
+
+        assert!(matches!(
+            ToDoParser::parse_document(input),
+            Err(ParseError::IncludesDisabled { .. })
+        ));
+
+        let mut options = ParseOptions {
+            includes: IncludePolicy::Ignore,
+            ..ParseOptions::default()
+        };
+        assert_eq!(
+            ToDoParser::parse_with(input, &options)
+                .unwrap()
+                .projects
+                .len(),
+            1
+        );
+
+        options.includes = IncludePolicy::Resolve;
+        options.base_dir = Some(dir.clone());
+        let doc = ToDoParser::parse_with(input, &options).unwrap();
+        let names: Vec<_> = doc.projects.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Main", "Team"]);
+    }
+
+    #[test]
+    fn include_cycle_and_missing_file() {
+        let dir = std::env::temp_dir().join("to_do_parcer_include_cycle");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a.todo"), r#"project "A" {} include "b.todo""#).unwrap();
+        fs::write(dir.join("b.todo"), r#"project "B" {} include "a.todo""#).unwrap();
+
+        let path = dir.join("a.todo");
+        let options = ParseOptions {
+            includes: IncludePolicy::Resolve,
+            ..ParseOptions::default()
+        };
+        assert!(matches!(
+            ToDoParser::parse_file_with(path.to_str().unwrap(), &options),
+            Err(ParseError::IncludeCycle { .. })
+        ));
+
+        let missing = ToDoParser::parse_with(r#"include "nope.todo""#, &options);
+        assert!(matches!(missing, Err(ParseError::Include { .. })));
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let dir = std::env::temp_dir().join("to_do_parcer_invalid_utf8");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("latin1.todo");
+        fs::write(&path, b"project \"P\" {\n  todo: \"Caf\xe9\",\n}\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        assert!(matches!(
+            ToDoParser::parse_file_with(path, &ParseOptions::default()),
+            Err(ParseError::Encoding {
+                line: 2,
+                offset: 26
+            })
+        ));
+
+        let lossy = ParseOptions {
+            lossy: true,
+            ..ParseOptions::default()
+        };
+        let doc = ToDoParser::parse_file_with(path, &lossy).unwrap();
+        assert_eq!(doc.projects[0].tasks[0].title, "Caf\u{fffd}");
+        assert_eq!(doc.warnings.len(), 1);
+        assert_eq!(doc.warnings[0].line, 2);
+
+        let included = ParseOptions {
+            includes: IncludePolicy::Resolve,
+            base_dir: Some(dir.clone()),
+            ..lossy
+        };
+        let doc = ToDoParser::parse_with(r#"include "latin1.todo""#, &included).unwrap();
+        assert_eq!(
+            doc.warnings[0].file.as_deref(),
+            Some(dir.join("latin1.todo").as_path())
+        );
+    }
+
+    #[test]
+    fn resource_limits() {
+        let dir = std::env::temp_dir().join("to_do_parcer_limits");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a.todo"), r#"include "b.todo""#).unwrap();
+        fs::write(dir.join("b.todo"), r#"project "B" { todo: "x", }"#).unwrap();
+        let input = r#"project "P" { todo: "A", todo: "B", } include "a.todo""#;
+        let with = |limits: Limits| ParseOptions {
+            includes: IncludePolicy::Resolve,
+            base_dir: Some(dir.clone()),
+            limits,
+            ..ParseOptions::default()
+        };
+
+        assert!(ToDoParser::parse_with(input, &with(Limits::default())).is_ok());
+
+        let tasks = Limits {
+            max_tasks_per_project: Some(1),
+            ..Limits::default()
+        };
+        let err = ToDoParser::parse_with(input, &with(tasks)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"Line 1: project "P" has more than 1 tasks"#
+        );
+
+        let depth = Limits {
+            max_include_depth: Some(1),
+            ..Limits::default()
+        };
+        assert!(matches!(
+            ToDoParser::parse_with(input, &with(depth)),
+            Err(ParseError::IncludeTooDeep { max: 1, ref path }) if path.ends_with("b.todo")
+        ));
+
+        // The included files push the total over the limit.
+        let size = Limits {
+            max_input_bytes: Some(input.len() + 20),
+            ..Limits::default()
+        };
+        let err = ToDoParser::parse_with(input, &with(size)).unwrap_err();
+        assert!(matches!(
+            err.innermost().1,
+            ParseError::InputTooLarge { .. }
+        ));
+        let tiny = Limits {
+            max_input_bytes: Some(10),
+            ..Limits::default()
+        };
+        assert!(matches!(
+            ToDoParser::parse_with(input, &with(tiny)),
+            Err(ParseError::InputTooLarge { max: 10 })
+        ));
+    }
+
+    #[test]
+    fn case_insensitive_keywords() {
+        let input = "Version 2\nPROJECT \"P\" {\n  TODO: \"A\", @High, Due: 2025-01-01, Tag: \"x\",\n  Done: \"B\",\n}";
+        let err = ToDoParser::parse_document(input).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Line 1: keyword `Version` must be written `version`"
+        );
+        assert_eq!(err.column(), Some(1));
+
+        let options = ParseOptions {
+            case_insensitive_keywords: true,
+            ..ParseOptions::default()
+        };
+        let doc = ToDoParser::parse_with(input, &options).unwrap();
+        assert_eq!(doc.version, 2);
+        let tasks = &doc.projects[0].tasks;
+        assert_eq!(tasks[0].status, TaskStatus::Todo);
+        assert_eq!(tasks[0].priority, Some(Priority::High));
+        assert_eq!(tasks[0].due_date.as_deref(), Some("2025-01-01"));
+        assert_eq!(tasks[0].tags, ["x"]);
+        assert_eq!(tasks[1].status, TaskStatus::Done);
+        assert_eq!(
+            to_do_parcer::pretty::format_document(&doc),
+            "version 2\n\nproject \"P\" {\n  todo: \"A\", @high, due: 2025-01-01, tag: \"x\",\n  done: \"B\",\n}\n"
+        );
+    }
+
+    #[test]
+    fn duplicate_project_policies() {
+        let input = "project \"A\" { todo: \"1\", }\nproject \"B\" {}\n// more\nproject \"A\" { todo: \"2\", }";
+        let names = |doc: &to_do_parcer::Document| -> Vec<String> {
+            doc.projects.iter().map(|p| p.name.clone()).collect()
+        };
+        assert_eq!(
+            names(&ToDoParser::parse_document(input).unwrap()),
+            ["A", "B", "A"]
+        );
+
+        let mut options = ParseOptions {
+            duplicate_projects: DuplicateProjectPolicy::Error,
+            ..ParseOptions::default()
+        };
+        let err = ToDoParser::parse_with(input, &options).unwrap_err();
+        assert_eq!(err.line(), Some(4));
+        assert_eq!(
+            err.to_string(),
+            r#"Line 4: project "A" is already defined on line 1"#
+        );
+
+        options.duplicate_projects = DuplicateProjectPolicy::Merge;
+        let doc = ToDoParser::parse_with(input, &options).unwrap();
+        assert_eq!(names(&doc), ["A", "B"]);
+        let tasks = &doc.projects[0].tasks;
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[1].title, "2");
+        assert_eq!(tasks[1].comments, ["// more"]);
+    }
+
+    #[test]
+    fn allow_empty() {
+        let options = ParseOptions {
+            allow_empty: true,
+            ..ParseOptions::default()
+        };
+        for input in ["", "  \n\t\n", "// nothing yet\n// or here", "\u{feff}\r\n"] {
+            assert!(ToDoParser::parse_document(input).is_err(), "{:?}", input);
+            let doc = ToDoParser::parse_with(input, &options).unwrap();
+            assert!(doc.projects.is_empty(), "{:?}", input);
+        }
+        assert!(ToDoParser::parse_with("project \"P\" {", &options).is_err());
+        assert!(ToDoParser::parse_with("version 2", &options).is_err());
+    }
+
+    #[test]
+    fn assignee_case() {
+        let input = r#"project "P" {
+            todo: "A", assign: @Alice,
+            todo: "B", assign: @alice,
+        }"#;
+        let doc = ToDoParser::parse_document(input).unwrap();
+        let tasks = &doc.projects[0].tasks;
+        assert_eq!(tasks[0].assignee.as_deref(), Some("Alice"));
+        assert_eq!(tasks[0].assignee_as_written, None);
+
+        let options = ParseOptions {
+            ignore_assignee_case: true,
+            ..ParseOptions::default()
+        };
+        let doc = ToDoParser::parse_with(input, &options).unwrap();
+        let tasks = &doc.projects[0].tasks;
+        assert_eq!(tasks[0].assignee.as_deref(), Some("alice"));
+        assert_eq!(tasks[0].assignee_display(), Some("Alice"));
+        assert_eq!(tasks[1].assignee_as_written, None);
+        assert_eq!(tasks[0].assignee, tasks[1].assignee);
+    }
+
+    #[test]
+    fn local_date_formats() {
+        let dotted = r#"project "P" { todo: "A", start: 01.12.2025, due: 2025-12-31, }"#;
+        let slashed = r#"project "P" { todo: "A", due: 12/31/2025, }"#;
+
+        assert!(matches!(
+            ToDoParser::parse_document(dotted),
+            Err(ParseError::InvalidDate { ref reason, .. }) if reason == "expected YYYY-MM-DD"
+        ));
+
+        let day_month_year = ParseOptions {
+            date_format: DateFormat::DayMonthYear,
+            ..ParseOptions::default()
+        };
+        let doc = ToDoParser::parse_with(dotted, &day_month_year).unwrap();
+        let task = &doc.projects[0].tasks[0];
+        assert_eq!(task.start_date.as_deref(), Some("2025-12-01"));
+        assert_eq!(task.due_date.as_deref(), Some("2025-12-31"));
+        assert!(matches!(
+            ToDoParser::parse_with(slashed, &day_month_year),
+            Err(ParseError::InvalidDate { ref reason, .. })
+                if reason == "expected YYYY-MM-DD or DD.MM.YYYY"
+        ));
+
+        let month_day_year = ParseOptions {
+            date_format: DateFormat::MonthDayYear,
+            ..ParseOptions::default()
+        };
+        let doc = ToDoParser::parse_with(slashed, &month_day_year).unwrap();
+        assert_eq!(
+            doc.projects[0].tasks[0].due_date.as_deref(),
+            Some("2025-12-31")
+        );
+        assert!(matches!(
+            ToDoParser::parse_with(
+                r#"project "P" { todo: "A", due: 02/30/2025, }"#,
+                &month_day_year
+            ),
+            Err(ParseError::InvalidDate { ref reason, .. }) if reason == "February 2025 has 28 days"
+        ));
+    }
+}
+
+mod dependency_tests {
+    use super::*;
+    use to_do_parcer::deps::TaskRef;
+
+    const INPUT: &str = r#"
+    project "Backend" {
+        todo: "Setup DB",
+        todo: "API", depends_on: "Setup DB",
+        todo: "Deploy", depends_on: "Frontend/Build",
+        todo: "Typo", depends_on: "Setup DBB",
+    }
+    project "Frontend" {
+        todo: "Setup DB",
+        todo: "Build", depends_on: "API",
+    }"#;
+
+    #[test]
+    fn prefers_same_project() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let api = &doc.projects[0].tasks[1];
+        assert_eq!(
+            api.resolved_dependencies(&doc),
+            vec![TaskRef {
+                project: 0,
+                task: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn qualified_and_cross_project() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let deploy = &doc.projects[0].tasks[2];
+        assert_eq!(
+            deploy.resolved_dependencies(&doc),
+            vec![TaskRef {
+                project: 1,
+                task: 1
+            }]
+        );
+        let build = &doc.projects[1].tasks[1];
+        let target = build.resolved_dependencies(&doc)[0];
+        assert_eq!(target.get(&doc).unwrap().title, "API");
+        assert_eq!(target.project(&doc).unwrap().name, "Backend");
+    }
+
+    #[test]
+    fn unresolved_is_empty() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        assert!(
+            doc.projects[0].tasks[3]
+                .resolved_dependencies(&doc)
+                .is_empty()
+        );
+        assert!(
+            doc.projects[0].tasks[0]
+                .resolved_dependencies(&doc)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn table_matches_resolve() {
+        let doc = ToDoParser::parse_document(
+            r##"project "Backend" {
+                todo: "Setup DB", id: db,
+                todo: "API", depends_on: "Setup DB",
+                todo: "Deploy", depends_on: "Frontend/Build",
+            }
+            project "Frontend" {
+                todo: "Setup DB",
+                todo: "Build", depends_on: "Setup DB",
+                todo: "Ship", depends_on: "#db",
+                todo: "Docs", depends_on: "API",
+                todo: "Typo", depends_on: "Frontend/Nope",
+            }"##,
+        )
+        .unwrap();
+        let deps = doc.dependencies();
+        for r in doc.task_refs() {
+            let reference = r.get(&doc).unwrap().depends_on.as_deref();
+            let expected = reference.and_then(|d| doc.resolve(d, Some(r.project)));
+            assert_eq!(deps.of(r), expected, "{:?}", r);
+        }
+        let build = TaskRef {
+            project: 1,
+            task: 1,
+        };
+        assert_eq!(
+            deps.of(build),
+            Some(TaskRef {
+                project: 1,
+                task: 0
+            })
+        );
+        // Handles, unlike borrowed tasks, keep working on a copy.
+        assert_eq!(doc.clone().dependencies(), deps);
+    }
+
+    #[test]
+    fn actionable_waits_for_open_dependencies() {
+        let doc = ToDoParser::parse_document(
+            r#"project "P" {
+                done: "Design",
+                todo: "Build", depends_on: "Design", due: 2025-12-01,
+                todo: "Test", depends_on: "Build", @high,
+                todo: "Docs", due: 2025-11-01,
+                todo: "Ship", depends_on: "Missing", @low,
+            }"#,
+        )
+        .unwrap();
+        let titles: Vec<_> = doc
+            .actionable()
+            .into_iter()
+            .map(|(_, t)| t.title.as_str())
+            .collect();
+        assert_eq!(titles, ["Ship", "Docs", "Build"]);
+    }
+
+    #[test]
+    fn cycles_list_every_task_once() {
+        let doc = ToDoParser::parse_document(
+            r#"project "A" {
+                todo: "Lead-in", depends_on: "Two",
+                todo: "One", depends_on: "B/Three",
+                todo: "Two", depends_on: "One",
+                todo: "Self", depends_on: "Self",
+            }
+            project "B" {
+                todo: "Three", depends_on: "A/Two",
+            }"#,
+        )
+        .unwrap();
+        let cycles: Vec<Vec<&str>> = doc
+            .dependency_cycles()
+            .iter()
+            .map(|c| {
+                c.iter()
+                    .map(|r| r.get(&doc).unwrap().title.as_str())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(cycles, [vec!["One", "Three", "Two"], vec!["Self"]]);
+        assert!(
+            ToDoParser::parse_document(INPUT)
+                .unwrap()
+                .dependency_cycles()
+                .is_empty()
+        );
+    }
+}
+
+mod validate_tests {
+    use super::*;
+    use to_do_parcer::diagnostic::Severity;
+    use to_do_parcer::validate::{
+        TagStyle, ValidateOptions, parse_roster, validate, validate_with,
+    };
+
+    #[test]
+    fn clean_document_has_no_issues() {
+        let doc = ToDoParser::parse_document(r#"project "P" { todo: "A", @tag: "x", }"#).unwrap();
+        assert!(validate(&doc).is_empty());
+    }
+
+    #[test]
+    fn issues_have_lines_and_codes() {
+        let doc = ToDoParser::parse_document(
+            r#"project "Empty" {}
+            project "P" {
+                todo: "A", @tag: "x", @tag: "x",
+            }"#,
+        )
+        .unwrap();
+        let issues = validate(&doc);
+        let codes: Vec<_> = issues.iter().map(|i| (i.code, i.line)).collect();
+        assert_eq!(codes, vec![("empty-project", 1), ("duplicate-tag", 3)]);
+        assert!(issues.iter().all(|i| i.severity == Severity::Warning));
+        assert_eq!(
+            issues[0].to_string(),
+            r#"1: warning[empty-project]: project "Empty" has no tasks"#
+        );
+    }
+
+    #[test]
+    fn unknown_dependencies_are_errors_with_span() {
+        let input = "project \"P\" {\n  todo: \"A\", id: a,\n  todo: \"B\", depends_on: \"#a\",\n  todo: \"C\", depends_on: \"P/Nope\",\n}";
+        let doc = ToDoParser::parse_document(input).unwrap();
+        let issues = validate(&doc);
+        assert_eq!(issues.len(), 1);
+        let issue = &issues[0];
+        assert_eq!(
+            (issue.code, issue.severity),
+            ("unknown-dependency", Severity::Error)
+        );
+        let span = issue.span.unwrap();
+        assert_eq!(&input[span.start..span.end], "depends_on: \"P/Nope\"");
+        assert_eq!(
+            issue.to_string(),
+            r#"4:14: error[unknown-dependency]: task "C" depends on unknown task "P/Nope""#
+        );
+
+        let options = ValidateOptions {
+            lenient: true,
+            ..ValidateOptions::default()
+        };
+        let lenient = validate_with(&doc, &options);
+        assert_eq!(lenient[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn unknown_dependencies_suggest_closest_task() {
+        let doc = ToDoParser::parse_document(
+            r#"project "Backend" {
+                todo: "Setup DB",
+                todo: "API", depends_on: "Setup DBB",
+                todo: "Deploy", depends_on: "Frontend/Bild",
+                todo: "Docs", depends_on: "Something else",
+            }
+            project "Frontend" {
+                todo: "Build",
+            }"#,
+        )
+        .unwrap();
+        let messages: Vec<_> = validate(&doc).into_iter().map(|i| i.message).collect();
+        assert_eq!(
+            messages,
+            [
+                r#"task "API" depends on unknown task "Setup DBB", did you mean "Setup DB"?"#,
+                r#"task "Deploy" depends on unknown task "Frontend/Bild", did you mean "Frontend/Build"?"#,
+                r#"task "Docs" depends on unknown task "Something else""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicate_titles_within_a_project() {
+        let doc = ToDoParser::parse_document(
+            "project \"P\" {\n  todo: \"A\",\n  done: \"A\",\n}\nproject \"Q\" {\n  todo: \"A\",\n}",
+        )
+        .unwrap();
+        let issues = validate(&doc);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(
+            issues[0].to_string(),
+            r#"3: warning[duplicate-title]: project "P" already has a task "A" on line 2"#
+        );
+
+        let options = ValidateOptions {
+            strict: true,
+            ..ValidateOptions::default()
+        };
+        assert_eq!(validate_with(&doc, &options)[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn done_task_with_open_dependency() {
+        let doc = ToDoParser::parse_document(
+            "project \"P\" {\n  todo: \"A\",\n  done: \"B\", depends_on: \"A\",\n  done: \"C\", depends_on: \"B\",\n  todo: \"D\", depends_on: \"A\",\n}",
+        )
+        .unwrap();
+        let issues = validate(&doc);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(
+            issues[0].to_string(),
+            r#"3:14: warning[done-before-dependency]: task "B" is done but depends on "A", which is not"#
+        );
+    }
+
+    #[test]
+    fn overdue_check_is_opt_in() {
+        let doc = ToDoParser::parse_document(
+            r#"project "P" { todo: "A", due: 2025-01-31, done: "B", due: 2025-01-01, }"#,
+        )
+        .unwrap();
+        assert!(validate(&doc).is_empty());
+
+        let options = ValidateOptions {
+            today: NaiveDate::from_ymd_opt(2025, 2, 1),
+            ..ValidateOptions::default()
+        };
+        let issues = validate_with(&doc, &options);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(
+            issues[0].to_string(),
+            r#"1: warning[overdue]: task "A" was due 2025-01-31 (1 day ago)"#
+        );
+    }
+
+    #[test]
+    fn dependency_cycles_are_errors() {
+        let doc = ToDoParser::parse_document(
+            "project \"P\" {\n  todo: \"A\", depends_on: \"B\",\n  todo: \"B\", depends_on: \"A\",\n}",
+        )
+        .unwrap();
+        let issues = validate(&doc);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(
+            issues[0].to_string(),
+            r#"2:14: error[dependency-cycle]: dependency cycle: "A" -> "B" -> "A""#
+        );
+    }
+
+    #[test]
+    fn spans_point_into_source() {
+        let input = "project \"P\" {\n  todo: \"A\", @high,\n}";
+        let doc = ToDoParser::parse_document(input).unwrap();
+        let task = &doc.projects[0].tasks[0];
+        assert_eq!((task.span.line, task.span.column), (2, 3));
+        assert_eq!(
+            &input[task.span.start..task.span.end],
+            "todo: \"A\", @high,"
+        );
+        assert_eq!(doc.projects[0].span.end, input.len());
+    }
+
+    #[test]
+    fn spans_on_a_long_line() {
+        let input = format!(
+            "project \"P\" {{\n  todo: \"A\",\n  todo: \"é\"{}, depends_on: \"A\",\n  done: \"C\",\n}}",
+            ", @tag: \"t\"".repeat(500)
+        );
+        let doc = ToDoParser::parse_document(&input).unwrap();
+        let tasks = &doc.projects[0].tasks;
+        assert_eq!(tasks[1].tags.len(), 500);
+        let span = tasks[1].depends_on_span.unwrap();
+        // Columns count characters: `é` is two bytes but one column.
+        assert_eq!((span.line, span.column), (3, 5514));
+        assert_eq!(&input[span.start..span.end], "depends_on: \"A\"");
+        assert_eq!((tasks[2].span.line, tasks[2].span.column), (4, 3));
+    }
+
+    #[test]
+    fn due_before_start() {
+        let doc = ToDoParser::parse_document(
+            "project \"P\" {\n  todo: \"A\", start: 2025-03-01,\n    due: 2025-02-01,\n  todo: \"B\", start: 2025-02-01, due: 2025-02-01,\n}",
+        )
+        .unwrap();
+        let issues: Vec<_> = validate(&doc).iter().map(ToString::to_string).collect();
+        assert_eq!(
+            issues,
+            [
+                r#"3:5: error[due-before-start]: task "A" is due 2025-02-01 (3:5) before it starts 2025-03-01 (2:14)"#
+            ]
+        );
+    }
+
+    #[test]
+    fn tag_naming_rules() {
+        let doc = ToDoParser::parse_document(
+            "project \"P\" {\n  todo: \"A\", @tag: \"Front End\", @tag: \"backend\",\n  todo: \"B\", @tag: \"bakend\",\n}",
+        )
+        .unwrap();
+        assert!(validate(&doc).is_empty());
+
+        let style = ValidateOptions {
+            tag_style: Some(TagStyle::KebabCase),
+            ..ValidateOptions::default()
+        };
+        let issues: Vec<_> = validate_with(&doc, &style)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                r#"2: error[tag-name]: tag "Front End" on task "A" is not kebab-case, write it as "front-end""#
+            ]
+        );
+
+        let allowed = ValidateOptions {
+            allowed_tags: vec!["backend".to_string(), "Front End".to_string()],
+            ..ValidateOptions::default()
+        };
+        let issues: Vec<_> = validate_with(&doc, &allowed)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                r#"3: error[tag-name]: tag "bakend" on task "B" is not an allowed tag, did you mean "backend"?"#
+            ]
+        );
+
+        assert_eq!("Snake_Case".parse(), Ok(TagStyle::SnakeCase));
+        assert_eq!(
+            "camel".parse::<TagStyle>().unwrap_err(),
+            "unknown tag style 'camel', expected one of: kebab-case, snake_case, lowercase"
+        );
+    }
+
+    #[test]
+    fn empty_and_reserved_names() {
+        let doc = ToDoParser::parse_document(
+            "project \"\" {\n  todo: \"  \",\n  todo: \"Done\",\n  todo: \"Done with it\",\n}\nproject \"Todo:\" { todo: \"A\", }",
+        )
+        .unwrap();
+        let issues: Vec<_> = validate(&doc).iter().map(ToString::to_string).collect();
+        assert_eq!(
+            issues,
+            [
+                r#"1: error[invalid-name]: project name "" is empty"#,
+                r#"2: error[invalid-name]: title "  " of a task in project "" is blank"#,
+                r#"3: error[invalid-name]: title "Done" of a task in project "" is a reserved keyword"#,
+                r#"6: error[invalid-name]: project name "Todo:" is a reserved keyword"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn assignee_roster() {
+        let doc = ToDoParser::parse_document(
+            "project \"P\" {\n  todo: \"A\", assign: @tanya,\n  todo: \"B\", assign: @tnya,\n  todo: \"C\",\n}",
+        )
+        .unwrap();
+        assert!(validate(&doc).is_empty());
+
+        let options = ValidateOptions {
+            assignees: parse_roster("# core team\n@tanya\noleksii\n"),
+            ..ValidateOptions::default()
+        };
+        let issues: Vec<_> = validate_with(&doc, &options)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                r#"3: error[unknown-assignee]: task "B" is assigned to @tnya, who is not on the roster, did you mean @tanya?"#
+            ]
+        );
+    }
+}
+
+mod pretty_tests {
+    use super::*;
+    use to_do_parcer::pretty::{format_document, format_task};
+
+    const MESSY: &str = r#"// Header
+project "P"{
+      todo: "A", @tag: "x", assign:@bob,@high, // why
+  // before B
+  done:"B",due:2025-01-02,start:2024-12-20,depends_on:"A",
+    // footer
+}
+include "more.todo"
+project "Q" {}
+// end
+"#;
+
+    const TIDY: &str = r#"// Header
+project "P" {
+  todo: "A", @high, assign: @bob, @tag: "x", // why
+  // before B
+  done: "B", start: 2024-12-20, due: 2025-01-02, depends_on: "A",
+  // footer
+}
+
+include "more.todo"
+
+project "Q" {}
+
+// end
+"#;
+
+    fn parse(input: &str) -> to_do_parcer::Document {
+        let options = to_do_parcer::ParseOptions {
+            includes: to_do_parcer::options::IncludePolicy::Ignore,
+            ..Default::default()
+        };
+        ToDoParser::parse_with(input, &options).unwrap()
+    }
+
+    #[test]
+    fn formats_and_keeps_comments() {
+        assert_eq!(format_document(&parse(MESSY)), TIDY);
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        assert_eq!(format_document(&parse(TIDY)), TIDY);
+        let example = std::fs::read_to_string("examples/project.txt").unwrap();
+        let once = format_document(&parse(&example));
+        assert_eq!(format_document(&parse(&once)), once);
+    }
+
+    #[test]
+    fn comment_attachment() {
+        let doc = parse(MESSY);
+        let p = &doc.projects[0];
+        assert_eq!(p.comments, vec!["// Header"]);
+        assert_eq!(p.tasks[0].trailing_comment.as_deref(), Some("// why"));
+        assert_eq!(p.tasks[1].comments, vec!["// before B"]);
+        assert_eq!(p.footer_comments, vec!["// footer"]);
+        assert_eq!(doc.includes[0].position, 1);
+        assert_eq!(doc.trailing_comments, vec!["// end"]);
+    }
+
+    #[test]
+    fn comment_markers_inside_titles_are_text() {
+        let doc = parse(r#"project "P" { todo: "http://example.com", }"#);
+        assert_eq!(doc.projects[0].tasks[0].title, "http://example.com");
+        assert!(doc.projects[0].tasks[0].trailing_comment.is_none());
+    }
+
+    #[test]
+    fn version_two_spelling() {
+        let doc = parse("version 2\nproject \"P\" { todo: \"A\", tag: \"x\", }");
+        assert_eq!(
+            format_task(&doc.projects[0].tasks[0], 2),
+            r#"todo: "A", tag: "x","#
+        );
+        assert!(format_document(&doc).starts_with("version 2\n\n"));
+    }
+}
+
+mod stats_tests {
+    use super::*;
+    use to_do_parcer::stats::Stats;
+
+    const INPUT: &str = r#"
+        project "A" {
+            todo: "One", @high, due: 2025-01-01, assign: @bob, @tag: "x",
+            done: "Two", @high, assign: @bob,
+            todo: "Three", due: 2030-01-01, @tag: "x", @tag: "y",
+        }
+        project "B" {
+            done: "Four", @low, assign: @ann, due: 2020-01-01,
+        }
+    "#;
+
+    fn compute(input: &str) -> Stats {
+        let doc = ToDoParser::parse_document(input).unwrap();
+        Stats::compute(&doc, &NaiveDate::from_ymd_opt(2025, 6, 1).unwrap())
+    }
+
+    #[test]
+    fn totals_and_rate() {
+        let stats = compute(INPUT);
+        assert_eq!(stats.projects, 2);
+        assert_eq!(stats.total, 4);
+        assert_eq!(stats.active, 2);
+        assert_eq!(stats.completed, 2);
+        assert_eq!(stats.completion_rate, 0.5);
+        assert_eq!(stats.overdue, 1);
+    }
+
+    #[test]
+    fn per_field_counts() {
+        let stats = compute(INPUT);
+        assert_eq!(stats.by_priority["high"], 2);
+        assert_eq!(stats.by_priority["medium"], 0);
+        assert_eq!(stats.by_priority["low"], 1);
+        assert_eq!(stats.by_priority["none"], 1);
+        assert_eq!(stats.by_assignee["bob"], 2);
+        assert_eq!(stats.by_assignee["ann"], 1);
+        assert_eq!(stats.by_assignee.len(), 2);
+        assert_eq!(stats.by_tag["x"], 2);
+        assert_eq!(stats.by_tag["y"], 1);
+    }
+
+    #[test]
+    fn empty_project_has_zero_rate() {
+        let stats = compute(r#"project "Empty" {}"#);
+        assert_eq!(stats.total, 0);
+        assert_eq!(stats.completion_rate, 0.0);
+    }
+}
+
+mod export_tests {
+    use super::*;
+    use to_do_parcer::export::{
+        ExportFormat, export, to_csv, to_jira, to_json, to_markdown, to_org, to_taskwarrior,
+        to_yaml,
+    };
+
+    const INPUT: &str = r#"project "P" {
+  todo: "A", @high, due: 2025-01-01, assign: @bob, @tag: "x",
+  done: "B", depends_on: "A",
+}"#;
+
+    fn json(input: &str) -> serde_json::Value {
+        let doc = ToDoParser::parse_document(input).unwrap();
+        serde_json::from_str(&to_json(&doc)).unwrap()
+    }
+
+    #[test]
+    fn json_layout() {
+        let value = json(INPUT);
+        assert_eq!(value["version"], 1);
+        let project = &value["projects"][0];
+        assert_eq!(project["name"], "P");
+        assert!(project["file"].is_null());
+        assert_eq!(project["line"], 1);
+
+        let a = &project["tasks"][0];
+        assert_eq!(a["status"], "todo");
+        assert_eq!(a["title"], "A");
+        assert_eq!(a["priority"], "high");
+        assert_eq!(a["due"], "2025-01-01");
+        assert_eq!(a["assignee"], "bob");
+        assert_eq!(a["tags"], serde_json::json!(["x"]));
+        assert_eq!(a["line"], 2);
+
+        let b = &project["tasks"][1];
+        assert_eq!(b["status"], "done");
+        assert!(b["priority"].is_null());
+        assert_eq!(b["depends_on"], "A");
+    }
+
+    #[test]
+    fn yaml_matches_json() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let yaml: serde_json::Value = serde_yaml_ng::from_str(&to_yaml(&doc)).unwrap();
+        assert_eq!(yaml, json(INPUT));
+        assert!(to_yaml(&doc).contains("  - status: todo\n    title: A\n"));
+    }
+
+    #[test]
+    fn csv_rows() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        assert_eq!(
+            to_csv(&doc),
+            "project,title,status,priority,due,assignee,tags\n\
+             P,A,todo,high,2025-01-01,bob,x\n\
+             P,B,done,,,,\n"
+        );
+    }
+
+    #[test]
+    fn csv_quotes_and_empty_documents() {
+        let doc = ToDoParser::parse_document(
+            r#"project "P, Q" { todo: "Say hi", @tag: "a", @tag: "b", }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            to_csv(&doc).lines().nth(1),
+            Some(r#""P, Q",Say hi,todo,,,,a;b"#)
+        );
+
+        let empty = ToDoParser::parse_document(r#"project "P" {}"#).unwrap();
+        assert_eq!(
+            to_csv(&empty),
+            "project,title,status,priority,due,assignee,tags\n"
+        );
+    }
+
+    #[test]
+    fn markdown_checklist() {
+        let doc =
+            ToDoParser::parse_document(&format!("{}\nproject \"Empty\" {{}}", INPUT)).unwrap();
+        assert_eq!(
+            to_markdown(&doc),
+            "## P\n\n\
+             - [ ] A — priority: high; due: 2025-01-01; assignee: bob; tags: x\n\
+             - [x] B — depends on: A\n\
+             \n## Empty\n"
+        );
+    }
+
+    #[test]
+    fn markdown_escapes_inline_syntax() {
+        let doc =
+            ToDoParser::parse_document(r#"project "P" { todo: "Fix *all* [bugs]", }"#).unwrap();
+        assert!(to_markdown(&doc).contains(r"- [ ] Fix \*all\* \[bugs\]"));
+    }
+
+    #[test]
+    fn org_outline() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        assert_eq!(
+            to_org(&doc),
+            "* P\n\
+             ** TODO [#A] A :x:\n\
+             DEADLINE: <2025-01-01 Wed>\n\
+             :PROPERTIES:\n\
+             :ASSIGNEE: bob\n\
+             :END:\n\
+             ** DONE B\n\
+             :PROPERTIES:\n\
+             :DEPENDS_ON: A\n\
+             :END:\n"
+        );
+    }
+
+    #[test]
+    fn org_tags_are_sanitized() {
+        let doc = ToDoParser::parse_document(r#"project "P" { todo: "A", @tag: "needs review", }"#)
+            .unwrap();
+        assert_eq!(to_org(&doc), "* P\n** TODO A :needs_review:\n");
+    }
+
+    #[test]
+    fn jira_csv() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        assert_eq!(
+            to_jira(&doc),
+            "Issue Id,Summary,Issue Type,Status,Priority,Due Date,Assignee,Component,Labels,Inward issue link (Blocks)\n\
+             1,A,Task,To Do,High,2025-01-01,bob,P,x,\n\
+             2,B,Task,Done,,,,P,,1\n"
+        );
+    }
+
+    #[test]
+    fn jira_labels_get_a_column_each() {
+        let doc = ToDoParser::parse_document(
+            r#"project "P" { todo: "A", @tag: "needs review", @tag: "ui", todo: "B", }"#,
+        )
+        .unwrap();
+        let jira = to_jira(&doc);
+        let lines: Vec<&str> = jira.lines().collect();
+        assert!(lines[0].ends_with(",Component,Labels,Labels,Inward issue link (Blocks)"));
+        assert_eq!(lines[1], "1,A,Task,To Do,,,,P,needs_review,ui,");
+        assert_eq!(lines[2], "2,B,Task,To Do,,,,P,,,");
+    }
+
+    #[test]
+    fn taskwarrior_json() {
+        let doc = ToDoParser::parse_document(
+            r#"project "P" {
+  todo: "A", @high, due: 2025-01-01, assign: @bob, @tag: "needs review",
+  done: "B", depends_on: "A", completed: 2025-01-02,
+}"#,
+        )
+        .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&to_taskwarrior(&doc)).unwrap();
+        let a = &json[0];
+        assert_eq!(a["description"], "A");
+        assert_eq!(a["status"], "pending");
+        assert_eq!(a["project"], "P");
+        assert_eq!(a["priority"], "H");
+        assert_eq!(a["due"], "20250101T000000Z");
+        assert_eq!(a["tags"], serde_json::json!(["needs_review"]));
+        assert!(a.get("assignee").is_none());
+
+        let b = &json[1];
+        assert_eq!(b["status"], "completed");
+        assert_eq!(b["end"], "20250102T000000Z");
+        assert!(b.get("priority").is_none());
+        assert_eq!(b["depends"][0], a["uuid"]);
+    }
+
+    #[test]
+    fn taskwarrior_uuids_are_stable() {
+        let uuid = |source: &str| {
+            let doc = ToDoParser::parse_document(source).unwrap();
+            let json: serde_json::Value = serde_json::from_str(&to_taskwarrior(&doc)).unwrap();
+            json[0]["uuid"].as_str().unwrap().to_string()
+        };
+        let first = uuid(r#"project "P" { todo: "A", }"#);
+        assert_eq!(first, uuid(r#"project "P" { done: "A", @low, }"#));
+        assert_ne!(first, uuid(r#"project "Q" { todo: "A", }"#));
+        assert_ne!(
+            uuid(r#"project "PA" { todo: "", }"#),
+            uuid(r#"project "P" { todo: "A", }"#)
+        );
+
+        let parts: Vec<&str> = first.split('-').collect();
+        assert_eq!(
+            parts.iter().map(|p| p.len()).collect::<Vec<_>>(),
+            [8, 4, 4, 4, 12]
+        );
+        assert!(parts[2].starts_with('8'));
+        assert!(matches!(
+            parts[3].chars().next(),
+            Some('8' | '9' | 'a' | 'b')
+        ));
+    }
+
+    #[test]
+    fn format_names() {
+        assert_eq!("json".parse::<ExportFormat>(), Ok(ExportFormat::Json));
+        assert_eq!(
+            "taskwarrior".parse::<ExportFormat>(),
+            Ok(ExportFormat::Taskwarrior)
+        );
+        assert_eq!("jira".parse::<ExportFormat>(), Ok(ExportFormat::Jira));
+        assert_eq!("org".parse::<ExportFormat>(), Ok(ExportFormat::Org));
+        assert_eq!("yaml".parse::<ExportFormat>(), Ok(ExportFormat::Yaml));
+        assert_eq!(
+            "markdown".parse::<ExportFormat>(),
+            Ok(ExportFormat::Markdown)
+        );
+        assert_eq!("JSON".parse::<ExportFormat>(), Ok(ExportFormat::Json));
+        assert!("xml".parse::<ExportFormat>().is_err());
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        assert_eq!(export(&doc, ExportFormat::Json), to_json(&doc));
+    }
+}
+
+mod import_tests {
+    use super::*;
+    use to_do_parcer::export::{to_csv, to_markdown, to_org};
+    use to_do_parcer::import::{
+        CsvField, CsvMapping, ImportFormat, from_csv, from_markdown, from_org, from_trello, import,
+    };
+    use to_do_parcer::pretty::format_document;
+
+    #[test]
+    fn headings_and_checkboxes() {
+        let doc = from_markdown(
+            "# Notes\n\nSome prose.\n\n## Work\n\n- [ ] Write report\n  * [X] Nested item\n- plain bullet\n+ [x] Ship it\n",
+        );
+        assert_eq!(doc.projects.len(), 1);
+        let work = &doc.projects[0];
+        assert_eq!(work.name, "Work");
+        let titles: Vec<_> = work.tasks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, ["Write report", "Nested item", "Ship it"]);
+        assert_eq!(work.tasks[0].status, TaskStatus::Todo);
+        assert_eq!(work.tasks[1].status, TaskStatus::Done);
+    }
+
+    #[test]
+    fn tasks_before_heading_go_to_inbox() {
+        let doc = from_markdown("- [ ] Loose end\n");
+        assert_eq!(doc.projects[0].name, "Inbox");
+    }
+
+    #[test]
+    fn round_trips_markdown_export() {
+        let source = std::fs::read_to_string("examples/project.txt").unwrap();
+        let original = ToDoParser::parse_document(&source).unwrap();
+        let imported = from_markdown(&to_markdown(&original));
+        assert_eq!(format_document(&imported), format_document(&original));
+    }
+
+    #[test]
+    fn unknown_details_stay_in_title() {
+        let doc = from_markdown("- [ ] Read — chapter: 3\n- [ ] Call — priority: urgent\n");
+        let tasks = &doc.projects[0].tasks;
+        assert_eq!(tasks[0].title, "Read — chapter: 3");
+        assert_eq!(tasks[1].title, "Call — priority: urgent");
+        assert!(tasks[1].priority.is_none());
+    }
+
+    #[test]
+    fn double_quotes_are_replaced_with_warning() {
+        let doc = from_markdown("# P\n- [ ] Say \"hi\"\n");
+        assert_eq!(doc.projects[0].tasks[0].title, "Say 'hi'");
+        assert_eq!(doc.warnings.len(), 1);
+        assert_eq!(doc.warnings[0].line, 2);
+        let text = format_document(&doc);
+        assert!(ToDoParser::parse_document(&text).is_ok());
+    }
+
+    #[test]
+    fn format_names() {
+        assert_eq!(
+            "Markdown".parse::<ImportFormat>(),
+            Ok(ImportFormat::Markdown)
+        );
+        assert_eq!("org".parse::<ImportFormat>(), Ok(ImportFormat::Org));
+        assert_eq!("CSV".parse::<ImportFormat>(), Ok(ImportFormat::Csv));
+        assert!("docx".parse::<ImportFormat>().is_err());
+    }
+
+    #[test]
+    fn org_tree() {
+        let doc = from_org(
+            "#+TITLE: Plans\n\
+             * Home\n\
+             Some notes.\n\
+             ** TODO [#B] Fix sink :plumbing:urgent:\n\
+             \x20  SCHEDULED: <2025-01-02 Thu> DEADLINE: <2025-01-05 Sun>\n\
+             \x20  :PROPERTIES:\n\
+             \x20  :ASSIGNEE: ann\n\
+             \x20  :END:\n\
+             *** DONE Buy washer\n\
+             * Someday\n\
+             ** Ideas\n\
+             *** TODO Learn Rust\n",
+        );
+        let names: Vec<_> = doc.projects.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Home", "Ideas"]);
+
+        let sink = &doc.projects[0].tasks[0];
+        assert_eq!(sink.title, "Fix sink");
+        assert_eq!(sink.priority, Some(Priority::Medium));
+        assert_eq!(sink.tags, vec!["plumbing", "urgent"]);
+        assert_eq!(sink.due_date.as_deref(), Some("2025-01-05"));
+        assert_eq!(sink.assignee.as_deref(), Some("ann"));
+
+        let washer = &doc.projects[0].tasks[1];
+        assert_eq!(washer.status, TaskStatus::Done);
+        assert!(washer.due_date.is_none());
+    }
+
+    #[test]
+    fn round_trips_org_export() {
+        let source = std::fs::read_to_string("examples/project.txt").unwrap();
+        let original = ToDoParser::parse_document(&source).unwrap();
+        let imported = from_org(&to_org(&original));
+        assert_eq!(format_document(&imported), format_document(&original));
+    }
+
+    const BOARD: &str = r#"{
+  "name": "Launch",
+  "lists": [
+    {"id": "l2", "name": "Doing", "closed": false, "pos": 2},
+    {"id": "l1", "name": "Backlog", "closed": false, "pos": 1},
+    {"id": "l3", "name": "Old", "closed": true, "pos": 3}
+  ],
+  "cards": [
+    {"id": "c1", "name": "Write \"copy\"", "idList": "l1", "pos": 2,
+     "due": "2025-11-15T12:00:00.000Z", "dueComplete": false,
+     "labels": [{"name": "web", "color": "green"}, {"name": "", "color": "red"}],
+     "idMembers": ["m1", "m2"]},
+    {"id": "c2", "name": "Pick name", "idList": "l1", "pos": 1, "dueComplete": true},
+    {"id": "c3", "name": "Archived", "idList": "l1", "closed": true},
+    {"id": "c4", "name": "Hidden", "idList": "l3"},
+    {"id": "c5", "name": "Deploy", "idList": "l2", "idMembers": ["m2"]}
+  ],
+  "members": [
+    {"id": "m1", "username": "amy"},
+    {"id": "m2", "username": "bob"}
+  ]
+}"#;
+
+    #[test]
+    fn trello_lists_and_cards() {
+        let doc = from_trello(BOARD);
+        assert_eq!(
+            format_document(&doc),
+            "project \"Backlog\" {\n  done: \"Pick name\",\n  todo: \"Write 'copy'\", due: 2025-11-15, assign: @amy, @tag: \"web\", @tag: \"red\",\n}\n\nproject \"Doing\" {\n  todo: \"Deploy\", assign: @bob,\n}\n"
+        );
+        let messages: Vec<String> = doc.warnings.iter().map(|w| w.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Line 9: replaced '\"' with '\\'' in \"Write \"copy\"\"",
+                "Line 9: \"Write 'copy'\" has 2 members; kept only @amy",
+            ]
+        );
+    }
+
+    #[test]
+    fn trello_rejects_other_json() {
+        let doc = import("[1, 2", ImportFormat::Trello);
+        assert!(doc.projects.is_empty());
+        assert!(
+            doc.warnings[0]
+                .message
+                .starts_with("not a Trello board export")
+        );
+    }
+
+    #[test]
+    fn round_trips_csv_export() {
+        let source = std::fs::read_to_string("examples/project.txt").unwrap();
+        let original = ToDoParser::parse_document(&source).unwrap();
+        let imported = import(&to_csv(&original), ImportFormat::Csv);
+        assert!(imported.warnings.is_empty(), "{:?}", imported.warnings);
+        assert_eq!(to_csv(&imported), to_csv(&original));
+    }
+
+    #[test]
+    fn csv_columns_are_mapped() {
+        let input = "Summary,Team,State,Prio,Due Date,Owner,Labels\n\
+                     Fix login,Web,Done,High,2025-03-01,@ann,\"bug, ui\"\n\
+                     Write docs,,open,,,,\n\
+                     Deploy,Web,,urgent,March,a b,\n";
+        let mut mapping = CsvMapping::default();
+        mapping
+            .bind(CsvField::Title, "summary")
+            .bind(CsvField::Project, "Team")
+            .bind(CsvField::Status, "State")
+            .bind(CsvField::Priority, "Prio")
+            .bind(CsvField::Due, "Due Date")
+            .bind(CsvField::Assignee, "Owner")
+            .bind(CsvField::Tags, "Labels");
+        let doc = from_csv(input, &mapping);
+
+        let names: Vec<_> = doc.projects.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Web", "Inbox"]);
+        let fix = &doc.projects[0].tasks[0];
+        assert_eq!(fix.status, TaskStatus::Done);
+        assert_eq!(fix.priority, Some(Priority::High));
+        assert_eq!(fix.due_date.as_deref(), Some("2025-03-01"));
+        assert_eq!(fix.assignee.as_deref(), Some("ann"));
+        assert_eq!(fix.tags, ["bug", "ui"]);
+
+        let deploy = &doc.projects[0].tasks[1];
+        assert_eq!(deploy.title, "Deploy");
+        assert_eq!((deploy.priority, &deploy.due_date), (None, &None));
+        let messages: Vec<_> = doc.warnings.iter().map(|w| (w.line, &*w.message)).collect();
+        assert_eq!(
+            messages,
+            [
+                (4, "dropped priority \"urgent\" of \"Deploy\""),
+                (4, "dropped due \"March\" of \"Deploy\""),
+                (4, "dropped assignee \"a b\" of \"Deploy\""),
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_without_title_column() {
+        let mut mapping = CsvMapping::default();
+        mapping.bind(CsvField::Title, "Name");
+        let doc = from_csv("title\nA\n", &mapping);
+        assert!(doc.projects.is_empty());
+        assert_eq!(doc.warnings[0].message, "no column \"Name\" for title");
+    }
+
+    #[test]
+    fn csv_field_names() {
+        assert_eq!("depends_on".parse::<CsvField>(), Ok(CsvField::DependsOn));
+        assert_eq!(
+            "owner".parse::<CsvField>().unwrap_err(),
+            "unknown field 'owner', expected one of: project, title, status, id, priority, \
+             start, due, completed, assignee, depends_on, tags"
+        );
+    }
+}
+
+mod query_tests {
+    use super::*;
+    use to_do_parcer::query::Filter;
+    use to_do_parcer::table::format_table;
+
+    const INPUT: &str = r#"
+        project "A" {
+            todo: "One", @high, assign: @bob, @tag: "bug", @tag: "ui",
+            done: "Two", @high, assign: @bob, @tag: "bug",
+            todo: "Three", @low, @tag: "ui",
+        }
+        project "B" {
+            todo: "Four", assign: @ann,
+        }
+    "#;
+
+    fn titles(doc: &to_do_parcer::Document, filter: &Filter) -> Vec<String> {
+        doc.matching(|t| filter.matches(t))
+            .iter()
+            .map(|(_, t)| t.title.clone())
+            .collect()
+    }
+
+    #[test]
+    fn default_matches_everything() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        assert_eq!(titles(&doc, &Filter::default()).len(), 4);
+    }
+
+    #[test]
+    fn fields_combine_with_and() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let filter = Filter {
+            status: Some(TaskStatus::Todo),
+            priority: Some(Priority::High),
+            tags: vec!["bug".to_string()],
+            assignee: Some("bob".to_string()),
+        };
+        assert_eq!(titles(&doc, &filter), ["One"]);
+
+        let both_tags = Filter {
+            tags: vec!["bug".to_string(), "ui".to_string()],
+            ..Filter::default()
+        };
+        assert_eq!(titles(&doc, &both_tags), ["One"]);
+    }
+
+    #[test]
+    fn select_drops_empty_projects() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let selected = doc.select(|t| t.assignee.as_deref() == Some("ann"));
+        assert_eq!(selected.projects.len(), 1);
+        assert_eq!(selected.projects[0].name, "B");
+    }
+
+    #[test]
+    fn table_alignment() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let rows = doc.matching(|t| t.priority == Some(Priority::High));
+        assert_eq!(
+            format_table(&rows),
+            "PROJECT  STATUS  TITLE  PRIORITY  DUE  ASSIGNEE  TAGS\n\
+             A        todo    One    high      -    @bob      bug,ui\n\
+             A        done    Two    high      -    @bob      bug\n"
+        );
+    }
+}
+
+mod query_language_tests {
+    use super::*;
+    use to_do_parcer::query::{CompareOp, Query, QueryError};
+
+    const INPUT: &str = r#"
+        project "P" {
+            todo: "Fix login", @high, due: 2025-11-20, assign: @alice, @tag: "bug",
+            todo: "Write docs", @low, due: 2026-01-10, @tag: "urgent",
+            done: "Ship beta", @high, due: 2025-10-01, assign: @bob,
+            todo: "Refactor",
+        }
+    "#;
+
+    fn titles(query: &str) -> Vec<String> {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let query = Query::parse(query).unwrap();
+        doc.matching(|t| query.matches(t))
+            .iter()
+            .map(|(_, t)| t.title.clone())
+            .collect()
+    }
+
+    #[test]
+    fn example_from_request() {
+        assert_eq!(
+            titles(r#"status == todo && (priority == high || tag:"urgent") && due < 2025-12-01"#),
+            ["Fix login"]
+        );
+    }
+
+    #[test]
+    fn precedence_and_negation() {
+        assert_eq!(
+            titles("priority == low || priority == high && status == done"),
+            ["Write docs", "Ship beta"]
+        );
+        assert_eq!(titles("!(status == todo)"), ["Ship beta"]);
+        assert_eq!(titles("!!(status == done)"), ["Ship beta"]);
+    }
+
+    #[test]
+    fn priority_ordering_and_none() {
+        assert_eq!(titles("priority >= medium"), ["Fix login", "Ship beta"]);
+        assert_eq!(titles("priority < low"), ["Refactor"]);
+        assert_eq!(titles("priority == none"), ["Refactor"]);
+    }
+
+    #[test]
+    fn due_dates() {
+        assert_eq!(titles("due >= 2025-11-20"), ["Fix login", "Write docs"]);
+        assert_eq!(titles("due == none"), ["Refactor"]);
+        assert_eq!(titles("due != none").len(), 3);
+    }
+
+    #[test]
+    fn text_fields() {
+        assert_eq!(titles("assignee == @bob"), ["Ship beta"]);
+        assert_eq!(titles("assignee == none"), ["Write docs", "Refactor"]);
+        assert_eq!(titles(r#"title ~ "LOG""#), ["Fix login"]);
+        assert_eq!(titles(r#"title == "Refactor""#), ["Refactor"]);
+        assert_eq!(titles("tag ~ urg"), ["Write docs"]);
+        assert_eq!(titles(r#"tag != "bug""#).len(), 3);
+    }
+
+    #[test]
+    fn builds_tree() {
+        let query: Query = "status == todo && tag:x".parse().unwrap();
+        assert_eq!(
+            query,
+            Query::And(
+                Box::new(Query::Status(CompareOp::Eq, TaskStatus::Todo)),
+                Box::new(Query::Tag(CompareOp::Eq, "x".to_string())),
+            )
+        );
+    }
+
+    #[test]
+    fn syntax_errors() {
+        for input in [
+            "",
+            "status ==",
+            "status == todo &&",
+            "(status == todo",
+            "colour == red",
+        ] {
+            assert!(
+                matches!(Query::parse(input), Err(QueryError::Syntax(_))),
+                "{input:?} should be a syntax error"
+            );
+        }
+    }
+
+    #[test]
+    fn semantic_errors() {
+        for input in [
+            "status < todo",
+            "status == later",
+            "priority == urgent",
+            "due < 2025-13-01",
+            "due < none",
+            "assignee > @bob",
+            "title < \"A\"",
+        ] {
+            assert!(
+                matches!(Query::parse(input), Err(QueryError::Invalid(_))),
+                "{input:?} should be rejected"
+            );
+        }
+    }
+}
+
+mod edit_tests {
+    use super::*;
+    use to_do_parcer::Task;
+    use to_do_parcer::edit::{EditError, Editor, SourceEdit, apply_edits, find_task};
+
+    fn add(source: &str, project: &str, task: Task) -> String {
+        let mut editor = Editor::new(source).unwrap();
+        editor.add_task(project, task).unwrap();
+        let out = editor.finish();
+        ToDoParser::parse_document(&out).expect("edited text parses");
+        out
+    }
+
+    fn todo(title: &str) -> Task {
+        Task::new(TaskStatus::Todo, title)
+    }
+
+    #[test]
+    fn apply_edits_in_any_order() {
+        let edits = [
+            SourceEdit {
+                range: 4..5,
+                replacement: "E".to_string(),
+            },
+            SourceEdit {
+                range: 0..0,
+                replacement: ">".to_string(),
+            },
+        ];
+        assert_eq!(apply_edits("abcde", &edits), ">abcdE");
+    }
+
+    #[test]
+    fn add_keeps_formatting_and_comments() {
+        let source = "// header\nproject \"P\" {\n\ttodo: \"A\",@high, // note\n\t// footer\n}\n";
+        let mut task = todo("B");
+        task.priority = Some(Priority::Low);
+        assert_eq!(
+            add(source, "P", task),
+            "// header\nproject \"P\" {\n\ttodo: \"A\",@high, // note\n\ttodo: \"B\", @low,\n\t// footer\n}\n"
+        );
+    }
+
+    #[test]
+    fn add_to_single_line_project() {
+        assert_eq!(
+            add(r#"project "P" { todo: "A", }"#, "P", todo("B")),
+            r#"project "P" { todo: "A", todo: "B", }"#
+        );
+    }
+
+    #[test]
+    fn add_to_empty_project() {
+        assert_eq!(
+            add("project \"P\" {}\n", "P", todo("A")),
+            "project \"P\" {\n  todo: \"A\",\n}\n"
+        );
+        assert_eq!(
+            add("project \"P\" {\n  // later\n}\n", "P", todo("A")),
+            "project \"P\" {\n  // later\n  todo: \"A\",\n}\n"
+        );
+    }
+
+    #[test]
+    fn add_creates_missing_project() {
+        assert_eq!(
+            add("project \"P\" {}\n", "Q", todo("A")),
+            "project \"P\" {}\n\nproject \"Q\" {\n  todo: \"A\",\n}\n"
+        );
+        assert_eq!(
+            add("", "Q", todo("A")),
+            "project \"Q\" {\n  todo: \"A\",\n}\n"
+        );
+    }
+
+    #[test]
+    fn add_uses_file_version_for_tags() {
+        let mut task = todo("B");
+        task.tags.push("x".to_string());
+        let out = add("version 2\nproject \"P\" {\n  todo: \"A\",\n}\n", "P", task);
+        assert!(out.contains("  todo: \"B\", tag: \"x\",\n"));
+    }
+
+    #[test]
+    fn add_rejects_unrepresentable_values() {
+        let mut editor = Editor::new("project \"P\" {}").unwrap();
+        assert!(matches!(
+            editor.add_task("P", todo("Say \"hi\"")),
+            Err(EditError::Unrepresentable { what: "title", .. })
+        ));
+        let mut task = todo("A");
+        task.assignee = Some("two words".to_string());
+        assert!(editor.add_task("P", task).is_err());
+        assert!(editor.add_task("P\"", todo("A")).is_err());
+    }
+
+    #[test]
+    fn find_task_by_title_and_path() {
+        let doc = ToDoParser::parse_document(
+            r#"project "A" { todo: "X", todo: "Y", } project "B" { todo: "X", }"#,
+        )
+        .unwrap();
+        let y = find_task(&doc, "Y").unwrap();
+        assert_eq!((y.project, y.task), (0, 1));
+        let bx = find_task(&doc, "B/X").unwrap();
+        assert_eq!((bx.project, bx.task), (1, 0));
+        assert!(matches!(
+            find_task(&doc, "X"),
+            Err(EditError::Ambiguous { .. })
+        ));
+        assert!(matches!(
+            find_task(&doc, "Z"),
+            Err(EditError::TaskNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn windows_files_keep_their_line_endings() {
+        let source = "\u{feff}project \"P\" {\r\n  todo: \"A\",\r\n}\r\n";
+        let mut editor = Editor::new(source).unwrap();
+        editor.add_task("P", todo("B")).unwrap();
+        assert_eq!(
+            editor.finish(),
+            "\u{feff}project \"P\" {\r\n  todo: \"A\",\r\n  todo: \"B\",\r\n}\r\n"
+        );
+    }
+}
+
+mod complete_tests {
+    use super::*;
+    use to_do_parcer::edit::{EditError, Editor};
+
+    fn complete(source: &str, selector: &str, date: Option<NaiveDate>) -> String {
+        let mut editor = Editor::new(source).unwrap();
+        assert!(editor.complete(selector, date).unwrap());
+        editor.finish()
+    }
+
+    #[test]
+    fn parses_id_and_completed() {
+        let doc = ToDoParser::parse_document(
+            r#"project "P" { done: "A", id: fix-1, completed: 2025-11-02, }"#,
+        )
+        .unwrap();
+        let task = &doc.projects[0].tasks[0];
+        assert_eq!(task.id.as_deref(), Some("fix-1"));
+        assert_eq!(task.completed.as_deref(), Some("2025-11-02"));
+        assert_eq!(
+            to_do_parcer::pretty::format_task(task, 1),
+            r#"done: "A", id: fix-1, completed: 2025-11-02,"#
+        );
+    }
+
+    #[test]
+    fn flips_keyword_in_place() {
+        let source = "project \"P\" {\n  todo:  \"A\" ,@high,   // why\n  todo: \"B\",\n}\n";
+        assert_eq!(
+            complete(source, "A", None),
+            "project \"P\" {\n  done:  \"A\" ,@high,   // why\n  todo: \"B\",\n}\n"
+        );
+    }
+
+    #[test]
+    fn records_completion_date() {
+        let date = NaiveDate::from_ymd_opt(2025, 11, 2);
+        assert_eq!(
+            complete(r#"project "P" { todo: "A", @high, }"#, "A", date),
+            r#"project "P" { done: "A", @high, completed: 2025-11-02, }"#
+        );
+        assert_eq!(
+            complete(
+                r#"project "P" { todo: "A", completed: 2025-01-01, }"#,
+                "A",
+                date
+            ),
+            r#"project "P" { done: "A", completed: 2025-01-01, }"#
+        );
+    }
+
+    #[test]
+    fn selects_by_id() {
+        let source =
+            r#"project "P" { todo: "Same", id: one, } project "Q" { todo: "Same", id: two, }"#;
+        assert_eq!(
+            complete(source, "#two", None),
+            r#"project "P" { todo: "Same", id: one, } project "Q" { done: "Same", id: two, }"#
+        );
+        let mut editor = Editor::new(source).unwrap();
+        assert!(matches!(
+            editor.complete("Same", None),
+            Err(EditError::Ambiguous { .. })
+        ));
+        assert!(matches!(
+            editor.complete("#three", None),
+            Err(EditError::TaskNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn already_done_is_unchanged() {
+        let source = r#"project "P" { done: "A", }"#;
+        let mut editor = Editor::new(source).unwrap();
+        assert!(!editor.complete("A", None).unwrap());
+        assert_eq!(editor.finish(), source);
+    }
+}
+
+mod remove_tests {
+    use to_do_parcer::edit::{EditError, Editor};
+
+    fn remove(source: &str, selector: &str) -> String {
+        let mut editor = Editor::new(source).unwrap();
+        editor.remove_task(selector, false).unwrap();
+        editor.finish()
+    }
+
+    #[test]
+    fn removes_whole_line_with_trailing_comment() {
+        let source = "project \"P\" {\n  // about A\n  todo: \"A\", // note\n  todo: \"B\",\n}\n";
+        assert_eq!(
+            remove(source, "A"),
+            "project \"P\" {\n  // about A\n  todo: \"B\",\n}\n"
+        );
+    }
+
+    #[test]
+    fn removes_multi_line_task() {
+        let source = "project \"P\" {\n  todo: \"A\",\n    @high,\n  todo: \"B\",\n}\n";
+        assert_eq!(remove(source, "A"), "project \"P\" {\n  todo: \"B\",\n}\n");
+    }
+
+    #[test]
+    fn removes_task_sharing_a_line() {
+        let source = r#"project "P" { todo: "A", todo: "B", }"#;
+        assert_eq!(remove(source, "A"), r#"project "P" { todo: "B", }"#);
+        assert_eq!(remove(source, "B"), r#"project "P" { todo: "A", }"#);
+    }
+
+    #[test]
+    fn dependents_block_removal_unless_forced() {
+        let source = r#"project "P" { todo: "A", todo: "B", depends_on: "A", }"#;
+        let mut editor = Editor::new(source).unwrap();
+        match editor.remove_task("A", false) {
+            Err(EditError::HasDependents { dependents, .. }) => {
+                assert_eq!(dependents, vec!["\"B\""])
+            }
+            other => panic!("expected HasDependents, got {:?}", other),
+        }
+        editor.remove_task("A", true).unwrap();
+        assert_eq!(
+            editor.finish(),
+            r#"project "P" { todo: "B", depends_on: "A", }"#
+        );
+    }
+}
+
+mod move_tests {
+    use to_do_parcer::edit::Editor;
+
+    fn move_task(source: &str, selector: &str, project: &str) -> String {
+        let mut editor = Editor::new(source).unwrap();
+        assert!(editor.move_task(selector, project).unwrap());
+        editor.finish()
+    }
+
+    #[test]
+    fn keeps_text_and_trailing_comment() {
+        let source = "project \"A\" {\n  todo: \"X\",   @high, // soon\n  todo: \"Y\",\n}\n\nproject \"B\" {\n  todo: \"Z\",\n}\n";
+        assert_eq!(
+            move_task(source, "X", "B"),
+            "project \"A\" {\n  todo: \"Y\",\n}\n\nproject \"B\" {\n  todo: \"Z\",\n  todo: \"X\",   @high, // soon\n}\n"
+        );
+    }
+
+    #[test]
+    fn rewrites_references_that_would_break() {
+        let source = "project \"A\" {\n  todo: \"X\",\n  todo: \"Y\", depends_on: \"X\",\n  todo: \"W\", depends_on: \"A/Y\",\n}\n\nproject \"B\" {\n  todo: \"X\",\n}\n";
+        // In B, "X" would find B's own X, and "A/Y" no longer exists.
+        assert_eq!(
+            move_task(source, "A/Y", "B"),
+            "project \"A\" {\n  todo: \"X\",\n  todo: \"W\", depends_on: \"B/Y\",\n}\n\nproject \"B\" {\n  todo: \"X\",\n  todo: \"Y\", depends_on: \"A/X\",\n}\n"
+        );
+    }
+
+    #[test]
+    fn creates_missing_project_and_ignores_same_project() {
+        let source = "project \"A\" {\n  todo: \"X\",\n  todo: \"Y\", depends_on: \"X\",\n}\n";
+        assert_eq!(
+            move_task(source, "Y", "C"),
+            "project \"A\" {\n  todo: \"X\",\n}\n\nproject \"C\" {\n  todo: \"Y\", depends_on: \"X\",\n}\n"
+        );
+
+        let mut editor = Editor::new(source).unwrap();
+        assert!(!editor.move_task("Y", "A").unwrap());
+        assert_eq!(editor.finish(), source);
+    }
+}
+
+mod split_tests {
+    use to_do_parcer::edit::Editor;
+    use to_do_parcer::split::file_name;
+
+    #[test]
+    fn file_names() {
+        assert_eq!(file_name("Backend"), "backend.todo");
+        assert_eq!(file_name("  Été -- 2025 "), "été-2025.todo");
+    }
+
+    #[test]
+    fn splits_every_project() {
+        let source = "version 2\n\nproject \"Ops\" { todo: \"A\", }\n// second\nproject \"ops!\" {\n  todo: \"B\", tag: \"x\",\n}\ninclude \"other.todo\"\n";
+        let mut editor = Editor::new(source).unwrap();
+        let files = editor.split("");
+        let names: Vec<_> = files.iter().map(|f| f.file_name.as_str()).collect();
+        assert_eq!(names, ["ops.todo", "ops-2.todo"]);
+        assert_eq!(files[1].project, "ops!");
+        assert_eq!(
+            files[1].text,
+            "version 2\n\nproject \"ops!\" {\n  todo: \"B\", tag: \"x\",\n}\n"
+        );
+        assert_eq!(
+            editor.finish(),
+            "version 2\n\ninclude \"ops.todo\"\n// second\ninclude \"ops-2.todo\"\ninclude \"other.todo\"\n"
+        );
+    }
+}
+
+mod dedupe_tests {
+    use super::*;
+    use to_do_parcer::dedupe::{find_duplicates, merge_group, similar_titles};
+    use to_do_parcer::edit::Editor;
+
+    #[test]
+    fn similarity() {
+        assert!(similar_titles("Review PR", "review pr."));
+        assert!(similar_titles(
+            "Update the onboarding guide",
+            "Update the onboardng guide"
+        ));
+        assert!(!similar_titles("Call Ann", "Call Dan"));
+        assert!(!similar_titles("Release 1.0", "Release 2.0"));
+    }
+
+    #[test]
+    fn groups_stay_within_projects() {
+        let doc = ToDoParser::parse_document(
+            r#"project "A" { todo: "Deploy", todo: "Test", todo: "deploy!", todo: "DEPLOY", }
+project "B" { todo: "Deploy", }"#,
+        )
+        .unwrap();
+        let groups = find_duplicates(&doc);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].keep.task, 0);
+        let copies: Vec<_> = groups[0].duplicates.iter().map(|r| r.task).collect();
+        assert_eq!(copies, [2, 3]);
+    }
+
+    #[test]
+    fn merges_attributes() {
+        let doc = ToDoParser::parse_document(
+            r#"project "P" {
+  todo: "Ship", @low, due: 2025-03-01, @tag: "a",
+  todo: "ship", @medium, due: 2025-02-01, assign: @ann, @tag: "b", @tag: "a",
+  todo: "Ship.", id: ship, depends_on: "Ship",
+}"#,
+        )
+        .unwrap();
+        let merged = merge_group(&doc, &find_duplicates(&doc)[0]);
+        assert_eq!(merged.title, "Ship");
+        assert_eq!(merged.status, TaskStatus::Todo);
+        assert_eq!(merged.priority, Some(Priority::Medium));
+        assert_eq!(merged.due_date.as_deref(), Some("2025-02-01"));
+        assert_eq!(merged.assignee.as_deref(), Some("ann"));
+        assert_eq!(merged.id.as_deref(), Some("ship"));
+        assert_eq!(merged.tags, ["a", "b"]);
+        // It depended on another copy, i.e. on itself.
+        assert_eq!(merged.depends_on, None);
+    }
+
+    #[test]
+    fn editor_merges_and_repoints_references() {
+        let source = "project \"P\" {\n  todo: \"Plan\", // first\n  todo: \"Build\", depends_on: \"plan\",\n  todo: \"plan\",\n}\nproject \"Q\" {\n  todo: \"Plan\",\n  todo: \"Test\", depends_on: \"P/plan\",\n}\n";
+        let mut editor = Editor::new(source).unwrap();
+        assert_eq!(editor.dedupe().len(), 1);
+        assert_eq!(
+            editor.finish(),
+            "project \"P\" {\n  todo: \"Plan\", // first\n  todo: \"Build\", depends_on: \"Plan\",\n}\nproject \"Q\" {\n  todo: \"Plan\",\n  todo: \"Test\", depends_on: \"P/Plan\",\n}\n"
+        );
+    }
+}
+
+mod reassign_tests {
+    use to_do_parcer::edit::{EditError, Editor};
+
+    const SOURCE: &str = "project \"A\" {\n  todo: \"One\", assign: @alice, due: 2025-01-01,\n  done: \"Two\", assign: @alice,\n  todo: \"Three\", assign: @carol,\n}\nproject \"B\" {\n  todo: \"Four\", assign: @alice, // hers\n}\n";
+
+    #[test]
+    fn open_tasks_in_every_project() {
+        let mut editor = Editor::new(SOURCE).unwrap();
+        assert_eq!(editor.reassign("@alice", "bob", None, false).unwrap(), 2);
+        assert_eq!(
+            editor.finish(),
+            "project \"A\" {\n  todo: \"One\", assign: @bob, due: 2025-01-01,\n  done: \"Two\", assign: @alice,\n  todo: \"Three\", assign: @carol,\n}\nproject \"B\" {\n  todo: \"Four\", assign: @bob, // hers\n}\n"
+        );
+    }
+
+    #[test]
+    fn project_scope_and_done_tasks() {
+        let mut editor = Editor::new(SOURCE).unwrap();
+        assert_eq!(editor.reassign("alice", "bob", Some("A"), true).unwrap(), 2);
+        let text = editor.finish();
+        assert!(text.contains("done: \"Two\", assign: @bob,"));
+        assert!(text.contains("todo: \"Four\", assign: @alice,"));
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        let mut editor = Editor::new(SOURCE).unwrap();
+        assert!(matches!(
+            editor.reassign("alice", "bob", Some("C"), false),
+            Err(EditError::ProjectNotFound(name)) if name == "C"
+        ));
+        assert!(matches!(
+            editor.reassign("alice", "bob smith", None, false),
+            Err(EditError::Unrepresentable { .. })
+        ));
+        assert_eq!(editor.reassign("dave", "bob", None, false).unwrap(), 0);
+        assert_eq!(editor.finish(), SOURCE);
+    }
+}
+
+mod rename_project_tests {
+    use to_do_parcer::edit::{EditError, Editor};
+
+    #[test]
+    fn rewrites_qualified_references_only() {
+        let source = "project \"Old\" { // main\n  todo: \"A\",\n}\n\nproject \"Q\" {\n  todo: \"B\", depends_on: \"Old/A\",\n  todo: \"C\", depends_on: \"A\",\n}\n";
+        let mut editor = Editor::new(source).unwrap();
+        assert_eq!(editor.rename_project("Old", "New").unwrap(), 1);
+        assert_eq!(
+            editor.finish(),
+            "project \"New\" { // main\n  todo: \"A\",\n}\n\nproject \"Q\" {\n  todo: \"B\", depends_on: \"New/A\",\n  todo: \"C\", depends_on: \"A\",\n}\n"
+        );
+    }
+
+    #[test]
+    fn leaves_references_to_other_projects() {
+        // "Old/A" is the title of a task in Q, not a path into Old.
+        let source = r#"project "Old" { todo: "A", } project "Q" { todo: "Old/A", todo: "B", depends_on: "Old/A", }"#;
+        let mut editor = Editor::new(source).unwrap();
+        assert_eq!(editor.rename_project("Old", "New").unwrap(), 0);
+        assert_eq!(
+            editor.finish(),
+            r#"project "New" { todo: "A", } project "Q" { todo: "Old/A", todo: "B", depends_on: "Old/A", }"#
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_and_taken_names() {
+        let source = r#"project "A" { todo: "X", } project "B" { todo: "Y", }"#;
+        let mut editor = Editor::new(source).unwrap();
+        assert!(matches!(
+            editor.rename_project("C", "D"),
+            Err(EditError::ProjectNotFound(name)) if name == "C"
+        ));
+        assert!(matches!(
+            editor.rename_project("A", "B"),
+            Err(EditError::ProjectExists(name)) if name == "B"
+        ));
+        assert!(matches!(
+            editor.rename_project("A", "Say \"hi\""),
+            Err(EditError::Unrepresentable { .. })
+        ));
+    }
+}
+
+mod migrate_tests {
+    use super::*;
+    use to_do_parcer::edit::{EditError, Editor};
+
+    #[test]
+    fn updates_pragma_and_tags_keeping_comments() {
+        let source = "version 1 // old format\nproject \"P\" {\n  // first\n  todo: \"A\", @tag:\"x\",  @tag: \"y\",\n}\n";
+        let mut editor = Editor::new(source).unwrap();
+        assert_eq!(editor.migrate(2).unwrap(), 2);
+        let migrated = editor.finish();
+        assert_eq!(
+            migrated,
+            "version 2 // old format\nproject \"P\" {\n  // first\n  todo: \"A\", tag:\"x\",  tag: \"y\",\n}\n"
+        );
+        let doc = ToDoParser::parse_document(&migrated).unwrap();
+        assert_eq!(doc.version, 2);
+        assert_eq!(
+            doc.fingerprint(),
+            ToDoParser::parse_document(source).unwrap().fingerprint()
+        );
+    }
+
+    #[test]
+    fn adds_a_pragma_when_there_is_none() {
+        let mut editor = Editor::new("\u{feff}project \"P\" {\r\n  todo: \"A\",\r\n}\r\n").unwrap();
+        assert_eq!(editor.migrate(2).unwrap(), 0);
+        assert_eq!(
+            editor.finish(),
+            "\u{feff}version 2\r\n\r\nproject \"P\" {\r\n  todo: \"A\",\r\n}\r\n"
+        );
+    }
+
+    #[test]
+    fn current_files_are_unchanged() {
+        for source in [
+            "version 2\nproject \"P\" { todo: \"A\", tag: \"x\", }\n",
+            "project \"P\" { todo: \"A\", @tag: \"x\", }\n",
+            "// nothing yet\n",
+        ] {
+            let version = if source.starts_with("version") { 2 } else { 1 };
+            let mut editor = Editor::new(source).unwrap();
+            assert_eq!(editor.migrate(version).unwrap(), 0);
+            assert_eq!(editor.finish(), source);
+        }
+    }
+
+    #[test]
+    fn rejects_unsupported_versions() {
+        let mut editor = Editor::new("project \"P\" { }").unwrap();
+        assert!(matches!(
+            editor.migrate(9),
+            Err(EditError::Parse(ParseError::UnsupportedVersion {
+                found: 9
+            }))
+        ));
+    }
+}
+
+mod update_tests {
+    use super::*;
+    use to_do_parcer::edit::{Editor, TaskChanges};
+
+    fn update(source: &str, changes: TaskChanges) -> String {
+        let mut editor = Editor::new(source).unwrap();
+        editor.update_task("A", &changes).unwrap();
+        let out = editor.finish();
+        ToDoParser::parse_document(&out).expect("edited text parses");
+        out
+    }
+
+    #[test]
+    fn replaces_attributes_in_place() {
+        let source = "project \"P\" {\n  todo: \"A\", due:2025-01-01,  @low, // c\n}\n";
+        let changes = TaskChanges {
+            priority: Some(Some(Priority::High)),
+            due: Some(NaiveDate::from_ymd_opt(2025, 12, 15)),
+            ..TaskChanges::default()
+        };
+        assert_eq!(
+            update(source, changes),
+            "project \"P\" {\n  todo: \"A\", due: 2025-12-15,  @high, // c\n}\n"
+        );
+    }
+
+    #[test]
+    fn appends_missing_attributes() {
+        let changes = TaskChanges {
+            assignee: Some(Some("bob".to_string())),
+            priority: Some(Some(Priority::Medium)),
+            ..TaskChanges::default()
+        };
+        assert_eq!(
+            update(r#"project "P" { todo: "A", @tag: "x" , }"#, changes),
+            r#"project "P" { todo: "A", @tag: "x", @medium, assign: @bob , }"#
+        );
+    }
+
+    #[test]
+    fn removes_attributes() {
+        let changes = TaskChanges {
+            priority: Some(None),
+            depends_on: Some(None),
+            ..TaskChanges::default()
+        };
+        assert_eq!(
+            update(
+                r#"project "P" { todo: "A", @high, depends_on: "B", assign: @c, todo: "B", }"#,
+                changes
+            ),
+            r#"project "P" { todo: "A", assign: @c, todo: "B", }"#
+        );
+    }
+
+    #[test]
+    fn rejects_bad_assignee() {
+        let mut editor = Editor::new(r#"project "P" { todo: "A", }"#).unwrap();
+        let changes = TaskChanges {
+            assignee: Some(Some("two words".to_string())),
+            ..TaskChanges::default()
+        };
+        assert!(editor.update_task("A", &changes).is_err());
+    }
+}
+
+mod sort_tests {
+    use super::*;
+    use to_do_parcer::edit::Editor;
+    use to_do_parcer::sort::SortKey;
+
+    fn sort(source: &str, key: SortKey) -> String {
+        let mut editor = Editor::new(source).unwrap();
+        editor.sort_tasks(key);
+        let out = editor.finish();
+        ToDoParser::parse_document(&out).expect("sorted text parses");
+        out
+    }
+
+    #[test]
+    fn moves_lines_with_their_comments() {
+        let source = "project \"P\" {\n  todo: \"A\", @low,\n\n  // about B\n  todo: \"B\", @high, // hot\n  todo: \"C\",\n  // footer\n}\n";
+        assert_eq!(
+            sort(source, SortKey::Priority),
+            "project \"P\" {\n\n  // about B\n  todo: \"B\", @high, // hot\n  todo: \"A\", @low,\n  todo: \"C\",\n  // footer\n}\n"
+        );
+    }
+
+    #[test]
+    fn sorts_by_due_with_undated_last() {
+        let source = "project \"P\" {\n  todo: \"A\",\n  todo: \"B\", due: 2025-03-01,\n  todo: \"C\", due: 2025-01-01,\n}\n";
+        assert_eq!(
+            sort(source, SortKey::Due),
+            "project \"P\" {\n  todo: \"C\", due: 2025-01-01,\n  todo: \"B\", due: 2025-03-01,\n  todo: \"A\",\n}\n"
+        );
+    }
+
+    #[test]
+    fn sorts_inline_tasks_in_place() {
+        let source = r#"project "P" { done: "A", todo: "B", @low, todo: "C", }"#;
+        assert_eq!(
+            sort(source, SortKey::Status),
+            r#"project "P" { todo: "B", @low, todo: "C", done: "A", }"#
+        );
+    }
+
+    #[test]
+    fn sort_is_stable_and_leaves_sorted_files_alone() {
+        let source = "project \"P\" {\n  todo: \"A\",\n  todo: \"B\",\n}\nproject \"Q\" { }\n";
+        assert_eq!(sort(source, SortKey::Priority), source);
+    }
+
+    #[test]
+    fn parses_sort_keys() {
+        assert_eq!("Due".parse::<SortKey>(), Ok(SortKey::Due));
+        assert!("title".parse::<SortKey>().is_err());
+    }
+}
+
+mod source_order_tests {
+    use super::*;
+    use std::fs;
+    use to_do_parcer::group::GroupKey;
+    use to_do_parcer::options::{DuplicateProjectPolicy, IncludePolicy, ParseOptions};
+    use to_do_parcer::sort::SortKey;
+
+    const INPUT: &str = r#"project "P" {
+        todo: "A", due: 2025-12-03, @tag: "x",
+        todo: "B", due: 2025-12-01, @high, @tag: "x",
+    }
+    project "Q" {
+        done: "C", @tag: "x",
+    }
+    project "P" {
+        todo: "D", due: 2025-12-02,
+    }"#;
+
+    fn orders(doc: &to_do_parcer::Document) -> Vec<(String, usize)> {
+        doc.tasks()
+            .map(|(_, t)| (t.title.clone(), t.order))
+            .collect()
+    }
+
+    #[test]
+    fn follows_the_file() {
+        let projects = ToDoParser::parse_projects(INPUT).unwrap();
+        let orders: Vec<_> = projects
+            .iter()
+            .flat_map(|p| &p.tasks)
+            .map(|t| (t.title.as_str(), t.order))
+            .collect();
+        assert_eq!(orders, [("A", 0), ("B", 1), ("C", 2), ("D", 3)]);
+    }
+
+    #[test]
+    fn counts_included_tasks_where_they_are_included() {
+        let dir = std::env::temp_dir().join("to_do_parcer_source_order");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("team.todo"), r#"project "Team" { todo: "T", }"#).unwrap();
+        let options = ParseOptions {
+            includes: IncludePolicy::Resolve,
+            base_dir: Some(dir),
+            ..ParseOptions::default()
+        };
+        let doc = ToDoParser::parse_with(
+            r#"project "A" { todo: "A1", } include "team.todo" project "B" { todo: "B1", }"#,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            orders(&doc),
+            [("A1".into(), 0), ("T".into(), 1), ("B1".into(), 2)]
+        );
+    }
+
+    #[test]
+    fn survives_merging_sorting_and_grouping() {
+        let options = ParseOptions {
+            duplicate_projects: DuplicateProjectPolicy::Merge,
+            ..ParseOptions::default()
+        };
+        let mut doc = ToDoParser::parse_with(INPUT, &options).unwrap();
+        assert_eq!(
+            orders(&doc),
+            [
+                ("A".into(), 0),
+                ("B".into(), 1),
+                ("D".into(), 3),
+                ("C".into(), 2)
+            ]
+        );
+
+        let groups = doc.group_by(GroupKey::Tag);
+        let grouped: Vec<_> = groups.values().flatten().map(|(_, t)| t.order).collect();
+        assert_eq!(grouped, [0, 1, 2, 3]);
+
+        let project = &mut doc.projects[0];
+        project.sort_tasks(SortKey::Due);
+        let titles: Vec<_> = project.tasks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, ["B", "D", "A"]);
+        project.sort_tasks(SortKey::Priority);
+        project.sort_by_source_order();
+        let titles: Vec<_> = project.tasks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, ["A", "B", "D"]);
+    }
+}
+
+mod diagnostic_tests {
+    use super::*;
+    use to_do_parcer::diagnostic::{Diagnostic, Severity};
+    use to_do_parcer::options::{ParseOptions, UnknownAttributePolicy};
+    use to_do_parcer::validate::validate;
+
+    #[test]
+    fn severities_are_ordered() {
+        assert!(Severity::Hint < Severity::Warning && Severity::Warning < Severity::Error);
+        assert_eq!("HINT".parse(), Ok(Severity::Hint));
+        assert_eq!(Severity::Warning.to_string(), "warning");
+    }
+
+    #[test]
+    fn parser_and_validator_report_alike() {
+        let options = ParseOptions {
+            unknown_attributes: UnknownAttributePolicy::Warn,
+            ..ParseOptions::default()
+        };
+        let doc = ToDoParser::parse_with(
+            "project \"P\" {\n  todo: \"Setup\", color: red,\n  todo: \"B\", depends_on: \"Setpu\",\n}",
+            &options,
+        )
+        .unwrap();
+        let issues = validate(&doc);
+        assert_eq!(
+            issues[0],
+            Diagnostic::new(
+                Severity::Warning,
+                "parse-warning",
+                2,
+                "unknown attribute `color` ignored".to_string()
+            )
+        );
+        assert_eq!(issues[1].code, "unknown-dependency");
+        assert_eq!(issues[1].suggestion.as_deref(), Some("Setup"));
+
+        let err = ToDoParser::parse_document("project \"P\" {\n  Todo: \"A\",\n}").unwrap_err();
+        let diagnostic = err.diagnostic();
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!(diagnostic.location(), "2:3");
+        assert_eq!(diagnostic.suggestion.as_deref(), Some("todo:"));
+
+        let io = ParseError::Io(std::io::Error::other("gone")).diagnostic();
+        assert_eq!(io.to_string(), "error[parse]: File reading error: gone");
+    }
+
+    #[test]
+    fn report_serializes_every_field() {
+        use to_do_parcer::diagnostic::{DiagnosticFormat, DiagnosticReport};
+
+        let err =
+            ToDoParser::parse_document("project \"P\" {\n  todo: \"A\", due: 2025/12/31,\n}\n")
+                .unwrap_err();
+        let mut parse = err.diagnostic();
+        parse.fix = to_do_parcer::fix::suggest_fix(
+            "project \"P\" {\n  todo: \"A\", due: 2025/12/31,\n}\n",
+            &Default::default(),
+        );
+        let mut report = DiagnosticReport::default();
+        report.push("a.todo", [parse]);
+        report.push(
+            "b.todo",
+            [Diagnostic {
+                suggestion: Some("Setup".to_string()),
+                ..Diagnostic::new(Severity::Hint, "x", 4, "m".to_string())
+            }],
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        let first = &json["diagnostics"][0];
+        assert_eq!(first["file"], "a.todo");
+        assert_eq!(first["code"], "parse");
+        assert_eq!(first["severity"], "error");
+        assert_eq!(first["span"]["line"], 2);
+        assert_eq!(first["fix"]["replacement"], "2025-12-31");
+        assert_eq!(first["fix"]["span"]["column"], 19);
+        let second = &json["diagnostics"][1];
+        assert_eq!(second["suggestion"], "Setup");
+        assert!(second["span"].is_null() && second["fix"].is_null());
+        assert_eq!(
+            json["summary"],
+            serde_json::json!({ "errors": 1, "warnings": 0, "hints": 1 })
+        );
+        assert_eq!("JSON".parse(), Ok(DiagnosticFormat::Json));
+        assert!("xml".parse::<DiagnosticFormat>().is_err());
+    }
+}
+
+mod lint_tests {
+    use super::*;
+    use to_do_parcer::diagnostic::Severity;
+    use to_do_parcer::lint::{LintConfig, LintRule, lint};
+
+    const SOURCE: &str = r#"project "P" {
+    todo: "Ship", @high,
+    todo: "Test", depends_on: "Nothing",
+    todo: "Ship", due: 2025-01-01,
+    done: "Old", due: 2024-01-01,
+}"#;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 6, 1).unwrap()
+    }
+
+    #[test]
+    fn default_rules() {
+        let doc = ToDoParser::parse_document(SOURCE).unwrap();
+        let issues = lint(&doc, &LintConfig::default(), &today());
+        let found: Vec<_> = issues
+            .iter()
+            .map(|i| (i.line, i.code, i.severity))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (2, "missing-due", Severity::Warning),
+                (3, "dangling-dependency", Severity::Error),
+                (4, "duplicate-title", Severity::Warning),
+                (4, "overdue", Severity::Warning),
+            ]
+        );
+        assert_eq!(
+            issues[2].message,
+            "title \"Ship\" is already used on line 2"
+        );
+    }
+
+    #[test]
+    fn rules_can_be_disabled_and_reconfigured() {
+        let doc = ToDoParser::parse_document(SOURCE).unwrap();
+        let mut config = LintConfig::default();
+        config
+            .disable(LintRule::DuplicateTitle)
+            .disable(LintRule::Overdue)
+            .set_severity(LintRule::MissingDue, Severity::Error);
+        let issues = lint(&doc, &config, &today());
+        assert_eq!(issues.len(), 2);
+        assert!(issues.iter().all(|i| i.severity == Severity::Error));
+
+        config.enable(LintRule::Overdue);
+        assert_eq!(lint(&doc, &config, &today()).len(), 3);
+    }
+
+    #[test]
+    fn qualified_dependencies_are_not_dangling() {
+        let doc = ToDoParser::parse_document(
+            r#"project "A" { todo: "X", } project "B" { todo: "Y", depends_on: "A/X", }"#,
+        )
+        .unwrap();
+        assert!(lint(&doc, &LintConfig::default(), &today()).is_empty());
+    }
+
+    #[test]
+    fn parses_rule_codes() {
+        assert_eq!("overdue".parse::<LintRule>(), Ok(LintRule::Overdue));
+        assert!("missing_due".parse::<LintRule>().is_err());
+    }
+}
+
+#[cfg(feature = "spellcheck")]
+mod spelling_tests {
+    use super::*;
+    use to_do_parcer::diagnostic::Severity;
+    use to_do_parcer::lint::{LintConfig, LintRule, lint};
+    use to_do_parcer::spell::Dictionary;
+
+    const SOURCE: &str = r#"project "P" {
+    todo: "Recieve invoices from Oleksii", @tag: "custmer",
+    todo: "Deploy the API to GitHub", @tag: "release-notes",
+}"#;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 6, 1).unwrap()
+    }
+
+    #[test]
+    fn is_off_by_default() {
+        let doc = ToDoParser::parse_document(SOURCE).unwrap();
+        assert!(lint(&doc, &LintConfig::default(), &today()).is_empty());
+    }
+
+    #[test]
+    fn flags_titles_and_tags_with_suggestions() {
+        let doc = ToDoParser::parse_document(SOURCE).unwrap();
+        let mut config = LintConfig::default();
+        config.enable(LintRule::Spelling);
+        let issues = lint(&doc, &config, &today());
+        let found: Vec<_> = issues
+            .iter()
+            .map(|i| (i.line, i.severity, i.suggestion.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (2, Severity::Hint, Some("receive")),
+                (2, Severity::Hint, None),
+                (2, Severity::Hint, Some("customer")),
+            ]
+        );
+        assert_eq!(
+            issues[0].message,
+            "\"Recieve\" in title \"Recieve invoices from Oleksii\" may be misspelled, \
+             did you mean `receive`?"
+        );
+    }
+
+    #[test]
+    fn project_words_are_accepted() {
+        let doc = ToDoParser::parse_document(SOURCE).unwrap();
+        let mut config = LintConfig::default();
+        config
+            .set_severity(LintRule::Spelling, Severity::Warning)
+            .add_words(["oleksii", "Custmer"]);
+        let issues = lint(&doc, &config, &today());
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn derives_inflected_forms() {
+        let dictionary = Dictionary::new();
+        for word in [
+            "tasks",
+            "planned",
+            "running",
+            "priorities",
+            "reopened",
+            "team's",
+        ] {
+            assert!(dictionary.contains(word), "{}", word);
+        }
+        assert_eq!(
+            dictionary.misspellings("Rewrite HTTP retry for v2 in utf8"),
+            Vec::<&str>::new()
+        );
+    }
+}
+
+mod fingerprint_tests {
+    use super::*;
+
+    fn fingerprint(source: &str) -> u64 {
+        ToDoParser::parse_document(source).unwrap().fingerprint()
+    }
+
+    const SOURCE: &str = r#"project "A" {
+    todo: "One", @high, due: 2025-01-01, assign: @tanya, @tag: "x", @tag: "y",
+    done: "Two", completed: 2024-12-30,
+}
+project "B" {
+    todo: "Three", depends_on: "A/One",
+}
+"#;
+
+    #[test]
+    fn ignores_layout_comments_and_order() {
+        let reordered = r#"version 2
+// B first this time
+project "B" { todo: "Three", depends_on: "A/One", }
+project "A" {
+    done: "Two", completed: 2024-12-30, // finished early
+    todo: "One", tag: "y", tag: "x", tag: "x", assign: @tanya, due: 2025-01-01, @high,
+}"#;
+        assert_eq!(fingerprint(SOURCE), fingerprint(reordered));
+    }
+
+    #[test]
+    fn projects_with_one_name_count_as_one() {
+        assert_eq!(
+            fingerprint(r#"project "P" { todo: "A", todo: "B", }"#),
+            fingerprint(r#"project "P" { todo: "B", } project "P" { todo: "A", }"#)
+        );
+    }
+
+    #[test]
+    fn every_attribute_counts() {
+        let base = fingerprint(SOURCE);
+        for (from, to) in [
+            ("\"One\"", "\"One!\""),
+            ("@high", "@low"),
+            ("2025-01-01", "2025-01-02"),
+            ("@tanya", "@oleksii"),
+            ("@tag: \"y\"", "@tag: \"z\""),
+            ("done: \"Two\"", "todo: \"Two\""),
+            ("depends_on: \"A/One\"", "id: three"),
+            ("project \"B\"", "project \"C\""),
+        ] {
+            assert_ne!(base, fingerprint(&SOURCE.replace(from, to)), "{}", to);
+        }
+    }
+
+    #[test]
+    fn duplicate_tasks_count_twice() {
+        assert_ne!(
+            fingerprint(r#"project "P" { todo: "A", }"#),
+            fingerprint(r#"project "P" { todo: "A", todo: "A", }"#)
+        );
+        assert_ne!(
+            fingerprint(r#"project "P" { }"#),
+            fingerprint(r#"project "P" { } project "Q" { }"#)
+        );
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary_tests {
+    use super::*;
+    use arbitrary::{Arbitrary, Unstructured};
+    use to_do_parcer::parser::{Document, Task};
+    use to_do_parcer::pretty::format_document;
+
+    /// Pseudo-random bytes, the same on every run.
+    fn bytes(seed: u64, len: usize) -> Vec<u8> {
+        let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn generated_sources_round_trip() {
+        for seed in 0..300 {
+            let data = bytes(seed, 64 + seed as usize * 16);
+            let source = to_do_parcer::arbitrary::source(&mut Unstructured::new(&data)).unwrap();
+            let doc = ToDoParser::parse_document(&source)
+                .unwrap_or_else(|e| panic!("seed {}: {}\n{}", seed, e, source));
+            assert!(doc.warnings.is_empty(), "seed {}: {:?}", seed, doc.warnings);
+            assert_eq!(format_document(&doc), source, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn generated_documents_keep_their_fingerprint() {
+        for seed in 0..100 {
+            let data = bytes(seed, 1024);
+            let doc = Document::arbitrary(&mut Unstructured::new(&data)).unwrap();
+            let parsed = ToDoParser::parse_document(&format_document(&doc)).unwrap();
+            assert_eq!(parsed.fingerprint(), doc.fingerprint(), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn generated_tasks_are_well_formed() {
+        let data = bytes(7, 4096);
+        let mut u = Unstructured::new(&data);
+        for _ in 0..50 {
+            let task = Task::arbitrary(&mut u).unwrap();
+            assert!(!task.title.contains('"'));
+            for date in [&task.start_date, &task.due_date, &task.completed]
+                .into_iter()
+                .flatten()
+            {
+                assert!(
+                    NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok(),
+                    "{}",
+                    date
+                );
+            }
+        }
+    }
+}
+
+mod diff_tests {
+    use super::*;
+    use to_do_parcer::diff::{Change, Diff, Field};
+
+    fn diff(old: &str, new: &str) -> Diff {
+        Diff::compute(
+            &ToDoParser::parse_document(old).unwrap(),
+            &ToDoParser::parse_document(new).unwrap(),
+        )
+    }
+
+    #[test]
+    fn identical_documents_have_no_changes() {
+        let source = r#"project "P" { todo: "A", @high, done: "B", }"#;
+        assert!(diff(source, source).is_empty());
+    }
+
+    #[test]
+    fn reports_added_removed_and_completed_tasks() {
+        let d = diff(
+            r#"project "P" { todo: "A", todo: "Gone", done: "C", }"#,
+            r#"project "P" { done: "A", completed: 2025-05-01, todo: "New", todo: "C", }"#,
+        );
+        assert_eq!(d.to_string(), "x P/A\n+ P/New\no P/C\n- P/Gone\n");
+    }
+
+    #[test]
+    fn reports_attribute_changes() {
+        let d = diff(
+            r#"project "P" { todo: "A", @high, @tag: "x", }"#,
+            r#"project "P" { todo: "A", @low, due: 2025-01-01, @tag: "x", @tag: "y", }"#,
+        );
+        assert_eq!(
+            d.to_string(),
+            "~ P/A: priority high -> low\n~ P/A: due none -> 2025-01-01\n~ P/A: tags x -> x, y\n"
+        );
+    }
+
+    #[test]
+    fn follows_ids_and_moves() {
+        let d = diff(
+            r#"project "P" { todo: "Old title", id: t1, todo: "Moved", } project "Q" { }"#,
+            r#"project "P" { todo: "New title", id: t1, } project "Q" { todo: "Moved", }"#,
+        );
+        assert_eq!(
+            d.changes,
+            vec![
+                Change::Changed {
+                    project: "P".to_string(),
+                    title: "New title".to_string(),
+                    field: Field::Title,
+                    old: Some("Old title".to_string()),
+                    new: Some("New title".to_string()),
+                },
+                Change::Changed {
+                    project: "Q".to_string(),
+                    title: "Moved".to_string(),
+                    field: Field::Project,
+                    old: Some("P".to_string()),
+                    new: Some("Q".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn json_layout() {
+        let d = diff(r#"project "P" { }"#, r#"project "P" { todo: "A", }"#);
+        let json = serde_json::to_value(&d).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"changes": [{"kind": "added", "project": "P", "title": "A"}]})
+        );
+    }
+}
+
+mod merge_tests {
+    use super::*;
+    use to_do_parcer::merge::{ConflictKind, Merge, merge};
+    use to_do_parcer::pretty::format_document;
+
+    fn merge3(base: &str, ours: &str, theirs: &str) -> Merge {
+        merge(
+            &ToDoParser::parse_document(base).unwrap(),
+            &ToDoParser::parse_document(ours).unwrap(),
+            &ToDoParser::parse_document(theirs).unwrap(),
+        )
+    }
+
+    #[test]
+    fn combines_independent_changes() {
+        let merged = merge3(
+            r#"project "P" { todo: "A", todo: "B", todo: "C", }"#,
+            r#"project "P" { done: "A", todo: "B", todo: "Ours", }"#,
+            r#"project "P" { todo: "A", @high, todo: "C", } project "Q" { todo: "Theirs", }"#,
+        );
+        assert!(merged.is_clean());
+        assert_eq!(
+            format_document(&merged.document),
+            "project \"P\" {\n  done: \"A\", @high,\n  todo: \"Ours\",\n}\n\nproject \"Q\" {\n  todo: \"Theirs\",\n}\n"
+        );
+    }
+
+    #[test]
+    fn same_change_on_both_sides_is_not_a_conflict() {
+        let merged = merge3(
+            r#"project "P" { todo: "A", }"#,
+            r#"project "P" { done: "A", todo: "New", }"#,
+            r#"project "P" { done: "A", todo: "New", }"#,
+        );
+        assert!(merged.is_clean());
+        assert_eq!(merged.document.projects[0].tasks.len(), 2);
+    }
+
+    #[test]
+    fn conflicting_attributes_keep_ours() {
+        let merged = merge3(
+            r#"project "P" { todo: "A", @low, }"#,
+            r#"project "P" { todo: "A", @high, }"#,
+            r#"project "P" { todo: "A", @medium, }"#,
+        );
+        assert_eq!(merged.conflicts.len(), 1);
+        assert_eq!(
+            merged.conflicts[0].kind,
+            ConflictKind::Field {
+                field: "priority",
+                ours: Some("high".to_string()),
+                theirs: Some("medium".to_string()),
+            }
+        );
+        let task = &merged.document.projects[0].tasks[0];
+        assert_eq!(task.priority, Some(Priority::High));
+        assert_eq!(
+            task.comments,
+            vec!["// merge conflict: priority is high in ours and medium in theirs, kept ours"]
+        );
+    }
+
+    #[test]
+    fn removal_conflicts_with_changes() {
+        let merged = merge3(
+            r#"project "P" { todo: "A", todo: "B", }"#,
+            r#"project "P" { todo: "B", }"#,
+            r#"project "P" { todo: "A", @high, }"#,
+        );
+        assert_eq!(merged.conflicts.len(), 1);
+        assert_eq!(merged.conflicts[0].kind, ConflictKind::RemovedInOurs);
+        let titles: Vec<_> = merged.document.projects[0]
+            .tasks
+            .iter()
+            .map(|t| t.title.as_str())
+            .collect();
+        assert_eq!(titles, ["A"]);
+    }
+}
+
+mod color_tests {
+    use super::*;
+    use to_do_parcer::color::{ColorChoice, Palette, Style};
+    use to_do_parcer::table::{format_table, format_table_with};
+
+    #[test]
+    fn choices() {
+        assert_eq!("Always".parse::<ColorChoice>(), Ok(ColorChoice::Always));
+        assert!("sometimes".parse::<ColorChoice>().is_err());
+        assert!(ColorChoice::Always.enabled(false));
+        assert!(!ColorChoice::Never.enabled(true));
+        assert!(!ColorChoice::Auto.enabled(false));
+    }
+
+    #[test]
+    fn paints_overdue_open_tasks_only() {
+        let doc = ToDoParser::parse_document(
+            r#"project "P" { todo: "A", due: 2025-01-01, done: "B", due: 2025-01-01, }"#,
+        )
+        .unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 2, 1).unwrap();
+        let palette = Palette::new(true).with_clock(&today);
+        let tasks = &doc.projects[0].tasks;
+        assert_eq!(
+            palette.due(&tasks[0], "x"),
+            palette.paint(Style::Overdue, "x")
+        );
+        assert_eq!(palette.due(&tasks[1], "x"), "x");
+    }
+
+    #[test]
+    fn display_marks_overdue_tasks() {
+        let doc = ToDoParser::parse_document(
+            r#"project "P" { todo: "A", due: 2025-01-01, todo: "B", due: 2025-03-01, }"#,
+        )
+        .unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 2, 1).unwrap();
+        let text = doc.projects[0].render_with(&Palette::default().with_clock(&today));
+        assert!(text.contains("Due: 2025-01-01 (overdue)\n"), "{}", text);
+        assert!(text.contains("Due: 2025-03-01\n"), "{}", text);
+        assert!(
+            !doc.projects[0]
+                .render_with(&Palette::default())
+                .contains("(overdue)")
+        );
+    }
+
+    #[test]
+    fn render_writes_what_render_with_returns() {
+        let doc = ToDoParser::parse_document(
+            "project \"P\" {\n  todo: \"A\", @high, due: 2025-01-01, assign: @bob, @tag: \"x\",\n  done: \"B\", depends_on: \"A\",\n}\n",
+        )
+        .unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 2, 1).unwrap();
+        let palette = Palette::new(true)
+            .with_clock(&today)
+            .with_date_format("%d.%m.%Y");
+        let mut out = String::new();
+        doc.projects[0].render_colored(&mut out, &palette).unwrap();
+        assert_eq!(out, doc.projects[0].render_with(&palette));
+        assert!(
+            out.contains("Due: \x1b[1;31m01.01.2025\x1b[0m (overdue)\n"),
+            "{:?}",
+            out
+        );
+
+        let mut plain = String::new();
+        doc.projects[0].render(&mut plain).unwrap();
+        assert_eq!(plain, doc.projects[0].render_with(&Palette::default()));
+        assert!(plain.ends_with("Total: 2 tasks (1 active, 1 completed)\n"));
+    }
+
+    #[test]
+    fn colored_table_keeps_alignment() {
+        let doc = ToDoParser::parse_document(r#"project "P" { todo: "A", @high, done: "Long", }"#)
+            .unwrap();
+        let rows: Vec<_> = doc.tasks().collect();
+        let colored = format_table_with(&rows, &Palette::new(true));
+        assert!(colored.contains("\x1b[1;31mhigh\x1b[0m"));
+
+        let stripped: String = colored
+            .split("\x1b[")
+            .enumerate()
+            .map(|(i, part)| match i {
+                0 => part,
+                _ => &part[part.find('m').unwrap() + 1..],
+            })
+            .collect();
+        assert_eq!(stripped, format_table(&rows));
+    }
+}
+
+mod column_tests {
+    use super::*;
+    use to_do_parcer::color::Palette;
+    use to_do_parcer::table::{Column, format_columns};
+
+    #[test]
+    fn selected_columns_in_order() {
+        let doc = ToDoParser::parse_document(
+            r#"project "P" { todo: "Write docs", assign: @ann, @tag: "a", @tag: "b", }"#,
+        )
+        .unwrap();
+        let rows: Vec<_> = doc.tasks().collect();
+        assert_eq!(
+            format_columns(
+                &rows,
+                &[Column::Tags, Column::Assignee, Column::Title],
+                &Palette::default()
+            ),
+            "TAGS  ASSIGNEE  TITLE\na,b   @ann      Write docs\n"
+        );
+    }
+
+    #[test]
+    fn pads_by_display_width() {
+        let doc = ToDoParser::parse_document(
+            r#"project "P" { todo: "A", todo: "任务", done: "🚀 Ship", }"#,
+        )
+        .unwrap();
+        let rows: Vec<_> = doc.tasks().collect();
+        assert_eq!(
+            format_columns(&rows, &[Column::Title, Column::Status], &Palette::default()),
+            "TITLE    STATUS\n\
+             A        todo\n\
+             任务     todo\n\
+             🚀 Ship  done\n"
+        );
+    }
+
+    #[test]
+    fn parses_column_names() {
+        assert_eq!("Due".parse::<Column>(), Ok(Column::Due));
+        assert!("owner".parse::<Column>().is_err());
+    }
+}
+
+mod graph_tests {
+    use super::*;
+    use to_do_parcer::graph::{to_dot, to_mermaid_gantt};
+
+    #[test]
+    fn dot_has_clusters_colors_and_edges() {
+        let doc = ToDoParser::parse_document(
+            r#"project "Back end" {
+                done: "DB",
+                todo: "API", depends_on: "DB",
+            }
+            project "Front" {
+                todo: "UI", depends_on: "Back end/API",
+                todo: "Docs", depends_on: "Nowhere",
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            to_dot(&doc),
+            r#"digraph tasks {
+  rankdir=LR;
+  node [shape=box, style=filled];
+  subgraph cluster_0 {
+    label="Back end";
+    t0_0 [label="DB", fillcolor="palegreen"];
+    t0_1 [label="API", fillcolor="lightgrey"];
+  }
+  subgraph cluster_1 {
+    label="Front";
+    t1_0 [label="UI", fillcolor="lightgrey"];
+    t1_1 [label="Docs", fillcolor="lightgrey"];
+  }
+  t0_0 -> t0_1;
+  t0_1 -> t1_0;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn mermaid_gantt_places_tasks_by_dates_and_dependencies() {
+        let doc = ToDoParser::parse_document(
+            r#"project "Back: end" {
+                done: "DB", start: 2025-11-01, due: 2025-11-15,
+                todo: "API", depends_on: "DB", due: 2025-11-30,
+                todo: "Docs", depends_on: "API",
+                todo: "Release", due: 2025-12-01,
+                todo: "Someday",
+            }
+            project "Ideas" {
+                todo: "Later",
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            to_mermaid_gantt(&doc),
+            "gantt
+  dateFormat YYYY-MM-DD
+  section Back#58; end
+    DB :done, t0_0, 2025-11-01, 2025-11-15
+    API :t0_1, after t0_0, 2025-11-30
+    Docs :t0_2, after t0_1, 1d
+    Release :milestone, t0_3, 2025-12-01, 0d
+"
+        );
+    }
+}
+
+mod report_tests {
+    use super::*;
+    use to_do_parcer::report::html_report;
+
+    #[test]
+    fn report_has_progress_rows_and_escaping() {
+        let doc = ToDoParser::parse_document(
+            r#"project "R&D" {
+                done: "Spec",
+                todo: "<script>", due: 2025-01-01,
+                todo: "Build", due: 2025-12-01, assign: @ann,
+            }
+            project "Empty" {}"#,
+        )
+        .unwrap();
+        let html = html_report(&doc, "Plan", &NaiveDate::from_ymd_opt(2025, 6, 1).unwrap());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Plan</title>"));
+        assert!(html.contains("<h2>R&amp;D</h2>"));
+        assert!(html.contains("<p class=\"progress\">1 of 3 done (33%)</p>"));
+        assert!(html.contains("<p class=\"progress\">0 of 0 done (0%)</p>"));
+        assert!(html.contains("<tr class=\"overdue\"><td>todo</td><td>&lt;script&gt;</td>"));
+        assert!(html.contains("<tr class=\"todo\"><td>todo</td><td>Build</td><td></td><td class=\"due\">2025-12-01</td><td>@ann</td>"));
+        assert!(!html.contains("<script>"));
+    }
+}
+
+mod search_tests {
+    use super::*;
+    use to_do_parcer::search::{SearchField, search};
+
+    const INPUT: &str = r#"project "P" {
+        todo: "DB migration",
+        todo: "Write docs", assign: @dbadmin,
+        todo: "Login", @tag: "Auth",
+        todo: "Unrelated",
+    }"#;
+
+    #[test]
+    fn exact_matches_come_first() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let hits = search(&doc, "db");
+        let found: Vec<_> = hits
+            .iter()
+            .map(|h| (h.task.title.as_str(), h.field, h.exact))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("DB migration", SearchField::Title, true),
+                ("Write docs", SearchField::Assignee, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn fuzzy_and_case_insensitive() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let hits = search(&doc, "dbmig");
+        assert_eq!(hits.len(), 1);
+        assert!(!hits[0].exact);
+        let hits = search(&doc, "AUTH");
+        assert_eq!(hits[0].task.title, "Login");
+        assert_eq!(hits[0].text, "Auth");
+        assert!(search(&doc, "zzz").is_empty());
+    }
+}
+
+mod archive_tests {
+    use to_do_parcer::archive::archive;
+
+    #[test]
+    fn moves_done_tasks_and_finished_projects() {
+        let source = "\
+project \"Old\" {
+  done: \"A\",
+  done: \"B\",
+}
+
+project \"Live\" {
+  // notes stay
+  todo: \"C\", depends_on: \"D\",
+  done: \"D\",
+  done: \"E\", @high,
+}
+";
+        let existing = "project \"Live\" {\n  done: \"Z\",\n}\n";
+        let result = archive(source, existing).unwrap();
+        assert_eq!(
+            result.source,
+            "project \"Live\" {\n  // notes stay\n  todo: \"C\", depends_on: \"D\",\n  done: \"D\",\n}\n"
+        );
+        assert_eq!(
+            result.archive,
+            "project \"Live\" {\n  done: \"Z\",\n  done: \"E\", @high,\n}\n\nproject \"Old\" {\n  done: \"A\",\n  done: \"B\",\n}\n"
+        );
+        assert_eq!((result.tasks, result.projects), (3, 1));
+        assert_eq!(result.kept, ["Live/D"]);
+    }
+
+    #[test]
+    fn nothing_done_changes_nothing() {
+        let source = "project \"P\" { todo: \"A\", }\n";
+        let result = archive(source, "").unwrap();
+        assert_eq!(result.source, source);
+        assert_eq!(result.archive, "");
+        assert_eq!(result.tasks, 0);
+    }
+}
+
+mod template_tests {
+    use super::*;
+    use to_do_parcer::pretty::format_document;
+    use to_do_parcer::template::Template;
+    use to_do_parcer::validate::validate;
+
+    #[test]
+    fn templates_are_valid_and_formatted() {
+        for template in Template::ALL {
+            let doc = ToDoParser::parse_document(template.text()).unwrap();
+            assert!(validate(&doc).is_empty(), "{} has issues", template);
+            assert_eq!(format_document(&doc), template.text(), "{}", template);
+        }
+    }
+
+    #[test]
+    fn sprint_shows_every_attribute() {
+        let doc = ToDoParser::parse_document(Template::Sprint.text()).unwrap();
+        let tasks: Vec<_> = doc.tasks().map(|(_, t)| t).collect();
+        assert!(tasks.iter().any(|t| t.id.is_some()));
+        assert!(tasks.iter().any(|t| t.priority.is_some()));
+        assert!(tasks.iter().any(|t| t.start_date.is_some()));
+        assert!(tasks.iter().any(|t| t.due_date.is_some()));
+        assert!(tasks.iter().any(|t| t.completed.is_some()));
+        assert!(tasks.iter().any(|t| t.assignee.is_some()));
+        assert!(tasks.iter().any(|t| t.depends_on.is_some()));
+        assert!(tasks.iter().any(|t| !t.tags.is_empty()));
+    }
+}
+
+mod config_tests {
+    use std::path::Path;
+    use to_do_parcer::config::{Config, ConfigError};
+    use to_do_parcer::diagnostic::Severity;
+    use to_do_parcer::export::ExportFormat;
+    use to_do_parcer::lint::LintRule;
+    use to_do_parcer::validate::TagStyle;
+
+    #[test]
+    fn later_files_override_earlier_ones() {
+        let mut config = Config::default();
+        config
+            .apply(
+                "format = \"yaml\"\n[lint]\ndisable = [\"overdue\"]\nseverity = { missing-due = \"error\" }",
+                Path::new("global.toml"),
+            )
+            .unwrap();
+        config
+            .apply("[lint]\nenable = [\"overdue\"]", Path::new("local.toml"))
+            .unwrap();
+        assert_eq!(config.format, Some(ExportFormat::Yaml));
+        assert_eq!(
+            config.lint.severity(LintRule::Overdue),
+            Some(Severity::Warning)
+        );
+        assert_eq!(
+            config.lint.severity(LintRule::MissingDue),
+            Some(Severity::Error)
+        );
+    }
+
+    #[test]
+    fn tag_rules() {
+        let mut config = Config::default();
+        config
+            .apply(
+                "[validate]\ntag-style = \"snake_case\"\nallowed-tags = [\"a_b\"]",
+                Path::new("c.toml"),
+            )
+            .unwrap();
+        assert_eq!(config.tag_style, Some(TagStyle::SnakeCase));
+        assert_eq!(config.allowed_tags, Some(vec!["a_b".to_string()]));
+
+        config
+            .apply(
+                "[validate]\nassignees = [\"@tanya\", \"bob\"]",
+                Path::new("c.toml"),
+            )
+            .unwrap();
+        assert_eq!(
+            config.assignees,
+            Some(vec!["tanya".to_string(), "bob".to_string()])
+        );
+
+        let err = config
+            .apply("[validate]\ntag-style = \"camel\"", Path::new("c.toml"))
+            .unwrap_err();
+        assert!(matches!(&err, ConfigError::Value { key, .. } if key == "validate.tag-style"));
+    }
+
+    #[test]
+    fn invalid_values_name_the_key() {
+        let mut config = Config::default();
+        let err = config
+            .apply("date-format = \"%Q\"", Path::new("c.toml"))
+            .unwrap_err();
+        assert!(matches!(&err, ConfigError::Value { key, .. } if key == "date-format"));
+
+        let err = config
+            .apply(
+                "[lint]\nseverity = { nope = \"error\" }",
+                Path::new("c.toml"),
+            )
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("c.toml: lint.severity.nope: unknown rule")
+        );
+
+        let err = config.apply("file = 3", Path::new("c.toml")).unwrap_err();
+        assert!(matches!(err, ConfigError::Syntax { .. }));
+    }
+
+    #[test]
+    fn missing_files_are_skipped() {
+        let config = Config::load_from(&["no/such/config.toml".into()]).unwrap();
+        assert!(config.file.is_none());
+    }
+}
+
+mod tree_tests {
+    use pest::Parser;
+    use to_do_parcer::ToDoParser;
+    use to_do_parcer::parser::Rule;
+    use to_do_parcer::tree::{TreeFormat, render_tree};
+
+    #[test]
+    fn text_shows_spans_and_snippets() {
+        let source = "project \"P\" {\n  todo: \"A rather long task title that goes on\",\n}";
+        let pairs = ToDoParser::parse(Rule::project, source).unwrap();
+        let tree = render_tree(pairs, TreeFormat::Text);
+        let lines: Vec<&str> = tree.lines().collect();
+        assert_eq!(lines[0], "- project 0..64 \"project \\\"P\\\" {…\"");
+        assert_eq!(
+            lines[2],
+            "  - task 16..62 \"todo: \\\"A rather long task title that goe…\""
+        );
+    }
+
+    #[test]
+    fn json_nests_children() {
+        let pairs = ToDoParser::parse(Rule::project, "project \"P\" {\n  todo: \"A\",\n}").unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&render_tree(pairs, TreeFormat::Json)).unwrap();
+        let task = &json[0]["children"][1];
+        assert_eq!(task["rule"], "task");
+        assert_eq!(task["text"], "todo: \"A\",");
+        assert_eq!(
+            (task["start"].as_u64(), task["end"].as_u64()),
+            (Some(16), Some(26))
+        );
+        assert_eq!(
+            (task["line"].as_u64(), task["column"].as_u64()),
+            (Some(2), Some(3))
+        );
+        assert_eq!(task["children"][0]["rule"], "todo_task");
+    }
+
+    #[test]
+    fn format_names_round_trip() {
+        for format in TreeFormat::ALL {
+            assert_eq!(format.as_str().parse::<TreeFormat>(), Ok(*format));
+        }
+        assert!("xml".parse::<TreeFormat>().is_err());
+    }
+}
+
+mod serve_tests {
+    use chrono::NaiveDate;
+    use to_do_parcer::ToDoParser;
+    use to_do_parcer::serve::respond;
+
+    const SOURCE: &str = r#"
+        project "Api" {
+            todo: "Auth", @high, assign: @bob, @tag: "needs review",
+            done: "Docs",
+        }
+        project "Web" {
+            todo: "Login", assign: @amy, due: 2025-01-01,
+        }
+    "#;
+
+    fn get(url: &str) -> (u16, serde_json::Value) {
+        let doc = ToDoParser::parse_document(SOURCE).unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+        let response = respond(&doc, "GET", url, &today);
+        (
+            response.status,
+            serde_json::from_str(&response.body).unwrap(),
+        )
+    }
+
+    #[test]
+    fn lists_projects_and_stats() {
+        let (status, projects) = get("/projects");
+        assert_eq!(status, 200);
+        assert_eq!(projects[1]["name"], "Web");
+        assert_eq!(projects[0]["tasks"][1]["status"], "done");
+
+        let (_, stats) = get("/stats/");
+        assert_eq!(stats["total"], 3);
+        assert_eq!(stats["overdue"], 1);
+    }
+
+    #[test]
+    fn filters_tasks_by_query() {
+        let (_, all) = get("/tasks");
+        assert_eq!(all.as_array().unwrap().len(), 3);
+        assert_eq!(all[2]["project"], "Web");
+
+        let (_, open) = get("/tasks?status=todo&assignee=%40bob&tag=needs+review");
+        assert_eq!(open.as_array().unwrap().len(), 1);
+        assert_eq!(open[0]["title"], "Auth");
+    }
+
+    #[test]
+    fn reports_bad_requests() {
+        assert_eq!(get("/tasks?status=later").0, 400);
+        assert_eq!(get("/tasks?owner=bob").0, 400);
+        assert_eq!(get("/tasks?tag=%zz").0, 400);
+        let (status, body) = get("/nothing");
+        assert_eq!(status, 404);
+        assert_eq!(body["error"], "no endpoint at /nothing");
+
+        let doc = ToDoParser::parse_document(SOURCE).unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+        assert_eq!(respond(&doc, "POST", "/tasks", &today).status, 405);
+    }
+}
+
+mod chunked_tests {
+    use super::*;
+    use std::fmt::Write;
+    use to_do_parcer::events::Event;
+    use to_do_parcer::options::{ParseOptions, UnknownAttributePolicy};
+
+    /// A version 2 file with comments and warnings, several chunks long.
+    fn source(projects: usize) -> String {
+        let mut out = String::from("// header\nversion 2\n");
+        for p in 0..projects {
+            let _ = writeln!(out, "\n// Project {}\nproject \"P{}\" {{", p, p);
+            for t in 0..5 {
+                let _ = writeln!(out, "  todo: \"T{}\", due: 2025-01-0{}, // note", t, t + 1);
+            }
+            if p % 300 == 0 {
+                out.push_str("  done: \"D\", colour: red, @high, @low,\n");
+            }
+            out.push_str("} // end\n");
+        }
+        out
+    }
+
+    /// Every event of a parse, as text.
+    fn events(
+        parse: impl FnOnce(&mut &mut dyn FnMut(Event<'_>)) -> Result<(), ParseError>,
+    ) -> (Vec<String>, Option<String>) {
+        let mut seen = Vec::new();
+        let mut push = |event: Event<'_>| seen.push(format!("{:?}", event));
+        let result = parse(&mut (&mut push as &mut dyn FnMut(Event<'_>)));
+        (seen, result.err().map(|e| e.to_string()))
+    }
+
+    fn options() -> ParseOptions {
+        ParseOptions {
+            unknown_attributes: UnknownAttributePolicy::Warn,
+            ..ParseOptions::default()
+        }
+    }
+
+    #[test]
+    fn streams_the_events_of_a_whole_parse() {
+        let input = source(2_000);
+        assert!(input.len() > 4 * to_do_parcer::chunked::MIN_CHUNK_BYTES);
+        let whole = events(|sink| ToDoParser::parse_events_with(&input, &options(), sink));
+        let chunked = events(|sink| ToDoParser::parse_reader(input.as_bytes(), &options(), sink));
+        assert_eq!(chunked, whole);
+        assert!(whole.0[0].starts_with("Version(2)"));
+        assert!(whole.0.iter().any(|e| e.contains("line: 4")));
+
+        let crlf = input.replace('\n', "\r\n");
+        let chunked = events(|sink| ToDoParser::parse_reader(crlf.as_bytes(), &options(), sink));
+        assert_eq!(chunked, whole);
+    }
+
+    #[test]
+    fn reports_errors_where_they_are_in_the_file() {
+        let mut input = source(2_000);
+        input.push_str("project \"Broken\" {\n  todo: \"A\"\n}\n");
+        let whole = ToDoParser::parse_with(&input, &options()).unwrap_err();
+        let chunked = events(|sink| ToDoParser::parse_reader(input.as_bytes(), &options(), sink));
+        assert_eq!(chunked.1, Some(whole.to_string()));
+        assert!(
+            whole
+                .to_string()
+                .contains(&format!("{}:3", input.lines().count() - 1))
+        );
+
+        let mut input = source(2_000);
+        input.push_str("project \"Late\" { todo: \"A\", due: 2025-02-30, }\n");
+        let whole = ToDoParser::parse_with(&input, &options()).unwrap_err();
+        let chunked = events(|sink| ToDoParser::parse_reader(input.as_bytes(), &options(), sink));
+        assert_eq!(chunked.1, Some(whole.to_string()));
+    }
+
+    #[test]
+    fn rejects_invalid_utf8_like_a_read_file() {
+        let mut input = source(1_000).into_bytes();
+        input.extend_from_slice(b"project \"Caf\xe9\" { }\n");
+        let offset = input.len() - 7;
+        let result = ToDoParser::parse_reader(input.as_slice(), &options(), &mut |_: Event<'_>| {});
+        assert!(matches!(
+            result,
+            Err(ParseError::Encoding { offset: o, .. }) if o == offset
+        ));
+
+        let lossy = ParseOptions {
+            lossy: true,
+            ..options()
+        };
+        let (seen, error) = events(|sink| ToDoParser::parse_reader(input.as_slice(), &lossy, sink));
+        assert_eq!(error, None);
+        assert!(seen.last().unwrap().contains("invalid UTF-8"));
+    }
+}
+
+mod cache_tests {
+    use std::fs;
+    use to_do_parcer::cache::{CacheStats, DocumentCache};
+    use to_do_parcer::options::{IncludePolicy, ParseOptions};
+
+    #[test]
+    fn reparses_only_changed_files() {
+        let dir = std::env::temp_dir().join("to_do_parcer_cache");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("tasks.todo");
+        fs::write(&path, r#"project "A" { todo: "1", }"#).unwrap();
+        let options = ParseOptions::default();
+        let mut cache = DocumentCache::new();
+
+        assert_eq!(
+            cache.parse_file(&path, &options).unwrap().projects[0].name,
+            "A"
+        );
+        assert_eq!(
+            cache.parse_file(&path, &options).unwrap().projects[0].name,
+            "A"
+        );
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 1 });
+
+        fs::write(&path, r#"project "B" { todo: "1", }"#).unwrap();
+        assert_eq!(
+            cache.parse_file(&path, &options).unwrap().projects[0].name,
+            "B"
+        );
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 2 });
+        assert_eq!(cache.len(), 1);
+
+        assert!(cache.invalidate(&path));
+        assert!(cache.is_empty());
+        cache.parse_file(&path, &options).unwrap();
+        assert_eq!(cache.stats().misses, 3);
+    }
+
+    #[test]
+    fn notices_changed_includes() {
+        let dir = std::env::temp_dir().join("to_do_parcer_cache_include");
+        fs::create_dir_all(&dir).unwrap();
+        let main = dir.join("main.todo");
+        fs::write(
+            &main,
+            r#"project "Main" { todo: "A", } include "team.todo""#,
+        )
+        .unwrap();
+        fs::write(dir.join("team.todo"), r#"project "Team" { todo: "Sync", }"#).unwrap();
+        let options = ParseOptions {
+            includes: IncludePolicy::Resolve,
+            ..ParseOptions::default()
+        };
+        let mut cache = DocumentCache::new();
+
+        assert_eq!(cache.parse_file(&main, &options).unwrap().projects.len(), 2);
+        fs::write(
+            dir.join("team.todo"),
+            r#"project "Team" { todo: "Sync", } project "Ops" { todo: "Deploy", }"#,
+        )
+        .unwrap();
+        assert_eq!(cache.parse_file(&main, &options).unwrap().projects.len(), 3);
+        assert_eq!(cache.stats().hits, 0);
+    }
+
+    #[test]
+    fn failed_parses_are_not_cached() {
+        let dir = std::env::temp_dir().join("to_do_parcer_cache_error");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("broken.todo");
+        fs::write(&path, r#"project "A" { todo: "1" "#).unwrap();
+        let mut cache = DocumentCache::new();
+
+        assert!(cache.parse_file(&path, &ParseOptions::default()).is_err());
+        assert!(cache.is_empty());
+        assert!(cache.parse_file(&path, &ParseOptions::default()).is_err());
+        assert_eq!(cache.stats(), CacheStats { hits: 0, misses: 2 });
+    }
+}
+
+mod workspace_tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use to_do_parcer::options::ParseOptions;
+    use to_do_parcer::validate::ValidateOptions;
+    use to_do_parcer::workspace::Workspace;
+
+    /// A fresh directory named after the test.
+    fn empty_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn revalidates_only_affected_files() {
+        let dir = empty_dir("to_do_parcer_workspace");
+        let (a, b, c) = (dir.join("a.todo"), dir.join("b.todo"), dir.join("c.todo"));
+        fs::write(&a, r#"project "A" { todo: "Ship", depends_on: "Design", }"#).unwrap();
+        fs::write(&b, r#"project "B" { todo: "Design", }"#).unwrap();
+        fs::write(&c, r#"project "C" { todo: "Other", }"#).unwrap();
+        let mut workspace =
+            Workspace::new(&dir, ParseOptions::default(), ValidateOptions::default());
+
+        let changes = workspace.refresh().unwrap();
+        assert_eq!(changes.reparsed, [a.clone(), b.clone(), c.clone()]);
+        assert_eq!(changes.revalidated, changes.reparsed);
+        assert!(workspace.diagnostics(&a).is_empty());
+        assert!(workspace.refresh().unwrap().is_empty());
+
+        fs::write(&b, r#"project "B" { todo: "Draft", }"#).unwrap();
+        let changes = workspace.refresh().unwrap();
+        assert_eq!(changes.reparsed, [b.as_path()]);
+        assert_eq!(changes.revalidated, [a.clone(), b.clone()]);
+        let issues = workspace.diagnostics(&a);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].code, "unknown-dependency");
+
+        fs::write(&c, r#"project "C" { todo: "Other", todo: "Design", }"#).unwrap();
+        let changes = workspace.refresh().unwrap();
+        assert_eq!(changes.revalidated, [a.clone(), c.clone()]);
+        assert!(workspace.diagnostics(&a).is_empty());
+
+        fs::remove_file(&b).unwrap();
+        let changes = workspace.refresh().unwrap();
+        assert_eq!(changes.removed, [b.as_path()]);
+        assert!(changes.revalidated.is_empty());
+        assert_eq!(workspace.files().count(), 2);
+    }
+
+    #[test]
+    fn parse_errors_are_diagnostics() {
+        let dir = empty_dir("to_do_parcer_workspace_broken");
+        let path = dir.join("tasks.todo");
+        fs::write(&path, r#"project "A" { todo: "1" "#).unwrap();
+        let mut workspace =
+            Workspace::new(&dir, ParseOptions::default(), ValidateOptions::default());
+
+        workspace.refresh().unwrap();
+        assert!(workspace.error(&path).is_some());
+        assert!(workspace.document(&path).is_none());
+        assert_eq!(workspace.diagnostics(&path).len(), 1);
+        assert!(workspace.refresh().unwrap().is_empty());
+
+        fs::write(&path, r#"project "A" { todo: "1", }"#).unwrap();
+        assert_eq!(workspace.refresh().unwrap().reparsed, [path.as_path()]);
+        assert!(workspace.error(&path).is_none());
+        assert!(workspace.diagnostics(&path).is_empty());
+        assert_eq!(workspace.document(&path).unwrap().projects[0].name, "A");
+    }
+}
+
+mod tags_tests {
+    use super::*;
+    use to_do_parcer::tags::Tags;
+
+    #[test]
+    fn few_tags_stay_inline() {
+        let projects = ToDoParser::parse_projects(
+            r#"project "P" {
+                todo: "A",
+                todo: "B", @tag: "x", @tag: "y",
+                todo: "C", @tag: "x", @tag: "y", @tag: "z",
+            }"#,
+        )
+        .unwrap();
+        let tags: Vec<&Tags> = projects[0].tasks.iter().map(|t| &t.tags).collect();
+        assert!(tags[0].is_empty() && tags[0].is_inline());
+        assert_eq!(*tags[1], ["x", "y"]);
+        assert!(tags[1].is_inline());
+        assert_eq!(*tags[2], ["x", "y", "z"]);
+        assert!(!tags[2].is_inline());
+    }
+
+    #[test]
+    fn behaves_like_a_vec() {
+        let mut tags: Tags = vec!["b".to_string()].into();
+        tags.extend(["a".to_string(), "c".to_string()]);
+        tags.sort();
+        assert_eq!(tags, vec!["a", "b", "c"]);
+        assert_eq!(format!("{:?}", tags), r#"["a", "b", "c"]"#);
+        assert_eq!(tags.last().unwrap(), "c");
+        let collected: Tags = tags.clone().into_iter().filter(|t| t != "b").collect();
+        assert_eq!(collected.into_vec(), vec!["a", "c"]);
+        tags.clear();
+        assert!(tags.is_empty());
+    }
+}
+
+#[cfg(feature = "mmap")]
+mod mmap_tests {
+    use super::*;
+    use std::fs;
+    use to_do_parcer::mmap::MappedSource;
+    use to_do_parcer::options::ParseOptions;
+
+    #[test]
+    fn parses_like_a_read_file() {
+        let dir = std::env::temp_dir().join("to_do_parcer_mmap");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("inc.todo"), "project \"Inc\" { todo: \"B\", }\n").unwrap();
+        let path = dir.join("main.todo");
+        fs::write(
+            &path,
+            "project \"P\" {\n  todo: \"A\", @high, due: 2025-01-01,\n}\ninclude \"inc.todo\"\n",
+        )
+        .unwrap();
+        let path = path.to_str().unwrap();
+
+        let mapped = unsafe { ToDoParser::parse_from_file_mmap(path) }.unwrap();
+        let read = ToDoParser::parse_from_file(path).unwrap();
+        assert_eq!(format!("{:?}", mapped), format!("{:?}", read));
+        assert_eq!(mapped.len(), 2);
+        assert_eq!(mapped[1].tasks[0].title, "B");
+    }
+
+    #[test]
+    fn empty_and_invalid_files() {
+        let dir = std::env::temp_dir().join("to_do_parcer_mmap_invalid");
+        fs::create_dir_all(&dir).unwrap();
+        let empty = dir.join("empty.todo");
+        fs::write(&empty, "").unwrap();
+        let source = unsafe { MappedSource::open(&empty) }.unwrap();
+        assert_eq!(source.as_str(), "");
+
+        let latin1 = dir.join("latin1.todo");
+        fs::write(&latin1, b"project \"P\" {\n  todo: \"Caf\xe9\",\n}\n").unwrap();
+        let latin1 = latin1.to_str().unwrap();
+        assert!(matches!(
+            unsafe { ToDoParser::parse_file_mmap_with(latin1, &ParseOptions::default()) },
+            Err(ParseError::Encoding {
+                line: 2,
+                offset: 26
+            })
+        ));
+        let lossy = ParseOptions {
+            lossy: true,
+            ..ParseOptions::default()
+        };
+        let doc = unsafe { ToDoParser::parse_file_mmap_with(latin1, &lossy) }.unwrap();
+        assert_eq!(doc.projects[0].tasks[0].title, "Caf\u{fffd}");
+        assert_eq!(doc.warnings.len(), 1);
+    }
+}
+
+#[cfg(feature = "fast-parser")]
+mod fast_parser_tests {
+    use super::*;
+    use to_do_parcer::options::{DateFormat, ParseOptions, UnknownAttributePolicy};
+
+    #[test]
+    fn backtracks_like_the_grammar() {
+        let options = ParseOptions {
+            unknown_attributes: UnknownAttributePolicy::Warn,
+            date_format: DateFormat::DayMonthYear,
+            case_insensitive_keywords: true,
+            ..ParseOptions::default()
+        };
+        let input = "project \"P\" {\n  todo: \"A\" // why\n  , id: \"x\", assign: @ bob, due: 12.03.2025,\n  DONE:\"B\",@low,\n}\n";
+        let doc = ToDoParser::parse_with(input, &options).unwrap();
+        let tasks = &doc.projects[0].tasks;
+        // `id:` with a quoted value is an unknown attribute, not an id.
+        assert_eq!(tasks[0].id, None);
+        assert!(doc.warnings[0].message.contains("unknown attribute `id`"));
+        assert_eq!(tasks[0].assignee.as_deref(), Some("bob"));
+        assert_eq!(tasks[0].due_date.as_deref(), Some("2025-03-12"));
+        assert_eq!(tasks[0].due_span.unwrap().line, 3);
+        assert_eq!((tasks[1].span.line, tasks[1].span.column), (4, 3));
+        assert_eq!(tasks[1].priority, Some(Priority::Low));
+        assert_eq!(doc.projects[0].comments, Vec::<String>::new());
+    }
+
+    #[test]
+    fn syntax_errors_are_reported_by_pest() {
+        let err = ToDoParser::parse_document("project \"P\" {\n  todo: \"A\"\n}\n").unwrap_err();
+        assert!(matches!(err, ParseError::Pest(_)));
+        assert_eq!((err.line(), err.column()), (Some(2), Some(3)));
+    }
+}
+
+#[cfg(feature = "parallel")]
+mod parallel_tests {
+    use super::*;
+    use std::fmt::Write;
+    use std::fs;
+    use to_do_parcer::options::{IncludePolicy, ParseOptions, UnknownAttributePolicy};
+
+    /// A file of `projects` projects with comments, warnings and includes,
+    /// large enough to be cut into several chunks.
+    fn source(projects: usize) -> String {
+        let mut out = String::from("// header\nversion 2\n\n");
+        for p in 0..projects {
+            let _ = writeln!(out, "// Project {}\n\nproject \"P{}\" {{", p, p);
+            for t in 0..5 {
+                let _ = writeln!(
+                    out,
+                    "  todo: \"T{}\", due: 2025-01-0{}, tag: \"x\", // note {}",
+                    t,
+                    t + 1,
+                    t
+                );
+            }
+            if p % 100 == 0 {
+                out.push_str("  done: \"D\", @high, @low, colour: red,\n");
+                out.push_str("  // footer\n");
+            }
+            out.push_str("} // end\n");
+            if p % 250 == 0 {
+                out.push_str("include \"inc.todo\"\n");
+            }
+        }
+        out.push_str("// trailing\n");
+        out
+    }
+
+    #[test]
+    fn matches_a_sequential_parse() {
+        let dir = std::env::temp_dir().join("to_do_parcer_parallel");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("inc.todo"), "project \"Inc\" { todo: \"I\", }\n").unwrap();
+        let options = ParseOptions {
+            includes: IncludePolicy::Resolve,
+            base_dir: Some(dir),
+            unknown_attributes: UnknownAttributePolicy::Warn,
+            ..ParseOptions::default()
+        };
+        let input = source(1_000);
+        let sequential = ToDoParser::parse_with(&input, &options).unwrap();
+        let parallel = ToDoParser::parse_parallel_with(&input, &options).unwrap();
+        assert_eq!(format!("{:?}", parallel), format!("{:?}", sequential));
+        assert_eq!(parallel.version, 2);
+        assert_eq!(parallel.warnings.len(), 20);
+        assert_eq!(parallel.projects.len(), 1_004);
+
+        let crlf = input.replace('\n', "\r\n");
+        assert_eq!(
+            format!(
+                "{:?}",
+                ToDoParser::parse_parallel_with(&crlf, &options).unwrap()
+            ),
+            format!("{:?}", sequential)
+        );
+    }
+
+    #[test]
+    fn reports_errors_like_a_sequential_parse() {
+        let mut input = source(1_000);
+        input.push_str("project \"Broken\" { todo: \"A\" }\n");
+        let sequential = ToDoParser::parse_document(&input).unwrap_err();
+        let parallel = ToDoParser::parse_parallel(&input).unwrap_err();
+        assert_eq!(parallel.to_string(), sequential.to_string());
+
+        let strict = ParseOptions {
+            strict: true,
+            ..ParseOptions::default()
+        };
+        let input = source(1_000);
+        assert_eq!(
+            ToDoParser::parse_parallel_with(&input, &strict)
+                .unwrap_err()
+                .to_string(),
+            ToDoParser::parse_with(&input, &strict)
+                .unwrap_err()
+                .to_string()
+        );
+    }
+}
+
+#[cfg(feature = "github")]
+mod github_tests {
+    use chrono::NaiveDate;
+    use to_do_parcer::ToDoParser;
+    use to_do_parcer::edit::Editor;
+    use to_do_parcer::github::{self, Action, Issue, Label, SyncOptions, User, plan};
+
+    const SOURCE: &str = "project \"P\" {\n  todo: \"New\", assign: @bob, @tag: \"github\",\n  todo: \"Closed upstream\", @tag: \"github\",\n  done: \"Finished\", @tag: \"github\",\n  todo: \"Local only\",\n}\n";
+
+    fn issue(number: u64, title: &str, state: &str) -> Issue {
+        Issue {
+            number,
+            title: title.to_string(),
+            state: state.to_string(),
+            closed_at: (state == "closed").then(|| "2025-03-04T10:00:00Z".to_string()),
+            labels: vec![Label {
+                name: "triage".to_string(),
+            }],
+            assignees: Vec::new(),
+            pull_request: None,
+        }
+    }
+
+    #[test]
+    fn plans_creates_updates_and_completions() {
+        let doc = ToDoParser::parse_document(SOURCE).unwrap();
+        let mut options = SyncOptions::default();
+        options
+            .assignees
+            .insert("bob".to_string(), "bob-gh".to_string());
+        let issues = [
+            issue(7, "Closed upstream", "closed"),
+            issue(8, "Finished", "open"),
+        ];
+        let actions = plan(&doc, &issues, &options);
+        assert_eq!(actions.len(), 3);
+
+        let Action::Create { fields, .. } = &actions[0] else {
+            panic!("expected create, got {:?}", actions[0]);
+        };
+        assert_eq!(fields.title, "New");
+        assert_eq!(fields.assignees, ["bob-gh"]);
+
+        assert!(matches!(
+            actions[1],
+            Action::Complete { number: 7, closed_on, .. }
+                if closed_on == NaiveDate::from_ymd_opt(2025, 3, 4)
+        ));
+
+        let Action::Update {
+            number: 8, fields, ..
+        } = &actions[2]
+        else {
+            panic!("expected update, got {:?}", actions[2]);
+        };
+        assert_eq!(fields.state, Some("closed"));
+        assert_eq!(fields.labels, ["triage"]);
+        assert_eq!(actions[2].to_string(), "close issue #8 \"Finished\"");
+    }
+
+    #[test]
+    fn in_sync_issues_need_nothing() {
+        let doc = ToDoParser::parse_document(
+            r#"project "P" { todo: "A", @low, assign: @amy, @tag: "github", }"#,
+        )
+        .unwrap();
+        let mut matched = issue(1, "A", "open");
+        matched.labels.push(Label {
+            name: "priority: low".to_string(),
+        });
+        matched.assignees.push(User {
+            login: "amy".to_string(),
+        });
+        assert!(plan(&doc, &[matched.clone()], &SyncOptions::default()).is_empty());
+
+        // Pull requests with the same title are not issues.
+        matched.pull_request = Some(serde_json::json!({}));
+        assert!(matches!(
+            plan(&doc, &[matched], &SyncOptions::default())[0],
+            Action::Create { .. }
+        ));
+    }
+
+    #[test]
+    fn apply_marks_closed_tasks_done() {
+        let mut editor = Editor::new(SOURCE).unwrap();
+        let actions = plan(
+            editor.document(),
+            &[issue(7, "Closed upstream", "closed")],
+            &SyncOptions::default(),
+        );
+        assert_eq!(github::apply(&mut editor, &actions), 1);
+        assert!(
+            editor.finish().contains(
+                "  done: \"Closed upstream\", @tag: \"github\", completed: 2025-03-04,\n"
+            )
+        );
+    }
+}
+
+mod notify_tests {
+    use super::*;
+    use std::cell::RefCell;
+    use to_do_parcer::notify::{Notification, Notifier, Urgency, due_notifications, parse_window};
+
+    const INPUT: &str = r#"project "Home" {
+  todo: "Taxes", due: 2025-01-29, assign: @ann,
+  todo: "Rent", due: 2025-02-01,
+  todo: "Dentist", due: 2025-02-04,
+  todo: "Vacation", due: 2025-03-01,
+  done: "Paid", due: 2025-01-01,
+}"#;
+
+    /// Keeps what it is sent, for checking.
+    #[derive(Default)]
+    struct Recorder(RefCell<Vec<Notification>>);
+
+    impl Notifier for Recorder {
+        fn send(&self, notification: &Notification) -> std::io::Result<()> {
+            self.0.borrow_mut().push(notification.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn overdue_and_upcoming() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 2, 1).unwrap();
+        let notes = due_notifications(&doc, &today, 3);
+        let summaries: Vec<_> = notes.iter().map(|n| n.summary.as_str()).collect();
+        assert_eq!(
+            summaries,
+            [
+                "Overdue: Taxes",
+                "Due today: Rent",
+                "Due Tue Feb 4: Dentist"
+            ]
+        );
+        assert_eq!(notes[0].body, "Home · due 2025-01-29, 3 days late · @ann");
+        assert_eq!(notes[0].urgency, Urgency::Critical);
+        assert_eq!(notes[1].body, "Home · due 2025-02-01");
+        assert_eq!(notes[1].urgency, Urgency::Normal);
+    }
+
+    #[test]
+    fn notifier_receives_each_notification() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 2, 1).unwrap();
+        let recorder = Recorder::default();
+        for note in due_notifications(&doc, &today, 0) {
+            recorder.send(&note).unwrap();
+        }
+        assert_eq!(recorder.0.borrow().len(), 2);
+    }
+
+    #[test]
+    fn huge_window_takes_every_dated_task() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 2, 1).unwrap();
+        let window = parse_window("4000000000d").unwrap();
+        assert_eq!(due_notifications(&doc, &today, window).len(), 4);
+    }
+
+    #[test]
+    fn windows() {
+        assert_eq!(parse_window("0h"), Ok(0));
+        assert_eq!(parse_window("1h"), Ok(1));
+        assert_eq!(parse_window("48h"), Ok(2));
+        assert_eq!(parse_window("3"), Ok(3));
+        assert_eq!(parse_window("3d"), Ok(3));
+        assert_eq!(
+            parse_window("3m"),
+            Err("invalid window '3m', expected e.g. 24h, 3d or 2w".to_string())
+        );
+        assert!(parse_window("h").is_err());
+    }
+}
+
+mod burndown_tests {
+    use super::*;
+    use to_do_parcer::burndown::{BurndownFormat, burndown, render_burndown};
+
+    const INPUT: &str = r#"project "P" {
+  todo: "Backlog",
+  todo: "Late start", start: 2025-11-03,
+  done: "Quick", start: 2025-11-02, completed: 2025-11-03,
+  done: "Early", completed: 2025-11-01,
+  done: "Undated",
+}"#;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, d).unwrap()
+    }
+
+    #[test]
+    fn counts_per_day() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let series: Vec<_> = burndown(&doc, day(1), day(4))
+            .iter()
+            .map(|d| (d.date, d.open, d.done, d.total))
+            .collect();
+        assert_eq!(
+            series,
+            [
+                (day(1), 1, 1, 2),
+                (day(2), 2, 1, 3),
+                (day(3), 2, 2, 4),
+                (day(4), 2, 2, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_range() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let days = burndown(&doc, day(2), day(1));
+        assert!(days.is_empty());
+        assert_eq!(
+            render_burndown(&days, BurndownFormat::Csv),
+            "date,open,done,total\n"
+        );
+    }
+
+    #[test]
+    fn csv_and_json() {
+        let doc = ToDoParser::parse_document(INPUT).unwrap();
+        let days = burndown(&doc, day(1), day(2));
+        assert_eq!(
+            render_burndown(&days, BurndownFormat::Csv),
+            "date,open,done,total\n2025-11-01,1,1,2\n2025-11-02,2,1,3\n"
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&render_burndown(&days, BurndownFormat::Json)).unwrap();
+        assert_eq!(
+            json[1],
+            serde_json::json!({"date": "2025-11-02", "open": 2, "done": 1, "total": 3})
+        );
+    }
+}
+
+mod critical_path_tests {
+    use super::*;
+    use to_do_parcer::critical_path::{CriticalPathError, duration, schedule};
+
+    #[test]
+    fn longest_chain_is_critical() {
+        let doc = ToDoParser::parse_document(
+            r#"project "P" {
+  todo: "Spec", start: 2025-11-01, due: 2025-11-02,
+  todo: "Backend", start: 2025-11-03, due: 2025-11-07, depends_on: "Spec",
+  todo: "Frontend", depends_on: "Spec",
+  todo: "Launch", depends_on: "Backend",
+  done: "Kickoff",
+}
+project "Q" {
+  todo: "Review", depends_on: "P/Frontend",
+}"#,
+        )
+        .unwrap();
+        let plan = schedule(&doc).unwrap();
+        assert_eq!(plan.length, 8);
+        let titles: Vec<_> = plan
+            .path
+            .iter()
+            .map(|r| r.get(&doc).unwrap().title.as_str())
+            .collect();
+        assert_eq!(titles, ["Spec", "Backend", "Launch"]);
+
+        let slack: Vec<_> = plan
+            .tasks
+            .iter()
+            .map(|s| {
+                (
+                    s.task.get(&doc).unwrap().title.as_str(),
+                    s.earliest_start,
+                    s.slack(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            slack,
+            [
+                ("Spec", 0, 0),
+                ("Backend", 2, 0),
+                ("Frontend", 2, 4),
+                ("Launch", 7, 0),
+                ("Review", 3, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn durations_come_from_dates() {
+        let doc = ToDoParser::parse_document(
+            r#"project "P" {
+  todo: "Week", start: 2025-11-03, due: 2025-11-09,
+  todo: "Due only", due: 2025-11-09,
+  todo: "Backwards", start: 2025-11-09, due: 2025-11-03,
+}"#,
+        )
+        .unwrap();
+        let days: Vec<_> = doc.projects[0].tasks.iter().map(duration).collect();
+        assert_eq!(days, [7, 1, 1]);
+    }
+
+    #[test]
+    fn cycles_are_reported() {
+        let doc = ToDoParser::parse_document(
+            r#"project "P" { todo: "A", depends_on: "B", todo: "B", depends_on: "A", }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            schedule(&doc).unwrap_err(),
+            CriticalPathError::Cycle("A".to_string())
+        );
+    }
+}
+
+mod fix_tests {
+    use to_do_parcer::ParseOptions;
+    use to_do_parcer::fix::{fix_all, suggest_fix};
+
+    fn fixed(source: &str) -> String {
+        let fixed = fix_all(source, &ParseOptions::default());
+        assert!(fixed.remaining.is_none(), "{:?}", fixed.remaining);
+        fixed.text
+    }
+
+    #[test]
+    fn missing_trailing_comma() {
+        assert_eq!(
+            fixed("project \"P\" {\n  todo: \"A\", @high // soon\n  done: \"B\"\n}\n"),
+            "project \"P\" {\n  todo: \"A\", @high, // soon\n  done: \"B\",\n}\n"
+        );
+    }
+
+    #[test]
+    fn unclosed_quote() {
+        assert_eq!(
+            fixed("project \"P\" {\n  todo: \"A, assign: @me,\n}\n"),
+            "project \"P\" {\n  todo: \"A\", assign: @me,\n}\n"
+        );
+    }
+
+    #[test]
+    fn date_separators() {
+        let source = "project \"P\" {\n  todo: \"A\", start: 2025.12.01, due: 2025/12/31,\n}\n";
+        let fix = suggest_fix(source, &ParseOptions::default()).unwrap();
+        assert_eq!(fix.span.start..fix.span.end, 34..61);
+        assert_eq!(fix.replacement, "2025-12-01, due: 2025-12-31");
+        assert_eq!(
+            fixed(source),
+            "project \"P\" {\n  todo: \"A\", start: 2025-12-01, due: 2025-12-31,\n}\n"
+        );
+    }
+
+    #[test]
+    fn nothing_to_fix() {
+        let options = ParseOptions::default();
+        assert_eq!(suggest_fix("project \"P\" {}", &options), None);
+        // Not one of the recognized mistakes.
+        let fixed = fix_all("project \"P\" { todo: \"A\", @urgent, }", &options);
+        assert!(fixed.applied.is_empty());
+        assert!(fixed.remaining.is_some());
+    }
 }